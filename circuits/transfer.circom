@@ -15,6 +15,8 @@ include "./poseidon.circom";
 //   - output_commitment_1, output_commitment_2: New note commitments
 //   - public_amount: Net public amount (positive for shield, negative for unshield, 0 for private transfer)
 //   - token_mint: Token mint address (for multi-token support)
+//   - ext_data_hash: Hash of the unshield recipient, relayer and relayer fee
+//     (computed on-chain; binding it stops a relayer rewriting them)
 //
 // Private inputs:
 //   - Input notes (amount, owner_pubkey, randomness, merkle path)
@@ -32,6 +34,7 @@ template Transfer(merkleDepth) {
     signal input output_commitment_2;
     signal input public_amount;  // Can be negative (represented in field)
     signal input token_mint;
+    signal input ext_data_hash;
 
     // ========================================
     // PRIVATE INPUTS - Input Notes
@@ -182,7 +185,14 @@ template Transfer(merkleDepth) {
 
     component rangeCheck4 = Num2Bits(64);
     rangeCheck4.in <== in_amount_2;
+
+    // ========================================
+    // STEP 8: Bind external data
+    // ========================================
+    // Unused otherwise; squaring keeps the signal in the constraint system
+    signal ext_data_square;
+    ext_data_square <== ext_data_hash * ext_data_hash;
 }
 
 // Main component with tree depth of 20 (~1M notes)
-component main {public [merkle_root, nullifier_1, nullifier_2, output_commitment_1, output_commitment_2, public_amount, token_mint]} = Transfer(20);
+component main {public [merkle_root, nullifier_1, nullifier_2, output_commitment_1, output_commitment_2, public_amount, token_mint, ext_data_hash]} = Transfer(20);
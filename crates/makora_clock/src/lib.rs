@@ -0,0 +1,120 @@
+//! Sanity checks on `Clock` timestamps, shared by Makora programs.
+//!
+//! Handlers that persist `Clock::get()?.unix_timestamp` into audit trails
+//! or expiries read it through [`checked_now`], so a drifting validator
+//! clock is rejected instead of being written into account state.
+
+use anchor_lang::prelude::*;
+
+/// How far a timestamp may run behind the account's `created_at` before it
+/// is treated as clock drift rather than ordinary slot skew.
+pub const MAX_BACKWARD_DRIFT_SECS: i64 = 3_600; // 1 hour
+
+/// Default upper bound on `now - created_at` (10 years).
+pub const DEFAULT_MAX_AGE_SECS: i64 = 10 * 365 * 86_400;
+
+/// 2024-01-01T00:00:00Z. No Makora account predates it, so it is the
+/// reference for accounts created with nothing older to compare against.
+pub const GENESIS_TIMESTAMP: i64 = 1_704_067_200;
+
+#[error_code(offset = 7100)]
+pub enum ClockError {
+    #[msg("Clock timestamp is non-positive or out of range for this account.")]
+    InvalidTimestamp,
+}
+
+/// Validate `now` against an account's `created_at`.
+///
+/// `now` must be positive. When `created_at` is set (non-zero), `now` may
+/// not fall more than [`MAX_BACKWARD_DRIFT_SECS`] before it, nor more than
+/// `max_age_secs` after it. Returns `now` so callers can use it inline.
+pub fn check_timestamp(now: i64, created_at: i64, max_age_secs: i64) -> Result<i64> {
+    require!(now > 0, ClockError::InvalidTimestamp);
+    if created_at != 0 {
+        require!(
+            now >= created_at.saturating_sub(MAX_BACKWARD_DRIFT_SECS),
+            ClockError::InvalidTimestamp
+        );
+        require!(
+            now.saturating_sub(created_at) <= max_age_secs,
+            ClockError::InvalidTimestamp
+        );
+    }
+    Ok(now)
+}
+
+/// Read the cluster clock and run [`check_timestamp`] on it.
+pub fn checked_now(created_at: i64, max_age_secs: i64) -> Result<i64> {
+    check_timestamp(Clock::get()?.unix_timestamp, created_at, max_age_secs)
+}
+
+/// Read the cluster clock for a new account with no parent to anchor it:
+/// `now` may not fall before [`GENESIS_TIMESTAMP`]. No upper bound applies.
+pub fn checked_now_since_genesis() -> Result<i64> {
+    check_timestamp(Clock::get()?.unix_timestamp, GENESIS_TIMESTAMP, i64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CREATED: i64 = 1_700_000_000;
+
+    #[test]
+    fn accepts_time_after_creation() {
+        assert_eq!(
+            check_timestamp(CREATED + 60, CREATED, DEFAULT_MAX_AGE_SECS).unwrap(),
+            CREATED + 60
+        );
+    }
+
+    #[test]
+    fn rejects_non_positive_clock() {
+        for bad in [0, -1, i64::MIN] {
+            let err = check_timestamp(bad, CREATED, DEFAULT_MAX_AGE_SECS).unwrap_err();
+            assert_eq!(err, ClockError::InvalidTimestamp.into());
+        }
+        assert!(check_timestamp(0, 0, DEFAULT_MAX_AGE_SECS).is_err());
+    }
+
+    #[test]
+    fn tolerates_small_backward_skew() {
+        check_timestamp(
+            CREATED - MAX_BACKWARD_DRIFT_SECS,
+            CREATED,
+            DEFAULT_MAX_AGE_SECS,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn rejects_clock_far_before_creation() {
+        let err = check_timestamp(
+            CREATED - MAX_BACKWARD_DRIFT_SECS - 1,
+            CREATED,
+            DEFAULT_MAX_AGE_SECS,
+        )
+        .unwrap_err();
+        assert_eq!(err, ClockError::InvalidTimestamp.into());
+    }
+
+    #[test]
+    fn rejects_clock_beyond_max_age() {
+        check_timestamp(CREATED + 86_400, CREATED, 86_400).unwrap();
+        let err = check_timestamp(CREATED + 86_401, CREATED, 86_400).unwrap_err();
+        assert_eq!(err, ClockError::InvalidTimestamp.into());
+    }
+
+    #[test]
+    fn genesis_reference_rejects_an_unset_clock() {
+        check_timestamp(GENESIS_TIMESTAMP, GENESIS_TIMESTAMP, i64::MAX).unwrap();
+        check_timestamp(i64::MAX, GENESIS_TIMESTAMP, i64::MAX).unwrap();
+        let err = check_timestamp(1, GENESIS_TIMESTAMP, i64::MAX).unwrap_err();
+        assert_eq!(err, ClockError::InvalidTimestamp.into());
+    }
+
+    #[test]
+    fn skips_relative_checks_without_created_at() {
+        check_timestamp(1, 0, 0).unwrap();
+    }
+}
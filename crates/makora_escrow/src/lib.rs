@@ -0,0 +1,73 @@
+//! Lamport moves out of program-owned escrow PDAs, shared by Makora programs.
+//!
+//! Stealth payments and vaults both hold SOL in PDAs owned by their
+//! program and pay it out by editing lamports directly. They go through
+//! [`transfer_lamports`] so the balance checks live in one place.
+
+use anchor_lang::prelude::*;
+
+/// Move lamports out of a program-owned account, checking both sides
+/// before writing either.
+///
+/// Direct lamport manipulation is only valid when `from` is owned by the
+/// calling program. Fails with `overflow` (the caller's own error) if
+/// `from` holds less than `amount` or `to` would overflow, leaving both
+/// balances untouched.
+pub fn transfer_lamports(
+    from: &AccountInfo,
+    to: &AccountInfo,
+    amount: u64,
+    overflow: impl Into<Error>,
+) -> Result<()> {
+    let (Some(from_after), Some(to_after)) = (
+        from.lamports().checked_sub(amount),
+        to.lamports().checked_add(amount),
+    ) else {
+        return Err(overflow.into());
+    };
+
+    **from.try_borrow_mut_lamports()? = from_after;
+    **to.try_borrow_mut_lamports()? = to_after;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_accounts(from_lamports: u64, to_lamports: u64, f: impl FnOnce(&AccountInfo, &AccountInfo)) {
+        let (from_key, to_key, owner) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (mut from_lamports, mut to_lamports) = (from_lamports, to_lamports);
+        let (mut from_data, mut to_data) = ([0u8; 0], [0u8; 0]);
+        let from = AccountInfo::new(&from_key, false, true, &mut from_lamports, &mut from_data, &owner, false, 0);
+        let to = AccountInfo::new(&to_key, false, true, &mut to_lamports, &mut to_data, &owner, false, 0);
+        f(&from, &to);
+    }
+
+    #[test]
+    fn moves_lamports() {
+        with_accounts(500, 5, |from, to| {
+            transfer_lamports(from, to, 500, ProgramError::ArithmeticOverflow).unwrap();
+            assert_eq!(from.lamports(), 0);
+            assert_eq!(to.lamports(), 505);
+        });
+    }
+
+    #[test]
+    fn fails_with_the_callers_error() {
+        with_accounts(10, 0, |from, to| {
+            let err = transfer_lamports(from, to, 11, ProgramError::InsufficientFunds).unwrap_err();
+            assert_eq!(err, ProgramError::InsufficientFunds.into());
+            assert_eq!(from.lamports(), 10);
+            assert_eq!(to.lamports(), 0);
+        });
+
+        with_accounts(10, u64::MAX, |from, to| {
+            let err = transfer_lamports(from, to, 1, ProgramError::ArithmeticOverflow).unwrap_err();
+            assert_eq!(err, ProgramError::ArithmeticOverflow.into());
+            assert_eq!(from.lamports(), 10);
+            assert_eq!(to.lamports(), u64::MAX);
+        });
+    }
+}
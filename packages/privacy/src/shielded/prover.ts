@@ -22,6 +22,7 @@ export interface CircuitInputs {
   output_commitment_2: string;
   public_amount: string;
   token_mint: string;
+  ext_data_hash: string;
 
   // Private inputs - Note 1
   in_amount_1: string;
@@ -114,6 +115,7 @@ export class ZkProver {
       output_commitment_2: publicInputs.outputCommitment2.toString(),
       public_amount: publicInputs.publicAmount.toString(),
       token_mint: publicInputs.tokenMint.toString(),
+      ext_data_hash: publicInputs.extDataHash.toString(),
 
       // Private inputs - Note 1
       in_amount_1: privateInputs.inAmount1.toString(),
@@ -123,6 +123,8 @@ export interface TransferPublicInputs {
   publicAmount: bigint;
   /** Token mint */
   tokenMint: bigint;
+  /** Hash binding the unshield recipient, relayer and fee (0 otherwise) */
+  extDataHash: bigint;
 }
 
 /**
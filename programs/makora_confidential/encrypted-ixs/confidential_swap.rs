@@ -8,7 +8,8 @@
 //   1. Decrypt the encrypted swap order using their shared key fragments
 //   2. Validate swap parameters (amount > 0, valid token pair)
 //   3. Compute the optimal route (Jupiter-compatible path)
-//   4. Execute the swap atomically
+//   4. Check the route against the client's min_output / max_price_impact,
+//      then execute the swap atomically
 //   5. Encrypt the settlement result for the client
 //
 // This uses Arcium's encrypted instruction format (Arcis).
@@ -17,13 +18,21 @@
 // ─────────────────────────────────────────────────────────────────────────────
 
 // NOTE: arcium_macros may not be available without the arcium CLI toolchain.
-// This code demonstrates the intended MPC logic for hackathon judges.
+// This code demonstrates the intended MPC logic for hackathon judges. The
+// mpc_* primitives come from the Arcium runtime; the plaintext checks are in
+// makora_confidential::order_checks, where cargo builds and tests them.
+
+use makora_confidential::order_checks::{check_route_bounds, is_valid_token_pair};
 
 /// Encrypted swap order structure (decrypted inside MPC)
 struct SwapOrder {
     from_token: String,
     to_token: String,
     amount: f64,
+    /// Smallest acceptable output amount (0 = no floor)
+    min_output: f64,
+    /// Largest acceptable price impact in percent (0 = no limit)
+    max_price_impact: f64,
     timestamp: i64,
 }
 
@@ -55,36 +64,33 @@ struct SwapResult {
 // #[arcium_computation]
 fn confidential_swap(encrypted_order: Vec<u8>) -> Vec<u8> {
     // Step 1: MPC collectively decrypts the order
-    // let order: SwapOrder = mpc_decrypt(encrypted_order);
-    //
+    let order: SwapOrder = mpc_decrypt(encrypted_order);
+
     // Step 2: Validate swap parameters
-    // assert!(order.amount > 0.0, "Invalid swap amount");
-    // assert!(is_valid_token_pair(&order.from_token, &order.to_token));
-    //
+    assert!(order.amount > 0.0, "Invalid swap amount");
+    assert!(is_valid_token_pair(&order.from_token, &order.to_token));
+
     // Step 3: Query Jupiter for optimal route (via MPC oracle)
-    // let route = mpc_oracle_call("jupiter_quote", &order);
-    //
-    // Step 4: Execute swap atomically
-    // let result = mpc_execute_swap(route);
-    //
-    // Step 5: Encrypt result for client
-    // mpc_encrypt(SwapResult {
-    //     output_amount: result.out_amount,
-    //     route: result.route_label,
-    //     price_impact: result.price_impact_pct,
-    //     executed_at: current_timestamp(),
-    // })
+    let route = mpc_oracle_call("jupiter_quote", &order);
 
-    // Placeholder — actual MPC logic requires arcium runtime
-    Vec::new()
-}
+    // Step 4: Reject a bad route before anything settles on-chain
+    if let Err(reason) = check_route_bounds(
+        route.out_amount,
+        route.price_impact_pct,
+        order.min_output,
+        order.max_price_impact,
+    ) {
+        return mpc_abort(reason); // cluster calls fail_confidential_swap
+    }
+
+    // Execute swap atomically
+    let result = mpc_execute_swap(route);
 
-/// Validate that a token pair is supported for confidential swaps.
-fn is_valid_token_pair(from: &str, to: &str) -> bool {
-    let supported = [
-        "SOL", "USDC", "BONK", "RAY", "JLP", "mSOL", "JitoSOL", "WBTC", "WETH",
-    ];
-    let from_ok = supported.iter().any(|t| t == &from);
-    let to_ok = supported.iter().any(|t| t == &to);
-    from_ok && to_ok && from != to
+    // Step 5: Encrypt result for client
+    mpc_encrypt(SwapResult {
+        output_amount: result.out_amount,
+        route: result.route_label,
+        price_impact: result.price_impact_pct,
+        executed_at: current_timestamp(),
+    })
 }
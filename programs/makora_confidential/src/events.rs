@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+
+// Order lifecycle events, so dashboards can follow MPC throughput live.
+// Each carries the first 8 bytes of the computation id for correlation.
+
+#[event]
+pub struct OrderSubmitted {
+    pub order: Pubkey,
+    pub owner: Pubkey,
+    pub computation_id_prefix: [u8; 8],
+    pub is_maker: bool,
+    /// False for orders submitted through the public fallback path
+    pub is_confidential: bool,
+    pub submitted_at: i64,
+}
+
+#[event]
+pub struct OrderSettled {
+    pub order: Pubkey,
+    pub owner: Pubkey,
+    pub computation_id_prefix: [u8; 8],
+    pub output_amount: u64,
+    pub fee: u64,
+    /// Public-market quote recorded at submission (0 = none)
+    pub reference_quote: u64,
+    /// Output improvement over `reference_quote` in bps (0 without a quote)
+    pub improvement_bps: i64,
+    pub submitted_at: i64,
+    pub settled_at: i64,
+}
+
+#[event]
+pub struct OrderFailed {
+    pub order: Pubkey,
+    pub owner: Pubkey,
+    pub computation_id_prefix: [u8; 8],
+    /// Cluster-supplied failure reason code
+    pub reason: u8,
+    pub submitted_at: i64,
+    pub failed_at: i64,
+}
+
+/// Emitted when a pending order times out, via expire_confidential_swap
+/// or sweep_expired_order.
+#[event]
+pub struct OrderExpired {
+    pub order: Pubkey,
+    pub owner: Pubkey,
+    pub computation_id_prefix: [u8; 8],
+    pub submitted_at: i64,
+    pub expired_at: i64,
+}
+
+/// Emitted when an expired order is swept and closed.
+#[event]
+pub struct OrderSwept {
+    pub order: Pubkey,
+    pub owner: Pubkey,
+    pub computation_id_prefix: [u8; 8],
+    pub cranker: Pubkey,
+    /// Lamports paid to the cranker from the book's sweep pool
+    pub bounty: u64,
+    /// Order rent returned to the owner
+    pub rent_refunded: u64,
+    pub swept_at: i64,
+}
+
+/// First 8 bytes of a computation id, as carried on lifecycle events.
+pub fn computation_id_prefix(computation_id: &[u8; 32]) -> [u8; 8] {
+    let mut prefix = [0u8; 8];
+    prefix.copy_from_slice(&computation_id[..8]);
+    prefix
+}
@@ -1,7 +1,42 @@
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+pub mod events;
+pub mod order_checks;
+pub mod vault_cpi;
+
+use events::*;
 
 declare_id!("ARC1UMconfSwapMakora11111111111111111111111");
 
+/// Maximum settlement fee an order book can charge (100 bps = 1%)
+pub const MAX_FEE_BPS: u16 = 100;
+
+/// Default ciphertext length bounds for an order book (bytes)
+pub const DEFAULT_MIN_ORDER_LEN: u16 = 32;
+pub const DEFAULT_MAX_ORDER_LEN: u16 = 512;
+
+/// Hard upper bound on a configured max_order_len (bytes)
+pub const MAX_ORDER_LEN_LIMIT: u16 = 1024;
+
+/// Arcis order schema version a new order book accepts
+pub const DEFAULT_SCHEMA_VERSION: u16 = 1;
+
+/// Hard upper bound on a configured max_pending
+pub const MAX_PENDING_LIMIT: u32 = 1_024;
+
+/// Number of recent orders indexed on each order book
+pub const RECENT_ORDERS_LEN: usize = 32;
+
+/// Callback timeout used when an order is submitted with `timeout_secs = 0`
+pub const DEFAULT_CALLBACK_TIMEOUT_SECS: u32 = 300;
+
+/// Longest callback timeout an order may request (1 hour)
+pub const MAX_CALLBACK_TIMEOUT_SECS: u32 = 3_600;
+
+/// Largest bounty (lamports) a book may pay per swept order
+pub const MAX_SWEEP_BOUNTY: u64 = 100_000;
+
 /// Makora Confidential Swaps — Arcium MPC encrypted token swaps.
 ///
 /// This program accepts encrypted swap orders via Arcium's Multi-Party
@@ -12,22 +47,183 @@ declare_id!("ARC1UMconfSwapMakora11111111111111111111111");
 ///   1. Client encrypts order with x25519 + RescueCipher
 ///   2. `submit_confidential_swap` stores the encrypted order on-chain
 ///   3. Arcium MPC cluster decrypts, validates, and computes optimal route
-///   4. `execute_swap_callback` settles the trade (Jupiter CPI or vault transfer)
+///   4. `execute_swap_callback` posts the result (order -> SettlementPending),
+///      optionally crediting SOL output straight into a Makora vault. A
+///      partial fill leaves the order PartiallyFilled until the last fill.
+///   5. `complete_settlement` records the fund transfer (order -> Settled)
+///
+/// If the computation fails, the cluster calls `fail_confidential_swap`
+/// instead of step 4 (order -> Failed). If no result arrives before the
+/// order's deadline, `expire_confidential_swap` moves it to Expired, or
+/// `sweep_expired_order` expires and closes it, paying the cranker a
+/// bounty from the book's sweep pool.
 ///
 /// When Arcium MPC is not available, the Telegram bot falls back to standard
-/// Jupiter routing. This program demonstrates the architecture for judges.
+/// Jupiter routing and records the order with `submit_public_swap` instead
+/// of step 2. Public orders carry a plaintext payload but otherwise follow
+/// the same lifecycle, so both paths settle through steps 4-5 and share the
+/// order book's audit record. This program demonstrates the architecture
+/// for judges.
 #[program]
 pub mod makora_confidential {
     use super::*;
 
     /// Initialize the confidential order book for a given authority.
-    pub fn init_order_book(ctx: Context<InitOrderBook>) -> Result<()> {
+    ///
+    /// `min_order_len` / `max_order_len` bound the encrypted order size for
+    /// this book's Arcis schema; 0 selects the default (32 / 512 bytes).
+    /// `submit_fee` lamports are charged to the owner on every confidential
+    /// submission and paid to `fee_collector` to fund the MPC cluster
+    /// (0 = no fee). `cluster_authority` is the only key that may settle,
+    /// fail or complete this book's orders.
+    pub fn init_order_book(
+        ctx: Context<InitOrderBook>,
+        min_order_len: u16,
+        max_order_len: u16,
+        submit_fee: u64,
+        fee_collector: Pubkey,
+        cluster_authority: Pubkey,
+    ) -> Result<()> {
+        let min_order_len = if min_order_len == 0 { DEFAULT_MIN_ORDER_LEN } else { min_order_len };
+        let max_order_len = if max_order_len == 0 { DEFAULT_MAX_ORDER_LEN } else { max_order_len };
+        require!(
+            min_order_len <= max_order_len && max_order_len <= MAX_ORDER_LEN_LIMIT,
+            ConfidentialError::InvalidOrderLenRange
+        );
+
         let book = &mut ctx.accounts.order_book;
         book.authority = ctx.accounts.authority.key();
         book.order_count = 0;
         book.settled_count = 0;
+        book.failed_count = 0;
         book.bump = ctx.bumps.order_book;
-        msg!("Confidential order book initialized");
+        book.fee_bps = 0;
+        book.maker_fee_bps = 0;
+        book.maker_volume = 0;
+        book.taker_volume = 0;
+        book.fees_accrued = 0;
+        book.recent_head = 0;
+        book.recent_orders = [RecentOrder::default(); RECENT_ORDERS_LEN];
+        book.min_order_len = min_order_len;
+        book.max_order_len = max_order_len;
+        book.submit_fee = submit_fee;
+        book.fee_collector = fee_collector;
+        book.min_schema_version = DEFAULT_SCHEMA_VERSION;
+        book.max_schema_version = DEFAULT_SCHEMA_VERSION;
+        book.pending_count = 0;
+        book.max_pending = 0;
+        book.sweep_bounty = 0;
+        book.sweep_pool = 0;
+        book.created_at = makora_clock::checked_now_since_genesis()?;
+        book.cluster_authority = cluster_authority;
+        msg!(
+            "Confidential order book initialized — order size {}..{} bytes, submit fee {} lamports",
+            min_order_len,
+            max_order_len,
+            submit_fee
+        );
+        Ok(())
+    }
+
+    /// Set the settlement fees for takers and makers.
+    ///
+    /// Makers (orders flagged `is_maker`) pay `maker_fee_bps`, which may be
+    /// reduced or zero to incentivize liquidity; takers pay `fee_bps`.
+    pub fn set_order_book_fees(
+        ctx: Context<SetOrderBookFees>,
+        fee_bps: u16,
+        maker_fee_bps: u16,
+    ) -> Result<()> {
+        require!(fee_bps <= MAX_FEE_BPS, ConfidentialError::FeeTooHigh);
+        require!(maker_fee_bps <= fee_bps, ConfidentialError::InvalidMakerFee);
+
+        let book = &mut ctx.accounts.order_book;
+        book.fee_bps = fee_bps;
+        book.maker_fee_bps = maker_fee_bps;
+
+        msg!(
+            "Order book fees set — taker: {} bps, maker: {} bps",
+            fee_bps,
+            maker_fee_bps
+        );
+        Ok(())
+    }
+
+    /// Set the range of Arcis order schema versions the book accepts.
+    ///
+    /// Widen it to roll out a new `SwapOrder` layout while old clients
+    /// drain, then raise the minimum to retire the old one.
+    pub fn set_schema_versions(
+        ctx: Context<SetSchemaVersions>,
+        min_schema_version: u16,
+        max_schema_version: u16,
+    ) -> Result<()> {
+        require!(
+            min_schema_version > 0 && min_schema_version <= max_schema_version,
+            ConfidentialError::InvalidSchemaRange
+        );
+
+        let book = &mut ctx.accounts.order_book;
+        book.min_schema_version = min_schema_version;
+        book.max_schema_version = max_schema_version;
+
+        msg!(
+            "Order schema versions set — {}..={}",
+            min_schema_version,
+            max_schema_version
+        );
+        Ok(())
+    }
+
+    /// Cap the number of in-flight orders on the book (0 = unlimited).
+    ///
+    /// An order is in flight from submission until it settles, fails or
+    /// expires; submissions beyond the cap are rejected so one owner cannot
+    /// flood the cluster.
+    pub fn set_max_pending(ctx: Context<SetMaxPending>, max_pending: u32) -> Result<()> {
+        require!(
+            max_pending <= MAX_PENDING_LIMIT,
+            ConfidentialError::InvalidMaxPending
+        );
+
+        ctx.accounts.order_book.max_pending = max_pending;
+
+        msg!("Order book max pending set — {}", max_pending);
+        Ok(())
+    }
+
+    /// Set the bounty paid to whoever sweeps an expired order
+    /// (0 = no bounty, at most `MAX_SWEEP_BOUNTY`).
+    pub fn set_sweep_bounty(ctx: Context<SetSweepBounty>, sweep_bounty: u64) -> Result<()> {
+        require!(
+            sweep_bounty <= MAX_SWEEP_BOUNTY,
+            ConfidentialError::InvalidSweepBounty
+        );
+
+        ctx.accounts.order_book.sweep_bounty = sweep_bounty;
+
+        msg!("Order book sweep bounty set — {} lamports", sweep_bounty);
+        Ok(())
+    }
+
+    /// Deposit `amount` lamports into the book's sweep pool, from which
+    /// sweep bounties are paid. Anyone may fund it.
+    pub fn fund_sweep_pool(ctx: Context<FundSweepPool>, amount: u64) -> Result<()> {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.funder.to_account_info(),
+                    to: ctx.accounts.order_book.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let book = &mut ctx.accounts.order_book;
+        book.sweep_pool = book.sweep_pool.checked_add(amount).unwrap();
+
+        msg!("Sweep pool funded — {} lamports ({} total)", amount, book.sweep_pool);
         Ok(())
     }
 
@@ -35,15 +231,74 @@ pub mod makora_confidential {
     ///
     /// The `encrypted_order` contains Enc<Shared, SwapOrder> — the order
     /// is only readable by the Arcium MPC cluster, not by validators.
+    ///
+    /// `timeout_secs` bounds how long the cluster has to post its result
+    /// (0 = default, clamped to `MAX_CALLBACK_TIMEOUT_SECS`).
+    ///
+    /// The book's `submit_fee` is transferred from the owner to its
+    /// `fee_collector`; if the owner cannot cover it the submission fails
+    /// and the order account is never created.
+    ///
+    /// `reference_quote` is the public-market output the client was quoted
+    /// for the same trade (0 = none); settlement reports the MPC fill's
+    /// improvement over it.
+    ///
+    /// `schema_version` tags the Arcis layout the ciphertext was encrypted
+    /// with, so the cluster can decode it; it must be within the book's
+    /// accepted range.
+    #[allow(clippy::too_many_arguments)]
     pub fn submit_confidential_swap(
         ctx: Context<SubmitConfidentialSwap>,
         encrypted_order: Vec<u8>,
         client_pubkey: [u8; 32],
         nonce: [u8; 12],
         computation_id: [u8; 32],
+        mxe_id: [u8; 32],
+        output_destination: Pubkey,
+        is_maker: bool,
+        timeout_secs: u32,
+        reference_quote: u64,
+        schema_version: u16,
     ) -> Result<()> {
-        require!(encrypted_order.len() <= 512, ConfidentialError::OrderTooLarge);
-        require!(encrypted_order.len() >= 32, ConfidentialError::OrderTooSmall);
+        let book = &ctx.accounts.order_book;
+        require!(
+            book.accepts_schema(schema_version),
+            ConfidentialError::UnsupportedSchemaVersion
+        );
+        require!(
+            encrypted_order.len() <= book.max_order_len as usize,
+            ConfidentialError::OrderTooLarge
+        );
+        require!(
+            encrypted_order.len() >= book.min_order_len as usize,
+            ConfidentialError::OrderTooSmall
+        );
+        // A ciphertext that is one repeated byte (e.g. all zeros) can never
+        // decrypt to a real order — reject it before it reaches the cluster
+        require!(
+            encrypted_order.iter().any(|&b| b != encrypted_order[0]),
+            ConfidentialError::EmptyCiphertext
+        );
+        require!(client_pubkey != [0u8; 32], ConfidentialError::InvalidClientPubkey);
+        require!(book.can_accept_order(), ConfidentialError::TooManyPendingOrders);
+
+        let submit_fee = book.submit_fee;
+        if submit_fee > 0 {
+            require!(
+                ctx.accounts.owner.lamports() >= submit_fee,
+                ConfidentialError::FeePaymentFailed
+            );
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.owner.to_account_info(),
+                        to: ctx.accounts.fee_collector.to_account_info(),
+                    },
+                ),
+                submit_fee,
+            )?;
+        }
 
         let order = &mut ctx.accounts.swap_order;
         order.owner = ctx.accounts.owner.key();
@@ -51,13 +306,52 @@ pub mod makora_confidential {
         order.client_pubkey = client_pubkey;
         order.nonce = nonce;
         order.computation_id = computation_id;
+        order.mxe_id = mxe_id;
+        order.output_destination = output_destination;
+        order.is_maker = is_maker;
         order.status = OrderStatus::Pending;
-        order.submitted_at = Clock::get()?.unix_timestamp;
+        // submitted_at and expires_at gate the callback, so reject a bad clock
+        let submitted_at =
+            makora_clock::checked_now(book.created_at, makora_clock::DEFAULT_MAX_AGE_SECS)?;
+        order.submitted_at = submitted_at;
+        let timeout_secs = match timeout_secs {
+            0 => DEFAULT_CALLBACK_TIMEOUT_SECS,
+            t => t.min(MAX_CALLBACK_TIMEOUT_SECS),
+        };
+        order.expires_at = submitted_at + timeout_secs as i64;
         order.settled_at = 0;
+        order.output_amount = 0;
+        order.filled_amount = 0;
+        order.remaining_amount = 0;
+        order.route_label = [0u8; 32];
+        order.output_mint = Pubkey::default();
+        order.output_decimals = 0;
+        order.failure_reason = 0;
+        order.is_confidential = true;
+        order.reference_quote = reference_quote;
+        order.schema_version = schema_version;
         order.bump = ctx.bumps.swap_order;
 
         let book = &mut ctx.accounts.order_book;
+        // The cluster dedups by computation id per book
+        ctx.accounts.computation_record.mark(
+            book.key(),
+            computation_id,
+            submitted_at,
+            ctx.bumps.computation_record,
+        )?;
         book.order_count = book.order_count.checked_add(1).unwrap();
+        book.pending_count = book.pending_count.checked_add(1).unwrap();
+        book.record_order(computation_id);
+
+        emit!(OrderSubmitted {
+            order: order.key(),
+            owner: order.owner,
+            computation_id_prefix: computation_id_prefix(&computation_id),
+            is_maker,
+            is_confidential: true,
+            submitted_at,
+        });
 
         msg!(
             "Confidential swap submitted — computation_id: {:?}",
@@ -66,37 +360,367 @@ pub mod makora_confidential {
         Ok(())
     }
 
+    /// Record a non-confidential swap routed without MPC (the standard
+    /// Jupiter fallback).
+    ///
+    /// `order_data` is the plaintext order as sent to the router. The order
+    /// starts Pending like a confidential one and is settled by the same
+    /// `execute_swap_callback` / `complete_settlement` path, with the router
+    /// acting as the settling authority. `computation_id` is the client's
+    /// order id and shares the book's spent-id records with confidential orders.
+    pub fn submit_public_swap(
+        ctx: Context<SubmitPublicSwap>,
+        order_data: Vec<u8>,
+        computation_id: [u8; 32],
+        mxe_id: [u8; 32],
+        output_destination: Pubkey,
+        is_maker: bool,
+        timeout_secs: u32,
+    ) -> Result<()> {
+        let book = &ctx.accounts.order_book;
+        require!(
+            order_data.len() <= book.max_order_len as usize,
+            ConfidentialError::OrderTooLarge
+        );
+        require!(!order_data.is_empty(), ConfidentialError::OrderTooSmall);
+        require!(book.can_accept_order(), ConfidentialError::TooManyPendingOrders);
+
+        let order = &mut ctx.accounts.swap_order;
+        order.owner = ctx.accounts.owner.key();
+        order.encrypted_order = order_data;
+        order.client_pubkey = [0u8; 32];
+        order.nonce = [0u8; 12];
+        order.computation_id = computation_id;
+        order.mxe_id = mxe_id;
+        order.output_destination = output_destination;
+        order.is_maker = is_maker;
+        order.status = OrderStatus::Pending;
+        let submitted_at =
+            makora_clock::checked_now(book.created_at, makora_clock::DEFAULT_MAX_AGE_SECS)?;
+        order.submitted_at = submitted_at;
+        let timeout_secs = match timeout_secs {
+            0 => DEFAULT_CALLBACK_TIMEOUT_SECS,
+            t => t.min(MAX_CALLBACK_TIMEOUT_SECS),
+        };
+        order.expires_at = submitted_at + timeout_secs as i64;
+        order.settled_at = 0;
+        order.output_amount = 0;
+        order.filled_amount = 0;
+        order.remaining_amount = 0;
+        order.route_label = [0u8; 32];
+        order.output_mint = Pubkey::default();
+        order.output_decimals = 0;
+        order.failure_reason = 0;
+        order.is_confidential = false;
+        order.reference_quote = 0;
+        order.schema_version = 0;
+        order.bump = ctx.bumps.swap_order;
+
+        let book = &mut ctx.accounts.order_book;
+        ctx.accounts.computation_record.mark(
+            book.key(),
+            computation_id,
+            submitted_at,
+            ctx.bumps.computation_record,
+        )?;
+        book.order_count = book.order_count.checked_add(1).unwrap();
+        book.pending_count = book.pending_count.checked_add(1).unwrap();
+        book.record_order(computation_id);
+
+        emit!(OrderSubmitted {
+            order: order.key(),
+            owner: order.owner,
+            computation_id_prefix: computation_id_prefix(&computation_id),
+            is_maker,
+            is_confidential: false,
+            submitted_at,
+        });
+
+        msg!(
+            "Public swap submitted — computation_id: {:?}",
+            &computation_id[..8]
+        );
+        Ok(())
+    }
+
     /// Callback from Arcium MPC after computation finalization.
     ///
     /// The MPC cluster decrypted the order, validated the swap parameters,
     /// computed the optimal route, and returns the encrypted settlement result.
-    /// Only the cluster authority can call this instruction.
+    /// Only the cluster authority can call this instruction, and it must
+    /// report the same MXE the client bound the order to at submission.
+    /// Settlement proceeds may only go to the order's output destination.
+    /// The cluster also reports the route's venue label and the output
+    /// mint and decimals, kept on the order alongside the output amount as
+    /// the settlement record.
+    ///
+    /// This only records the MPC result: the order moves to
+    /// `SettlementPending` until `complete_settlement` confirms the transfer.
+    ///
+    /// A route may fill the order in several pieces. Each call reports the
+    /// output of one fill and the `remaining_amount` still unfilled; while
+    /// it is nonzero the order stays `PartiallyFilled` and the callback may
+    /// be called again. The fill that brings it to zero moves the order to
+    /// `SettlementPending`.
+    ///
+    /// `result_nonce` must differ from the nonce the order was submitted
+    /// with, since both are used with the same client key.
+    ///
+    /// With `destination_vault` (plus `vault_program` and `system_program`),
+    /// the cluster also forwards the SOL output into that Makora vault,
+    /// which counts it toward the vault's total_deposited.
+    #[allow(clippy::too_many_arguments)]
     pub fn execute_swap_callback(
         ctx: Context<ExecuteSwapCallback>,
         encrypted_result: Vec<u8>,
         result_nonce: [u8; 12],
         output_amount: u64,
+        remaining_amount: u64,
+        mxe_id: [u8; 32],
+        route_label: [u8; 32],
+        output_mint: Pubkey,
+        output_decimals: u8,
     ) -> Result<()> {
         let order = &mut ctx.accounts.swap_order;
         require!(
-            order.status == OrderStatus::Pending,
+            matches!(order.status, OrderStatus::Pending | OrderStatus::PartiallyFilled),
             ConfidentialError::OrderNotPending
         );
+        // Later fills must make progress and settle into the same mint
+        if order.status == OrderStatus::PartiallyFilled {
+            require!(
+                remaining_amount < order.remaining_amount && output_mint == order.output_mint,
+                ConfidentialError::InvalidFill
+            );
+        }
+        require!(order.mxe_id == mxe_id, ConfidentialError::MxeMismatch);
+        // The result is encrypted under the client's shared key; reusing
+        // the request nonce would expose both plaintexts
+        require!(result_nonce != order.nonce, ConfidentialError::NonceReuse);
+        require!(route_label != [0u8; 32], ConfidentialError::EmptyRoute);
+        // A late result is not finalized; the order must be expired instead
+        require!(
+            Clock::get()?.unix_timestamp <= order.expires_at,
+            ConfidentialError::OrderExpired
+        );
+
+        // Vault-transfer branch: the cluster forwards the SOL output into
+        // the destination vault, which records it as a deposit
+        let accounts = &ctx.accounts;
+        match (&accounts.destination_vault, &accounts.vault_program, &accounts.system_program) {
+            (Some(vault), Some(vault_program), Some(system_program)) => {
+                require!(
+                    output_mint == vault_cpi::NATIVE_MINT,
+                    ConfidentialError::VaultSettlementNotSol
+                );
+                vault_cpi::credit_vault(
+                    vault_program,
+                    &accounts.cluster_authority.to_account_info(),
+                    vault,
+                    &system_program.to_account_info(),
+                    output_amount,
+                )?;
+            }
+            (None, None, None) => {}
+            _ => return err!(ConfidentialError::InvalidVaultAccounts),
+        }
 
-        order.status = OrderStatus::Settled;
-        order.settled_at = Clock::get()?.unix_timestamp;
+        let order = &mut ctx.accounts.swap_order;
+        let next = if remaining_amount == 0 {
+            OrderStatus::SettlementPending
+        } else {
+            OrderStatus::PartiallyFilled
+        };
+        order.status.transition_to(next)?;
+        order.filled_amount = order
+            .filled_amount
+            .checked_add(output_amount)
+            .ok_or(ConfidentialError::InvalidFill)?;
+        order.remaining_amount = remaining_amount;
+        order.output_amount = order.filled_amount;
+        order.route_label = route_label;
+        order.output_mint = output_mint;
+        order.output_decimals = output_decimals;
+        ctx.accounts
+            .order_book
+            .mark_order(&order.computation_id, next);
+
+        msg!(
+            "Confidential swap fill posted — output: {} of mint {} ({} decimals), filled: {}, remaining: {}",
+            output_amount,
+            output_mint,
+            output_decimals,
+            order.filled_amount,
+            remaining_amount
+        );
+        Ok(())
+    }
+
+    /// Second phase of settlement: record that the output transfer to the
+    /// order's destination completed. Only valid from `SettlementPending`.
+    pub fn complete_settlement(ctx: Context<CompleteSettlement>) -> Result<()> {
+        let order = &mut ctx.accounts.swap_order;
+        order.status.transition_to(OrderStatus::Settled)?;
+        let settled_at = Clock::get()?.unix_timestamp;
+        order.settled_at = settled_at;
+        let output_amount = order.output_amount;
 
         let book = &mut ctx.accounts.order_book;
         book.settled_count = book.settled_count.checked_add(1).unwrap();
+        book.pending_count = book.pending_count.saturating_sub(1);
+        book.mark_order(&order.computation_id, OrderStatus::Settled);
+
+        // Makers and takers are charged and tracked separately
+        let fee_bps = if order.is_maker { book.maker_fee_bps } else { book.fee_bps };
+        let fee = (output_amount as u128 * fee_bps as u128 / 10_000) as u64;
+        if order.is_maker {
+            book.maker_volume = book.maker_volume.saturating_add(output_amount);
+        } else {
+            book.taker_volume = book.taker_volume.saturating_add(output_amount);
+        }
+        book.fees_accrued = book.fees_accrued.saturating_add(fee);
+
+        emit!(OrderSettled {
+            order: order.key(),
+            owner: order.owner,
+            computation_id_prefix: computation_id_prefix(&order.computation_id),
+            output_amount,
+            fee,
+            reference_quote: order.reference_quote,
+            improvement_bps: order.improvement_bps(),
+            submitted_at: order.submitted_at,
+            settled_at,
+        });
+
+        msg!(
+            "Confidential swap settled — output: {} lamports, fee: {} ({})",
+            output_amount,
+            fee,
+            if order.is_maker { "maker" } else { "taker" }
+        );
+        Ok(())
+    }
+
+    /// Callback from Arcium MPC when the computation failed.
+    ///
+    /// Moves a `Pending` or `PartiallyFilled` order to `Failed` with the
+    /// cluster's reason code, so clients can tell a rejected order from one
+    /// still in flight, and frees its in-flight slot on the book.
+    pub fn fail_confidential_swap(ctx: Context<FailConfidentialSwap>, reason: u8) -> Result<()> {
+        let order = &mut ctx.accounts.swap_order;
+        require!(
+            matches!(order.status, OrderStatus::Pending | OrderStatus::PartiallyFilled),
+            ConfidentialError::OrderNotPending
+        );
+
+        order.status.transition_to(OrderStatus::Failed)?;
+        order.failure_reason = reason;
+        let failed_at = Clock::get()?.unix_timestamp;
+
+        let book = &mut ctx.accounts.order_book;
+        book.failed_count = book.failed_count.checked_add(1).unwrap();
+        book.pending_count = book.pending_count.saturating_sub(1);
+        book.mark_order(&order.computation_id, OrderStatus::Failed);
+
+        emit!(OrderFailed {
+            order: order.key(),
+            owner: order.owner,
+            computation_id_prefix: computation_id_prefix(&order.computation_id),
+            reason,
+            submitted_at: order.submitted_at,
+            failed_at,
+        });
+
+        msg!("Confidential swap failed — reason: {}", reason);
+        Ok(())
+    }
+
+    /// Mark a `Pending` or `PartiallyFilled` order whose callback deadline
+    /// has passed as `Expired`, freeing its in-flight slot on the order book.
+    ///
+    /// The cluster can no longer finalize such an order, so anyone may
+    /// crank it.
+    pub fn expire_confidential_swap(ctx: Context<ExpireConfidentialSwap>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        expire_order(&mut ctx.accounts.swap_order, &mut ctx.accounts.order_book, now)
+    }
+
+    /// Expire a `Pending` or `PartiallyFilled` order past its deadline, or
+    /// take one already `Expired`, and close it: the rent goes back to the order owner and
+    /// the caller is paid the book's `sweep_bounty` from its sweep pool
+    /// (less if the pool runs low).
+    ///
+    /// Permissionless, so anyone can keep the book tidy.
+    pub fn sweep_expired_order(ctx: Context<SweepExpiredOrder>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        match ctx.accounts.swap_order.status {
+            OrderStatus::Pending | OrderStatus::PartiallyFilled => {
+                expire_order(&mut ctx.accounts.swap_order, &mut ctx.accounts.order_book, now)?
+            }
+            OrderStatus::Expired => {}
+            _ => return err!(ConfidentialError::OrderNotPending),
+        }
+
+        let book = &mut ctx.accounts.order_book;
+        let bounty = book.sweep_bounty.min(book.sweep_pool);
+        if bounty > 0 {
+            book.sweep_pool -= bounty;
+            let book_info = book.to_account_info();
+            let cranker_info = ctx.accounts.cranker.to_account_info();
+            **book_info.try_borrow_mut_lamports()? -= bounty;
+            **cranker_info.try_borrow_mut_lamports()? += bounty;
+        }
+
+        // The order account itself is closed to the owner on exit
+        let order = &ctx.accounts.swap_order;
+        let rent_refunded = order.to_account_info().lamports();
+
+        emit!(OrderSwept {
+            order: order.key(),
+            owner: order.owner,
+            computation_id_prefix: computation_id_prefix(&order.computation_id),
+            cranker: ctx.accounts.cranker.key(),
+            bounty,
+            rent_refunded,
+            swept_at: now,
+        });
 
         msg!(
-            "Confidential swap settled — output: {} lamports",
-            output_amount
+            "Expired swap swept — rent {} lamports to owner, bounty {} lamports",
+            rent_refunded,
+            bounty
         );
         Ok(())
     }
 }
 
+/// Move a `Pending` or `PartiallyFilled` order whose deadline has passed
+/// to `Expired` and release its in-flight slot on the book. Fills already
+/// posted stay recorded on the order.
+fn expire_order(order: &mut Account<SwapOrder>, book: &mut OrderBook, now: i64) -> Result<()> {
+    require!(
+        matches!(order.status, OrderStatus::Pending | OrderStatus::PartiallyFilled),
+        ConfidentialError::OrderNotPending
+    );
+    require!(now > order.expires_at, ConfidentialError::OrderNotExpired);
+
+    order.status.transition_to(OrderStatus::Expired)?;
+
+    book.pending_count = book.pending_count.saturating_sub(1);
+    book.mark_order(&order.computation_id, OrderStatus::Expired);
+
+    emit!(OrderExpired {
+        order: order.key(),
+        owner: order.owner,
+        computation_id_prefix: computation_id_prefix(&order.computation_id),
+        submitted_at: order.submitted_at,
+        expired_at: now,
+    });
+
+    msg!("Confidential swap expired — computation_id: {:?}", &order.computation_id[..8]);
+    Ok(())
+}
+
 // ─── Accounts ────────────────────────────────────────────────────────────────
 
 #[derive(Accounts)]
@@ -108,7 +732,7 @@ pub struct InitOrderBook<'info> {
         seeds = [b"order_book", authority.key().as_ref()],
         bump,
     )]
-    pub order_book: Account<'info, OrderBook>,
+    pub order_book: Box<Account<'info, OrderBook>>,
 
     #[account(mut)]
     pub authority: Signer<'info>,
@@ -117,7 +741,46 @@ pub struct InitOrderBook<'info> {
 }
 
 #[derive(Accounts)]
-#[instruction(encrypted_order: Vec<u8>, client_pubkey: [u8; 32], nonce: [u8; 12], computation_id: [u8; 32])]
+pub struct SetOrderBookFees<'info> {
+    #[account(
+        mut,
+        seeds = [b"order_book", authority.key().as_ref()],
+        bump = order_book.bump,
+        has_one = authority,
+    )]
+    pub order_book: Box<Account<'info, OrderBook>>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetSchemaVersions<'info> {
+    #[account(
+        mut,
+        seeds = [b"order_book", authority.key().as_ref()],
+        bump = order_book.bump,
+        has_one = authority,
+    )]
+    pub order_book: Box<Account<'info, OrderBook>>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetMaxPending<'info> {
+    #[account(
+        mut,
+        seeds = [b"order_book", authority.key().as_ref()],
+        bump = order_book.bump,
+        has_one = authority,
+    )]
+    pub order_book: Box<Account<'info, OrderBook>>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(encrypted_order: Vec<u8>, client_pubkey: [u8; 32], nonce: [u8; 12], computation_id: [u8; 32], mxe_id: [u8; 32], output_destination: Pubkey, is_maker: bool, timeout_secs: u32, reference_quote: u64, schema_version: u16)]
 pub struct SubmitConfidentialSwap<'info> {
     #[account(
         init,
@@ -133,7 +796,61 @@ pub struct SubmitConfidentialSwap<'info> {
         seeds = [b"order_book", owner.key().as_ref()],
         bump = order_book.bump,
     )]
-    pub order_book: Account<'info, OrderBook>,
+    pub order_book: Box<Account<'info, OrderBook>>,
+
+    /// Spent-id marker. Outlives both the order and the book's
+    /// recent-orders ring, so a computation id is never accepted twice.
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + ComputationRecord::INIT_SPACE,
+        seeds = [b"computation", order_book.key().as_ref(), &computation_id],
+        bump,
+    )]
+    pub computation_record: Account<'info, ComputationRecord>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: receives the submit fee; must be the book's fee collector
+    #[account(
+        mut,
+        address = order_book.fee_collector @ ConfidentialError::FeePaymentFailed,
+    )]
+    pub fee_collector: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(order_data: Vec<u8>, computation_id: [u8; 32])]
+pub struct SubmitPublicSwap<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + SwapOrder::INIT_SPACE + order_data.len(),
+        seeds = [b"swap_order", owner.key().as_ref(), &computation_id],
+        bump,
+    )]
+    pub swap_order: Account<'info, SwapOrder>,
+
+    #[account(
+        mut,
+        seeds = [b"order_book", owner.key().as_ref()],
+        bump = order_book.bump,
+    )]
+    pub order_book: Box<Account<'info, OrderBook>>,
+
+    /// Spent-id marker. Outlives both the order and the book's
+    /// recent-orders ring, so a computation id is never accepted twice.
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + ComputationRecord::INIT_SPACE,
+        seeds = [b"computation", order_book.key().as_ref(), &computation_id],
+        bump,
+    )]
+    pub computation_record: Account<'info, ComputationRecord>,
 
     #[account(mut)]
     pub owner: Signer<'info>,
@@ -154,16 +871,174 @@ pub struct ExecuteSwapCallback<'info> {
         seeds = [b"order_book", owner.key().as_ref()],
         bump = order_book.bump,
     )]
-    pub order_book: Account<'info, OrderBook>,
+    pub order_book: Box<Account<'info, OrderBook>>,
+
+    /// The order owner (for PDA derivation).
+    /// CHECK: Validated via has_one on swap_order.
+    pub owner: UncheckedAccount<'info>,
+
+    /// Token/SOL account receiving the settled output.
+    /// CHECK: Must match the destination the owner chose at submission.
+    #[account(
+        mut,
+        constraint = output_destination.key() == swap_order.output_destination @ ConfidentialError::OutputDestinationMismatch,
+    )]
+    pub output_destination: UncheckedAccount<'info>,
+
+    /// The Arcium cluster authority — only it can finalize computations.
+    /// Funds the vault credit when settling into a vault.
+    #[account(
+        mut,
+        address = order_book.cluster_authority @ ConfidentialError::UnauthorizedCluster,
+    )]
+    pub cluster_authority: Signer<'info>,
+
+    /// Makora vault to credit with the output; must be the order's
+    /// output destination. Passed with vault_program and system_program.
+    /// CHECK: Deserialized and PDA-checked by the vault program.
+    #[account(
+        mut,
+        constraint = destination_vault.key() == swap_order.output_destination @ ConfidentialError::OutputDestinationMismatch,
+    )]
+    pub destination_vault: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Must be the makora_vault program.
+    #[account(address = vault_cpi::VAULT_PROGRAM_ID)]
+    pub vault_program: Option<UncheckedAccount<'info>>,
+
+    pub system_program: Option<Program<'info, System>>,
+}
+
+#[derive(Accounts)]
+pub struct FailConfidentialSwap<'info> {
+    #[account(
+        mut,
+        has_one = owner,
+    )]
+    pub swap_order: Account<'info, SwapOrder>,
+
+    #[account(
+        mut,
+        seeds = [b"order_book", owner.key().as_ref()],
+        bump = order_book.bump,
+    )]
+    pub order_book: Box<Account<'info, OrderBook>>,
+
+    /// The order owner (for PDA derivation).
+    /// CHECK: Validated via has_one on swap_order.
+    pub owner: UncheckedAccount<'info>,
+
+    /// The Arcium cluster authority — only it can finalize computations.
+    #[account(address = order_book.cluster_authority @ ConfidentialError::UnauthorizedCluster)]
+    pub cluster_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CompleteSettlement<'info> {
+    #[account(
+        mut,
+        has_one = owner,
+    )]
+    pub swap_order: Account<'info, SwapOrder>,
+
+    #[account(
+        mut,
+        seeds = [b"order_book", owner.key().as_ref()],
+        bump = order_book.bump,
+    )]
+    pub order_book: Box<Account<'info, OrderBook>>,
 
     /// The order owner (for PDA derivation).
     /// CHECK: Validated via has_one on swap_order.
     pub owner: UncheckedAccount<'info>,
 
+    /// Token/SOL account that received the settled output.
+    /// CHECK: Must match the destination the owner chose at submission.
+    #[account(
+        mut,
+        constraint = output_destination.key() == swap_order.output_destination @ ConfidentialError::OutputDestinationMismatch,
+    )]
+    pub output_destination: UncheckedAccount<'info>,
+
     /// The Arcium cluster authority — only it can finalize computations.
+    #[account(address = order_book.cluster_authority @ ConfidentialError::UnauthorizedCluster)]
     pub cluster_authority: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct ExpireConfidentialSwap<'info> {
+    #[account(
+        mut,
+        has_one = owner,
+    )]
+    pub swap_order: Account<'info, SwapOrder>,
+
+    #[account(
+        mut,
+        seeds = [b"order_book", owner.key().as_ref()],
+        bump = order_book.bump,
+    )]
+    pub order_book: Box<Account<'info, OrderBook>>,
+
+    /// The order owner (for PDA derivation).
+    /// CHECK: Validated via has_one on swap_order.
+    pub owner: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetSweepBounty<'info> {
+    #[account(
+        mut,
+        seeds = [b"order_book", authority.key().as_ref()],
+        bump = order_book.bump,
+        has_one = authority,
+    )]
+    pub order_book: Box<Account<'info, OrderBook>>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FundSweepPool<'info> {
+    #[account(
+        mut,
+        seeds = [b"order_book", order_book.authority.as_ref()],
+        bump = order_book.bump,
+    )]
+    pub order_book: Box<Account<'info, OrderBook>>,
+
+    #[account(mut)]
+    pub funder: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SweepExpiredOrder<'info> {
+    #[account(
+        mut,
+        has_one = owner,
+        close = owner,
+    )]
+    pub swap_order: Account<'info, SwapOrder>,
+
+    #[account(
+        mut,
+        seeds = [b"order_book", owner.key().as_ref()],
+        bump = order_book.bump,
+    )]
+    pub order_book: Box<Account<'info, OrderBook>>,
+
+    /// The order owner; receives the order's rent.
+    /// CHECK: Validated via has_one on swap_order.
+    #[account(mut)]
+    pub owner: UncheckedAccount<'info>,
+
+    /// Whoever cranks the sweep; receives the bounty
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+}
+
 // ─── State ───────────────────────────────────────────────────────────────────
 
 #[account]
@@ -172,42 +1047,281 @@ pub struct OrderBook {
     pub authority: Pubkey,
     pub order_count: u64,
     pub settled_count: u64,
+    /// Orders the MPC cluster reported as failed
+    pub failed_count: u64,
+    pub bump: u8,
+    /// Settlement fee for taker orders (bps)
+    pub fee_bps: u16,
+    /// Settlement fee for maker orders (bps, <= fee_bps)
+    pub maker_fee_bps: u16,
+    /// Cumulative settled output of maker orders
+    pub maker_volume: u64,
+    /// Cumulative settled output of taker orders
+    pub taker_volume: u64,
+    /// Cumulative settlement fees charged
+    pub fees_accrued: u64,
+    /// Next write position in `recent_orders`
+    pub recent_head: u8,
+    /// Ring buffer of the most recently submitted orders, so clients can
+    /// enumerate them without knowing every computation id
+    pub recent_orders: [RecentOrder; RECENT_ORDERS_LEN],
+    /// Smallest accepted encrypted order (bytes)
+    pub min_order_len: u16,
+    /// Largest accepted encrypted order (bytes)
+    pub max_order_len: u16,
+    /// Flat fee (lamports) charged per confidential submission
+    pub submit_fee: u64,
+    /// Account that receives `submit_fee`
+    pub fee_collector: Pubkey,
+    /// Oldest Arcis order schema version accepted
+    pub min_schema_version: u16,
+    /// Newest Arcis order schema version accepted
+    pub max_schema_version: u16,
+    /// Orders submitted but not yet settled, failed or expired
+    pub pending_count: u32,
+    /// Cap on `pending_count` (0 = unlimited)
+    pub max_pending: u32,
+    /// Lamports paid per swept expired order (0 = no bounty)
+    pub sweep_bounty: u64,
+    /// Lamports held by the book to pay sweep bounties, on top of its rent
+    pub sweep_pool: u64,
+    /// When the book was initialized; the reference for order timestamps
+    pub created_at: i64,
+    /// Arcium cluster key allowed to finalize this book's computations
+    pub cluster_authority: Pubkey,
+}
+
+impl OrderBook {
+    /// True if confidential orders encoded with `schema_version` are accepted.
+    pub fn accepts_schema(&self, schema_version: u16) -> bool {
+        (self.min_schema_version..=self.max_schema_version).contains(&schema_version)
+    }
+
+    /// True if another order fits under the book's in-flight cap.
+    pub fn can_accept_order(&self) -> bool {
+        self.max_pending == 0 || self.pending_count < self.max_pending
+    }
+
+    /// Index a newly submitted order, overwriting the oldest entry when full.
+    pub fn record_order(&mut self, computation_id: [u8; 32]) {
+        let idx = self.recent_head as usize % RECENT_ORDERS_LEN;
+        self.recent_orders[idx] = RecentOrder {
+            computation_id,
+            status: OrderStatus::Pending,
+        };
+        self.recent_head = ((idx + 1) % RECENT_ORDERS_LEN) as u8;
+    }
+
+    /// Update an indexed order's status. No-op if it has been evicted.
+    pub fn mark_order(&mut self, computation_id: &[u8; 32], status: OrderStatus) {
+        if let Some(entry) = self
+            .recent_orders
+            .iter_mut()
+            .find(|o| o.computation_id == *computation_id)
+        {
+            entry.status = status;
+        }
+    }
+}
+
+/// Marks a computation id as used in one order book. Never closed, unlike
+/// the SwapOrder, so the id stays spent after its order is swept.
+#[account]
+#[derive(InitSpace)]
+pub struct ComputationRecord {
+    pub order_book: Pubkey,
+    pub computation_id: [u8; 32],
+    pub used_at: i64,
     pub bump: u8,
 }
 
+impl ComputationRecord {
+    /// Claim the id for `order_book`, failing if it was claimed before.
+    pub fn mark(
+        &mut self,
+        order_book: Pubkey,
+        computation_id: [u8; 32],
+        now: i64,
+        bump: u8,
+    ) -> Result<()> {
+        require!(self.used_at == 0, ConfidentialError::DuplicateComputationId);
+        self.order_book = order_book;
+        self.computation_id = computation_id;
+        self.used_at = now;
+        self.bump = bump;
+        Ok(())
+    }
+}
+
+/// Entry in an order book's recent-orders index.
+/// A zero computation id marks an unused slot.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct RecentOrder {
+    pub computation_id: [u8; 32],
+    pub status: OrderStatus,
+}
+
+impl Default for RecentOrder {
+    fn default() -> Self {
+        Self {
+            computation_id: [0u8; 32],
+            status: OrderStatus::Pending,
+        }
+    }
+}
+
 #[account]
 #[derive(InitSpace)]
 pub struct SwapOrder {
     pub owner: Pubkey,
+    /// Order payload: Enc<Shared, SwapOrder> for confidential orders, the
+    /// plaintext router order for public ones
     #[max_len(512)]
     pub encrypted_order: Vec<u8>,
+    /// x25519 key and nonce of the encryption (zeroed for public orders)
     pub client_pubkey: [u8; 32],
     pub nonce: [u8; 12],
     pub computation_id: [u8; 32],
+    /// Arcium MXE the client expects to process this order
+    pub mxe_id: [u8; 32],
+    /// Account the settled output must be delivered to
+    pub output_destination: Pubkey,
+    /// Maker orders provide liquidity and pay the reduced maker fee
+    pub is_maker: bool,
     pub status: OrderStatus,
     pub submitted_at: i64,
+    /// Deadline for the MPC callback (submitted_at + timeout)
+    pub expires_at: i64,
     pub settled_at: i64,
+    /// Total output reported by the MPC cluster across all fills
+    pub output_amount: u64,
+    /// Output delivered so far by the fills posted to this order
+    pub filled_amount: u64,
+    /// Amount the cluster reports as still unfilled (0 once fully filled)
+    pub remaining_amount: u64,
+    /// Venue label of the route the MPC settled through (zero-padded UTF-8)
+    pub route_label: [u8; 32],
+    /// Mint of the settled output token (set at callback)
+    pub output_mint: Pubkey,
+    /// Decimals of `output_mint`, for presenting `output_amount`
+    pub output_decimals: u8,
+    /// Cluster-supplied reason code when status is Failed (0 otherwise)
+    pub failure_reason: u8,
+    /// False when the order went through the public (non-MPC) fallback
+    pub is_confidential: bool,
+    /// Public-market output quoted to the client at submission (0 = none)
+    pub reference_quote: u64,
+    /// Arcis schema version of `encrypted_order` (0 for public orders)
+    pub schema_version: u16,
     pub bump: u8,
 }
 
+impl SwapOrder {
+    /// Execution quality against the reference quote, in basis points:
+    /// `(output_amount - reference_quote) * 10_000 / reference_quote`.
+    /// Negative when the fill came in under the quote; 0 without a quote.
+    pub fn improvement_bps(&self) -> i64 {
+        if self.reference_quote == 0 {
+            return 0;
+        }
+        let diff = self.output_amount as i128 - self.reference_quote as i128;
+        let bps = diff * 10_000 / self.reference_quote as i128;
+        bps.clamp(i64::MIN as i128, i64::MAX as i128) as i64
+    }
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
 pub enum OrderStatus {
     Pending,
     Settled,
     Failed,
     Expired,
+    /// MPC result posted, fund transfer not yet completed.
+    /// Logically sits between Pending and Settled; declared last to keep
+    /// the existing variants' encoding stable.
+    SettlementPending,
+    /// Some fills posted, more to come. Sits between Pending and
+    /// SettlementPending; declared last for the same reason.
+    PartiallyFilled,
+}
+
+impl OrderStatus {
+    /// Move to `next`, allowing only forward transitions:
+    /// Pending -> [PartiallyFilled ->] SettlementPending -> Settled, and
+    /// Pending or PartiallyFilled -> Failed/Expired.
+    pub fn transition_to(&mut self, next: OrderStatus) -> Result<()> {
+        let allowed = matches!(
+            (*self, next),
+            (OrderStatus::Pending, OrderStatus::SettlementPending)
+                | (OrderStatus::Pending, OrderStatus::PartiallyFilled)
+                | (OrderStatus::PartiallyFilled, OrderStatus::PartiallyFilled)
+                | (OrderStatus::PartiallyFilled, OrderStatus::SettlementPending)
+                | (OrderStatus::SettlementPending, OrderStatus::Settled)
+                | (OrderStatus::Pending, OrderStatus::Failed)
+                | (OrderStatus::Pending, OrderStatus::Expired)
+                | (OrderStatus::PartiallyFilled, OrderStatus::Failed)
+                | (OrderStatus::PartiallyFilled, OrderStatus::Expired)
+        );
+        require!(allowed, ConfidentialError::InvalidStatusTransition);
+        *self = next;
+        Ok(())
+    }
 }
 
 // ─── Errors ──────────────────────────────────────────────────────────────────
 
 #[error_code]
 pub enum ConfidentialError {
-    #[msg("Encrypted order exceeds maximum size (512 bytes)")]
+    #[msg("Encrypted order exceeds the order book's maximum size")]
     OrderTooLarge,
     #[msg("Encrypted order too small to be valid")]
     OrderTooSmall,
+    #[msg("Encrypted order is empty or a single repeated byte")]
+    EmptyCiphertext,
+    #[msg("Client x25519 public key must not be zero")]
+    InvalidClientPubkey,
     #[msg("Order is not in Pending status")]
     OrderNotPending,
     #[msg("Unauthorized cluster authority")]
     UnauthorizedCluster,
+    #[msg("Settling MXE does not match the order's expected MXE")]
+    MxeMismatch,
+    #[msg("Settlement destination does not match the order's output destination")]
+    OutputDestinationMismatch,
+    #[msg("Settlement fee exceeds the maximum (100 bps)")]
+    FeeTooHigh,
+    #[msg("Maker fee cannot exceed the taker fee")]
+    InvalidMakerFee,
+    #[msg("Invalid order status transition")]
+    InvalidStatusTransition,
+    #[msg("Invalid order size range")]
+    InvalidOrderLenRange,
+    #[msg("Order callback deadline has passed")]
+    OrderExpired,
+    #[msg("Settlement route label must not be empty")]
+    EmptyRoute,
+    #[msg("Vault settlement needs destination_vault, vault_program and system_program together")]
+    InvalidVaultAccounts,
+    #[msg("Only SOL output can be settled into a vault")]
+    VaultSettlementNotSol,
+    #[msg("Fill must reduce the remaining amount and keep the output mint")]
+    InvalidFill,
+    #[msg("Computation id was already submitted to this order book")]
+    DuplicateComputationId,
+    #[msg("Submit fee could not be paid to the order book's fee collector")]
+    FeePaymentFailed,
+    #[msg("Order schema version is not accepted by this order book")]
+    UnsupportedSchemaVersion,
+    #[msg("Invalid order schema version range")]
+    InvalidSchemaRange,
+    #[msg("Order book has too many pending orders")]
+    TooManyPendingOrders,
+    #[msg("Max pending orders exceeds the limit")]
+    InvalidMaxPending,
+    #[msg("Order callback deadline has not passed yet")]
+    OrderNotExpired,
+    #[msg("Result nonce must differ from the order's request nonce")]
+    NonceReuse,
+    #[msg("Sweep bounty exceeds the maximum")]
+    InvalidSweepBounty,
 }
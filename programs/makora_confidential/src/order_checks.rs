@@ -0,0 +1,105 @@
+//! Pure checks the `confidential_swap` MPC computation runs on a decrypted
+//! order (see `encrypted-ixs/confidential_swap.rs`).
+//!
+//! The Arcis code is deployed with the arcium CLI and never built by cargo,
+//! so its plaintext-only logic lives here where it is compiled and tested,
+//! and the computation imports it from this crate.
+
+/// Tokens a confidential swap may route between
+pub const SUPPORTED_TOKENS: [&str; 9] = [
+    "SOL", "USDC", "BONK", "RAY", "JLP", "mSOL", "JitoSOL", "WBTC", "WETH",
+];
+
+/// Validate that a token pair is supported for confidential swaps.
+pub fn is_valid_token_pair(from: &str, to: &str) -> bool {
+    let from_ok = SUPPORTED_TOKENS.iter().any(|t| t == &from);
+    let to_ok = SUPPORTED_TOKENS.iter().any(|t| t == &to);
+    from_ok && to_ok && from != to
+}
+
+/// Why a quoted route was rejected against the client's bounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteRejection {
+    /// Output or impact is not a finite, non-negative number
+    InvalidQuote,
+    /// Quoted output is below the client's min_output
+    BelowMinOutput,
+    /// Quoted price impact is above the client's max_price_impact
+    PriceImpactTooHigh,
+}
+
+/// True if `output` meets `min_output` (a floor of 0 always passes).
+pub fn meets_min_output(output: f64, min_output: f64) -> bool {
+    min_output <= 0.0 || output >= min_output
+}
+
+/// True if `price_impact` is within `max_price_impact` (0 = no limit).
+pub fn within_price_impact(price_impact: f64, max_price_impact: f64) -> bool {
+    max_price_impact <= 0.0 || price_impact <= max_price_impact
+}
+
+/// Check a quoted route against the client's bounds.
+pub fn check_route_bounds(
+    output: f64,
+    price_impact: f64,
+    min_output: f64,
+    max_price_impact: f64,
+) -> Result<(), RouteRejection> {
+    if !output.is_finite() || !price_impact.is_finite() || output < 0.0 || price_impact < 0.0 {
+        return Err(RouteRejection::InvalidQuote);
+    }
+    if !meets_min_output(output, min_output) {
+        return Err(RouteRejection::BelowMinOutput);
+    }
+    if !within_price_impact(price_impact, max_price_impact) {
+        return Err(RouteRejection::PriceImpactTooHigh);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_supported_pairs_only() {
+        assert!(is_valid_token_pair("SOL", "USDC"));
+        assert!(!is_valid_token_pair("SOL", "SOL"));
+        assert!(!is_valid_token_pair("SOL", "DOGE"));
+    }
+
+    #[test]
+    fn accepts_route_within_bounds() {
+        assert_eq!(check_route_bounds(105.0, 0.4, 100.0, 0.5), Ok(()));
+        // Exactly at both limits
+        assert_eq!(check_route_bounds(100.0, 0.5, 100.0, 0.5), Ok(()));
+    }
+
+    #[test]
+    fn zero_bounds_disable_the_checks() {
+        assert_eq!(check_route_bounds(1.0, 12.0, 0.0, 0.0), Ok(()));
+    }
+
+    #[test]
+    fn rejects_output_below_floor() {
+        assert_eq!(
+            check_route_bounds(99.9, 0.1, 100.0, 0.5),
+            Err(RouteRejection::BelowMinOutput)
+        );
+    }
+
+    #[test]
+    fn rejects_excess_price_impact() {
+        assert_eq!(
+            check_route_bounds(150.0, 0.51, 100.0, 0.5),
+            Err(RouteRejection::PriceImpactTooHigh)
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_quotes() {
+        assert_eq!(check_route_bounds(f64::NAN, 0.1, 0.0, 0.0), Err(RouteRejection::InvalidQuote));
+        assert_eq!(check_route_bounds(100.0, f64::INFINITY, 0.0, 0.0), Err(RouteRejection::InvalidQuote));
+        assert_eq!(check_route_bounds(-1.0, 0.1, 0.0, 0.0), Err(RouteRejection::InvalidQuote));
+    }
+}
@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke;
+
+/// makora_vault program id.
+///
+/// This crate builds outside the main workspace and does not link the vault
+/// crate, so the `deposit_many` call is built by hand.
+pub const VAULT_PROGRAM_ID: Pubkey = pubkey!("BTAd1ghiv4jKd4kREh14jCtHrVG6zDFNgLRNoF9pUgqw");
+
+/// Wrapped SOL mint; vaults only hold native SOL
+pub const NATIVE_MINT: Pubkey = pubkey!("So11111111111111111111111111111111111111112");
+
+/// CPI into makora_vault `deposit_many` with a single vault, funded by
+/// `funder`. The vault program checks the account and its deposit cap, and
+/// credits `amount` to the vault's total_deposited.
+pub fn credit_vault<'info>(
+    vault_program: &AccountInfo<'info>,
+    funder: &AccountInfo<'info>,
+    vault: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let mut data = hash(b"global:deposit_many").to_bytes()[..8].to_vec();
+    vec![amount].serialize(&mut data)?;
+
+    // Account order follows makora_vault::DepositMany, then the vault as
+    // its one remaining account.
+    let ix = Instruction {
+        program_id: VAULT_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(funder.key(), true),
+            AccountMeta::new_readonly(system_program.key(), false),
+            AccountMeta::new(vault.key(), false),
+        ],
+        data,
+    };
+
+    invoke(
+        &ix,
+        &[
+            funder.clone(),
+            system_program.clone(),
+            vault.clone(),
+            vault_program.clone(),
+        ],
+    )?;
+
+    Ok(())
+}
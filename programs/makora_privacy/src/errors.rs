@@ -16,4 +16,42 @@ pub enum PrivacyError {
     NullifierAlreadyUsed,
     #[msg("Invalid zero-knowledge proof.")]
     InvalidProof,
+    #[msg("Unauthorized: signer is not the pool authority.")]
+    Unauthorized,
+    #[msg("Commitment already exists in the pool.")]
+    CommitmentExists,
+    #[msg("Amount is outside the pool's allowed range.")]
+    AmountOutOfRange,
+    #[msg("Relayer fee must be less than the withdrawn amount.")]
+    FeeExceedsAmount,
+    #[msg("Amount does not match any of the pool's denominations.")]
+    InvalidDenomination,
+    #[msg("Nullifier hash must not be zero.")]
+    InvalidNullifier,
+    #[msg("Merkle root must not be zero.")]
+    InvalidRoot,
+    #[msg("Merkle tree is full.")]
+    TreeFull,
+    #[msg("Proof root does not match a recent pool root.")]
+    UnknownRoot,
+    #[msg("Stealth payment is not claimable yet.")]
+    NotYetClaimable,
+    #[msg("Claim batch must hold between 1 and 10 stealth accounts.")]
+    InvalidBatchSize,
+    #[msg("Commitment input is not a valid BN254 field element.")]
+    InvalidFieldElement,
+    #[msg("Commitment does not match the provided openings.")]
+    CommitmentMismatch,
+    #[msg("Amount must equal the pool's fixed denomination.")]
+    WrongDenomination,
+    #[msg("Circuit version does not match the pool's current version.")]
+    CircuitVersionMismatch,
+    #[msg("Pool does not hold enough notes to unshield yet.")]
+    AnonymitySetTooSmall,
+    #[msg("Minimum anonymity set exceeds the tree's capacity.")]
+    InvalidAnonymitySet,
+    #[msg("Proof public amount does not match the unshielded amount.")]
+    PublicAmountMismatch,
+    #[msg("Proof does not commit to this recipient, relayer and fee.")]
+    ExtDataMismatch,
 }
@@ -1,6 +1,7 @@
 use anchor_lang::prelude::*;
 use crate::state::StealthAccount;
 use crate::errors::PrivacyError;
+use makora_escrow::transfer_lamports;
 
 #[derive(Accounts)]
 pub struct ClaimStealth<'info> {
@@ -15,6 +16,10 @@ pub struct ClaimStealth<'info> {
     #[account(mut)]
     pub recipient: Signer<'info>,
 
+    /// CHECK: Only receives lamports. Defaults to `recipient` when omitted.
+    #[account(mut)]
+    pub destination: Option<UncheckedAccount<'info>>,
+
     pub system_program: Program<'info, System>,
 }
 
@@ -22,26 +27,25 @@ pub fn handler(ctx: Context<ClaimStealth>) -> Result<()> {
     let amount = ctx.accounts.stealth_account.amount;
 
     require!(amount > 0, PrivacyError::InvalidAmount);
+    require!(
+        Clock::get()?.unix_timestamp >= ctx.accounts.stealth_account.claimable_after,
+        PrivacyError::NotYetClaimable
+    );
 
     // Get account infos before mutating
     let stealth_account_info = ctx.accounts.stealth_account.to_account_info();
-    let recipient_info = ctx.accounts.recipient.to_account_info();
-
-    // Transfer lamports from PDA to recipient
-    **stealth_account_info.try_borrow_mut_lamports()? = stealth_account_info
-        .lamports()
-        .checked_sub(amount)
-        .ok_or(PrivacyError::InsufficientPoolBalance)?;
+    let destination_info = match &ctx.accounts.destination {
+        Some(destination) => destination.to_account_info(),
+        None => ctx.accounts.recipient.to_account_info(),
+    };
 
-    **recipient_info.try_borrow_mut_lamports()? = recipient_info
-        .lamports()
-        .checked_add(amount)
-        .ok_or(PrivacyError::InvalidAmount)?;
+    // Transfer lamports from PDA to destination (the signer by default)
+    transfer_lamports(&stealth_account_info, &destination_info, amount, PrivacyError::InvalidAmount)?;
 
     // Mark as claimed
     ctx.accounts.stealth_account.claimed = true;
 
-    msg!("Stealth payment claimed: {} lamports", amount);
+    msg!("Stealth payment claimed: {} lamports to {}", amount, destination_info.key());
 
     Ok(())
 }
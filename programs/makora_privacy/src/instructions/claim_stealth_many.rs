@@ -0,0 +1,76 @@
+use anchor_lang::prelude::*;
+use crate::state::StealthAccount;
+use crate::errors::PrivacyError;
+use makora_escrow::transfer_lamports;
+
+/// Most stealth accounts one claim_stealth_many can sweep
+pub const MAX_CLAIM_BATCH: usize = 10;
+
+#[derive(Accounts)]
+pub struct ClaimStealthMany<'info> {
+    #[account(mut)]
+    pub recipient: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    // remaining_accounts: up to MAX_CLAIM_BATCH stealth PDAs (writable)
+}
+
+/// Claim several stealth payments to one recipient in a single transaction.
+/// With `skip_claimed`, already-claimed accounts are passed over instead of
+/// failing the batch. Any other problem aborts the whole batch.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ClaimStealthMany<'info>>,
+    skip_claimed: bool,
+) -> Result<()> {
+    let count = ctx.remaining_accounts.len();
+    require!(
+        count > 0 && count <= MAX_CLAIM_BATCH,
+        PrivacyError::InvalidBatchSize
+    );
+
+    let now = Clock::get()?.unix_timestamp;
+    let recipient_info = ctx.accounts.recipient.to_account_info();
+    let mut total: u64 = 0;
+    let mut claimed_count = 0;
+
+    for stealth_info in ctx.remaining_accounts.iter() {
+        // Checks owner program and discriminator
+        let mut stealth: Account<'info, StealthAccount> = Account::try_from(stealth_info)?;
+        let expected = Pubkey::create_program_address(
+            &[b"stealth", stealth.stealth_address.as_ref(), &[stealth.bump]],
+            ctx.program_id,
+        )
+        .map_err(|_| error!(PrivacyError::UnauthorizedClaim))?;
+        require_keys_eq!(stealth_info.key(), expected, PrivacyError::UnauthorizedClaim);
+
+        if stealth.claimed {
+            require!(skip_claimed, PrivacyError::AlreadyClaimed);
+            continue;
+        }
+
+        let amount = stealth.amount;
+        require!(amount > 0, PrivacyError::InvalidAmount);
+        require!(now >= stealth.claimable_after, PrivacyError::NotYetClaimable);
+
+        transfer_lamports(stealth_info, &recipient_info, amount, PrivacyError::InvalidAmount)?;
+
+        stealth.claimed = true;
+        // Remaining accounts are not written back automatically; doing it
+        // now also makes a duplicate entry read as claimed
+        stealth.exit(ctx.program_id)?;
+
+        total = total
+            .checked_add(amount)
+            .ok_or(PrivacyError::InvalidAmount)?;
+        claimed_count += 1;
+    }
+
+    msg!(
+        "Stealth payments claimed: {} of {} accounts, {} lamports",
+        claimed_count,
+        count,
+        total
+    );
+
+    Ok(())
+}
@@ -0,0 +1,22 @@
+use anchor_lang::prelude::*;
+use crate::poseidon::{note_commitment, CommitmentOpening};
+use crate::state::ShieldedPool;
+
+#[derive(Accounts)]
+pub struct ComputeCommitment<'info> {
+    /// Pool the note is meant for (read-only)
+    #[account(
+        seeds = [b"pool", pool.authority.as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, ShieldedPool>,
+}
+
+/// Returns the circuit's Poseidon note commitment via return data.
+pub fn handler(
+    _ctx: Context<ComputeCommitment>,
+    amount: u64,
+    opening: CommitmentOpening,
+) -> Result<[u8; 32]> {
+    note_commitment(amount, &opening)
+}
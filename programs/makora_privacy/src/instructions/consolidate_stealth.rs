@@ -0,0 +1,90 @@
+use anchor_lang::prelude::*;
+use crate::state::StealthAccount;
+use crate::errors::PrivacyError;
+use crate::instructions::claim_stealth_many::MAX_CLAIM_BATCH;
+use makora_escrow::transfer_lamports;
+
+#[derive(Accounts)]
+pub struct ConsolidateStealth<'info> {
+    /// Stealth account that receives the merged escrow
+    #[account(
+        mut,
+        seeds = [b"stealth", target_account.stealth_address.as_ref()],
+        bump = target_account.bump,
+        constraint = !target_account.claimed @ PrivacyError::AlreadyClaimed,
+        constraint = target_account.stealth_address == target_owner.key().to_bytes() @ PrivacyError::UnauthorizedClaim,
+    )]
+    pub target_account: Account<'info, StealthAccount>,
+
+    /// One-time stealth key of the target account
+    pub target_owner: Signer<'info>,
+
+    /// Receives the rent of the closed source accounts
+    #[account(mut)]
+    pub recipient: Signer<'info>,
+    // remaining_accounts: up to MAX_CLAIM_BATCH pairs of
+    // (source stealth PDA (writable), its one-time stealth key (signer))
+}
+
+/// Merge several unclaimed stealth payments into one before claiming.
+/// Each source's escrow moves to the target and the source is closed,
+/// its rent refunded to the recipient. The target keeps the latest
+/// claimable_after so merging never shortens a claim delay.
+pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, ConsolidateStealth<'info>>) -> Result<()> {
+    let remaining = ctx.remaining_accounts;
+    require!(
+        !remaining.is_empty() && remaining.len() % 2 == 0 && remaining.len() / 2 <= MAX_CLAIM_BATCH,
+        PrivacyError::InvalidBatchSize
+    );
+
+    let target_info = ctx.accounts.target_account.to_account_info();
+    let recipient_info = ctx.accounts.recipient.to_account_info();
+    let mut total: u64 = 0;
+    let mut claimable_after = ctx.accounts.target_account.claimable_after;
+
+    for pair in remaining.chunks(2) {
+        let (source_info, source_owner) = (&pair[0], &pair[1]);
+        require_keys_neq!(source_info.key(), target_info.key(), PrivacyError::UnauthorizedClaim);
+
+        // Checks owner program and discriminator
+        let source: Account<'info, StealthAccount> = Account::try_from(source_info)?;
+        let expected = Pubkey::create_program_address(
+            &[b"stealth", source.stealth_address.as_ref(), &[source.bump]],
+            ctx.program_id,
+        )
+        .map_err(|_| error!(PrivacyError::UnauthorizedClaim))?;
+        require_keys_eq!(source_info.key(), expected, PrivacyError::UnauthorizedClaim);
+        require!(
+            source_owner.is_signer && source.stealth_address == source_owner.key().to_bytes(),
+            PrivacyError::UnauthorizedClaim
+        );
+        require!(!source.claimed, PrivacyError::AlreadyClaimed);
+        require!(source.amount > 0, PrivacyError::InvalidAmount);
+
+        transfer_lamports(source_info, &target_info, source.amount, PrivacyError::InvalidAmount)?;
+        total = total
+            .checked_add(source.amount)
+            .ok_or(PrivacyError::InvalidAmount)?;
+        claimable_after = claimable_after.max(source.claimable_after);
+
+        // Remaining rent goes to the recipient; a duplicate entry then
+        // fails to deserialize
+        source.close(recipient_info.clone())?;
+    }
+
+    let target = &mut ctx.accounts.target_account;
+    target.amount = target
+        .amount
+        .checked_add(total)
+        .ok_or(PrivacyError::InvalidAmount)?;
+    target.claimable_after = claimable_after;
+
+    msg!(
+        "Stealth payments consolidated: {} accounts, {} lamports (target now {})",
+        remaining.len() / 2,
+        total,
+        target.amount
+    );
+
+    Ok(())
+}
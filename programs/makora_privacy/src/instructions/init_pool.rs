@@ -1,5 +1,6 @@
 use anchor_lang::prelude::*;
-use crate::state::ShieldedPool;
+use crate::state::{ShieldedPool, MAX_LEAVES, ROOT_HISTORY_LEN};
+use crate::errors::PrivacyError;
 
 #[derive(Accounts)]
 pub struct InitPool<'info> {
@@ -18,7 +19,32 @@ pub struct InitPool<'info> {
     pub system_program: Program<'info, System>,
 }
 
-pub fn handler(ctx: Context<InitPool>) -> Result<()> {
+/// `denomination` switches the pool to fixed-denomination mode, where every
+/// shield and unshield must be exactly that many lamports (0 = free mode).
+/// Unshield is refused until the tree holds `min_anonymity_set` leaves.
+pub fn handler(
+    ctx: Context<InitPool>,
+    min_amount: u64,
+    max_amount: u64,
+    denomination: u64,
+    min_anonymity_set: u32,
+) -> Result<()> {
+    require!(
+        max_amount == 0 || min_amount <= max_amount,
+        PrivacyError::AmountOutOfRange
+    );
+    // A fixed denomination outside the band could never be used
+    require!(
+        denomination == 0
+            || (denomination >= min_amount && (max_amount == 0 || denomination <= max_amount)),
+        PrivacyError::AmountOutOfRange
+    );
+    // A threshold the tree can never reach would lock every deposit
+    require!(
+        min_anonymity_set as u64 <= MAX_LEAVES,
+        PrivacyError::InvalidAnonymitySet
+    );
+
     let pool = &mut ctx.accounts.pool;
     let clock = Clock::get()?;
 
@@ -30,9 +56,24 @@ pub fn handler(ctx: Context<InitPool>) -> Result<()> {
     pool.created_at = clock.unix_timestamp;
     pool.last_tx_at = clock.unix_timestamp;
     pool.bump = ctx.bumps.pool;
-    pool._padding = [0u8; 32];
+    pool.min_amount = min_amount;
+    pool.max_amount = max_amount;
+    pool.allowed_denominations = [0u64; 4];
+    pool.root_history = [[0u8; 32]; ROOT_HISTORY_LEN];
+    pool.root_history_head = 0;
+    pool.denomination = denomination;
+    pool.circuit_version = 1;
+    pool.min_anonymity_set = min_anonymity_set;
+    pool._padding = [0u8; 2];
 
-    msg!("Shielded pool initialized by authority: {}", ctx.accounts.authority.key());
+    msg!(
+        "Shielded pool initialized by authority: {} | amount band: {}..{} | denomination: {} | min anonymity set: {}",
+        ctx.accounts.authority.key(),
+        min_amount,
+        max_amount,
+        denomination,
+        min_anonymity_set
+    );
 
     Ok(())
 }
@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+use crate::state::ShieldedPool;
+use crate::errors::PrivacyError;
+
+#[derive(Accounts)]
+pub struct MigrateRoot<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.authority.as_ref()],
+        bump = pool.bump,
+        has_one = authority @ PrivacyError::Unauthorized
+    )]
+    pub pool: Account<'info, ShieldedPool>,
+
+    pub authority: Signer<'info>,
+}
+
+/// Move the pool to a new Transfer circuit (new depth or hash). `new_root`
+/// is the existing leaves' root under the new circuit; unshield proofs
+/// must then target `circuit_version`, so proofs against the old circuit
+/// are rejected.
+pub fn handler(ctx: Context<MigrateRoot>, new_root: [u8; 32], circuit_version: u16) -> Result<()> {
+    require!(new_root != [0u8; 32], PrivacyError::InvalidRoot);
+
+    let pool = &mut ctx.accounts.pool;
+    // Versions only move forward, so a stale proof can never become valid again
+    require!(
+        circuit_version > pool.circuit_version,
+        PrivacyError::CircuitVersionMismatch
+    );
+
+    let previous = pool.circuit_version;
+    pool.migrate_root(new_root, circuit_version);
+    pool.last_tx_at = Clock::get()?.unix_timestamp;
+
+    msg!(
+        "Shielded pool migrated to circuit v{} (from v{}) | root: {:?}",
+        circuit_version,
+        previous,
+        new_root
+    );
+
+    Ok(())
+}
@@ -3,9 +3,27 @@ pub mod send_stealth;
 pub mod claim_stealth;
 pub mod shield;
 pub mod unshield;
+pub mod preview_unshield;
+pub mod rotate_stealth;
+pub mod set_pool_active;
+pub mod set_denominations;
+pub mod claim_stealth_many;
+pub mod compute_commitment;
+pub mod migrate_root;
+pub mod consolidate_stealth;
+pub mod shield_and_send;
 
 pub use init_pool::*;
 pub use send_stealth::*;
 pub use claim_stealth::*;
 pub use shield::*;
 pub use unshield::*;
+pub use preview_unshield::*;
+pub use rotate_stealth::*;
+pub use set_pool_active::*;
+pub use set_denominations::*;
+pub use claim_stealth_many::*;
+pub use compute_commitment::*;
+pub use migrate_root::*;
+pub use consolidate_stealth::*;
+pub use shield_and_send::*;
@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+use crate::state::ShieldedPool;
+use crate::errors::PrivacyError;
+use crate::instructions::unshield::split_relayer_fee;
+
+#[derive(Accounts)]
+pub struct PreviewUnshield<'info> {
+    #[account(
+        seeds = [b"pool", pool.authority.as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, ShieldedPool>,
+}
+
+/// Returned to the client via return data.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct UnshieldPreview {
+    pub net_amount: u64,            // lamports the recipient would receive (0 if the fee is rejected)
+    pub relayer_fee: u64,           // fee unshield would pay the relayer
+    pub pool_has_balance: bool,     // pool.total_shielded >= amount
+    pub feasible: bool,             // in range, denomination, anonymity set, enough balance, fee < amount
+}
+
+pub fn handler(
+    ctx: Context<PreviewUnshield>,
+    amount: u64,
+    relayer_fee: u64,
+) -> Result<UnshieldPreview> {
+    require!(amount > 0, PrivacyError::InvalidAmount);
+
+    let pool = &ctx.accounts.pool;
+
+    // Same split as unshield, which rejects a fee not below the amount
+    let split = split_relayer_fee(amount, relayer_fee).ok();
+    let net_amount = split.map_or(0, |(net, _)| net);
+
+    let pool_has_balance = pool.total_shielded >= amount;
+    // Unshield is allowed even while the pool is paused
+    let feasible = pool.amount_in_range(amount)
+        && pool.matches_denomination(amount)
+        && pool.has_anonymity_set()
+        && pool_has_balance
+        && split.is_some();
+
+    msg!(
+        "Unshield preview: {} lamports | relayer fee: {} | net: {} | feasible: {}",
+        amount,
+        relayer_fee,
+        net_amount,
+        feasible
+    );
+
+    Ok(UnshieldPreview {
+        net_amount,
+        relayer_fee,
+        pool_has_balance,
+        feasible,
+    })
+}
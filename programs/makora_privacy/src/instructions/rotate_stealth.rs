@@ -0,0 +1,90 @@
+use anchor_lang::prelude::*;
+use crate::state::StealthAccount;
+use crate::errors::PrivacyError;
+use crate::instructions::send_stealth::{init_stealth, StealthAnnouncement};
+use makora_clock::checked_now_since_genesis;
+
+#[derive(Accounts)]
+#[instruction(new_stealth_address: [u8; 32])]
+pub struct RotateStealth<'info> {
+    #[account(
+        mut,
+        seeds = [b"stealth", stealth_account.stealth_address.as_ref()],
+        bump = stealth_account.bump,
+        constraint = !stealth_account.claimed @ PrivacyError::AlreadyClaimed,
+        constraint = stealth_account.stealth_address == stealth_owner.key().to_bytes() @ PrivacyError::UnauthorizedClaim,
+        close = payer
+    )]
+    pub stealth_account: Account<'info, StealthAccount>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = StealthAccount::SIZE,
+        seeds = [b"stealth", new_stealth_address.as_ref()],
+        bump
+    )]
+    pub new_stealth_account: Account<'info, StealthAccount>,
+
+    /// One-time stealth key derived by the recipient from the ephemeral key
+    pub stealth_owner: Signer<'info>,
+
+    /// Pays rent for the new account and receives the old account's rent
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(
+    ctx: Context<RotateStealth>,
+    new_stealth_address: [u8; 32],
+    new_ephemeral_pubkey: [u8; 32],
+    new_view_tag: u8,
+    new_view_tag16: Option<u16>,
+) -> Result<()> {
+    let amount = ctx.accounts.stealth_account.amount;
+
+    require!(amount > 0, PrivacyError::InvalidAmount);
+
+    let now = checked_now_since_genesis()?;
+
+    init_stealth(
+        &mut ctx.accounts.new_stealth_account,
+        ctx.bumps.new_stealth_account,
+        ctx.accounts.stealth_account.sender,
+        StealthAnnouncement {
+            stealth_address: new_stealth_address,
+            ephemeral_pubkey: new_ephemeral_pubkey,
+            view_tag: new_view_tag,
+            view_tag16: new_view_tag16.unwrap_or(0),
+        },
+        amount,
+        now,
+        // Rotation must not shorten the claim delay
+        ctx.accounts.stealth_account.claimable_after,
+    );
+
+    // Move escrowed lamports between the two PDAs; the old account's
+    // remaining rent goes to the payer when it is closed
+    let old_info = ctx.accounts.stealth_account.to_account_info();
+    let new_info = ctx.accounts.new_stealth_account.to_account_info();
+
+    **old_info.try_borrow_mut_lamports()? = old_info
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(PrivacyError::InsufficientPoolBalance)?;
+
+    **new_info.try_borrow_mut_lamports()? = new_info
+        .lamports()
+        .checked_add(amount)
+        .ok_or(PrivacyError::InvalidAmount)?;
+
+    msg!(
+        "Stealth payment rotated: {} lamports to new stealth address (view_tag: {})",
+        amount,
+        new_view_tag
+    );
+
+    Ok(())
+}
@@ -2,6 +2,7 @@ use anchor_lang::prelude::*;
 use anchor_lang::system_program;
 use crate::state::StealthAccount;
 use crate::errors::PrivacyError;
+use makora_clock::checked_now_since_genesis;
 
 #[derive(Accounts)]
 #[instruction(stealth_address: [u8; 32])]
@@ -27,38 +28,127 @@ pub fn handler(
     ephemeral_pubkey: [u8; 32],
     view_tag: u8,
     amount: u64,
+    claim_delay_secs: u32,
 ) -> Result<()> {
-    require!(amount > 0, PrivacyError::InvalidAmount);
+    let accounts = ctx.accounts;
+    create_stealth(
+        &mut accounts.stealth_account,
+        ctx.bumps.stealth_account,
+        &accounts.sender,
+        &accounts.system_program,
+        StealthAnnouncement {
+            stealth_address,
+            ephemeral_pubkey,
+            view_tag,
+            view_tag16: 0,
+        },
+        amount,
+        claim_delay_secs,
+    )
+}
+
+/// Same as `handler` but with a 16-bit view tag. The legacy u8 tag is set
+/// to the high byte so 8-bit scanners still match the payment.
+pub fn handler_v2(
+    ctx: Context<SendStealth>,
+    stealth_address: [u8; 32],
+    ephemeral_pubkey: [u8; 32],
+    view_tag16: u16,
+    amount: u64,
+    claim_delay_secs: u32,
+) -> Result<()> {
+    let view_tag = view_tag16.to_be_bytes()[0];
+    let accounts = ctx.accounts;
+    create_stealth(
+        &mut accounts.stealth_account,
+        ctx.bumps.stealth_account,
+        &accounts.sender,
+        &accounts.system_program,
+        StealthAnnouncement {
+            stealth_address,
+            ephemeral_pubkey,
+            view_tag,
+            view_tag16,
+        },
+        amount,
+        claim_delay_secs,
+    )
+}
 
-    let stealth_account = &mut ctx.accounts.stealth_account;
-    let clock = Clock::get()?;
+/// Address and scan tags a stealth payment is published under.
+pub(crate) struct StealthAnnouncement {
+    pub stealth_address: [u8; 32],
+    pub ephemeral_pubkey: [u8; 32],
+    pub view_tag: u8,
+    /// 0 when only the legacy u8 tag is set
+    pub view_tag16: u16,
+}
 
-    // Initialize stealth account
-    stealth_account.sender = ctx.accounts.sender.key();
-    stealth_account.stealth_address = stealth_address;
-    stealth_account.ephemeral_pubkey = ephemeral_pubkey;
-    stealth_account.view_tag = view_tag;
+/// Set every field of a newly created stealth account holding `amount`.
+/// Shared by `create_stealth` and `rotate_stealth`.
+pub(crate) fn init_stealth(
+    stealth_account: &mut StealthAccount,
+    bump: u8,
+    sender: Pubkey,
+    announcement: StealthAnnouncement,
+    amount: u64,
+    created_at: i64,
+    claimable_after: i64,
+) {
+    stealth_account.sender = sender;
+    stealth_account.stealth_address = announcement.stealth_address;
+    stealth_account.ephemeral_pubkey = announcement.ephemeral_pubkey;
+    stealth_account.view_tag = announcement.view_tag;
+    stealth_account.view_tag16 = announcement.view_tag16;
     stealth_account.amount = amount;
     stealth_account.claimed = false;
-    stealth_account.created_at = clock.unix_timestamp;
-    stealth_account.bump = ctx.bumps.stealth_account;
+    stealth_account.created_at = created_at;
+    stealth_account.claimable_after = claimable_after;
+    stealth_account.bump = bump;
+}
+
+/// Fund and initialize a new stealth account. Shared with `shield_and_send`.
+pub(crate) fn create_stealth<'info>(
+    stealth_account: &mut Account<'info, StealthAccount>,
+    stealth_account_bump: u8,
+    sender: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    announcement: StealthAnnouncement,
+    amount: u64,
+    claim_delay_secs: u32,
+) -> Result<()> {
+    require!(amount > 0, PrivacyError::InvalidAmount);
+
+    // New account with no parent, so check against the genesis reference
+    let now = checked_now_since_genesis()?;
+
+    init_stealth(
+        stealth_account,
+        stealth_account_bump,
+        sender.key(),
+        announcement,
+        amount,
+        now,
+        now + claim_delay_secs as i64,
+    );
 
     // Transfer SOL to the stealth account PDA (holds the funds in escrow)
     system_program::transfer(
         CpiContext::new(
-            ctx.accounts.system_program.to_account_info(),
+            system_program.to_account_info(),
             system_program::Transfer {
-                from: ctx.accounts.sender.to_account_info(),
-                to: ctx.accounts.stealth_account.to_account_info(),
+                from: sender.to_account_info(),
+                to: stealth_account.to_account_info(),
             },
         ),
         amount,
     )?;
 
     msg!(
-        "Stealth payment created: {} lamports to stealth address (view_tag: {})",
+        "Stealth payment created: {} lamports to stealth address (view_tag: {}, view_tag16: {})",
         amount,
-        view_tag
+        stealth_account.view_tag,
+        stealth_account.view_tag16
     );
 
     Ok(())
@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+use crate::state::ShieldedPool;
+use crate::errors::PrivacyError;
+
+#[derive(Accounts)]
+pub struct SetDenominations<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.authority.as_ref()],
+        bump = pool.bump,
+        has_one = authority @ PrivacyError::Unauthorized
+    )]
+    pub pool: Account<'info, ShieldedPool>,
+
+    pub authority: Signer<'info>,
+}
+
+/// Configure the shield denomination tiers. Zero entries are unused slots;
+/// an all-zero array allows any amount within the pool's band.
+pub fn handler(ctx: Context<SetDenominations>, allowed_denominations: [u64; 4]) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+
+    for &denomination in allowed_denominations.iter().filter(|&&d| d != 0) {
+        require!(
+            pool.amount_in_range(denomination),
+            PrivacyError::AmountOutOfRange
+        );
+    }
+
+    pool.allowed_denominations = allowed_denominations;
+    pool.last_tx_at = Clock::get()?.unix_timestamp;
+
+    msg!("Shielded pool denominations: {:?}", allowed_denominations);
+
+    Ok(())
+}
@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+use crate::state::ShieldedPool;
+use crate::errors::PrivacyError;
+
+#[derive(Accounts)]
+pub struct SetPoolActive<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.authority.as_ref()],
+        bump = pool.bump,
+        has_one = authority @ PrivacyError::Unauthorized
+    )]
+    pub pool: Account<'info, ShieldedPool>,
+
+    pub authority: Signer<'info>,
+}
+
+/// Pause or resume a pool. While paused, `shield` is blocked but
+/// `unshield` keeps working so users can always exit.
+pub fn handler(ctx: Context<SetPoolActive>, active: bool) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    pool.is_active = active;
+    pool.last_tx_at = Clock::get()?.unix_timestamp;
+
+    msg!("Shielded pool active: {}", active);
+
+    Ok(())
+}
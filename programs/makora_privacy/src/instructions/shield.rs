@@ -1,9 +1,11 @@
 use anchor_lang::prelude::*;
 use anchor_lang::system_program;
-use crate::state::ShieldedPool;
+use crate::state::{ShieldedPool, CommitmentRecord};
 use crate::errors::PrivacyError;
+use crate::poseidon::{note_commitment, CommitmentOpening};
 
 #[derive(Accounts)]
+#[instruction(amount: u64, commitment: [u8; 32])]
 pub struct Shield<'info> {
     #[account(
         mut,
@@ -13,29 +15,91 @@ pub struct Shield<'info> {
     )]
     pub pool: Account<'info, ShieldedPool>,
 
+    // One record per commitment guarantees leaf uniqueness
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        space = CommitmentRecord::SIZE,
+        seeds = [b"commitment", pool.key().as_ref(), commitment.as_ref()],
+        bump
+    )]
+    pub commitment_record: Account<'info, CommitmentRecord>,
+
     #[account(mut)]
     pub depositor: Signer<'info>,
 
     pub system_program: Program<'info, System>,
 }
 
+/// `opening` opts into a non-private "proof of deposit": when provided,
+/// the commitment must equal the circuit's Poseidon hash of `amount` and
+/// the openings, which are then public in the transaction.
 pub fn handler(
     ctx: Context<Shield>,
     amount: u64,
     commitment: [u8; 32],
     new_root: [u8; 32],
+    opening: Option<CommitmentOpening>,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+    shield_deposit(
+        &mut accounts.pool,
+        &mut accounts.commitment_record,
+        ctx.bumps.commitment_record,
+        &accounts.depositor,
+        &accounts.system_program,
+        amount,
+        commitment,
+        new_root,
+        opening,
+    )
+}
+
+/// Validate and insert a shield deposit. Shared with `shield_and_send`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn shield_deposit<'info>(
+    pool: &mut Account<'info, ShieldedPool>,
+    commitment_record: &mut Account<'info, CommitmentRecord>,
+    commitment_record_bump: u8,
+    depositor: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    amount: u64,
+    commitment: [u8; 32],
+    new_root: [u8; 32],
+    opening: Option<CommitmentOpening>,
 ) -> Result<()> {
     require!(amount > 0, PrivacyError::InvalidAmount);
 
-    let pool = &mut ctx.accounts.pool;
+    if let Some(opening) = opening {
+        require!(
+            note_commitment(amount, &opening)? == commitment,
+            PrivacyError::CommitmentMismatch
+        );
+    }
+
+    require!(pool.amount_in_range(amount), PrivacyError::AmountOutOfRange);
+    require!(pool.matches_denomination(amount), PrivacyError::WrongDenomination);
+    require!(
+        pool.is_allowed_denomination(amount),
+        PrivacyError::InvalidDenomination
+    );
+    require!(pool.has_leaf_capacity(), PrivacyError::TreeFull);
+
     let clock = Clock::get()?;
 
+    // A freshly created record is zeroed; a populated one means this
+    // commitment was already inserted
+    require!(
+        commitment_record.pool == Pubkey::default(),
+        PrivacyError::CommitmentExists
+    );
+
     // Transfer SOL to pool PDA
     system_program::transfer(
         CpiContext::new(
-            ctx.accounts.system_program.to_account_info(),
+            system_program.to_account_info(),
             system_program::Transfer {
-                from: ctx.accounts.depositor.to_account_info(),
+                from: depositor.to_account_info(),
                 to: pool.to_account_info(),
             },
         ),
@@ -54,7 +118,13 @@ pub fn handler(
     pool.last_tx_at = clock.unix_timestamp;
 
     // Store updated Merkle root after inserting the new commitment leaf
-    pool.merkle_root = new_root;
+    pool.push_root(new_root);
+
+    commitment_record.pool = pool.key();
+    commitment_record.commitment = commitment;
+    commitment_record.leaf_index = pool.next_leaf_index - 1;
+    commitment_record.created_at = clock.unix_timestamp;
+    commitment_record.bump = commitment_record_bump;
 
     msg!(
         "Shield deposit: {} lamports | leaf_index: {} | commitment: {:?}",
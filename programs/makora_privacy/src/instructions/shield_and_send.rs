@@ -0,0 +1,95 @@
+use anchor_lang::prelude::*;
+use crate::state::{ShieldedPool, CommitmentRecord, StealthAccount};
+use crate::errors::PrivacyError;
+use crate::poseidon::CommitmentOpening;
+use crate::instructions::send_stealth::{create_stealth, StealthAnnouncement};
+use crate::instructions::shield::shield_deposit;
+
+#[derive(Accounts)]
+#[instruction(
+    shield_amount: u64,
+    commitment: [u8; 32],
+    new_root: [u8; 32],
+    opening: Option<CommitmentOpening>,
+    stealth_address: [u8; 32],
+)]
+pub struct ShieldAndSend<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.authority.as_ref()],
+        bump = pool.bump,
+        constraint = pool.is_active @ PrivacyError::PoolNotActive
+    )]
+    pub pool: Account<'info, ShieldedPool>,
+
+    // One record per commitment guarantees leaf uniqueness
+    #[account(
+        init_if_needed,
+        payer = sender,
+        space = CommitmentRecord::SIZE,
+        seeds = [b"commitment", pool.key().as_ref(), commitment.as_ref()],
+        bump
+    )]
+    pub commitment_record: Account<'info, CommitmentRecord>,
+
+    #[account(
+        init,
+        payer = sender,
+        space = StealthAccount::SIZE,
+        seeds = [b"stealth", stealth_address.as_ref()],
+        bump
+    )]
+    pub stealth_account: Account<'info, StealthAccount>,
+
+    /// Funds both the shield deposit and the stealth payment
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Shield `shield_amount` into the pool and send `stealth_amount` to a
+/// stealth address in one transaction, so the two can't be linked by
+/// timing. Each half runs the same checks as `shield` / `send_stealth_v2`.
+#[allow(clippy::too_many_arguments)]
+pub fn handler(
+    ctx: Context<ShieldAndSend>,
+    shield_amount: u64,
+    commitment: [u8; 32],
+    new_root: [u8; 32],
+    opening: Option<CommitmentOpening>,
+    stealth_address: [u8; 32],
+    ephemeral_pubkey: [u8; 32],
+    view_tag16: u16,
+    stealth_amount: u64,
+    claim_delay_secs: u32,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+
+    shield_deposit(
+        &mut accounts.pool,
+        &mut accounts.commitment_record,
+        ctx.bumps.commitment_record,
+        &accounts.sender,
+        &accounts.system_program,
+        shield_amount,
+        commitment,
+        new_root,
+        opening,
+    )?;
+
+    create_stealth(
+        &mut accounts.stealth_account,
+        ctx.bumps.stealth_account,
+        &accounts.sender,
+        &accounts.system_program,
+        StealthAnnouncement {
+            stealth_address,
+            ephemeral_pubkey,
+            view_tag: view_tag16.to_be_bytes()[0],
+            view_tag16,
+        },
+        stealth_amount,
+        claim_delay_secs,
+    )
+}
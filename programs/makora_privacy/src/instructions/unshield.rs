@@ -1,77 +1,142 @@
 use anchor_lang::prelude::*;
 use crate::state::{ShieldedPool, NullifierRecord};
 use crate::errors::PrivacyError;
-use crate::verifying_key::{VERIFYING_KEY, NR_PUBLIC_INPUTS};
+use crate::verifying_key::{
+    VERIFYING_KEY, NULLIFIER_INDEX, PUBLIC_AMOUNT_INDEX, EXT_DATA_HASH_INDEX,
+};
+use anchor_lang::solana_program::hash::hashv;
 use groth16_solana::groth16::Groth16Verifier;
 
+/// BN254 scalar field modulus, big-endian.
+const FIELD_MODULUS: [u8; 32] = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29,
+    0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+    0x28, 0x33, 0xe8, 0x48, 0x79, 0xb9, 0x70, 0x91,
+    0x43, 0xe1, 0xf5, 0x93, 0xf0, 0x00, 0x00, 0x01,
+];
+
 #[derive(Accounts)]
 #[instruction(
     amount: u64,
+    fee: u64,
     nullifier_hash: [u8; 32],
     new_root: [u8; 32],
     proof_a: [u8; 64],
     proof_b: [u8; 128],
     proof_c: [u8; 64],
-    public_inputs: [[u8; 32]; 7],
+    public_inputs: [[u8; 32]; 8],
+    circuit_version: u16,
 )]
 pub struct Unshield<'info> {
+    // No is_active check: unshield keeps working while the pool is paused
+    // so users can always exit. The zero checks live here so they run
+    // before the nullifier PDA below is created.
     #[account(
         mut,
         seeds = [b"pool", pool.authority.as_ref()],
         bump = pool.bump,
-        constraint = pool.is_active @ PrivacyError::PoolNotActive
+        constraint = nullifier_hash != [0u8; 32] @ PrivacyError::InvalidNullifier,
+        constraint = new_root != [0u8; 32] @ PrivacyError::InvalidRoot,
+        constraint = circuit_version == pool.circuit_version @ PrivacyError::CircuitVersionMismatch
     )]
     pub pool: Account<'info, ShieldedPool>,
 
     #[account(
         init,
-        payer = recipient,
+        payer = fee_payer,
         space = NullifierRecord::SIZE,
         seeds = [b"nullifier", pool.key().as_ref(), nullifier_hash.as_ref()],
         bump
     )]
     pub nullifier_record: Account<'info, NullifierRecord>,
 
+    /// Need not sign, so a fresh address can be paid without ever holding
+    /// SOL; the proof commits to it through `ext_data_hash`.
+    /// CHECK: Only receives lamports.
+    #[account(mut)]
+    pub recipient: UncheckedAccount<'info>,
+
+    /// Optional relayer paid `fee` out of the withdrawn amount.
+    /// CHECK: Only receives lamports.
     #[account(mut)]
-    pub recipient: Signer<'info>,
+    pub relayer: Option<UncheckedAccount<'info>>,
+
+    /// Signs the transaction and pays the nullifier record's rent: the
+    /// relayer when relaying, otherwise usually the recipient.
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
 
     pub system_program: Program<'info, System>,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn handler(
     ctx: Context<Unshield>,
     amount: u64,
+    fee: u64,
     nullifier_hash: [u8; 32],
     new_root: [u8; 32],
     proof_a: [u8; 64],
     proof_b: [u8; 128],
     proof_c: [u8; 64],
-    public_inputs: [[u8; 32]; 7],
+    public_inputs: [[u8; 32]; 8],
+    _circuit_version: u16, // checked against the pool in the account constraints
 ) -> Result<()> {
     require!(amount > 0, PrivacyError::InvalidAmount);
+    // A fee is only payable when a relayer is supplied
+    require!(
+        fee == 0 || ctx.accounts.relayer.is_some(),
+        PrivacyError::InvalidAmount
+    );
+    let (recipient_amount, relayer_fee) = split_relayer_fee(amount, fee)?;
 
     let pool = &mut ctx.accounts.pool;
     let nullifier_record = &mut ctx.accounts.nullifier_record;
     let clock = Clock::get()?;
 
+    require!(pool.amount_in_range(amount), PrivacyError::AmountOutOfRange);
+    require!(pool.matches_denomination(amount), PrivacyError::WrongDenomination);
+    // Unshielding from a near-empty tree would make the note easy to link
+    require!(pool.has_anonymity_set(), PrivacyError::AnonymitySetTooSmall);
+
     // Verify pool has sufficient balance
     require!(
         pool.total_shielded >= amount,
         PrivacyError::InsufficientPoolBalance
     );
 
-    // ── Groth16 proof verification ──────────────────────────────────────────
-    // Flatten public_inputs into a contiguous byte slice for the verifier.
-    let mut public_inputs_bytes = [[0u8; 32]; NR_PUBLIC_INPUTS];
-    for i in 0..NR_PUBLIC_INPUTS {
-        public_inputs_bytes[i] = public_inputs[i];
-    }
+    // The proof's root (public input 0) may be any recent root, so a proof
+    // built before a concurrent shield moved the root still verifies
+    require!(
+        pool.is_known_root(&public_inputs[0]),
+        PrivacyError::UnknownRoot
+    );
 
+    // The verifier only checks the proof against its public inputs, so
+    // tie those to what this instruction actually pays out. Without this a
+    // relayer could submit someone's proof with a different amount, fee
+    // or recipient, or replay it under a fresh nullifier.
+    require!(
+        public_inputs[NULLIFIER_INDEX] == nullifier_hash,
+        PrivacyError::InvalidNullifier
+    );
+    require!(
+        public_inputs[PUBLIC_AMOUNT_INDEX] == unshield_public_amount(amount),
+        PrivacyError::PublicAmountMismatch
+    );
+    let relayer_key = ctx.accounts.relayer.as_ref().map(|r| r.key()).unwrap_or_default();
+    require!(
+        public_inputs[EXT_DATA_HASH_INDEX]
+            == ext_data_hash(&ctx.accounts.recipient.key(), &relayer_key, fee),
+        PrivacyError::ExtDataMismatch
+    );
+
+    // ── Groth16 proof verification ──────────────────────────────────────────
     let mut verifier = Groth16Verifier::new(
         &proof_a,
         &proof_b,
         &proof_c,
-        &public_inputs_bytes,
+        &public_inputs,
         &VERIFYING_KEY,
     )
     .map_err(|_| error!(PrivacyError::InvalidProof))?;
@@ -82,6 +147,10 @@ pub fn handler(
     );
     // ── End proof verification ──────────────────────────────────────────────
 
+    // Every check above runs before any state is written. Any error from
+    // here on still aborts the whole transaction, so the nullifier, root,
+    // pool balance and transfer are committed together or not at all.
+
     // Initialize nullifier record (prevents double-spend)
     nullifier_record.pool = pool.key();
     nullifier_record.nullifier = nullifier_hash;
@@ -89,7 +158,7 @@ pub fn handler(
     nullifier_record.bump = ctx.bumps.nullifier_record;
 
     // Update merkle root
-    pool.merkle_root = new_root;
+    pool.push_root(new_root);
 
     // Update pool state
     pool.total_shielded = pool.total_shielded
@@ -98,7 +167,8 @@ pub fn handler(
 
     pool.last_tx_at = clock.unix_timestamp;
 
-    // Transfer SOL from pool to recipient
+    // Transfer SOL from pool: `amount - fee` to recipient, `fee` to relayer.
+    // The proof covers the gross `amount`.
     let pool_info = pool.to_account_info();
     let recipient_info = ctx.accounts.recipient.to_account_info();
 
@@ -109,14 +179,104 @@ pub fn handler(
 
     **recipient_info.try_borrow_mut_lamports()? = recipient_info
         .lamports()
-        .checked_add(amount)
+        .checked_add(recipient_amount)
         .ok_or(PrivacyError::InvalidAmount)?;
 
+    if let Some(relayer) = &ctx.accounts.relayer {
+        let relayer_info = relayer.to_account_info();
+        **relayer_info.try_borrow_mut_lamports()? = relayer_info
+            .lamports()
+            .checked_add(relayer_fee)
+            .ok_or(PrivacyError::InvalidAmount)?;
+    }
+
     msg!(
-        "Unshield withdrawal: {} lamports | proof verified | new_root: {:?}",
+        "Unshield withdrawal: {} lamports (relayer fee {}) | proof verified | new_root: {:?}",
         amount,
+        relayer_fee,
         new_root
     );
 
     Ok(())
 }
+
+/// Split a gross withdrawal into (recipient amount, relayer fee).
+/// The fee must leave the recipient a non-zero amount.
+pub fn split_relayer_fee(amount: u64, fee: u64) -> Result<(u64, u64)> {
+    require!(fee < amount, PrivacyError::FeeExceedsAmount);
+    Ok((amount - fee, fee))
+}
+
+/// `public_amount` for an unshield of `amount`: `-amount` in the BN254
+/// scalar field, big-endian.
+pub fn unshield_public_amount(amount: u64) -> [u8; 32] {
+    let mut out = FIELD_MODULUS;
+    let mut low = [0u8; 8];
+    low.copy_from_slice(&out[24..]);
+    let (diff, borrow) = u64::from_be_bytes(low).overflowing_sub(amount);
+    out[24..].copy_from_slice(&diff.to_be_bytes());
+    if borrow {
+        // The modulus' byte 23 is non-zero, so the borrow stops there
+        out[23] -= 1;
+    }
+    out
+}
+
+/// `ext_data_hash` for an unshield: sha256(recipient || relayer || fee_le)
+/// with the top three bits cleared so it is a field element. `relayer` is
+/// the default pubkey when no relayer is used.
+pub fn ext_data_hash(recipient: &Pubkey, relayer: &Pubkey, fee: u64) -> [u8; 32] {
+    let mut hash = hashv(&[recipient.as_ref(), relayer.as_ref(), &fee.to_le_bytes()]).to_bytes();
+    hash[0] &= 0x1f;
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_without_fee_pays_recipient_in_full() {
+        assert_eq!(split_relayer_fee(1_000_000, 0).unwrap(), (1_000_000, 0));
+    }
+
+    #[test]
+    fn split_sends_fee_to_relayer() {
+        assert_eq!(split_relayer_fee(1_000_000, 5_000).unwrap(), (995_000, 5_000));
+    }
+
+    #[test]
+    fn split_rejects_fee_not_below_amount() {
+        assert!(split_relayer_fee(1_000_000, 1_000_000).is_err());
+        assert!(split_relayer_fee(1_000_000, 2_000_000).is_err());
+    }
+
+    #[test]
+    fn public_amount_is_the_field_negation() {
+        let mut expected = FIELD_MODULUS;
+        expected[31] = 0x00; // ...f0000001 - 1
+        assert_eq!(unshield_public_amount(1), expected);
+
+        // Borrows across bytes: ...f0000001 - 0x02 = ...efffffff
+        let neg_two = unshield_public_amount(2);
+        assert_eq!(&neg_two[28..], &[0xef, 0xff, 0xff, 0xff]);
+        assert_eq!(&neg_two[..24], &FIELD_MODULUS[..24]);
+
+        // Borrows out of the low word
+        let neg_max = unshield_public_amount(u64::MAX);
+        assert_eq!(neg_max[23], FIELD_MODULUS[23] - 1);
+        assert_eq!(&neg_max[24..], &0x43e1_f593_f000_0002u64.to_be_bytes());
+    }
+
+    #[test]
+    fn ext_data_hash_commits_to_every_field() {
+        let recipient = Pubkey::new_unique();
+        let relayer = Pubkey::new_unique();
+        let base = ext_data_hash(&recipient, &relayer, 5_000);
+
+        assert!(base[0] < 0x20);
+        assert_ne!(base, ext_data_hash(&relayer, &relayer, 5_000));
+        assert_ne!(base, ext_data_hash(&recipient, &Pubkey::default(), 5_000));
+        assert_ne!(base, ext_data_hash(&recipient, &relayer, 5_001));
+    }
+}
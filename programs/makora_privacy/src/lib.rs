@@ -2,10 +2,12 @@ use anchor_lang::prelude::*;
 
 pub mod errors;
 pub mod instructions;
+pub mod poseidon;
 pub mod state;
 pub mod verifying_key;
 
 use instructions::*;
+use poseidon::CommitmentOpening;
 
 declare_id!("C1qXFsB6oJgZLQnXwRi9mwrm3QshKMU8kGGUZTAa9xcM");
 
@@ -13,8 +15,14 @@ declare_id!("C1qXFsB6oJgZLQnXwRi9mwrm3QshKMU8kGGUZTAa9xcM");
 pub mod makora_privacy {
     use super::*;
 
-    pub fn init_pool(ctx: Context<InitPool>) -> Result<()> {
-        instructions::init_pool::handler(ctx)
+    pub fn init_pool(
+        ctx: Context<InitPool>,
+        min_amount: u64,
+        max_amount: u64,
+        denomination: u64,
+        min_anonymity_set: u32,
+    ) -> Result<()> {
+        instructions::init_pool::handler(ctx, min_amount, max_amount, denomination, min_anonymity_set)
     }
 
     pub fn send_stealth(
@@ -23,42 +31,151 @@ pub mod makora_privacy {
         ephemeral_pubkey: [u8; 32],
         view_tag: u8,
         amount: u64,
+        claim_delay_secs: u32,
     ) -> Result<()> {
-        instructions::send_stealth::handler(ctx, stealth_address, ephemeral_pubkey, view_tag, amount)
+        instructions::send_stealth::handler(ctx, stealth_address, ephemeral_pubkey, view_tag, amount, claim_delay_secs)
+    }
+
+    pub fn send_stealth_v2(
+        ctx: Context<SendStealth>,
+        stealth_address: [u8; 32],
+        ephemeral_pubkey: [u8; 32],
+        view_tag16: u16,
+        amount: u64,
+        claim_delay_secs: u32,
+    ) -> Result<()> {
+        instructions::send_stealth::handler_v2(ctx, stealth_address, ephemeral_pubkey, view_tag16, amount, claim_delay_secs)
     }
 
     pub fn claim_stealth(ctx: Context<ClaimStealth>) -> Result<()> {
         instructions::claim_stealth::handler(ctx)
     }
 
+    pub fn claim_stealth_many<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimStealthMany<'info>>,
+        skip_claimed: bool,
+    ) -> Result<()> {
+        instructions::claim_stealth_many::handler(ctx, skip_claimed)
+    }
+
     pub fn shield(
         ctx: Context<Shield>,
         amount: u64,
         commitment: [u8; 32],
         new_root: [u8; 32],
+        opening: Option<CommitmentOpening>,
     ) -> Result<()> {
-        instructions::shield::handler(ctx, amount, commitment, new_root)
+        instructions::shield::handler(ctx, amount, commitment, new_root, opening)
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn unshield(
         ctx: Context<Unshield>,
         amount: u64,
+        fee: u64,
         nullifier_hash: [u8; 32],
         new_root: [u8; 32],
         proof_a: [u8; 64],
         proof_b: [u8; 128],
         proof_c: [u8; 64],
-        public_inputs: [[u8; 32]; 7],
+        public_inputs: [[u8; 32]; 8],
+        circuit_version: u16,
     ) -> Result<()> {
         instructions::unshield::handler(
             ctx,
             amount,
+            fee,
             nullifier_hash,
             new_root,
             proof_a,
             proof_b,
             proof_c,
             public_inputs,
+            circuit_version,
+        )
+    }
+
+    pub fn preview_unshield(
+        ctx: Context<PreviewUnshield>,
+        amount: u64,
+        relayer_fee: u64,
+    ) -> Result<UnshieldPreview> {
+        instructions::preview_unshield::handler(ctx, amount, relayer_fee)
+    }
+
+    pub fn rotate_stealth(
+        ctx: Context<RotateStealth>,
+        new_stealth_address: [u8; 32],
+        new_ephemeral_pubkey: [u8; 32],
+        new_view_tag: u8,
+        new_view_tag16: Option<u16>,
+    ) -> Result<()> {
+        instructions::rotate_stealth::handler(
+            ctx,
+            new_stealth_address,
+            new_ephemeral_pubkey,
+            new_view_tag,
+            new_view_tag16,
+        )
+    }
+
+    pub fn set_pool_active(ctx: Context<SetPoolActive>, active: bool) -> Result<()> {
+        instructions::set_pool_active::handler(ctx, active)
+    }
+
+    pub fn set_denominations(
+        ctx: Context<SetDenominations>,
+        allowed_denominations: [u64; 4],
+    ) -> Result<()> {
+        instructions::set_denominations::handler(ctx, allowed_denominations)
+    }
+
+    pub fn compute_commitment(
+        ctx: Context<ComputeCommitment>,
+        amount: u64,
+        opening: CommitmentOpening,
+    ) -> Result<[u8; 32]> {
+        instructions::compute_commitment::handler(ctx, amount, opening)
+    }
+
+    pub fn migrate_root(
+        ctx: Context<MigrateRoot>,
+        new_root: [u8; 32],
+        circuit_version: u16,
+    ) -> Result<()> {
+        instructions::migrate_root::handler(ctx, new_root, circuit_version)
+    }
+
+    pub fn consolidate_stealth<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ConsolidateStealth<'info>>,
+    ) -> Result<()> {
+        instructions::consolidate_stealth::handler(ctx)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn shield_and_send(
+        ctx: Context<ShieldAndSend>,
+        shield_amount: u64,
+        commitment: [u8; 32],
+        new_root: [u8; 32],
+        opening: Option<CommitmentOpening>,
+        stealth_address: [u8; 32],
+        ephemeral_pubkey: [u8; 32],
+        view_tag16: u16,
+        stealth_amount: u64,
+        claim_delay_secs: u32,
+    ) -> Result<()> {
+        instructions::shield_and_send::handler(
+            ctx,
+            shield_amount,
+            commitment,
+            new_root,
+            opening,
+            stealth_address,
+            ephemeral_pubkey,
+            view_tag16,
+            stealth_amount,
+            claim_delay_secs,
         )
     }
 }
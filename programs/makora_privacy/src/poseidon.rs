@@ -0,0 +1,101 @@
+/// Note commitments, computed exactly as `NoteCommitment` in
+/// circuits/poseidon.circom:
+///
+///   commitment = Poseidon(amount, owner_pubkey, randomness, token_mint)
+///
+/// over BN254 (circomlib parameters, x^5 S-box). Every input is a field
+/// element encoded as 32 big-endian bytes, the same encoding the client
+/// SDK uses.
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::poseidon::{hashv, Endianness, Parameters};
+use crate::errors::PrivacyError;
+
+/// Private openings of a note commitment, everything but the amount.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct CommitmentOpening {
+    /// Poseidon(spending_key) of the note owner
+    pub owner_pubkey: [u8; 32],
+    /// Blinding factor
+    pub randomness: [u8; 32],
+    /// Token mint as a field element
+    pub token_mint: [u8; 32],
+}
+
+/// Encode an amount as a 32-byte big-endian field element.
+pub fn amount_to_field(amount: u64) -> [u8; 32] {
+    let mut field = [0u8; 32];
+    field[24..].copy_from_slice(&amount.to_be_bytes());
+    field
+}
+
+/// Poseidon note commitment. Fails with `InvalidFieldElement` if any input
+/// is not below the BN254 scalar field modulus.
+pub fn note_commitment(amount: u64, opening: &CommitmentOpening) -> Result<[u8; 32]> {
+    let amount = amount_to_field(amount);
+    let hash = hashv(
+        Parameters::Bn254X5,
+        Endianness::BigEndian,
+        &[
+            &amount,
+            &opening.owner_pubkey,
+            &opening.randomness,
+            &opening.token_mint,
+        ],
+    )
+    .map_err(|_| error!(PrivacyError::InvalidFieldElement))?;
+    Ok(hash.to_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(n: u64) -> [u8; 32] {
+        amount_to_field(n)
+    }
+
+    fn hex32(s: &str) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        for (i, byte) in out.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap();
+        }
+        out
+    }
+
+    #[test]
+    fn matches_circomlib_vector() {
+        // circomlib poseidon([1, 2, 3, 4])
+        let opening = CommitmentOpening {
+            owner_pubkey: field(2),
+            randomness: field(3),
+            token_mint: field(4),
+        };
+        assert_eq!(
+            note_commitment(1, &opening).unwrap(),
+            hex32("299c867db6c1fdd79dcefa40e4510b9837e60ebb1ce0663dbaa525df65250465")
+        );
+    }
+
+    #[test]
+    fn amount_changes_commitment() {
+        let opening = CommitmentOpening {
+            owner_pubkey: field(7),
+            randomness: field(8),
+            token_mint: field(9),
+        };
+        assert_ne!(
+            note_commitment(1_000, &opening).unwrap(),
+            note_commitment(1_001, &opening).unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_input_outside_the_field() {
+        let opening = CommitmentOpening {
+            owner_pubkey: [0xff; 32],
+            randomness: field(3),
+            token_mint: field(4),
+        };
+        assert!(note_commitment(1, &opening).is_err());
+    }
+}
@@ -0,0 +1,14 @@
+use anchor_lang::prelude::*;
+
+#[account]
+pub struct CommitmentRecord {
+    pub pool: Pubkey,               // 32
+    pub commitment: [u8; 32],       // 32
+    pub leaf_index: u64,            // 8
+    pub created_at: i64,            // 8
+    pub bump: u8,                   // 1
+}
+
+impl CommitmentRecord {
+    pub const SIZE: usize = 8 + 32 + 32 + 8 + 8 + 1;
+}
@@ -1,7 +1,9 @@
 pub mod stealth_account;
 pub mod shielded_pool;
 pub mod nullifier;
+pub mod commitment;
 
 pub use stealth_account::*;
 pub use shielded_pool::*;
 pub use nullifier::*;
+pub use commitment::*;
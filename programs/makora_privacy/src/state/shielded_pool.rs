@@ -1,5 +1,14 @@
 use anchor_lang::prelude::*;
 
+/// Depth of the commitment Merkle tree (must match the Transfer circuit)
+pub const TREE_DEPTH: u32 = 20;
+
+/// Number of leaves the tree can hold (2^TREE_DEPTH)
+pub const MAX_LEAVES: u64 = 1 << TREE_DEPTH;
+
+/// Number of recent roots an unshield proof may reference
+pub const ROOT_HISTORY_LEN: usize = 16;
+
 #[account]
 pub struct ShieldedPool {
     pub authority: Pubkey,           // 32
@@ -10,9 +19,184 @@ pub struct ShieldedPool {
     pub created_at: i64,             // 8
     pub last_tx_at: i64,             // 8
     pub bump: u8,                    // 1
-    pub _padding: [u8; 32],          // 32 - future use
+    pub min_amount: u64,             // 8 - smallest shield/unshield amount
+    pub max_amount: u64,             // 8 - largest shield/unshield amount (0 = no cap)
+    pub allowed_denominations: [u64; 4], // 32 - shield tiers (all 0 = any amount)
+    pub root_history: [[u8; 32]; ROOT_HISTORY_LEN], // 512 - recent roots, ring buffer
+    pub root_history_head: u8,       // 1 - next write position in root_history
+    pub denomination: u64,           // 8 - fixed shield/unshield amount (0 = free mode)
+    pub circuit_version: u16,        // 2 - Transfer circuit version proofs must target
+    pub min_anonymity_set: u32,      // 4 - leaves required before unshield (0 = none)
+    pub _padding: [u8; 2],           // 2 - future use
 }
 
 impl ShieldedPool {
-    pub const SIZE: usize = 8 + 32 + 32 + 8 + 8 + 1 + 8 + 8 + 1 + 32;
+    pub const SIZE: usize = 8 + 32 + 32 + 8 + 8 + 1 + 8 + 8 + 1 + 8 + 8 + 32 + 32 * ROOT_HISTORY_LEN + 1 + 8 + 2 + 4 + 2;
+
+    pub fn amount_in_range(&self, amount: u64) -> bool {
+        amount >= self.min_amount && (self.max_amount == 0 || amount <= self.max_amount)
+    }
+
+    /// True while the tree still has room for another leaf
+    pub fn has_leaf_capacity(&self) -> bool {
+        self.next_leaf_index < MAX_LEAVES
+    }
+
+    /// Set a new current root, keeping it in the recent-root ring.
+    pub fn push_root(&mut self, root: [u8; 32]) {
+        let idx = self.root_history_head as usize % ROOT_HISTORY_LEN;
+        self.root_history[idx] = root;
+        self.root_history_head = ((idx + 1) % ROOT_HISTORY_LEN) as u8;
+        self.merkle_root = root;
+    }
+
+    /// Replace the root for a new circuit version. Roots from the previous
+    /// version are dropped from the history, since no new proof can
+    /// target them.
+    pub fn migrate_root(&mut self, root: [u8; 32], circuit_version: u16) {
+        self.root_history = [[0u8; 32]; ROOT_HISTORY_LEN];
+        self.root_history_head = 0;
+        self.push_root(root);
+        self.circuit_version = circuit_version;
+    }
+
+    /// True if `root` is the current root or one of the last
+    /// ROOT_HISTORY_LEN roots, so proofs built just before a concurrent
+    /// shield still verify.
+    pub fn is_known_root(&self, root: &[u8; 32]) -> bool {
+        *root != [0u8; 32]
+            && (*root == self.merkle_root || self.root_history.contains(root))
+    }
+
+    /// True once the tree holds at least min_anonymity_set leaves, so an
+    /// unshield cannot be linked to a pool with only a handful of notes.
+    pub fn has_anonymity_set(&self) -> bool {
+        self.next_leaf_index >= self.min_anonymity_set as u64
+    }
+
+    /// True if `amount` is the pool's fixed denomination, or the pool is in
+    /// free mode.
+    pub fn matches_denomination(&self, amount: u64) -> bool {
+        self.denomination == 0 || amount == self.denomination
+    }
+
+    /// True if `amount` matches a configured tier, or no tiers are configured.
+    pub fn is_allowed_denomination(&self, amount: u64) -> bool {
+        let mut tiers = self.allowed_denominations.iter().filter(|&&d| d != 0).peekable();
+        tiers.peek().is_none() || tiers.any(|&d| d == amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool_at(next_leaf_index: u64) -> ShieldedPool {
+        ShieldedPool {
+            authority: Pubkey::default(),
+            merkle_root: [0u8; 32],
+            next_leaf_index,
+            total_shielded: 0,
+            is_active: true,
+            created_at: 0,
+            last_tx_at: 0,
+            bump: 0,
+            min_amount: 0,
+            max_amount: 0,
+            allowed_denominations: [0u64; 4],
+            root_history: [[0u8; 32]; ROOT_HISTORY_LEN],
+            root_history_head: 0,
+            denomination: 0,
+            circuit_version: 1,
+            min_anonymity_set: 0,
+            _padding: [0u8; 2],
+        }
+    }
+
+    fn root(n: u8) -> [u8; 32] {
+        [n; 32]
+    }
+
+    #[test]
+    fn accepts_the_last_leaf() {
+        assert!(pool_at(MAX_LEAVES - 1).has_leaf_capacity());
+    }
+
+    #[test]
+    fn rejects_once_the_tree_is_full() {
+        assert!(!pool_at(MAX_LEAVES).has_leaf_capacity());
+        assert!(!pool_at(MAX_LEAVES + 1).has_leaf_capacity());
+    }
+
+    #[test]
+    fn anonymity_set_requires_enough_leaves() {
+        let mut pool = pool_at(4);
+        assert!(pool.has_anonymity_set());
+
+        pool.min_anonymity_set = 5;
+        assert!(!pool.has_anonymity_set());
+
+        pool.next_leaf_index = 5;
+        assert!(pool.has_anonymity_set());
+    }
+
+    #[test]
+    fn accepts_the_previous_root() {
+        let mut pool = pool_at(0);
+        pool.push_root(root(1));
+        pool.push_root(root(2));
+
+        assert_eq!(pool.merkle_root, root(2));
+        assert!(pool.is_known_root(&root(1)));
+        assert!(pool.is_known_root(&root(2)));
+        assert!(!pool.is_known_root(&root(3)));
+    }
+
+    #[test]
+    fn forgets_roots_older_than_the_history() {
+        let mut pool = pool_at(0);
+        for n in 1..=(ROOT_HISTORY_LEN as u8 + 1) {
+            pool.push_root(root(n));
+        }
+
+        assert!(!pool.is_known_root(&root(1)));
+        assert!(pool.is_known_root(&root(2)));
+        assert!(pool.is_known_root(&root(ROOT_HISTORY_LEN as u8 + 1)));
+    }
+
+    #[test]
+    fn never_accepts_the_zero_root() {
+        assert!(!pool_at(0).is_known_root(&[0u8; 32]));
+    }
+
+    #[test]
+    fn migration_forgets_previous_roots() {
+        let mut pool = pool_at(0);
+        pool.push_root(root(1));
+        pool.push_root(root(2));
+
+        pool.migrate_root(root(9), 2);
+
+        assert_eq!(pool.circuit_version, 2);
+        assert_eq!(pool.merkle_root, root(9));
+        assert!(pool.is_known_root(&root(9)));
+        assert!(!pool.is_known_root(&root(1)));
+        assert!(!pool.is_known_root(&root(2)));
+    }
+
+    #[test]
+    fn free_mode_accepts_any_amount() {
+        let pool = pool_at(0);
+        assert!(pool.matches_denomination(1));
+        assert!(pool.matches_denomination(123_456_789));
+    }
+
+    #[test]
+    fn fixed_mode_accepts_only_the_denomination() {
+        let mut pool = pool_at(0);
+        pool.denomination = 100_000_000;
+        assert!(pool.matches_denomination(100_000_000));
+        assert!(!pool.matches_denomination(99_999_999));
+        assert!(!pool.matches_denomination(200_000_000));
+    }
 }
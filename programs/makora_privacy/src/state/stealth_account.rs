@@ -6,12 +6,14 @@ pub struct StealthAccount {
     pub stealth_address: [u8; 32],   // 32 - one-time stealth address
     pub ephemeral_pubkey: [u8; 32],  // 32 - for recipient to derive key
     pub view_tag: u8,                // 1 - fast scanning
+    pub view_tag16: u16,             // 2 - wider tag (0 = legacy u8 only)
     pub amount: u64,                 // 8 - lamports
     pub claimed: bool,               // 1
     pub created_at: i64,             // 8
+    pub claimable_after: i64,        // 8 - claims rejected before this time
     pub bump: u8,                    // 1
 }
 
 impl StealthAccount {
-    pub const SIZE: usize = 8 + 32 + 32 + 32 + 1 + 8 + 1 + 8 + 1;
+    pub const SIZE: usize = 8 + 32 + 32 + 32 + 1 + 2 + 8 + 1 + 8 + 8 + 1;
 }
@@ -1,28 +1,43 @@
-/// Groth16 verification key constants for the Transfer circuit (depth 20).
-///
-/// Generated from circuits/build/verification_key.json after trusted setup.
-/// 7 public inputs: merkle_root, nullifier_1, nullifier_2,
-///   output_commitment_1, output_commitment_2, public_amount, token_mint
-///
-/// To regenerate after circuit changes:
-///   cd circuits
-///   circom transfer.circom --r1cs --wasm --sym -o build/
-///   snarkjs groth16 setup build/transfer.r1cs build/pot22_final.ptau build/transfer_0000.zkey
-///   snarkjs zkey contribute build/transfer_0000.zkey build/transfer_final.zkey --name="makora" -v
-///   snarkjs zkey export verificationkey build/transfer_final.zkey build/verification_key.json
-///   Then parse verification_key.json and update the constants below.
+//! Groth16 verification key constants for the Transfer circuit (depth 20,
+//! see `state::TREE_DEPTH`).
+//!
+//! Generated from circuits/build/verification_key.json after trusted setup.
+//! 8 public inputs: merkle_root, nullifier_1, nullifier_2,
+//!   output_commitment_1, output_commitment_2, public_amount, token_mint,
+//!   ext_data_hash
+//!
+//! To regenerate after circuit changes:
+//!   cd circuits
+//!   circom transfer.circom --r1cs --wasm --sym -o build/
+//!   snarkjs groth16 setup build/transfer.r1cs build/pot22_final.ptau build/transfer_0000.zkey
+//!   snarkjs zkey contribute build/transfer_0000.zkey build/transfer_final.zkey --name="makora" -v
+//!   snarkjs zkey export verificationkey build/transfer_final.zkey build/verification_key.json
+//!   Then parse verification_key.json and update the constants below.
 
 /// Number of public inputs in the circuit
-pub const NR_PUBLIC_INPUTS: usize = 7;
+pub const NR_PUBLIC_INPUTS: usize = 8;
+
+/// Index of `nullifier_1`, the nullifier an unshield spends. It must equal
+/// the instruction's `nullifier_hash`, or one proof could be replayed
+/// under fresh nullifiers.
+pub const NULLIFIER_INDEX: usize = 1;
+
+/// Index of `public_amount`: the net value entering the pool, so an
+/// unshield of `amount` carries `-amount` in the field.
+pub const PUBLIC_AMOUNT_INDEX: usize = 5;
+
+/// Index of `ext_data_hash`, which binds the data the circuit does not see
+/// (recipient, relayer, relayer fee) to the proof.
+pub const EXT_DATA_HASH_INDEX: usize = 7;
 
 /// Verification key byte length:
 ///   alpha_g1:  64 bytes  (G1 point)
 ///   beta_g2:  128 bytes  (G2 point)
 ///   gamma_g2: 128 bytes  (G2 point)
 ///   delta_g2: 128 bytes  (G2 point)
-///   IC:       (NR_PUBLIC_INPUTS + 1) * 64 = 512 bytes
-///   Total:    64 + 128 + 128 + 128 + 512 = 960 bytes
-pub const VERIFYING_KEY_LEN: usize = 960;
+///   IC:       (NR_PUBLIC_INPUTS + 1) * 64 = 576 bytes
+///   Total:    64 + 128 + 128 + 128 + 576 = 1024 bytes
+pub const VERIFYING_KEY_LEN: usize = 1024;
 
 /// Packed verifying key bytes.
 ///
@@ -31,7 +46,7 @@ pub const VERIFYING_KEY_LEN: usize = 960;
 ///   [ 64..192) beta_g2        (G2 uncompressed, big-endian x_c1 || x_c0 || y_c1 || y_c0)
 ///   [192..320) gamma_g2       (G2 uncompressed)
 ///   [320..448) delta_g2       (G2 uncompressed)
-///   [448..960) IC[0..8]       (8 × 64 bytes, G1 uncompressed)
+///   [448..1024) IC[0..9]      (9 × 64 bytes, G1 uncompressed)
 ///
 /// IMPORTANT: These are placeholder values. Replace with real ceremony output
 /// from verification_key.json before deploying to mainnet.
@@ -34,4 +34,73 @@ pub enum StrategyError {
 
     #[msg("Protocol name too long. Maximum 16 bytes.")]
     ProtocolTooLong,
+
+    #[msg("Unauthorized: only the registry authority can update the symbol registry.")]
+    UnauthorizedRegistryUpdate,
+
+    #[msg("Symbol batch must contain 1-16 entries.")]
+    SymbolBatchTooLarge,
+
+    #[msg("Symbol registry is full. Maximum 32 symbols.")]
+    SymbolRegistryFull,
+
+    #[msg("Symbol is already registered or duplicated within the batch.")]
+    DuplicateSymbol,
+
+    #[msg("Symbol is not registered.")]
+    SymbolNotRegistered,
+
+    #[msg("Invalid symbol: must not be empty.")]
+    InvalidSymbol,
+
+    #[msg("Action type is on cooldown. Wait before executing it again.")]
+    ActionTypeOnCooldown,
+
+    #[msg("Cooldown table is full. Maximum 4 action types.")]
+    CooldownTableFull,
+
+    #[msg("Max actions per cycle exceeded. Wait for the next cycle.")]
+    MaxActionsExceeded,
+
+    #[msg("Invalid action kind. Must be 0-7.")]
+    InvalidActionKind,
+
+    #[msg("Vault does not belong to the strategy owner.")]
+    VaultOwnerMismatch,
+
+    #[msg("Agent mode does not match the vault's mode.")]
+    ModeMismatch,
+
+    #[msg("Max slippage must be at most 10000 bps.")]
+    InvalidSlippageBps,
+
+    #[msg("Proposed action slippage exceeds the strategy's maximum.")]
+    SlippageExceeded,
+
+    #[msg("Protocol and description must be ASCII.")]
+    NonAsciiString,
+
+    #[msg("Confidence step must be at most 100.")]
+    InvalidConfidenceStep,
+
+    #[msg("Allocation metadata needs its account and one entry per allocation slot.")]
+    InvalidAllocationMeta,
+
+    #[msg("Allocation tolerance must be at most 5.")]
+    InvalidAllocationTolerance,
+
+    #[msg("Protocol is not on the strategy's allowlist.")]
+    ProtocolNotAllowed,
+
+    #[msg("Protocol allowlist holds at most 8 non-empty ASCII names of up to 16 bytes.")]
+    InvalidProtocolAllowlist,
+
+    #[msg("Action batch must contain 1-8 entries.")]
+    ActionBatchTooLarge,
+
+    #[msg("Audit trail is already on the current layout.")]
+    AuditTrailAlreadyMigrated,
+
+    #[msg("Account is not an audit trail in a supported layout.")]
+    UnsupportedAuditLayout,
 }
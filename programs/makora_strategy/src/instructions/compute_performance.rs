@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+use crate::state::{AuditTrail, StrategyAccount};
+use crate::errors::StrategyError;
+
+#[derive(Accounts)]
+pub struct ComputePerformance<'info> {
+    /// Signer must be owner OR agent_authority
+    pub authority: Signer<'info>,
+
+    /// Strategy PDA (caches the score)
+    #[account(
+        mut,
+        seeds = [b"strategy", strategy_account.owner.as_ref()],
+        bump = strategy_account.bump,
+        constraint = strategy_account.is_authorized(authority.key) @ StrategyError::UnauthorizedStrategyUpdate
+    )]
+    pub strategy_account: Account<'info, StrategyAccount>,
+
+    /// Audit trail PDA to score (read-only)
+    #[account(
+        seeds = [b"audit", strategy_account.owner.as_ref()],
+        bump = audit_trail.bump,
+    )]
+    pub audit_trail: Box<Account<'info, AuditTrail>>,
+}
+
+/// Agent performance over the retained audit entries, returned via
+/// return data.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct PerformanceReport {
+    /// Executed agent actions scored (owner deposits excluded)
+    pub executed_actions: u8,
+    /// Percentage of those that succeeded
+    pub success_rate_pct: u8,
+    /// Success rate weighted towards the most recent actions (0-100)
+    pub score: u8,
+}
+
+pub fn handler(ctx: Context<ComputePerformance>) -> Result<PerformanceReport> {
+    let audit = &ctx.accounts.audit_trail;
+    let report = PerformanceReport {
+        executed_actions: audit.executed_outcomes().len() as u8,
+        success_rate_pct: audit.success_rate_pct(),
+        score: audit.performance_score(),
+    };
+
+    ctx.accounts.strategy_account.last_performance_score = report.score;
+
+    msg!(
+        "Performance: score {} ({}% of {} executed actions succeeded)",
+        report.score,
+        report.success_rate_pct,
+        report.executed_actions
+    );
+
+    Ok(report)
+}
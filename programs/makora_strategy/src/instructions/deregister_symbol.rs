@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+use crate::state::SymbolRegistry;
+use crate::errors::StrategyError;
+
+#[derive(Accounts)]
+pub struct DeregisterSymbol<'info> {
+    /// ONLY the registry authority can deregister symbols
+    pub authority: Signer<'info>,
+
+    /// Symbol registry PDA
+    #[account(
+        mut,
+        seeds = [b"symbol_registry"],
+        bump = symbol_registry.bump,
+        has_one = authority @ StrategyError::UnauthorizedRegistryUpdate
+    )]
+    pub symbol_registry: Box<Account<'info, SymbolRegistry>>,
+}
+
+pub fn handler(ctx: Context<DeregisterSymbol>, symbol: [u8; 8]) -> Result<()> {
+    let registry = &mut ctx.accounts.symbol_registry;
+
+    let idx = registry
+        .entries
+        .iter()
+        .position(|e| e.symbol == symbol)
+        .ok_or(error!(StrategyError::SymbolNotRegistered))?;
+    let removed = registry.entries.remove(idx);
+
+    msg!(
+        "Deregistered symbol {} (mint {}). {} remaining",
+        String::from_utf8_lossy(&symbol).trim_end_matches('\0'),
+        removed.mint,
+        registry.entries.len()
+    );
+
+    Ok(())
+}
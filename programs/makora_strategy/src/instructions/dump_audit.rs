@@ -0,0 +1,81 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::MAX_RETURN_DATA;
+use crate::state::{AuditEntry, AuditTrail, AUDIT_TRAIL_CAPACITY};
+
+#[derive(Accounts)]
+pub struct DumpAudit<'info> {
+    /// Audit trail to export (read-only)
+    #[account(
+        seeds = [b"audit", audit_trail.owner.as_ref()],
+        bump = audit_trail.bump,
+    )]
+    pub audit_trail: Box<Account<'info, AuditTrail>>,
+}
+
+/// One audit entry with its string fields decoded.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct AuditExport {
+    pub index: u32,
+    pub action_kind: String,
+    pub protocol: String,
+    pub description: String,
+    pub executed: bool,
+    pub success: bool,
+    pub timestamp: i64,
+    pub balance_after: u64,
+}
+
+impl From<&AuditEntry> for AuditExport {
+    fn from(entry: &AuditEntry) -> Self {
+        Self {
+            index: entry.index,
+            action_kind: entry.action_kind_str().to_string(),
+            protocol: entry.protocol_str(),
+            description: entry.description_str(),
+            executed: entry.executed,
+            success: entry.success,
+            timestamp: entry.timestamp,
+            balance_after: entry.balance_after,
+        }
+    }
+}
+
+/// Audit trail export returned via return data.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct AuditDump {
+    /// Entries ever logged (the trail keeps the latest AUDIT_TRAIL_CAPACITY)
+    pub total_count: u32,
+    /// True if older retained entries were dropped to fit the return data
+    pub truncated: bool,
+    /// Retained entries, oldest first
+    pub entries: Vec<AuditExport>,
+}
+
+/// Bytes of an AuditDump with no entries: total_count + truncated + vec len
+const DUMP_HEADER_LEN: usize = 4 + 1 + 4;
+
+pub fn handler(ctx: Context<DumpAudit>) -> Result<AuditDump> {
+    let audit = &ctx.accounts.audit_trail;
+
+    // Keep the newest entries that fit, then put them in log order
+    let mut entries = Vec::new();
+    let mut len = DUMP_HEADER_LEN;
+    let recent = audit.recent(AUDIT_TRAIL_CAPACITY);
+    for entry in recent.iter() {
+        let export = AuditExport::from(*entry);
+        let entry_len = export.try_to_vec()?.len();
+        if len + entry_len > MAX_RETURN_DATA {
+            break;
+        }
+        len += entry_len;
+        entries.push(export);
+    }
+    let truncated = entries.len() < recent.len();
+    entries.reverse();
+
+    Ok(AuditDump {
+        total_count: audit.count,
+        truncated,
+        entries,
+    })
+}
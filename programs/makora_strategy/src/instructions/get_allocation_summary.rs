@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+use crate::state::StrategyAccount;
+
+#[derive(Accounts)]
+pub struct GetAllocationSummary<'info> {
+    /// Strategy PDA to summarize (read-only)
+    #[account(
+        seeds = [b"strategy", strategy_account.owner.as_ref()],
+        bump = strategy_account.bump,
+    )]
+    pub strategy_account: Account<'info, StrategyAccount>,
+}
+
+/// Remaining allocation capacity, returned via return data.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct AllocationSummary {
+    /// Allocation slots not in use
+    pub free_slots: u8,
+    /// Percentage not assigned to any in-use slot
+    pub remaining_pct: u8,
+}
+
+pub fn handler(ctx: Context<GetAllocationSummary>) -> Result<AllocationSummary> {
+    let strategy = &ctx.accounts.strategy_account;
+
+    Ok(AllocationSummary {
+        free_slots: strategy.free_slots(),
+        remaining_pct: strategy.remaining_pct(),
+    })
+}
@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+use crate::state::SymbolRegistry;
+
+#[derive(Accounts)]
+pub struct InitSymbolRegistry<'info> {
+    /// The registry authority (pays for account creation)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Symbol registry PDA: seeds = ["symbol_registry"]
+    #[account(
+        init,
+        payer = authority,
+        space = SymbolRegistry::SIZE,
+        seeds = [b"symbol_registry"],
+        bump
+    )]
+    pub symbol_registry: Box<Account<'info, SymbolRegistry>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<InitSymbolRegistry>) -> Result<()> {
+    let registry = &mut ctx.accounts.symbol_registry;
+    registry.authority = ctx.accounts.authority.key();
+    registry.entries = Vec::new();
+    registry.bump = ctx.bumps.symbol_registry;
+
+    msg!(
+        "Symbol registry initialized with authority {}",
+        registry.authority
+    );
+
+    Ok(())
+}
@@ -1,5 +1,5 @@
 use anchor_lang::prelude::*;
-use crate::state::{StrategyAccount, StrategyType, AgentMode, AllocationTarget, AuditTrail, AUDIT_TRAIL_CAPACITY};
+use crate::state::{StrategyAccount, StrategyType, AgentMode, AllocationTarget, AuditTrail, AUDIT_TRAIL_CAPACITY, AUDIT_TRAIL_VERSION, ActionCooldown, MAX_ACTION_COOLDOWNS, MAX_ALLOWED_PROTOCOLS, AllocationMeta, AllocationMetaEntry, validate_allocation_meta, validate_allocation_sum};
 use crate::errors::StrategyError;
 
 #[derive(Accounts)]
@@ -28,6 +28,16 @@ pub struct Initialize<'info> {
     )]
     pub audit_trail: Box<Account<'info, AuditTrail>>,
 
+    /// Optional allocation metadata PDA: seeds = ["allocation_meta", owner]
+    #[account(
+        init,
+        payer = owner,
+        space = AllocationMeta::SIZE,
+        seeds = [b"allocation_meta", owner.key().as_ref()],
+        bump
+    )]
+    pub allocation_meta: Option<Box<Account<'info, AllocationMeta>>>,
+
     pub system_program: Program<'info, System>,
 }
 
@@ -40,6 +50,7 @@ pub fn handler(
     max_actions_per_cycle: u8,
     alloc_symbols: Vec<[u8; 8]>,
     alloc_pcts: Vec<u8>,
+    alloc_meta: Vec<AllocationMetaEntry>,
 ) -> Result<()> {
     // Validate strategy type
     let st = StrategyType::from_u8(strategy_type)
@@ -59,15 +70,19 @@ pub fn handler(
         StrategyError::InvalidAllocationEntry
     );
 
-    let alloc_sum: u16 = alloc_pcts.iter().map(|&p| p as u16).sum();
-    if !alloc_pcts.is_empty() {
-        require!(alloc_sum == 100, StrategyError::InvalidAllocationSum);
-    }
+    // A new strategy has no tolerance yet; the sum must be exactly 100
+    validate_allocation_sum(&alloc_pcts, 0)?;
 
     for &pct in &alloc_pcts {
         require!(pct <= 100, StrategyError::InvalidAllocationEntry);
     }
 
+    validate_allocation_meta(
+        &alloc_meta,
+        alloc_symbols.len(),
+        ctx.accounts.allocation_meta.is_some(),
+    )?;
+
     // Build target allocation
     let mut target_allocation = [AllocationTarget::default(); 5];
     for (i, (symbol, &pct)) in alloc_symbols.iter().zip(alloc_pcts.iter()).enumerate() {
@@ -94,7 +109,15 @@ pub fn handler(
     strategy.last_cycle_at = clock.unix_timestamp;
     strategy.created_at = clock.unix_timestamp;
     strategy.bump = ctx.bumps.strategy_account;
-    strategy._padding = [0u8; 32];
+    strategy.actions_this_cycle = 0;
+    strategy.cycle_start_ts = clock.unix_timestamp;
+    strategy.max_slippage_bps = st.default_max_slippage_bps();
+    strategy.confidence_step = 0;
+    strategy.allocation_tolerance = 0;
+    strategy.last_performance_score = 0;
+    strategy._padding = [0u8; 18];
+    strategy.action_cooldowns = [ActionCooldown::default(); MAX_ACTION_COOLDOWNS];
+    strategy.allowed_protocols = [[0u8; 16]; MAX_ALLOWED_PROTOCOLS];
 
     // Initialize audit trail
     let audit = &mut ctx.accounts.audit_trail;
@@ -103,6 +126,13 @@ pub fn handler(
     audit.count = 0;
     audit.entries = [Default::default(); AUDIT_TRAIL_CAPACITY];
     audit.bump = ctx.bumps.audit_trail;
+    audit.version = AUDIT_TRAIL_VERSION;
+
+    if let Some(meta) = &mut ctx.accounts.allocation_meta {
+        meta.owner = ctx.accounts.owner.key();
+        meta.set_entries(&alloc_meta);
+        meta.bump = ctx.bumps.allocation_meta.unwrap();
+    }
 
     msg!(
         "Strategy account initialized for owner {} with strategy type {:?} in {:?} mode",
@@ -1,6 +1,8 @@
 use anchor_lang::prelude::*;
-use crate::state::{StrategyAccount, AuditTrail, AuditEntry};
+use crate::state::{StrategyAccount, AuditTrail, AuditEntry, ActionCooldown, ActionKind, ActionInput};
 use crate::errors::StrategyError;
+use makora_vault::state::Vault;
+use makora_clock::{checked_now, DEFAULT_MAX_AGE_SECS};
 
 #[derive(Accounts)]
 pub struct LogAction<'info> {
@@ -28,51 +30,134 @@ pub struct LogAction<'info> {
     /// CHECK: Owner pubkey for the has_one constraint on audit_trail.
     /// Not a signer -- the authority signer provides the authorization.
     pub owner: UncheckedAccount<'info>,
+
+    /// Optional vault (makora_vault) the action was taken on.
+    /// Owner and discriminator are checked by Account; it must belong
+    /// to the same owner as the strategy.
+    #[account(
+        constraint = vault.owner == strategy_account.owner @ StrategyError::VaultOwnerMismatch
+    )]
+    pub vault: Option<Box<Account<'info, Vault>>>,
 }
 
 pub fn handler(
     ctx: Context<LogAction>,
-    action_type: String,
+    action_kind: u8,
     protocol: String,
     description: String,
     executed: bool,
     success: bool,
 ) -> Result<()> {
+    // A drifting validator clock must not end up in the audit trail
+    let now = checked_now(ctx.accounts.strategy_account.created_at, DEFAULT_MAX_AGE_SECS)?;
+
+    // Snapshot the vault balance so entries can be reconciled with fund flows
+    let balance_after = ctx
+        .accounts
+        .vault
+        .as_ref()
+        .map(|v| v.current_balance())
+        .unwrap_or(0);
+
+    let action = ActionInput {
+        action_kind,
+        protocol,
+        description,
+        executed,
+        success,
+    };
+    let strategy = &mut ctx.accounts.strategy_account;
+    record_action(strategy, &mut ctx.accounts.audit_trail, &action, now, balance_after)?;
+    strategy.last_cycle_at = now;
+
+    Ok(())
+}
+
+/// Validate one action, enforce the budget, allowlist and cooldown on
+/// executed agent actions, append it to the audit trail and update the
+/// strategy's counters. Shared by log_action and log_actions_batch.
+pub(crate) fn record_action(
+    strategy: &mut StrategyAccount,
+    audit: &mut AuditTrail,
+    action: &ActionInput,
+    now: i64,
+    balance_after: u64,
+) -> Result<()> {
+    let kind = ActionKind::from_u8(action.action_kind)?;
+    let protocol = action.protocol.as_str();
+    let description = action.description.as_str();
+    let (executed, success) = (action.executed, action.success);
+
     // Validate string lengths
-    require!(action_type.len() <= 16, StrategyError::ActionTypeTooLong);
     require!(protocol.len() <= 16, StrategyError::ProtocolTooLong);
     require!(description.len() <= 64, StrategyError::DescriptionTooLong);
+    require!(
+        protocol.is_ascii() && description.is_ascii(),
+        StrategyError::NonAsciiString
+    );
+
+    // Owner deposits are recorded for completeness but are not agent
+    // actions, so they skip the budget, cooldowns and counters
+    let agent_action = executed && kind != ActionKind::Deposit;
+
+    // Enforce the per-cycle action budget; advisory logs don't count
+    if agent_action {
+        require!(
+            strategy.actions_this_cycle < strategy.max_actions_per_cycle,
+            StrategyError::MaxActionsExceeded
+        );
+    }
+
+    // Executed actions must stay within the owner's protocol allowlist
+    if agent_action {
+        require!(
+            strategy.protocol_allowed(protocol),
+            StrategyError::ProtocolNotAllowed
+        );
+    }
 
-    let clock = Clock::get()?;
+    // Enforce the per-action-type cooldown on executed actions
+    if agent_action {
+        let key = ActionCooldown::key(kind.as_str());
+        if let Some(cooldown) = strategy.cooldown_mut(&key) {
+            let ready_at = cooldown
+                .last_at
+                .saturating_add(cooldown.cooldown_secs as i64);
+            require!(
+                cooldown.last_at == 0 || now >= ready_at,
+                StrategyError::ActionTypeOnCooldown
+            );
+            cooldown.last_at = now;
+        }
+    }
 
     // Create audit entry
-    let audit = &mut ctx.accounts.audit_trail;
     let entry = AuditEntry::new(
         audit.count,
-        &action_type,
-        &protocol,
-        &description,
+        kind,
+        protocol,
+        description,
         executed,
         success,
-        clock.unix_timestamp,
+        now,
+        balance_after,
     );
 
     // Append to ring buffer
     audit.append(entry);
 
     // Update strategy account counters
-    let strategy = &mut ctx.accounts.strategy_account;
-    if executed {
+    if agent_action {
         strategy.total_actions_executed = strategy
             .total_actions_executed
             .checked_add(1)
             .unwrap_or(u64::MAX);
+        strategy.actions_this_cycle = strategy.actions_this_cycle.saturating_add(1);
     }
-    strategy.last_cycle_at = clock.unix_timestamp;
 
     msg!(
         "Logged action: {} via {} (executed: {}, success: {})",
-        action_type,
+        kind.as_str(),
         protocol,
         executed,
         success
@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+use crate::state::{StrategyAccount, AuditTrail, ActionInput, MAX_ACTION_BATCH};
+use crate::errors::StrategyError;
+use crate::instructions::log_action::record_action;
+use makora_vault::state::Vault;
+use makora_clock::{checked_now, DEFAULT_MAX_AGE_SECS};
+
+#[derive(Accounts)]
+pub struct LogActionsBatch<'info> {
+    /// Signer must be owner OR agent_authority
+    pub authority: Signer<'info>,
+
+    /// Strategy PDA (for authorization check)
+    #[account(
+        mut,
+        seeds = [b"strategy", strategy_account.owner.as_ref()],
+        bump = strategy_account.bump,
+        constraint = strategy_account.is_authorized(authority.key) @ StrategyError::UnauthorizedLogAction
+    )]
+    pub strategy_account: Account<'info, StrategyAccount>,
+
+    /// Audit trail PDA
+    #[account(
+        mut,
+        seeds = [b"audit", strategy_account.owner.as_ref()],
+        bump = audit_trail.bump,
+        has_one = owner @ StrategyError::UnauthorizedLogAction
+    )]
+    pub audit_trail: Box<Account<'info, AuditTrail>>,
+
+    /// CHECK: Owner pubkey for the has_one constraint on audit_trail.
+    /// Not a signer -- the authority signer provides the authorization.
+    pub owner: UncheckedAccount<'info>,
+
+    /// Optional vault (makora_vault) the actions were taken on; must
+    /// belong to the same owner as the strategy.
+    #[account(
+        constraint = vault.owner == strategy_account.owner @ StrategyError::VaultOwnerMismatch
+    )]
+    pub vault: Option<Box<Account<'info, Vault>>>,
+}
+
+/// Log up to MAX_ACTION_BATCH actions from one cycle, in order. Each
+/// entry goes through the same checks as log_action, and any failure
+/// rejects the whole batch.
+pub fn handler(ctx: Context<LogActionsBatch>, actions: Vec<ActionInput>) -> Result<()> {
+    require!(
+        !actions.is_empty() && actions.len() <= MAX_ACTION_BATCH,
+        StrategyError::ActionBatchTooLarge
+    );
+
+    let now = checked_now(ctx.accounts.strategy_account.created_at, DEFAULT_MAX_AGE_SECS)?;
+    let balance_after = ctx
+        .accounts
+        .vault
+        .as_ref()
+        .map(|v| v.current_balance())
+        .unwrap_or(0);
+
+    let strategy = &mut ctx.accounts.strategy_account;
+    for action in actions.iter() {
+        record_action(strategy, &mut ctx.accounts.audit_trail, action, now, balance_after)?;
+    }
+    strategy.last_cycle_at = now;
+
+    msg!("Logged {} actions", actions.len());
+
+    Ok(())
+}
@@ -0,0 +1,226 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use crate::state::{ActionKind, AuditEntry, AuditTrail, AUDIT_TRAIL_CAPACITY, AUDIT_TRAIL_VERSION};
+use crate::errors::StrategyError;
+
+/// Account length of the original audit trail layout:
+/// 8 + 32 + 4 + 4 + 8 * 110 + 1
+pub const AUDIT_TRAIL_V0_LEN: usize = 929;
+
+/// Audit entry as laid out before ActionKind, with a free-form action type
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+struct AuditEntryV0 {
+    index: u32,
+    action_type: [u8; 16],
+    protocol: [u8; 16],
+    description: [u8; 64],
+    executed: bool,
+    success: bool,
+    timestamp: i64,
+}
+
+/// Audit trail fields in the original layout, after the discriminator
+#[derive(AnchorSerialize, AnchorDeserialize)]
+struct AuditTrailV0 {
+    owner: Pubkey,
+    head: u32,
+    count: u32,
+    entries: [AuditEntryV0; AUDIT_TRAIL_CAPACITY],
+    bump: u8,
+}
+
+#[derive(Accounts)]
+pub struct MigrateAuditTrail<'info> {
+    /// The strategy owner; receives the rent freed by the smaller layout
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: still in the original layout, so it cannot be loaded as an
+    /// AuditTrail. The discriminator and stored owner are checked in the
+    /// handler.
+    #[account(
+        mut,
+        seeds = [b"audit", owner.key().as_ref()],
+        bump,
+        owner = crate::ID @ StrategyError::UnsupportedAuditLayout,
+    )]
+    pub audit_trail: UncheckedAccount<'info>,
+}
+
+/// Rewrite an audit trail created before ActionKind in the current layout.
+///
+/// Each entry's action_type string becomes the ActionKind with that name
+/// (case-insensitive), or Other when none matches. balance_after was not
+/// recorded and is 0. Ring order, head and count are kept. The account
+/// shrinks to AuditTrail::SIZE and the rent it no longer needs goes to
+/// the owner.
+pub fn handler(ctx: Context<MigrateAuditTrail>) -> Result<()> {
+    let audit_info = ctx.accounts.audit_trail.to_account_info();
+    require!(
+        audit_info.data_len() != AuditTrail::SIZE,
+        StrategyError::AuditTrailAlreadyMigrated
+    );
+
+    let legacy = read_v0(&audit_info.try_borrow_data()?)?;
+    require_keys_eq!(
+        legacy.owner,
+        ctx.accounts.owner.key(),
+        StrategyError::UnsupportedAuditLayout
+    );
+
+    let mut audit = upgrade_v0(&legacy);
+    audit.bump = ctx.bumps.audit_trail;
+
+    audit_info.realloc(AuditTrail::SIZE, false)?;
+    {
+        let mut data = audit_info.try_borrow_mut_data()?;
+        let mut dst: &mut [u8] = &mut data;
+        audit.try_serialize(&mut dst)?;
+    }
+
+    let surplus = audit_info
+        .lamports()
+        .saturating_sub(Rent::get()?.minimum_balance(AuditTrail::SIZE));
+    if surplus > 0 {
+        let owner_info = ctx.accounts.owner.to_account_info();
+        **audit_info.try_borrow_mut_lamports()? -= surplus;
+        **owner_info.try_borrow_mut_lamports()? += surplus;
+    }
+
+    msg!(
+        "Audit trail {} migrated to layout v{} ({} entries)",
+        audit_info.key(),
+        AUDIT_TRAIL_VERSION,
+        audit.count
+    );
+    Ok(())
+}
+
+fn read_v0(data: &[u8]) -> Result<AuditTrailV0> {
+    require!(
+        data.len() == AUDIT_TRAIL_V0_LEN && data[..8] == AuditTrail::DISCRIMINATOR,
+        StrategyError::UnsupportedAuditLayout
+    );
+    AuditTrailV0::deserialize(&mut &data[8..])
+        .map_err(|_| error!(StrategyError::UnsupportedAuditLayout))
+}
+
+fn upgrade_v0(legacy: &AuditTrailV0) -> AuditTrail {
+    let mut entries = [AuditEntry::default(); AUDIT_TRAIL_CAPACITY];
+    for (entry, old) in entries.iter_mut().zip(legacy.entries.iter()) {
+        *entry = AuditEntry {
+            index: old.index,
+            action_kind: kind_from_action_type(&old.action_type) as u8,
+            protocol: old.protocol,
+            description: old.description,
+            executed: old.executed,
+            success: old.success,
+            timestamp: old.timestamp,
+            balance_after: 0,
+        };
+    }
+
+    AuditTrail {
+        owner: legacy.owner,
+        head: legacy.head,
+        count: legacy.count,
+        entries,
+        bump: legacy.bump,
+        version: AUDIT_TRAIL_VERSION,
+    }
+}
+
+fn kind_from_action_type(action_type: &[u8; 16]) -> ActionKind {
+    let end = action_type.iter().position(|&b| b == 0).unwrap_or(16);
+    let name = String::from_utf8_lossy(&action_type[..end]).to_ascii_lowercase();
+    (0..=ActionKind::Deposit as u8)
+        .filter_map(|v| ActionKind::from_u8(v).ok())
+        .find(|kind| kind.as_str() == name)
+        .unwrap_or(ActionKind::Other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn padded<const N: usize>(s: &str) -> [u8; N] {
+        let mut buf = [0u8; N];
+        buf[..s.len()].copy_from_slice(s.as_bytes());
+        buf
+    }
+
+    fn legacy_entry(index: u32, action_type: &str) -> AuditEntryV0 {
+        AuditEntryV0 {
+            index,
+            action_type: padded(action_type),
+            protocol: padded("jupiter"),
+            description: padded("legacy entry"),
+            executed: true,
+            success: index % 2 == 0,
+            timestamp: 1_700_000_000 + index as i64,
+        }
+    }
+
+    fn legacy_account() -> Vec<u8> {
+        let mut entries = [legacy_entry(0, ""); AUDIT_TRAIL_CAPACITY];
+        entries[0] = legacy_entry(0, "Swap");
+        entries[1] = legacy_entry(1, "STAKE");
+        entries[2] = legacy_entry(2, "yield-farm");
+        let legacy = AuditTrailV0 {
+            owner: Pubkey::new_unique(),
+            head: 3,
+            count: 3,
+            entries,
+            bump: 253,
+        };
+        let mut data = AuditTrail::DISCRIMINATOR.to_vec();
+        legacy.serialize(&mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn maps_action_type_strings_to_kinds() {
+        assert_eq!(kind_from_action_type(&padded("swap")), ActionKind::Swap);
+        assert_eq!(kind_from_action_type(&padded("Rebalance")), ActionKind::Rebalance);
+        assert_eq!(kind_from_action_type(&padded("UNSTAKE")), ActionKind::Unstake);
+        assert_eq!(kind_from_action_type(&padded("yield-farm")), ActionKind::Other);
+        assert_eq!(kind_from_action_type(&padded("")), ActionKind::Other);
+    }
+
+    #[test]
+    fn upgrades_an_original_trail() {
+        let data = legacy_account();
+        assert_eq!(data.len(), AUDIT_TRAIL_V0_LEN);
+        let legacy = read_v0(&data).unwrap();
+
+        let mut migrated = vec![0u8; AuditTrail::SIZE];
+        let mut dst: &mut [u8] = &mut migrated;
+        upgrade_v0(&legacy).try_serialize(&mut dst).unwrap();
+        assert!(dst.is_empty());
+
+        let audit = AuditTrail::try_deserialize(&mut &migrated[..]).unwrap();
+        assert_eq!(audit.owner, legacy.owner);
+        assert_eq!((audit.head, audit.count, audit.bump), (3, 3, 253));
+        assert_eq!(audit.version, AUDIT_TRAIL_VERSION);
+
+        let recent = audit.recent(3);
+        assert_eq!(recent[0].action_kind, ActionKind::Other as u8);
+        assert_eq!(recent[1].action_kind, ActionKind::Stake as u8);
+        assert_eq!(recent[2].action_kind, ActionKind::Swap as u8);
+        assert_eq!(recent[2].protocol_str(), "jupiter");
+        assert_eq!(recent[2].description_str(), "legacy entry");
+        assert_eq!(recent[2].timestamp, 1_700_000_000);
+        assert_eq!(recent[2].balance_after, 0);
+        assert_eq!(audit.executed_outcomes(), vec![true, false, true]);
+    }
+
+    #[test]
+    fn rejects_other_accounts() {
+        let mut data = legacy_account();
+        data[0] ^= 1;
+        assert!(read_v0(&data).is_err());
+
+        let data = legacy_account();
+        assert!(read_v0(&data[..AUDIT_TRAIL_V0_LEN - 1]).is_err());
+    }
+}
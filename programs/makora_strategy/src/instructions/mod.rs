@@ -2,8 +2,38 @@ pub mod initialize;
 pub mod update_strategy;
 pub mod log_action;
 pub mod update_permissions;
+pub mod init_symbol_registry;
+pub mod register_symbols_batch;
+pub mod deregister_symbol;
+pub mod set_action_cooldown;
+pub mod sync_mode;
+pub mod snapshot_allocation;
+pub mod validate_action_slippage;
+pub mod set_confidence_step;
+pub mod dump_audit;
+pub mod get_allocation_summary;
+pub mod set_allocation_tolerance;
+pub mod compute_performance;
+pub mod set_allowed_protocols;
+pub mod log_actions_batch;
+pub mod migrate_audit_trail;
 
 pub use initialize::*;
 pub use update_strategy::*;
 pub use log_action::*;
 pub use update_permissions::*;
+pub use init_symbol_registry::*;
+pub use register_symbols_batch::*;
+pub use deregister_symbol::*;
+pub use set_action_cooldown::*;
+pub use sync_mode::*;
+pub use snapshot_allocation::*;
+pub use validate_action_slippage::*;
+pub use set_confidence_step::*;
+pub use dump_audit::*;
+pub use get_allocation_summary::*;
+pub use set_allocation_tolerance::*;
+pub use compute_performance::*;
+pub use set_allowed_protocols::*;
+pub use log_actions_batch::*;
+pub use migrate_audit_trail::*;
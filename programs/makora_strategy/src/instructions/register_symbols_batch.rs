@@ -0,0 +1,55 @@
+use anchor_lang::prelude::*;
+use crate::state::{SymbolRegistry, SymbolEntry, MAX_REGISTERED_SYMBOLS, MAX_SYMBOL_BATCH};
+use crate::errors::StrategyError;
+
+#[derive(Accounts)]
+pub struct RegisterSymbolsBatch<'info> {
+    /// ONLY the registry authority can register symbols
+    pub authority: Signer<'info>,
+
+    /// Symbol registry PDA
+    #[account(
+        mut,
+        seeds = [b"symbol_registry"],
+        bump = symbol_registry.bump,
+        has_one = authority @ StrategyError::UnauthorizedRegistryUpdate
+    )]
+    pub symbol_registry: Box<Account<'info, SymbolRegistry>>,
+}
+
+pub fn handler(ctx: Context<RegisterSymbolsBatch>, entries: Vec<SymbolEntry>) -> Result<()> {
+    require!(
+        !entries.is_empty() && entries.len() <= MAX_SYMBOL_BATCH,
+        StrategyError::SymbolBatchTooLarge
+    );
+
+    let registry = &mut ctx.accounts.symbol_registry;
+    require!(
+        registry.entries.len() + entries.len() <= MAX_REGISTERED_SYMBOLS,
+        StrategyError::SymbolRegistryFull
+    );
+
+    // Reject duplicates within the batch and against existing entries
+    for (i, entry) in entries.iter().enumerate() {
+        require!(entry.symbol[0] != 0, StrategyError::InvalidSymbol);
+        require!(
+            !registry.contains(&entry.symbol),
+            StrategyError::DuplicateSymbol
+        );
+        require!(
+            !entries[..i].iter().any(|e| e.symbol == entry.symbol),
+            StrategyError::DuplicateSymbol
+        );
+    }
+
+    let added = entries.len();
+    registry.entries.extend(entries);
+
+    msg!(
+        "Registered {} symbols ({} total)",
+        added,
+        registry.entries.len()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+use crate::state::{StrategyAccount, ActionCooldown, ActionKind};
+use crate::errors::StrategyError;
+
+#[derive(Accounts)]
+pub struct SetActionCooldown<'info> {
+    /// ONLY the owner can configure cooldowns (not the agent)
+    pub owner: Signer<'info>,
+
+    /// Strategy PDA
+    #[account(
+        mut,
+        seeds = [b"strategy", strategy_account.owner.as_ref()],
+        bump = strategy_account.bump,
+        has_one = owner @ StrategyError::UnauthorizedPermissionsUpdate
+    )]
+    pub strategy_account: Account<'info, StrategyAccount>,
+}
+
+/// Set (or with cooldown_secs == 0, clear) the cooldown for an action kind.
+pub fn handler(
+    ctx: Context<SetActionCooldown>,
+    action_kind: u8,
+    cooldown_secs: u32,
+) -> Result<()> {
+    let kind = ActionKind::from_u8(action_kind)?;
+    let key = ActionCooldown::key(kind.as_str());
+    let strategy = &mut ctx.accounts.strategy_account;
+
+    if cooldown_secs == 0 {
+        if let Some(cooldown) = strategy.cooldown_mut(&key) {
+            *cooldown = ActionCooldown::default();
+        }
+    } else if let Some(cooldown) = strategy.cooldown_mut(&key) {
+        cooldown.cooldown_secs = cooldown_secs;
+    } else {
+        let slot = strategy
+            .action_cooldowns
+            .iter_mut()
+            .find(|c| c.is_empty())
+            .ok_or(error!(StrategyError::CooldownTableFull))?;
+        *slot = ActionCooldown {
+            action_type: key,
+            cooldown_secs,
+            last_at: 0,
+        };
+    }
+
+    msg!(
+        "Cooldown for '{}' set to {}s",
+        kind.as_str(),
+        cooldown_secs
+    );
+
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+use crate::state::{StrategyAccount, MAX_ALLOCATION_TOLERANCE};
+use crate::errors::StrategyError;
+
+#[derive(Accounts)]
+pub struct SetAllocationTolerance<'info> {
+    /// ONLY the owner can loosen the allocation check (not the agent)
+    pub owner: Signer<'info>,
+
+    /// Strategy PDA
+    #[account(
+        mut,
+        seeds = [b"strategy", strategy_account.owner.as_ref()],
+        bump = strategy_account.bump,
+        has_one = owner @ StrategyError::UnauthorizedPermissionsUpdate
+    )]
+    pub strategy_account: Account<'info, StrategyAccount>,
+}
+
+/// Set how many points update_strategy lets the allocation percentages
+/// sum away from 100 (0 requires exactly 100).
+pub fn handler(ctx: Context<SetAllocationTolerance>, tolerance: u8) -> Result<()> {
+    require!(
+        tolerance <= MAX_ALLOCATION_TOLERANCE,
+        StrategyError::InvalidAllocationTolerance
+    );
+
+    ctx.accounts.strategy_account.allocation_tolerance = tolerance;
+
+    msg!("Allocation tolerance set to {}", tolerance);
+
+    Ok(())
+}
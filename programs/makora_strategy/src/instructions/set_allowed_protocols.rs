@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+use crate::state::{protocol_key, StrategyAccount, MAX_ALLOWED_PROTOCOLS};
+use crate::errors::StrategyError;
+
+#[derive(Accounts)]
+pub struct SetAllowedProtocols<'info> {
+    /// ONLY the owner can restrict the agent's protocols (not the agent)
+    pub owner: Signer<'info>,
+
+    /// Strategy PDA
+    #[account(
+        mut,
+        seeds = [b"strategy", strategy_account.owner.as_ref()],
+        bump = strategy_account.bump,
+        has_one = owner @ StrategyError::UnauthorizedPermissionsUpdate
+    )]
+    pub strategy_account: Account<'info, StrategyAccount>,
+}
+
+/// Replace the protocol allowlist (an empty list allows every protocol).
+pub fn handler(ctx: Context<SetAllowedProtocols>, protocols: Vec<String>) -> Result<()> {
+    require!(
+        protocols.len() <= MAX_ALLOWED_PROTOCOLS,
+        StrategyError::InvalidProtocolAllowlist
+    );
+
+    let mut allowed = [[0u8; 16]; MAX_ALLOWED_PROTOCOLS];
+    for (slot, protocol) in allowed.iter_mut().zip(&protocols) {
+        require!(
+            !protocol.is_empty() && protocol.len() <= 16 && protocol.is_ascii(),
+            StrategyError::InvalidProtocolAllowlist
+        );
+        *slot = protocol_key(protocol);
+    }
+
+    ctx.accounts.strategy_account.allowed_protocols = allowed;
+
+    msg!("Protocol allowlist set ({} protocols)", protocols.len());
+
+    Ok(())
+}
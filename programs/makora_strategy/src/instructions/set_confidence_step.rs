@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+use crate::state::StrategyAccount;
+use crate::errors::StrategyError;
+
+#[derive(Accounts)]
+pub struct SetConfidenceStep<'info> {
+    /// ONLY the owner can configure adaptive confidence (not the agent)
+    pub owner: Signer<'info>,
+
+    /// Strategy PDA
+    #[account(
+        mut,
+        seeds = [b"strategy", strategy_account.owner.as_ref()],
+        bump = strategy_account.bump,
+        has_one = owner @ StrategyError::UnauthorizedPermissionsUpdate
+    )]
+    pub strategy_account: Account<'info, StrategyAccount>,
+}
+
+/// Set how far update_strategy moves the confidence threshold after a
+/// losing or winning streak (0 turns adaptive confidence off).
+pub fn handler(ctx: Context<SetConfidenceStep>, step: u8) -> Result<()> {
+    require!(step <= 100, StrategyError::InvalidConfidenceStep);
+
+    ctx.accounts.strategy_account.confidence_step = step;
+
+    msg!("Confidence step set to {}", step);
+
+    Ok(())
+}
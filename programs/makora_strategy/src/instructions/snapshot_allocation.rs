@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+use crate::state::{StrategyAccount, AllocationHistory, AllocationSnapshot};
+use crate::errors::StrategyError;
+
+#[derive(Accounts)]
+pub struct SnapshotAllocation<'info> {
+    /// Signer must be owner OR agent_authority (pays for the history on first use)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Strategy PDA
+    #[account(
+        seeds = [b"strategy", strategy_account.owner.as_ref()],
+        bump = strategy_account.bump,
+        constraint = strategy_account.is_authorized(authority.key) @ StrategyError::UnauthorizedStrategyUpdate
+    )]
+    pub strategy_account: Account<'info, StrategyAccount>,
+
+    /// Allocation history PDA, created on the first snapshot
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = AllocationHistory::SIZE,
+        seeds = [b"allocation_history", strategy_account.owner.as_ref()],
+        bump
+    )]
+    pub allocation_history: Box<Account<'info, AllocationHistory>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<SnapshotAllocation>) -> Result<()> {
+    let strategy = &ctx.accounts.strategy_account;
+    let history = &mut ctx.accounts.allocation_history;
+
+    // A freshly created history is zeroed
+    if history.owner == Pubkey::default() {
+        history.owner = strategy.owner;
+        history.bump = ctx.bumps.allocation_history;
+    }
+
+    let clock = Clock::get()?;
+    history.append(AllocationSnapshot {
+        timestamp: clock.unix_timestamp,
+        allocation_count: strategy.allocation_count,
+        allocation: strategy.target_allocation,
+    });
+
+    msg!(
+        "Allocation snapshot #{} recorded ({} targets)",
+        history.count,
+        strategy.allocation_count
+    );
+
+    Ok(())
+}
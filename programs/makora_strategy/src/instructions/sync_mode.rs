@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+use crate::state::{StrategyAccount, AgentMode};
+use crate::errors::StrategyError;
+use makora_vault::state::Vault;
+
+#[derive(Accounts)]
+pub struct SyncMode<'info> {
+    /// Owner or agent_authority may resync; the vault is the source of truth
+    pub authority: Signer<'info>,
+
+    /// Strategy PDA
+    #[account(
+        mut,
+        seeds = [b"strategy", strategy_account.owner.as_ref()],
+        bump = strategy_account.bump,
+        constraint = strategy_account.is_authorized(authority.key) @ StrategyError::UnauthorizedPermissionsUpdate
+    )]
+    pub strategy_account: Account<'info, StrategyAccount>,
+
+    /// The owner's vault (makora_vault) to copy the mode from
+    #[account(
+        constraint = vault.owner == strategy_account.owner @ StrategyError::VaultOwnerMismatch
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+}
+
+pub fn handler(ctx: Context<SyncMode>) -> Result<()> {
+    let mode = AgentMode::from_vault(ctx.accounts.vault.mode);
+
+    let strategy = &mut ctx.accounts.strategy_account;
+    let previous = strategy.mode;
+    strategy.mode = mode;
+
+    msg!("Strategy mode synced to vault: {:?} -> {:?}", previous, mode);
+
+    Ok(())
+}
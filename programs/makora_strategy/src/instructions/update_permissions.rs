@@ -1,6 +1,7 @@
 use anchor_lang::prelude::*;
 use crate::state::{StrategyAccount, AgentMode};
 use crate::errors::StrategyError;
+use makora_vault::state::Vault;
 
 #[derive(Accounts)]
 pub struct UpdatePermissions<'info> {
@@ -15,6 +16,14 @@ pub struct UpdatePermissions<'info> {
         has_one = owner @ StrategyError::UnauthorizedPermissionsUpdate
     )]
     pub strategy_account: Account<'info, StrategyAccount>,
+
+    /// The owner's vault (makora_vault); the new mode must match its mode
+    #[account(
+        seeds = [b"vault", strategy_account.owner.as_ref()],
+        bump = vault.bump,
+        seeds::program = makora_vault::ID,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
 }
 
 pub fn handler(
@@ -25,6 +34,11 @@ pub fn handler(
     let mode = AgentMode::from_u8(new_mode)
         .map_err(|_| error!(StrategyError::InvalidAgentMode))?;
 
+    require!(
+        mode == AgentMode::from_vault(ctx.accounts.vault.mode),
+        StrategyError::ModeMismatch
+    );
+
     let strategy = &mut ctx.accounts.strategy_account;
     strategy.agent_authority = new_agent_authority;
     strategy.mode = mode;
@@ -1,5 +1,5 @@
 use anchor_lang::prelude::*;
-use crate::state::{StrategyAccount, StrategyType, AllocationTarget};
+use crate::state::{StrategyAccount, StrategyType, AllocationTarget, AuditTrail, AllocationMeta, AllocationMetaEntry, validate_allocation_meta, validate_allocation_sum};
 use crate::errors::StrategyError;
 
 #[derive(Accounts)]
@@ -15,8 +15,25 @@ pub struct UpdateStrategy<'info> {
         constraint = strategy_account.is_authorized(authority.key) @ StrategyError::UnauthorizedStrategyUpdate
     )]
     pub strategy_account: Account<'info, StrategyAccount>,
+
+    /// Optional audit trail; with a nonzero confidence_step, its recent
+    /// outcomes adjust the confidence threshold
+    #[account(
+        seeds = [b"audit", strategy_account.owner.as_ref()],
+        bump = audit_trail.bump,
+    )]
+    pub audit_trail: Option<Box<Account<'info, AuditTrail>>>,
+
+    /// Optional allocation metadata, rewritten to match the new allocation
+    #[account(
+        mut,
+        seeds = [b"allocation_meta", strategy_account.owner.as_ref()],
+        bump = allocation_meta.bump,
+    )]
+    pub allocation_meta: Option<Box<Account<'info, AllocationMeta>>>,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn handler(
     ctx: Context<UpdateStrategy>,
     strategy_type: u8,
@@ -24,6 +41,8 @@ pub fn handler(
     max_actions_per_cycle: u8,
     alloc_symbols: Vec<[u8; 8]>,
     alloc_pcts: Vec<u8>,
+    max_slippage_bps: Option<u16>,
+    alloc_meta: Vec<AllocationMetaEntry>,
 ) -> Result<()> {
     // Validate strategy type
     let st = StrategyType::from_u8(strategy_type)
@@ -39,15 +58,22 @@ pub fn handler(
         StrategyError::InvalidAllocationEntry
     );
 
-    let alloc_sum: u16 = alloc_pcts.iter().map(|&p| p as u16).sum();
-    if !alloc_pcts.is_empty() {
-        require!(alloc_sum == 100, StrategyError::InvalidAllocationSum);
-    }
+    validate_allocation_sum(&alloc_pcts, ctx.accounts.strategy_account.allocation_tolerance)?;
 
     for &pct in &alloc_pcts {
         require!(pct <= 100, StrategyError::InvalidAllocationEntry);
     }
 
+    validate_allocation_meta(
+        &alloc_meta,
+        alloc_symbols.len(),
+        ctx.accounts.allocation_meta.is_some(),
+    )?;
+
+    // Fall back to the strategy type's default cap
+    let max_slippage_bps = max_slippage_bps.unwrap_or_else(|| st.default_max_slippage_bps());
+    require!(max_slippage_bps <= 10_000, StrategyError::InvalidSlippageBps);
+
     // Build target allocation
     let mut target_allocation = [AllocationTarget::default(); 5];
     for (i, (symbol, &pct)) in alloc_symbols.iter().zip(alloc_pcts.iter()).enumerate() {
@@ -57,6 +83,18 @@ pub fn handler(
         };
     }
 
+    // Adaptive confidence: more cautious after a losing streak, less
+    // after a winning one
+    let step = ctx.accounts.strategy_account.confidence_step;
+    let confidence_threshold = match (&ctx.accounts.audit_trail, step) {
+        (Some(audit), step) if step > 0 => match audit.recent_streak() {
+            Some(false) => confidence_threshold.saturating_add(step).min(100),
+            Some(true) => confidence_threshold.saturating_sub(step),
+            None => confidence_threshold,
+        },
+        _ => confidence_threshold,
+    };
+
     let clock = Clock::get()?;
 
     // Update strategy account
@@ -66,13 +104,24 @@ pub fn handler(
     strategy.max_actions_per_cycle = max_actions_per_cycle;
     strategy.target_allocation = target_allocation;
     strategy.allocation_count = alloc_symbols.len() as u8;
+    strategy.max_slippage_bps = max_slippage_bps;
     strategy.total_cycles = strategy.total_cycles.checked_add(1).unwrap_or(u64::MAX);
+    // A new cycle starts with a fresh action budget
+    strategy.actions_this_cycle = 0;
+    strategy.cycle_start_ts = clock.unix_timestamp;
     strategy.last_cycle_at = clock.unix_timestamp;
 
+    // The slots were replaced, so stale metadata is cleared even when no
+    // new entries are given
+    if let Some(meta) = &mut ctx.accounts.allocation_meta {
+        meta.set_entries(&alloc_meta);
+    }
+
     msg!(
-        "Strategy updated to {:?} by {}",
+        "Strategy updated to {:?} by {} (confidence threshold {})",
         st,
-        ctx.accounts.authority.key()
+        ctx.accounts.authority.key(),
+        confidence_threshold
     );
 
     Ok(())
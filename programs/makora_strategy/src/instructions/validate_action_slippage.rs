@@ -0,0 +1,25 @@
+use anchor_lang::prelude::*;
+use crate::state::StrategyAccount;
+
+#[derive(Accounts)]
+pub struct ValidateActionSlippage<'info> {
+    /// Strategy PDA whose slippage cap applies
+    #[account(
+        seeds = [b"strategy", strategy_account.owner.as_ref()],
+        bump = strategy_account.bump,
+    )]
+    pub strategy_account: Account<'info, StrategyAccount>,
+}
+
+pub fn handler(ctx: Context<ValidateActionSlippage>, slippage_bps: u16) -> Result<()> {
+    let strategy = &ctx.accounts.strategy_account;
+    strategy.validate_action_slippage(slippage_bps)?;
+
+    msg!(
+        "Slippage {} bps within cap of {} bps",
+        slippage_bps,
+        strategy.max_slippage_bps
+    );
+
+    Ok(())
+}
@@ -5,6 +5,7 @@ pub mod instructions;
 pub mod state;
 
 use instructions::*;
+use state::{ActionInput, AllocationMetaEntry, SymbolEntry};
 
 declare_id!("EH5sixTHAoLsdFox1bR3YUqgwf5VuX2BdXFew5wTE6dj");
 
@@ -16,6 +17,8 @@ pub mod makora_strategy {
     /// Creates two PDAs:
     ///   - StrategyAccount: seeds = ["strategy", owner]
     ///   - AuditTrail: seeds = ["audit", owner]
+    /// and, when passed, AllocationMeta (["allocation_meta", owner]) holding
+    /// `alloc_meta`: the mint and decimals of each allocation slot.
     pub fn initialize(
         ctx: Context<Initialize>,
         agent_authority: Pubkey,
@@ -25,6 +28,7 @@ pub mod makora_strategy {
         max_actions_per_cycle: u8,
         alloc_symbols: Vec<[u8; 8]>,
         alloc_pcts: Vec<u8>,
+        alloc_meta: Vec<AllocationMetaEntry>,
     ) -> Result<()> {
         instructions::initialize::handler(
             ctx,
@@ -35,11 +39,17 @@ pub mod makora_strategy {
             max_actions_per_cycle,
             alloc_symbols,
             alloc_pcts,
+            alloc_meta,
         )
     }
 
     /// Update the active strategy and target allocation.
+    /// `max_slippage_bps` defaults to the strategy type's cap when None.
+    /// With the audit trail passed and a confidence step set, the
+    /// confidence threshold adapts to recent action outcomes. With the
+    /// allocation metadata passed, it is rewritten from `alloc_meta`.
     /// Callable by owner OR agent_authority.
+    #[allow(clippy::too_many_arguments)]
     pub fn update_strategy(
         ctx: Context<UpdateStrategy>,
         strategy_type: u8,
@@ -47,6 +57,8 @@ pub mod makora_strategy {
         max_actions_per_cycle: u8,
         alloc_symbols: Vec<[u8; 8]>,
         alloc_pcts: Vec<u8>,
+        max_slippage_bps: Option<u16>,
+        alloc_meta: Vec<AllocationMetaEntry>,
     ) -> Result<()> {
         instructions::update_strategy::handler(
             ctx,
@@ -55,6 +67,8 @@ pub mod makora_strategy {
             max_actions_per_cycle,
             alloc_symbols,
             alloc_pcts,
+            max_slippage_bps,
+            alloc_meta,
         )
     }
 
@@ -62,7 +76,7 @@ pub mod makora_strategy {
     /// Callable by owner OR agent_authority.
     pub fn log_action(
         ctx: Context<LogAction>,
-        action_type: String,
+        action_kind: u8,
         protocol: String,
         description: String,
         executed: bool,
@@ -70,7 +84,7 @@ pub mod makora_strategy {
     ) -> Result<()> {
         instructions::log_action::handler(
             ctx,
-            action_type,
+            action_kind,
             protocol,
             description,
             executed,
@@ -78,8 +92,19 @@ pub mod makora_strategy {
         )
     }
 
+    /// Log up to 8 actions from one cycle in a single transaction. Each
+    /// entry is checked as in log_action; one failure rejects the batch.
+    /// Callable by owner OR agent_authority.
+    pub fn log_actions_batch(
+        ctx: Context<LogActionsBatch>,
+        actions: Vec<ActionInput>,
+    ) -> Result<()> {
+        instructions::log_actions_batch::handler(ctx, actions)
+    }
+
     /// Update agent permissions (authority key, mode).
-    /// ONLY callable by the owner (not the agent).
+    /// ONLY callable by the owner (not the agent). The new mode must
+    /// match the owner's vault.
     pub fn update_permissions(
         ctx: Context<UpdatePermissions>,
         new_agent_authority: Pubkey,
@@ -91,4 +116,114 @@ pub mod makora_strategy {
             new_mode,
         )
     }
+
+    /// Initialize the deployment-wide symbol registry.
+    /// The signer becomes the registry authority.
+    pub fn init_symbol_registry(ctx: Context<InitSymbolRegistry>) -> Result<()> {
+        instructions::init_symbol_registry::handler(ctx)
+    }
+
+    /// Register up to 16 symbols in one transaction.
+    /// Rejects duplicates within the batch and against existing entries.
+    /// ONLY callable by the registry authority.
+    pub fn register_symbols_batch(
+        ctx: Context<RegisterSymbolsBatch>,
+        entries: Vec<SymbolEntry>,
+    ) -> Result<()> {
+        instructions::register_symbols_batch::handler(ctx, entries)
+    }
+
+    /// Remove a delisted symbol from the registry.
+    /// ONLY callable by the registry authority.
+    pub fn deregister_symbol(ctx: Context<DeregisterSymbol>, symbol: [u8; 8]) -> Result<()> {
+        instructions::deregister_symbol::handler(ctx, symbol)
+    }
+
+    /// Set the cooldown for an action kind (0 clears it).
+    /// Executed actions of that type are rejected until it elapses.
+    /// ONLY callable by the owner (not the agent).
+    pub fn set_action_cooldown(
+        ctx: Context<SetActionCooldown>,
+        action_kind: u8,
+        cooldown_secs: u32,
+    ) -> Result<()> {
+        instructions::set_action_cooldown::handler(ctx, action_kind, cooldown_secs)
+    }
+
+    /// Set the adaptive confidence step (0 turns it off).
+    /// When update_strategy is passed the audit trail, the threshold is
+    /// raised by the step after a losing streak and lowered after a
+    /// winning one. ONLY callable by the owner (not the agent).
+    pub fn set_confidence_step(ctx: Context<SetConfidenceStep>, step: u8) -> Result<()> {
+        instructions::set_confidence_step::handler(ctx, step)
+    }
+
+    /// Align the strategy mode with the owner's vault mode.
+    /// Callable by owner OR agent_authority.
+    pub fn sync_mode(ctx: Context<SyncMode>) -> Result<()> {
+        instructions::sync_mode::handler(ctx)
+    }
+
+    /// Record the current target allocation in the allocation history.
+    /// Callable by owner OR agent_authority, typically once per cycle.
+    pub fn snapshot_allocation(ctx: Context<SnapshotAllocation>) -> Result<()> {
+        instructions::snapshot_allocation::handler(ctx)
+    }
+
+    /// Check a proposed action's slippage against the strategy cap.
+    /// Read-only; clients simulate it before proposing an action.
+    pub fn validate_action_slippage(
+        ctx: Context<ValidateActionSlippage>,
+        slippage_bps: u16,
+    ) -> Result<()> {
+        instructions::validate_action_slippage::handler(ctx, slippage_bps)
+    }
+
+    /// Export the retained audit entries, oldest first, with decoded
+    /// strings, via return data. Entries that would not fit the return
+    /// data limit are dropped oldest first. Read-only.
+    pub fn dump_audit(ctx: Context<DumpAudit>) -> Result<AuditDump> {
+        instructions::dump_audit::handler(ctx)
+    }
+
+    /// Report how many allocation slots are free and how much of the
+    /// 100% is unassigned, via return data. Read-only.
+    pub fn get_allocation_summary(ctx: Context<GetAllocationSummary>) -> Result<AllocationSummary> {
+        instructions::get_allocation_summary::handler(ctx)
+    }
+
+    /// Set how far the allocation percentages may sum from 100 (0-5
+    /// points) on update_strategy, to allow for rounding across tokens.
+    /// ONLY callable by the owner (not the agent).
+    pub fn set_allocation_tolerance(
+        ctx: Context<SetAllocationTolerance>,
+        tolerance: u8,
+    ) -> Result<()> {
+        instructions::set_allocation_tolerance::handler(ctx, tolerance)
+    }
+
+    /// Score the agent from the audit trail: the success rate of executed
+    /// actions and a recency-weighted score, via return data. The score is
+    /// cached on the strategy as last_performance_score.
+    /// Callable by owner OR agent_authority.
+    pub fn compute_performance(ctx: Context<ComputePerformance>) -> Result<PerformanceReport> {
+        instructions::compute_performance::handler(ctx)
+    }
+
+    /// Restrict executed actions in log_action to up to 8 protocols (an
+    /// empty list allows every protocol).
+    /// ONLY callable by the owner (not the agent).
+    pub fn set_allowed_protocols(
+        ctx: Context<SetAllowedProtocols>,
+        protocols: Vec<String>,
+    ) -> Result<()> {
+        instructions::set_allowed_protocols::handler(ctx, protocols)
+    }
+
+    /// Rewrite an audit trail created before ActionKind in the current
+    /// layout, mapping each free-form action type to its ActionKind.
+    /// ONLY callable by the owner (not the agent).
+    pub fn migrate_audit_trail(ctx: Context<MigrateAuditTrail>) -> Result<()> {
+        instructions::migrate_audit_trail::handler(ctx)
+    }
 }
@@ -0,0 +1,85 @@
+use anchor_lang::prelude::*;
+use crate::state::AllocationTarget;
+
+/// Ring buffer capacity for allocation snapshots
+pub const ALLOCATION_HISTORY_CAPACITY: usize = 32;
+
+/// The strategy's target allocation at a point in time.
+///
+/// Size: 8 + 1 + 45 = 54 bytes per snapshot
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug)]
+pub struct AllocationSnapshot {
+    /// Unix timestamp the snapshot was taken
+    pub timestamp: i64,
+    /// How many of the 5 allocation slots were in use
+    pub allocation_count: u8,
+    /// Copy of StrategyAccount.target_allocation
+    pub allocation: [AllocationTarget; 5],
+}
+
+impl AllocationSnapshot {
+    pub const SIZE: usize = 8 + 1 + 45;
+}
+
+/// Allocation History PDA
+///
+/// Seeds: ["allocation_history", owner_pubkey]
+/// Stores the last 32 allocation snapshots as a ring buffer.
+///
+/// Size calculation:
+///   discriminator: 8
+///   owner: 32
+///   head: 4
+///   count: 4
+///   snapshots: 32 * 54 = 1728
+///   bump: 1
+///   TOTAL: 8 + 32 + 4 + 4 + 1728 + 1 = 1777
+#[account]
+pub struct AllocationHistory {
+    /// The wallet owner
+    pub owner: Pubkey,
+
+    /// Index of the next write position (wraps around at ALLOCATION_HISTORY_CAPACITY)
+    pub head: u32,
+
+    /// Total number of snapshots taken (can exceed ALLOCATION_HISTORY_CAPACITY)
+    pub count: u32,
+
+    /// Ring buffer of snapshots
+    pub snapshots: [AllocationSnapshot; ALLOCATION_HISTORY_CAPACITY],
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl AllocationHistory {
+    pub const SIZE: usize = 8 +    // discriminator
+        32 +                         // owner
+        4 +                          // head
+        4 +                          // count
+        (AllocationSnapshot::SIZE * ALLOCATION_HISTORY_CAPACITY) + // snapshots
+        1;                           // bump
+
+    /// Append a snapshot to the ring buffer.
+    /// Overwrites the oldest snapshot when full.
+    pub fn append(&mut self, snapshot: AllocationSnapshot) {
+        let idx = (self.head as usize) % ALLOCATION_HISTORY_CAPACITY;
+        self.snapshots[idx] = snapshot;
+        self.head = self.head.wrapping_add(1);
+        self.count = self.count.saturating_add(1);
+    }
+
+    /// Get the most recent N snapshots (newest first).
+    pub fn recent(&self, n: usize) -> Vec<&AllocationSnapshot> {
+        let effective_count = (self.count as usize).min(ALLOCATION_HISTORY_CAPACITY);
+        let take = n.min(effective_count);
+
+        (0..take)
+            .map(|i| {
+                let idx = (self.head as usize + ALLOCATION_HISTORY_CAPACITY - 1 - i)
+                    % ALLOCATION_HISTORY_CAPACITY;
+                &self.snapshots[idx]
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,126 @@
+use anchor_lang::prelude::*;
+use crate::state::MAX_ALLOCATION_SLOTS;
+
+/// Decimals assumed for a slot with no metadata (SOL)
+pub const DEFAULT_ALLOCATION_DECIMALS: u8 = 9;
+
+/// Mint and decimals of one target allocation slot.
+/// A default (zero) mint means the slot has no metadata.
+///
+/// Size: 32 + 1 = 33 bytes per entry
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct AllocationMetaEntry {
+    /// SPL mint of the slot's token
+    pub mint: Pubkey,
+    /// Decimals of `mint`
+    pub decimals: u8,
+}
+
+impl AllocationMetaEntry {
+    pub const SIZE: usize = 32 + 1;
+
+    pub fn is_set(&self) -> bool {
+        self.mint != Pubkey::default()
+    }
+}
+
+/// Allocation Metadata PDA
+///
+/// Seeds: ["allocation_meta", owner_pubkey]
+/// Parallel to StrategyAccount.target_allocation: entry i describes slot i.
+///
+/// Size calculation:
+///   discriminator: 8
+///   owner: 32
+///   entries: 5 * 33 = 165
+///   bump: 1
+///   TOTAL: 8 + 32 + 165 + 1 = 206
+#[account]
+pub struct AllocationMeta {
+    /// The wallet owner (same as strategy owner)
+    pub owner: Pubkey,
+
+    /// Mint and decimals per allocation slot
+    pub entries: [AllocationMetaEntry; MAX_ALLOCATION_SLOTS],
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl AllocationMeta {
+    pub const SIZE: usize = 8 + 32 + AllocationMetaEntry::SIZE * MAX_ALLOCATION_SLOTS + 1;
+
+    /// Replace all entries; slots beyond `entries` become unset.
+    pub fn set_entries(&mut self, entries: &[AllocationMetaEntry]) {
+        self.entries = [AllocationMetaEntry::default(); MAX_ALLOCATION_SLOTS];
+        for (slot, entry) in self.entries.iter_mut().zip(entries) {
+            *slot = *entry;
+        }
+    }
+
+    /// Decimals for a slot, falling back to SOL's 9 when unset.
+    pub fn decimals(&self, slot: usize) -> u8 {
+        match self.entries.get(slot) {
+            Some(entry) if entry.is_set() => entry.decimals,
+            _ => DEFAULT_ALLOCATION_DECIMALS,
+        }
+    }
+}
+
+/// Check that allocation metadata is either absent or one entry per slot.
+pub fn validate_allocation_meta(
+    alloc_meta: &[AllocationMetaEntry],
+    slot_count: usize,
+    has_account: bool,
+) -> Result<()> {
+    if !alloc_meta.is_empty() {
+        require!(
+            has_account && alloc_meta.len() == slot_count,
+            crate::errors::StrategyError::InvalidAllocationMeta
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meta() -> AllocationMeta {
+        AllocationMeta {
+            owner: Pubkey::default(),
+            entries: [AllocationMetaEntry::default(); MAX_ALLOCATION_SLOTS],
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn unset_slots_default_to_sol_decimals() {
+        let m = meta();
+        assert_eq!(m.decimals(0), 9);
+        assert_eq!(m.decimals(MAX_ALLOCATION_SLOTS), 9);
+    }
+
+    #[test]
+    fn set_entries_round_trips_and_clears_the_rest() {
+        let usdc = AllocationMetaEntry { mint: Pubkey::new_unique(), decimals: 6 };
+        let bonk = AllocationMetaEntry { mint: Pubkey::new_unique(), decimals: 5 };
+        let mut m = meta();
+        m.set_entries(&[usdc, bonk, usdc]);
+        m.set_entries(&[usdc, bonk]);
+
+        assert_eq!(m.entries[0], usdc);
+        assert_eq!(m.decimals(1), 5);
+        assert!(!m.entries[2].is_set());
+        assert_eq!(m.decimals(2), 9);
+    }
+
+    #[test]
+    fn metadata_must_cover_every_slot() {
+        let e = AllocationMetaEntry::default();
+        assert!(validate_allocation_meta(&[], 3, false).is_ok());
+        assert!(validate_allocation_meta(&[e, e, e], 3, true).is_ok());
+        assert!(validate_allocation_meta(&[e, e], 3, true).is_err());
+        assert!(validate_allocation_meta(&[e, e, e], 3, false).is_err());
+    }
+}
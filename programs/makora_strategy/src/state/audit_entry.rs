@@ -1,16 +1,83 @@
 use anchor_lang::prelude::*;
+use crate::errors::StrategyError;
+
+/// Canonical action kind, stored as a u8 on each audit entry
+/// 0 = swap, 1 = stake, 2 = unstake, 3 = rebalance,
+/// 4 = lend, 5 = borrow, 6 = claim, 7 = other, 8 = deposit
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum ActionKind {
+    Swap = 0,
+    Stake = 1,
+    Unstake = 2,
+    Rebalance = 3,
+    Lend = 4,
+    Borrow = 5,
+    Claim = 6,
+    Other = 7,
+    /// Owner deposit into the vault, logged by makora_vault via CPI
+    Deposit = 8,
+}
+
+impl ActionKind {
+    pub fn from_u8(val: u8) -> Result<Self> {
+        match val {
+            0 => Ok(ActionKind::Swap),
+            1 => Ok(ActionKind::Stake),
+            2 => Ok(ActionKind::Unstake),
+            3 => Ok(ActionKind::Rebalance),
+            4 => Ok(ActionKind::Lend),
+            5 => Ok(ActionKind::Borrow),
+            6 => Ok(ActionKind::Claim),
+            7 => Ok(ActionKind::Other),
+            8 => Ok(ActionKind::Deposit),
+            _ => Err(error!(StrategyError::InvalidActionKind)),
+        }
+    }
+
+    /// Canonical lowercase name (also the cooldown table key)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActionKind::Swap => "swap",
+            ActionKind::Stake => "stake",
+            ActionKind::Unstake => "unstake",
+            ActionKind::Rebalance => "rebalance",
+            ActionKind::Lend => "lend",
+            ActionKind::Borrow => "borrow",
+            ActionKind::Claim => "claim",
+            ActionKind::Other => "other",
+            ActionKind::Deposit => "deposit",
+        }
+    }
+}
+
+/// One action as passed to log_action / log_actions_batch, before it is
+/// validated and packed into an AuditEntry.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ActionInput {
+    /// ActionKind discriminant
+    pub action_kind: u8,
+    /// Protocol used, at most 16 ASCII bytes
+    pub protocol: String,
+    /// Description, at most 64 ASCII bytes
+    pub description: String,
+    /// Whether the action was executed (vs. just proposed)
+    pub executed: bool,
+    /// Whether the action succeeded
+    pub success: bool,
+}
 
 /// A single audit log entry for an agent action.
 /// Fixed-size for ring buffer storage.
 ///
-/// Size: 4 + 16 + 16 + 64 + 1 + 1 + 8 = 110 bytes per entry
+/// Size: 4 + 1 + 16 + 64 + 1 + 1 + 8 + 8 = 103 bytes per entry
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
 pub struct AuditEntry {
     /// Entry index (monotonically increasing)
     pub index: u32,
 
-    /// Action type (e.g., "swap", "stake"), padded to 16 bytes
-    pub action_type: [u8; 16],
+    /// Action kind (ActionKind discriminant)
+    pub action_kind: u8,
 
     /// Protocol used (e.g., "jupiter", "marinade"), padded to 16 bytes
     pub protocol: [u8; 16],
@@ -26,63 +93,56 @@ pub struct AuditEntry {
 
     /// Unix timestamp
     pub timestamp: i64,
+
+    /// Vault current_balance() at action time (0 if no vault was passed)
+    pub balance_after: u64,
 }
 
 impl Default for AuditEntry {
     fn default() -> Self {
         Self {
             index: 0,
-            action_type: [0u8; 16],
+            action_kind: ActionKind::Other as u8,
             protocol: [0u8; 16],
             description: [0u8; 64],
             executed: false,
             success: false,
             timestamp: 0,
+            balance_after: 0,
         }
     }
 }
 
 impl AuditEntry {
-    pub const SIZE: usize = 4 + 16 + 16 + 64 + 1 + 1 + 8;
+    pub const SIZE: usize = 4 + 1 + 16 + 64 + 1 + 1 + 8 + 8;
 
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         index: u32,
-        action_type: &str,
+        action_kind: ActionKind,
         protocol: &str,
         description: &str,
         executed: bool,
         success: bool,
         timestamp: i64,
+        balance_after: u64,
     ) -> Self {
-        let mut at = [0u8; 16];
-        let at_bytes = action_type.as_bytes();
-        let at_len = at_bytes.len().min(16);
-        at[..at_len].copy_from_slice(&at_bytes[..at_len]);
-
-        let mut pr = [0u8; 16];
-        let pr_bytes = protocol.as_bytes();
-        let pr_len = pr_bytes.len().min(16);
-        pr[..pr_len].copy_from_slice(&pr_bytes[..pr_len]);
-
-        let mut desc = [0u8; 64];
-        let desc_bytes = description.as_bytes();
-        let desc_len = desc_bytes.len().min(64);
-        desc[..desc_len].copy_from_slice(&desc_bytes[..desc_len]);
-
         Self {
             index,
-            action_type: at,
-            protocol: pr,
-            description: desc,
+            action_kind: action_kind as u8,
+            protocol: pad_str(protocol),
+            description: pad_str(description),
             executed,
             success,
             timestamp,
+            balance_after,
         }
     }
 
-    pub fn action_type_str(&self) -> String {
-        let end = self.action_type.iter().position(|&b| b == 0).unwrap_or(16);
-        String::from_utf8_lossy(&self.action_type[..end]).to_string()
+    pub fn action_kind_str(&self) -> &'static str {
+        ActionKind::from_u8(self.action_kind)
+            .map(|k| k.as_str())
+            .unwrap_or("unknown")
     }
 
     pub fn protocol_str(&self) -> String {
@@ -96,9 +156,30 @@ impl AuditEntry {
     }
 }
 
+/// Copy `s` into a zero-padded N-byte buffer, truncating on a char
+/// boundary so a multibyte character is never cut in half.
+fn pad_str<const N: usize>(s: &str) -> [u8; N] {
+    let mut len = s.len().min(N);
+    while !s.is_char_boundary(len) {
+        len -= 1;
+    }
+    let mut buf = [0u8; N];
+    buf[..len].copy_from_slice(&s.as_bytes()[..len]);
+    buf
+}
+
+/// Layout version written by initialize and migrate_audit_trail
+pub const AUDIT_TRAIL_VERSION: u8 = 1;
+
 /// Ring buffer capacity for audit entries (8 to stay within SBF stack limits)
 pub const AUDIT_TRAIL_CAPACITY: usize = 8;
 
+/// Maximum number of actions accepted by a single batch log
+pub const MAX_ACTION_BATCH: usize = AUDIT_TRAIL_CAPACITY;
+
+/// Executed actions considered when judging a winning or losing streak
+pub const STREAK_WINDOW: usize = 3;
+
 /// Audit Trail PDA
 ///
 /// Seeds: ["audit", owner_pubkey]
@@ -109,10 +190,13 @@ pub const AUDIT_TRAIL_CAPACITY: usize = 8;
 ///   owner: 32
 ///   head: 4
 ///   count: 4
-///   entries: 8 * 110 = 880
+///   entries: 8 * 103 = 824
 ///   bump: 1
-///   TOTAL: 8 + 32 + 4 + 4 + 880 + 1 = 929
-///   Round up to 960 for safety
+///   version: 1
+///   TOTAL: 8 + 32 + 4 + 4 + 824 + 1 + 1 = 870
+///
+/// Trails created before ActionKind store a 16-byte action_type string per
+/// entry and must go through migrate_audit_trail before they can be loaded.
 #[account]
 pub struct AuditTrail {
     /// The wallet owner
@@ -129,6 +213,9 @@ pub struct AuditTrail {
 
     /// PDA bump seed
     pub bump: u8,
+
+    /// Layout version (AUDIT_TRAIL_VERSION), bumped by future migrations
+    pub version: u8,
 }
 
 impl AuditTrail {
@@ -137,7 +224,8 @@ impl AuditTrail {
         4 +                          // head
         4 +                          // count
         (AuditEntry::SIZE * AUDIT_TRAIL_CAPACITY) + // entries
-        1;                           // bump
+        1 +                          // bump
+        1;                           // version
 
     /// Append an entry to the ring buffer.
     /// Overwrites the oldest entry when full.
@@ -166,4 +254,192 @@ impl AuditTrail {
 
         result
     }
+
+    /// Success of each retained executed agent action, newest first.
+    /// Owner deposits are not agent actions and are skipped.
+    pub fn executed_outcomes(&self) -> Vec<bool> {
+        self.recent(AUDIT_TRAIL_CAPACITY)
+            .into_iter()
+            .filter(|e| e.executed && e.action_kind != ActionKind::Deposit as u8)
+            .map(|e| e.success)
+            .collect()
+    }
+
+    /// Outcome of the last STREAK_WINDOW executed agent actions: Some(false)
+    /// if all failed, Some(true) if all succeeded, None if mixed or there
+    /// are too few.
+    pub fn recent_streak(&self) -> Option<bool> {
+        let outcomes: Vec<bool> = self
+            .executed_outcomes()
+            .into_iter()
+            .take(STREAK_WINDOW)
+            .collect();
+
+        if outcomes.len() < STREAK_WINDOW {
+            return None;
+        }
+        if outcomes.iter().all(|&s| s) {
+            Some(true)
+        } else if outcomes.iter().all(|&s| !s) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    /// Percentage of retained executed agent actions that succeeded
+    /// (0 if there are none).
+    pub fn success_rate_pct(&self) -> u8 {
+        let outcomes = self.executed_outcomes();
+        if outcomes.is_empty() {
+            return 0;
+        }
+        let successes = outcomes.iter().filter(|&&s| s).count();
+        (successes * 100 / outcomes.len()) as u8
+    }
+
+    /// Success rate (0-100) with the newest executed action weighted n,
+    /// the next n - 1, down to 1 for the oldest (0 if there are none).
+    pub fn performance_score(&self) -> u8 {
+        let outcomes = self.executed_outcomes();
+        let n = outcomes.len();
+        if n == 0 {
+            return 0;
+        }
+        let total_weight = n * (n + 1) / 2;
+        let earned: usize = outcomes
+            .iter()
+            .enumerate()
+            .filter(|(_, &s)| s)
+            .map(|(i, _)| n - i)
+            .sum();
+        (earned * 100 / total_weight) as u8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(protocol: &str, description: &str) -> AuditEntry {
+        AuditEntry::new(0, ActionKind::Swap, protocol, description, true, true, 0, 0)
+    }
+
+    fn trail(outcomes: &[(ActionKind, bool, bool)]) -> AuditTrail {
+        let mut trail = AuditTrail {
+            owner: Pubkey::default(),
+            head: 0,
+            count: 0,
+            entries: [AuditEntry::default(); AUDIT_TRAIL_CAPACITY],
+            bump: 0,
+            version: AUDIT_TRAIL_VERSION,
+        };
+        for &(kind, executed, success) in outcomes {
+            trail.append(AuditEntry::new(0, kind, "", "", executed, success, 0, 0));
+        }
+        trail
+    }
+
+    #[test]
+    fn keeps_ascii_that_fits() {
+        let e = entry("jupiter", "Swap 1 SOL to USDC");
+        assert_eq!(e.protocol_str(), "jupiter");
+        assert_eq!(e.description_str(), "Swap 1 SOL to USDC");
+    }
+
+    #[test]
+    fn truncates_on_a_char_boundary() {
+        // 15 ASCII bytes + a 4-byte emoji straddles the 16-byte limit
+        let e = entry("abcdefghijklmno\u{1F680}", "");
+        assert_eq!(e.protocol_str(), "abcdefghijklmno");
+    }
+
+    #[test]
+    fn never_produces_replacement_chars() {
+        let description = "é".repeat(40); // 80 bytes of 2-byte chars
+        let e = entry("", &description);
+        assert_eq!(e.description_str(), "é".repeat(32));
+        assert!(!e.description_str().contains('\u{FFFD}'));
+    }
+
+    #[test]
+    fn detects_losing_and_winning_streaks() {
+        let losing = trail(&[
+            (ActionKind::Swap, true, true),
+            (ActionKind::Swap, true, false),
+            (ActionKind::Stake, true, false),
+            (ActionKind::Swap, true, false),
+        ]);
+        assert_eq!(losing.recent_streak(), Some(false));
+
+        let winning = trail(&[
+            (ActionKind::Swap, true, true),
+            (ActionKind::Lend, true, true),
+            (ActionKind::Swap, true, true),
+        ]);
+        assert_eq!(winning.recent_streak(), Some(true));
+    }
+
+    #[test]
+    fn ignores_unexecuted_actions_and_deposits() {
+        let t = trail(&[
+            (ActionKind::Swap, true, false),
+            (ActionKind::Swap, true, false),
+            (ActionKind::Deposit, true, true),
+            (ActionKind::Swap, false, true),
+            (ActionKind::Swap, true, false),
+        ]);
+        assert_eq!(t.recent_streak(), Some(false));
+    }
+
+    #[test]
+    fn no_streak_when_mixed_or_short() {
+        let mixed = trail(&[
+            (ActionKind::Swap, true, false),
+            (ActionKind::Swap, true, true),
+            (ActionKind::Swap, true, false),
+        ]);
+        assert_eq!(mixed.recent_streak(), None);
+
+        let short = trail(&[(ActionKind::Swap, true, false), (ActionKind::Swap, true, false)]);
+        assert_eq!(short.recent_streak(), None);
+    }
+
+    #[test]
+    fn weights_recent_outcomes_more_heavily() {
+        // Oldest to newest: fail, fail, succeed, succeed
+        let improving = trail(&[
+            (ActionKind::Swap, true, false),
+            (ActionKind::Swap, true, false),
+            (ActionKind::Swap, true, true),
+            (ActionKind::Swap, true, true),
+        ]);
+        assert_eq!(improving.success_rate_pct(), 50);
+        assert_eq!(improving.performance_score(), 70); // (4 + 3) / 10
+
+        let declining = trail(&[
+            (ActionKind::Swap, true, true),
+            (ActionKind::Swap, true, true),
+            (ActionKind::Swap, true, false),
+            (ActionKind::Swap, true, false),
+        ]);
+        assert_eq!(declining.success_rate_pct(), 50);
+        assert_eq!(declining.performance_score(), 30); // (2 + 1) / 10
+    }
+
+    #[test]
+    fn scores_only_executed_agent_actions() {
+        let t = trail(&[
+            (ActionKind::Swap, true, true),
+            (ActionKind::Deposit, true, false),
+            (ActionKind::Swap, false, false),
+            (ActionKind::Stake, true, false),
+        ]);
+        assert_eq!(t.success_rate_pct(), 50);
+        assert_eq!(t.performance_score(), 33); // 1 / 3
+
+        let empty = trail(&[(ActionKind::Swap, false, true)]);
+        assert_eq!(empty.success_rate_pct(), 0);
+        assert_eq!(empty.performance_score(), 0);
+    }
 }
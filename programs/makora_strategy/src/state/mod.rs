@@ -1,5 +1,11 @@
 pub mod strategy_account;
 pub mod audit_entry;
+pub mod symbol_registry;
+pub mod allocation_history;
+pub mod allocation_meta;
 
 pub use strategy_account::*;
 pub use audit_entry::*;
+pub use symbol_registry::*;
+pub use allocation_history::*;
+pub use allocation_meta::*;
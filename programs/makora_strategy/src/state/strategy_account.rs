@@ -27,6 +27,17 @@ impl StrategyType {
             _ => Err(()),
         }
     }
+
+    /// Default max slippage (bps) for actions under this strategy type.
+    /// Yield strategies hold stable positions and tolerate the least.
+    pub fn default_max_slippage_bps(&self) -> u16 {
+        match self {
+            StrategyType::Yield => 30,
+            StrategyType::Trading => 100,
+            StrategyType::Rebalance => 50,
+            StrategyType::Liquidity => 50,
+        }
+    }
 }
 
 /// Agent mode (advisory or auto) -- mirrors vault program
@@ -51,6 +62,14 @@ impl AgentMode {
             _ => Err(()),
         }
     }
+
+    /// The strategy-side equivalent of a vault's mode.
+    pub fn from_vault(mode: makora_vault::state::AgentMode) -> Self {
+        match mode {
+            makora_vault::state::AgentMode::Advisory => AgentMode::Advisory,
+            makora_vault::state::AgentMode::Auto => AgentMode::Auto,
+        }
+    }
 }
 
 /// Target allocation for a single token (symbol + percentage)
@@ -82,6 +101,73 @@ impl AllocationTarget {
     }
 }
 
+/// Number of target allocation slots on a strategy
+pub const MAX_ALLOCATION_SLOTS: usize = 5;
+
+/// Largest allocation-sum tolerance: one point of rounding per slot
+pub const MAX_ALLOCATION_TOLERANCE: u8 = MAX_ALLOCATION_SLOTS as u8;
+
+/// Check that allocation percentages sum to 100 within `tolerance`
+/// points either way. An empty allocation is always valid.
+pub fn validate_allocation_sum(pcts: &[u8], tolerance: u8) -> Result<()> {
+    if pcts.is_empty() {
+        return Ok(());
+    }
+    let sum: u16 = pcts.iter().map(|&p| p as u16).sum();
+    require!(
+        sum.abs_diff(100) <= tolerance as u16,
+        crate::errors::StrategyError::InvalidAllocationSum
+    );
+    Ok(())
+}
+
+/// Maximum number of action types with a configured cooldown
+pub const MAX_ACTION_COOLDOWNS: usize = 4;
+
+/// Per-action-type cooldown (e.g., at most one "rebalance" per hour).
+/// Fixed-size for predictable account layout.
+///
+/// Size: 16 + 4 + 8 = 28 bytes per entry
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug)]
+pub struct ActionCooldown {
+    /// Canonical action kind name (e.g., "rebalance"), padded to 16 bytes. All zeros = free slot.
+    pub action_type: [u8; 16],
+    /// Minimum seconds between executed actions of this type
+    pub cooldown_secs: u32,
+    /// Unix timestamp of the last executed action of this type
+    pub last_at: i64,
+}
+
+impl ActionCooldown {
+    pub const SIZE: usize = 16 + 4 + 8;
+
+    /// Pad an action type string to the 16-byte key used in the table.
+    pub fn key(action_type: &str) -> [u8; 16] {
+        let mut key = [0u8; 16];
+        let bytes = action_type.as_bytes();
+        let len = bytes.len().min(16);
+        key[..len].copy_from_slice(&bytes[..len]);
+        key
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.action_type[0] == 0
+    }
+}
+
+/// Maximum number of protocols on the agent allowlist
+pub const MAX_ALLOWED_PROTOCOLS: usize = 8;
+
+/// Pad a protocol name to the 16-byte form stored on the allowlist
+/// (and in audit entries).
+pub fn protocol_key(protocol: &str) -> [u8; 16] {
+    let mut key = [0u8; 16];
+    let bytes = protocol.as_bytes();
+    let len = bytes.len().min(16);
+    key[..len].copy_from_slice(&bytes[..len]);
+    key
+}
+
 /// Strategy Account PDA
 ///
 /// Seeds: ["strategy", owner_pubkey]
@@ -102,9 +188,16 @@ impl AllocationTarget {
 ///   last_cycle_at: 8
 ///   created_at: 8
 ///   bump: 1
-///   _padding: 32
-///   TOTAL: 8 + 32 + 32 + 1 + 1 + 1 + 1 + 45 + 1 + 8 + 8 + 8 + 8 + 1 + 32 = 187
-///   Round up to 200 for safety
+///   actions_this_cycle: 1
+///   cycle_start_ts: 8
+///   max_slippage_bps: 2
+///   confidence_step: 1
+///   allocation_tolerance: 1
+///   last_performance_score: 1
+///   _padding: 18
+///   action_cooldowns: 4 * 28 = 112
+///   allowed_protocols: 8 * 16 = 128
+///   TOTAL: 8 + 32 + 32 + 1 + 1 + 1 + 1 + 45 + 1 + 8 + 8 + 8 + 8 + 1 + 1 + 8 + 2 + 1 + 20 + 112 + 128 = 427
 #[account]
 pub struct StrategyAccount {
     /// The wallet owner (same as vault owner)
@@ -146,8 +239,36 @@ pub struct StrategyAccount {
     /// PDA bump seed
     pub bump: u8,
 
+    /// Executed actions logged in the current cycle
+    pub actions_this_cycle: u8,
+
+    /// Unix timestamp when the current cycle started
+    pub cycle_start_ts: i64,
+
+    /// Maximum slippage (bps) accepted for proposed actions
+    pub max_slippage_bps: u16,
+
+    /// Adaptive confidence: update_strategy raises the threshold by this
+    /// much after a losing streak and lowers it after a winning one
+    /// (0 = off)
+    pub confidence_step: u8,
+
+    /// Points the allocation percentages may sum away from 100, to allow
+    /// for rounding across tokens (0 = exactly 100)
+    pub allocation_tolerance: u8,
+
+    /// Recency-weighted success score (0-100) cached by compute_performance
+    pub last_performance_score: u8,
+
     /// Reserved space for future upgrades
-    pub _padding: [u8; 32],
+    pub _padding: [u8; 18],
+
+    /// Cooldowns for specific action types, enforced by log_action
+    pub action_cooldowns: [ActionCooldown; MAX_ACTION_COOLDOWNS],
+
+    /// Protocols executed actions may use, padded to 16 bytes. All zeros =
+    /// free slot; an empty list allows every protocol.
+    pub allowed_protocols: [[u8; 16]; MAX_ALLOWED_PROTOCOLS],
 }
 
 impl StrategyAccount {
@@ -166,10 +287,170 @@ impl StrategyAccount {
         8 +   // last_cycle_at
         8 +   // created_at
         1 +   // bump
-        32;   // _padding
+        1 +   // actions_this_cycle
+        8 +   // cycle_start_ts
+        2 +   // max_slippage_bps
+        1 +   // confidence_step
+        1 +   // allocation_tolerance
+        1 +   // last_performance_score
+        18 +  // _padding
+        (ActionCooldown::SIZE * MAX_ACTION_COOLDOWNS) + // action_cooldowns
+        (16 * MAX_ALLOWED_PROTOCOLS); // allowed_protocols
 
     /// Check if a pubkey is authorized to update strategy
     pub fn is_authorized(&self, signer: &Pubkey) -> bool {
         *signer == self.owner || *signer == self.agent_authority
     }
+
+    /// Reject a proposed action whose slippage exceeds the strategy cap
+    pub fn validate_action_slippage(&self, slippage_bps: u16) -> Result<()> {
+        require!(
+            slippage_bps <= self.max_slippage_bps,
+            crate::errors::StrategyError::SlippageExceeded
+        );
+        Ok(())
+    }
+
+    /// Find the cooldown entry for an action type, if one is configured
+    pub fn cooldown_mut(&mut self, action_type: &[u8; 16]) -> Option<&mut ActionCooldown> {
+        self.action_cooldowns
+            .iter_mut()
+            .find(|c| !c.is_empty() && c.action_type == *action_type)
+    }
+
+    /// True if executed actions may use `protocol`
+    pub fn protocol_allowed(&self, protocol: &str) -> bool {
+        let mut listed = self.allowed_protocols.iter().filter(|p| p[0] != 0).peekable();
+        if listed.peek().is_none() {
+            return true;
+        }
+        let key = protocol_key(protocol);
+        listed.any(|p| *p == key)
+    }
+
+    /// Allocation slots not in use
+    pub fn free_slots(&self) -> u8 {
+        (MAX_ALLOCATION_SLOTS as u8).saturating_sub(self.allocation_count)
+    }
+
+    /// Percentage not yet assigned to any in-use allocation slot
+    pub fn remaining_pct(&self) -> u8 {
+        let used = (self.allocation_count as usize).min(MAX_ALLOCATION_SLOTS);
+        let assigned: u16 = self.target_allocation[..used]
+            .iter()
+            .map(|a| a.target_pct as u16)
+            .sum();
+        100u16.saturating_sub(assigned) as u8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strategy(allocations: &[(&str, u8)]) -> StrategyAccount {
+        let mut target_allocation = [AllocationTarget::default(); MAX_ALLOCATION_SLOTS];
+        for (slot, &(symbol, pct)) in target_allocation.iter_mut().zip(allocations) {
+            *slot = AllocationTarget::new(symbol, pct);
+        }
+        StrategyAccount {
+            owner: Pubkey::default(),
+            agent_authority: Pubkey::default(),
+            strategy_type: StrategyType::Yield,
+            mode: AgentMode::Advisory,
+            confidence_threshold: 0,
+            max_actions_per_cycle: 0,
+            target_allocation,
+            allocation_count: allocations.len() as u8,
+            total_cycles: 0,
+            total_actions_executed: 0,
+            last_cycle_at: 0,
+            created_at: 0,
+            bump: 0,
+            actions_this_cycle: 0,
+            cycle_start_ts: 0,
+            max_slippage_bps: 0,
+            confidence_step: 0,
+            allocation_tolerance: 0,
+            last_performance_score: 0,
+            _padding: [0u8; 18],
+            action_cooldowns: [ActionCooldown::default(); MAX_ACTION_COOLDOWNS],
+            allowed_protocols: [[0u8; 16]; MAX_ALLOWED_PROTOCOLS],
+        }
+    }
+
+    #[test]
+    fn empty_allocation_is_all_free() {
+        let s = strategy(&[]);
+        assert_eq!(s.free_slots(), 5);
+        assert_eq!(s.remaining_pct(), 100);
+    }
+
+    #[test]
+    fn partial_allocation() {
+        let s = strategy(&[("SOL", 40), ("USDC", 25)]);
+        assert_eq!(s.free_slots(), 3);
+        assert_eq!(s.remaining_pct(), 35);
+    }
+
+    #[test]
+    fn full_allocation() {
+        let s = strategy(&[("SOL", 20), ("mSOL", 20), ("USDC", 20), ("JitoSOL", 20), ("BONK", 20)]);
+        assert_eq!(s.free_slots(), 0);
+        assert_eq!(s.remaining_pct(), 0);
+    }
+
+    #[test]
+    fn ignores_slots_beyond_the_count() {
+        let mut s = strategy(&[("SOL", 100)]);
+        s.target_allocation[1] = AllocationTarget::new("USDC", 50);
+        assert_eq!(s.free_slots(), 4);
+        assert_eq!(s.remaining_pct(), 0);
+    }
+
+    #[test]
+    fn exact_sum_needs_no_tolerance() {
+        assert!(validate_allocation_sum(&[60, 40], 0).is_ok());
+        assert!(validate_allocation_sum(&[], 0).is_ok());
+    }
+
+    #[test]
+    fn rejects_rounding_drift_without_tolerance() {
+        for pcts in [[33, 33, 33], [34, 34, 33]] {
+            let err = validate_allocation_sum(&pcts, 0).unwrap_err();
+            assert_eq!(err, crate::errors::StrategyError::InvalidAllocationSum.into());
+        }
+    }
+
+    #[test]
+    fn accepts_99_100_and_101_with_tolerance_one() {
+        for pcts in [[33, 33, 33], [34, 33, 33], [34, 34, 33]] {
+            assert!(validate_allocation_sum(&pcts, 1).is_ok());
+        }
+    }
+
+    #[test]
+    fn rejects_drift_beyond_tolerance() {
+        assert!(validate_allocation_sum(&[49, 49], 1).is_err());
+        assert!(validate_allocation_sum(&[51, 51], 1).is_err());
+        assert!(validate_allocation_sum(&[51, 51], 2).is_ok());
+    }
+
+    #[test]
+    fn empty_allowlist_allows_every_protocol() {
+        let s = strategy(&[]);
+        assert!(s.protocol_allowed("jupiter"));
+        assert!(s.protocol_allowed(""));
+    }
+
+    #[test]
+    fn allowlist_matches_exact_names() {
+        let mut s = strategy(&[]);
+        s.allowed_protocols[0] = protocol_key("jupiter");
+        s.allowed_protocols[3] = protocol_key("marinade");
+        assert!(s.protocol_allowed("jupiter"));
+        assert!(s.protocol_allowed("marinade"));
+        assert!(!s.protocol_allowed("jup"));
+        assert!(!s.protocol_allowed("kamino"));
+    }
 }
@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+
+/// Maximum number of symbols the registry can hold
+pub const MAX_REGISTERED_SYMBOLS: usize = 32;
+
+/// Maximum number of symbols accepted by a single batch registration
+pub const MAX_SYMBOL_BATCH: usize = 16;
+
+/// A supported token: symbol (padded to 8 bytes) and its mint.
+///
+/// Size: 8 + 32 = 40 bytes per entry
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct SymbolEntry {
+    /// Token symbol (e.g., "SOL", "USDC"), padded to 8 bytes
+    pub symbol: [u8; 8],
+    /// SPL mint address for the token
+    pub mint: Pubkey,
+}
+
+impl SymbolEntry {
+    pub const SIZE: usize = 8 + 32;
+}
+
+/// Symbol Registry PDA
+///
+/// Seeds: ["symbol_registry"]
+/// One per deployment. Lists the token symbols strategies may allocate to.
+/// Entries are a Vec (heap) rather than a fixed array to stay within
+/// SBF stack limits.
+///
+/// Size calculation:
+///   discriminator: 8
+///   authority: 32
+///   entries: 4 + 32 * 40 = 1284
+///   bump: 1
+///   TOTAL: 8 + 32 + 1284 + 1 = 1325
+#[account]
+pub struct SymbolRegistry {
+    /// Authority allowed to register and deregister symbols
+    pub authority: Pubkey,
+
+    /// Registered symbols (at most MAX_REGISTERED_SYMBOLS)
+    pub entries: Vec<SymbolEntry>,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl SymbolRegistry {
+    pub const SIZE: usize = 8 +    // discriminator
+        32 +                         // authority
+        4 + (SymbolEntry::SIZE * MAX_REGISTERED_SYMBOLS) + // entries
+        1;                           // bump
+
+    /// Check if a symbol is already registered
+    pub fn contains(&self, symbol: &[u8; 8]) -> bool {
+        self.entries.iter().any(|e| e.symbol == *symbol)
+    }
+}
@@ -46,11 +46,164 @@ pub enum VaultError {
     #[msg("Vault must be in Auto mode for agent operations")]
     NotAutoMode,
 
-    /// Agent withdraw exceeds max position size
-    #[msg("Agent withdraw exceeds max position size percentage")]
+    /// Agent withdraw or swap exceeds max position size
+    #[msg("Agent action exceeds max position size percentage")]
     ExceedsMaxPosition,
 
     /// In-session amount underflow (return exceeds tracked amount)
     #[msg("In-session amount underflow")]
     SessionAmountUnderflow,
+
+    /// Swap quote implies more slippage than the vault allows
+    #[msg("Quoted output exceeds the vault's max slippage")]
+    SlippageExceeded,
+
+    /// Swap quote is malformed (expected output must be non-zero)
+    #[msg("Invalid swap quote: expected output must be greater than zero")]
+    InvalidSwapQuote,
+
+    /// Protocol fee exceeds the allowed maximum
+    #[msg("Protocol fee exceeds the maximum (100 bps)")]
+    FeeTooHigh,
+
+    /// Fee collector account does not match the global config
+    #[msg("Fee collector does not match the global config")]
+    InvalidFeeCollector,
+
+    /// Agent withdraw is below the minimum position size
+    #[msg("Agent withdraw is below min position size percentage")]
+    BelowMinPosition,
+
+    /// Deposit would push the vault past its deposit cap
+    #[msg("Deposit exceeds the vault's max total deposit")]
+    DepositCapExceeded,
+
+    /// No open session with the given id
+    #[msg("Unknown or already closed agent session")]
+    UnknownSession,
+
+    /// All session slots are in use
+    #[msg("Too many open agent sessions")]
+    TooManySessions,
+
+    /// Token account still holds tokens and cannot be closed
+    #[msg("Token account has a non-zero balance")]
+    TokenAccountNotEmpty,
+
+    /// Destructive instruction called without explicit confirmation
+    #[msg("This action requires confirm = true")]
+    ConfirmationRequired,
+
+    /// Agent is registered but lacks the role for this operation
+    #[msg("Agent does not have the required role")]
+    MissingRole,
+
+    /// All agent slots are in use
+    #[msg("Too many agent authorities")]
+    TooManyAgents,
+
+    /// Role bitmask contains unknown bits
+    #[msg("Invalid agent roles")]
+    InvalidRoles,
+
+    /// Deposit source is not the session's recorded wallet
+    #[msg("Deposit source is not a recorded session wallet")]
+    UnknownSource,
+
+    /// Balance covers the amount but not the rent and min_sol_reserve
+    #[msg("Withdraw would breach the vault's minimum SOL reserve")]
+    ReserveWouldBeBreached,
+
+    /// accept_owner called without a matching propose_owner
+    #[msg("No pending owner transfer for this signer")]
+    NoPendingOwner,
+
+    /// Strategy accounts passed to deposit are incomplete or not the owner's
+    #[msg("Invalid strategy accounts for audit logging")]
+    InvalidStrategyAccounts,
+
+    /// deposit_many amounts do not line up with the vault accounts
+    #[msg("Amounts must match the number of vault accounts")]
+    AmountsLengthMismatch,
+
+    /// Agent instruction carried a nonce other than the vault's current one
+    #[msg("Agent nonce is stale or out of order")]
+    StaleNonce,
+
+    /// Withdrawal attempted before the vault's cooldown elapsed
+    #[msg("Withdrawal cooldown has not elapsed")]
+    WithdrawCooldown,
+
+    /// Token-2022 mint carries an extension the vault cannot handle
+    #[msg("Mint uses an unsupported Token-2022 extension")]
+    UnsupportedMintExtension,
+
+    /// Aggregate read got no vaults, too many, or the same vault twice
+    #[msg("Vault list is empty, too long, or contains duplicates")]
+    InvalidVaultList,
+
+    /// Session has not yet reached its max duration
+    #[msg("Agent session has not expired yet")]
+    SessionNotExpired,
+
+    /// Multisig vault operation is missing required co-signers
+    #[msg("Not enough co-signers signed this operation")]
+    InsufficientSigners,
+
+    /// Co-signer set is too long, has duplicates, or threshold exceeds it
+    #[msg("Invalid co-signer set or threshold")]
+    InvalidCosigners,
+
+    /// agent_swap got a non-Jupiter program or two accounts of one mint
+    #[msg("Swap must route through Jupiter between two different mints")]
+    InvalidSwapRoute,
+
+    /// The swap route spent more than the agent's amount_in
+    #[msg("Swap spent more than the requested input amount")]
+    SwapOverspent,
+
+    /// Price update is older than the vault accepts
+    #[msg("Oracle price is stale")]
+    StaleOracle,
+
+    /// Price account is not a verified Pyth SOL/USD update
+    #[msg("Invalid oracle price account")]
+    InvalidOracle,
+
+    /// Deposit or withdrawal is smaller than the vault's minimum
+    #[msg("Amount is below the vault's minimum")]
+    BelowMinimum,
+
+    /// All per-mint risk limit slots are in use
+    #[msg("Too many per-mint risk limits")]
+    TooManyMintRiskLimits,
+
+    /// The agent has not sent a heartbeat within the vault's timeout
+    #[msg("Agent heartbeat is stale")]
+    AgentStale,
+
+    /// agent_swap on a mint pair the owner has not set a price floor for
+    #[msg("No swap price floor is set for this mint pair")]
+    SwapFloorNotSet,
+
+    /// All swap price floor slots are in use
+    #[msg("Too many swap price floors")]
+    TooManySwapFloors,
+
+    /// A token account passed to accept_owner without its token program
+    #[msg("Token program for a vault token account was not supplied")]
+    MissingTokenProgram,
+
+    /// migrate_vault on a vault that already has the current layout
+    #[msg("Vault is already on the current layout")]
+    VaultAlreadyMigrated,
+
+    /// migrate_vault on an account that is not a vault in a known layout
+    #[msg("Account is not a vault in a supported layout")]
+    UnsupportedVaultLayout,
+
+    /// Agent withdraw while realized losses in the rolling window have
+    /// reached max_daily_loss_pct
+    #[msg("Daily loss limit reached")]
+    DailyLossLimitReached,
 }
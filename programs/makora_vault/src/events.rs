@@ -0,0 +1,175 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::spl_token::native_mint;
+
+use crate::state::vault::RiskLimits;
+
+/// Mint reported on SOL money-movement events (wrapped SOL mint)
+pub const SOL_MINT: Pubkey = native_mint::ID;
+
+/// Decimals reported on SOL money-movement events (lamports per SOL = 10^9)
+pub const SOL_DECIMALS: u8 = native_mint::DECIMALS;
+
+/// Emitted when a vault is created. Carries the full starting
+/// configuration so indexers don't need to fetch the account.
+#[event]
+pub struct InitializedEvent {
+    pub owner: Pubkey,
+    pub vault: Pubkey,
+    pub agent_authority: Pubkey,
+    /// Agent mode (0 = advisory, 1 = auto)
+    pub mode: u8,
+    pub max_position_size_pct: u8,
+    pub min_position_size_pct: u8,
+    pub max_slippage_bps: u16,
+    pub max_daily_loss_pct: u8,
+    pub min_sol_reserve: u64,
+    pub max_protocol_exposure_pct: u8,
+    pub max_total_deposit: u64,
+    pub timestamp: i64,
+}
+
+// Money-movement events carry `mint` and `decimals` alongside the raw
+// amount so downstream tooling can format amounts consistently, including
+// once SPL tokens are supported.
+
+/// Emitted when the owner deposits into the vault.
+#[event]
+pub struct DepositEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub mint: Pubkey,
+    pub decimals: u8,
+    pub total_deposited: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when the owner withdraws from the vault.
+#[event]
+pub struct WithdrawEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    /// Gross amount leaving the vault (fee included)
+    pub amount: u64,
+    pub fee: u64,
+    pub mint: Pubkey,
+    pub decimals: u8,
+    pub total_withdrawn: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when the agent moves funds out to a session wallet.
+#[event]
+pub struct AgentWithdrawEvent {
+    pub vault: Pubkey,
+    pub destination: Pubkey,
+    /// Gross amount leaving the vault (fee included)
+    pub amount: u64,
+    pub fee: u64,
+    pub session_id: u64,
+    pub mint: Pubkey,
+    pub decimals: u8,
+    pub timestamp: i64,
+}
+
+/// Emitted when the agent returns funds from a session wallet.
+#[event]
+pub struct AgentDepositEvent {
+    pub vault: Pubkey,
+    pub source: Pubkey,
+    pub amount: u64,
+    pub session_id: u64,
+    pub session_closed: bool,
+    pub realized_pnl: i64,
+    pub mint: Pubkey,
+    pub decimals: u8,
+    pub timestamp: i64,
+}
+
+/// Warning emitted when the owner force-clears agent sessions.
+#[event]
+pub struct SessionsResetEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    /// in_session_amount before the reset
+    pub cleared_in_session: u64,
+    /// Recorded balance missing from the PDA, now counted as withdrawn
+    pub written_off: u64,
+    pub current_balance: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when a session past its max duration stops being tracked.
+#[event]
+pub struct SessionExpiredEvent {
+    pub vault: Pubkey,
+    pub session_id: u64,
+    pub wallet: Pubkey,
+    /// SOL the session still held out, now counted as withdrawn
+    pub written_off: u64,
+    pub current_balance: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when a vault moves to a new owner's PDA.
+#[event]
+pub struct OwnershipTransferredEvent {
+    pub old_vault: Pubkey,
+    pub new_vault: Pubkey,
+    pub previous_owner: Pubkey,
+    pub new_owner: Pubkey,
+    /// Lamports moved from the old PDA (excluding its rent)
+    pub migrated: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when the owner withdraws SPL tokens held by the vault.
+#[event]
+pub struct TokenWithdrawEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    /// Amount debited from the vault's token account
+    pub amount: u64,
+    /// Withheld by a Token-2022 transfer-fee mint (0 otherwise)
+    pub transfer_fee: u64,
+    pub mint: Pubkey,
+    pub decimals: u8,
+    /// SPL Token or Token-2022 program the transfer went through
+    pub token_program: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted when an agent swaps between two vault token accounts
+#[event]
+pub struct AgentSwapEvent {
+    pub vault: Pubkey,
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    /// Input actually spent by the route
+    pub amount_in: u64,
+    /// Output actually received by the vault
+    pub amount_out: u64,
+    /// Minimum output allowed by the swap floor and max_slippage_bps
+    pub min_out: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted by set_mode. A full checkpoint of the vault's risk state at
+/// the moment the agent's authority changes, so risk systems can
+/// re-evaluate without fetching the account.
+#[event]
+pub struct VaultSnapshotEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    /// Agent mode before and after the change (0 = advisory, 1 = auto)
+    pub old_mode: u8,
+    pub new_mode: u8,
+    pub risk_limits: RiskLimits,
+    pub total_deposited: u64,
+    pub total_withdrawn: u64,
+    /// Balance available for new operations (excludes in_session_amount)
+    pub current_balance: u64,
+    pub in_session_amount: u64,
+    pub total_realized_pnl: i64,
+    pub timestamp: i64,
+}
@@ -0,0 +1,137 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::instruction::AuthorityType;
+use anchor_spl::token_interface::{self, SetAuthority};
+use crate::state::Vault;
+use crate::errors::VaultError;
+use crate::events::OwnershipTransferredEvent;
+use crate::jupiter_cpi::is_vault_token_account;
+use crate::utils::transfer_from_pda;
+
+#[derive(Accounts)]
+pub struct AcceptOwner<'info> {
+    /// The proposed owner; pays rent for the new vault PDA
+    #[account(mut)]
+    pub new_owner: Signer<'info>,
+
+    /// The vault being transferred. Closed once migrated; its rent
+    /// goes back to the previous owner.
+    #[account(
+        mut,
+        seeds = [b"vault", vault.owner.as_ref()],
+        bump = vault.bump,
+        constraint = vault.pending_owner != Pubkey::default() @ VaultError::NoPendingOwner,
+        constraint = vault.pending_owner == new_owner.key() @ VaultError::NoPendingOwner,
+        close = previous_owner,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// The vault PDA keyed by the new owner
+    #[account(
+        init,
+        payer = new_owner,
+        space = Vault::SIZE,
+        seeds = [b"vault", new_owner.key().as_ref()],
+        bump
+    )]
+    pub new_vault: Account<'info, Vault>,
+
+    /// CHECK: Receives the old vault's rent; must be its owner.
+    #[account(mut, address = vault.owner @ VaultError::Unauthorized)]
+    pub previous_owner: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Second step of an ownership transfer. The vault PDA is derived from
+/// the owner, so the state and funds move to a new PDA for `new_owner`
+/// in this one transaction and the old PDA is closed.
+///
+/// Token accounts owned by the old PDA go in remaining accounts, with
+/// their token program, and are handed to the new PDA. Otherwise they
+/// would stay under a closed PDA that the previous owner could re-create.
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, AcceptOwner<'info>>) -> Result<()> {
+    ctx.accounts.vault.check_cosigners(ctx.remaining_accounts)?;
+
+    let clock = Clock::get()?;
+
+    let previous_owner = ctx.accounts.previous_owner.key();
+    let seeds: &[&[u8]] = &[b"vault", previous_owner.as_ref(), &[ctx.accounts.vault.bump]];
+    let reassigned = reassign_token_accounts(
+        ctx.remaining_accounts,
+        &ctx.accounts.vault.to_account_info(),
+        &ctx.accounts.new_vault.key(),
+        seeds,
+    )?;
+
+    // Everything above the old PDA's rent is vault funds
+    let old_info = ctx.accounts.vault.to_account_info();
+    let min_rent = Rent::get()?.minimum_balance(Vault::SIZE);
+    let migrated = old_info.lamports().saturating_sub(min_rent);
+
+    let mut state = (*ctx.accounts.vault).clone();
+    state.owner = ctx.accounts.new_owner.key();
+    state.pending_owner = Pubkey::default();
+    state.bump = ctx.bumps.new_vault;
+    state.last_action_at = clock.unix_timestamp;
+    ctx.accounts.new_vault.set_inner(state);
+
+    let new_info = ctx.accounts.new_vault.to_account_info();
+    transfer_from_pda(&old_info, &new_info, migrated)?;
+
+    msg!(
+        "Vault ownership transferred from {} to {}. Migrated {} lamports and {} token accounts to {}",
+        ctx.accounts.previous_owner.key(),
+        ctx.accounts.new_owner.key(),
+        migrated,
+        reassigned,
+        new_info.key()
+    );
+
+    emit!(OwnershipTransferredEvent {
+        old_vault: old_info.key(),
+        new_vault: new_info.key(),
+        previous_owner: ctx.accounts.previous_owner.key(),
+        new_owner: ctx.accounts.new_owner.key(),
+        migrated,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Make `new_vault` the owner of every token account in `accounts` that
+/// `old_vault` owns, signing as the old PDA. Returns how many moved.
+/// Token-2022 accounts with an immutable owner (e.g. ATAs) cannot move and
+/// fail the migration; close them with close_token_account first.
+fn reassign_token_accounts<'info>(
+    accounts: &[AccountInfo<'info>],
+    old_vault: &AccountInfo<'info>,
+    new_vault: &Pubkey,
+    old_vault_seeds: &[&[u8]],
+) -> Result<u32> {
+    let mut reassigned = 0u32;
+    for account in accounts {
+        if !is_vault_token_account(account, old_vault.key) {
+            continue;
+        }
+        let token_program = accounts
+            .iter()
+            .find(|a| a.key == account.owner)
+            .ok_or(VaultError::MissingTokenProgram)?;
+
+        token_interface::set_authority(
+            CpiContext::new_with_signer(
+                token_program.clone(),
+                SetAuthority {
+                    current_authority: old_vault.clone(),
+                    account_or_mint: account.clone(),
+                },
+                &[old_vault_seeds],
+            ),
+            AuthorityType::AccountOwner,
+            Some(*new_vault),
+        )?;
+        reassigned += 1;
+    }
+    Ok(reassigned)
+}
@@ -1,11 +1,12 @@
 use anchor_lang::prelude::*;
 use anchor_lang::system_program;
-use crate::state::Vault;
+use crate::state::{Vault, SessionSlot, ROLE_DEPOSIT};
 use crate::errors::VaultError;
+use crate::events::{AgentDepositEvent, SOL_DECIMALS, SOL_MINT};
 
 #[derive(Accounts)]
 pub struct AgentDeposit<'info> {
-    /// The agent authority that controls the vault in auto mode
+    /// A registered agent with the deposit role
     pub agent: Signer<'info>,
 
     /// The vault PDA to deposit into.
@@ -13,12 +14,14 @@ pub struct AgentDeposit<'info> {
         mut,
         seeds = [b"vault", vault.owner.as_ref()],
         bump = vault.bump,
-        constraint = vault.agent_authority == agent.key() @ VaultError::UnauthorizedAgent,
+        constraint = vault.agent_roles(agent.key).is_some() @ VaultError::UnauthorizedAgent,
+        constraint = vault.has_agent_role(agent.key, ROLE_DEPOSIT) @ VaultError::MissingRole,
     )]
     pub vault: Account<'info, Vault>,
 
     /// The session wallet returning SOL to the vault.
-    /// Must be a signer so it can transfer lamports via CPI.
+    /// Must be a signer so it can transfer lamports via CPI, and must be
+    /// the wallet recorded when the session was opened.
     #[account(mut)]
     pub source: Signer<'info>,
 
@@ -27,10 +30,30 @@ pub struct AgentDeposit<'info> {
 
 /// Agent deposits SOL back into the vault from a stealth session wallet.
 /// Called during session sweep to return funds (possibly with profit).
-pub fn handler(ctx: Context<AgentDeposit>, amount: u64) -> Result<()> {
+///
+/// With `close_session`, the slot is closed and `amount - amount_out` is
+/// realized as PnL and booked into total_deposited (profit) or
+/// total_withdrawn (loss), so the vault's balance tracks the lamports
+/// actually returned. Otherwise this is a partial return of principal that
+/// reduces the slot, and may not exceed what is still out.
+///
+/// A session removed by expire_session is still accepted from its wallet:
+/// its SOL was written off at expiry, so the amount is credited back as
+/// deposited instead, and may not exceed what was written off unless
+/// `close_session` is set.
+///
+/// `nonce` must equal the vault's agent_nonce, so a replayed deposit fails.
+pub fn handler(
+    ctx: Context<AgentDeposit>,
+    amount: u64,
+    session_id: u64,
+    close_session: bool,
+    nonce: u64,
+) -> Result<()> {
     require!(amount > 0, VaultError::ZeroDeposit);
 
     let vault = &mut ctx.accounts.vault;
+    vault.consume_agent_nonce(nonce)?;
 
     // Transfer SOL from session wallet to vault PDA via CPI
     system_program::transfer(
@@ -44,25 +67,62 @@ pub fn handler(ctx: Context<AgentDeposit>, amount: u64) -> Result<()> {
         amount,
     )?;
 
-    // Decrement in_session_amount (capped at 0 to handle profits)
-    vault.in_session_amount = vault.in_session_amount.saturating_sub(amount);
+    let source = ctx.accounts.source.key();
+    let (principal_returned, realized_pnl) = if vault.credit_expired_session(session_id, source, amount, close_session)? {
+        // Written off at expiry and credited back as deposited: nothing
+        // was in session and no PnL is realized
+        (0, 0)
+    } else {
+        let slot = vault.session_mut(session_id)?;
+        require!(slot.wallet == source, VaultError::UnknownSource);
+        let amount_out = slot.amount_out;
 
-    // If the return is more than what was tracked as in-session,
-    // the excess is profit — credit it to total_deposited
-    // (in_session_amount saturating_sub already handles the tracking)
-    // We always increment total_deposited to reflect the actual
-    // SOL that re-entered the vault, keeping the accounting clean.
-    // The caller is expected to return everything including profits.
+        if close_session {
+            // Whatever came back minus what was still out is realized PnL
+            *slot = SessionSlot::default();
+            let pnl = i64::try_from(amount as i128 - amount_out as i128)
+                .map_err(|_| VaultError::ArithmeticOverflow)?;
+            (amount_out, pnl)
+        } else {
+            slot.amount_out = amount_out
+                .checked_sub(amount)
+                .ok_or(VaultError::SessionAmountUnderflow)?;
+            if slot.amount_out == 0 {
+                *slot = SessionSlot::default();
+            }
+            (amount, 0)
+        }
+    };
 
+    vault.in_session_amount = vault
+        .in_session_amount
+        .checked_sub(principal_returned)
+        .ok_or(VaultError::SessionAmountUnderflow)?;
     let clock = Clock::get()?;
+    vault.book_realized_pnl(realized_pnl, clock.unix_timestamp)?;
     vault.last_action_at = clock.unix_timestamp;
 
     msg!(
-        "Agent deposited {} lamports from session wallet {}. In-session: {}",
+        "Agent deposited {} lamports from session wallet {}. Session {} (closed: {}) PnL: {} In-session: {}",
         amount,
         ctx.accounts.source.key(),
+        session_id,
+        close_session,
+        realized_pnl,
         vault.in_session_amount
     );
 
+    emit!(AgentDepositEvent {
+        vault: vault.key(),
+        source: ctx.accounts.source.key(),
+        amount,
+        session_id,
+        session_closed: close_session,
+        realized_pnl,
+        mint: SOL_MINT,
+        decimals: SOL_DECIMALS,
+        timestamp: clock.unix_timestamp,
+    });
+
     Ok(())
 }
@@ -0,0 +1,153 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+use crate::state::{Vault, AgentMode, ROLE_SWAP, SWAP_RATE_SCALE};
+use crate::errors::VaultError;
+use crate::events::AgentSwapEvent;
+use crate::jupiter_cpi::{self, JUPITER_PROGRAM_ID};
+
+#[derive(Accounts)]
+pub struct AgentSwap<'info> {
+    /// A registered agent with the withdraw role
+    pub agent: Signer<'info>,
+
+    /// The vault PDA; authority over both token accounts and signer of
+    /// the Jupiter route
+    #[account(
+        mut,
+        seeds = [b"vault", vault.owner.as_ref()],
+        bump = vault.bump,
+        constraint = vault.agent_roles(agent.key).is_some() @ VaultError::UnauthorizedAgent,
+        constraint = vault.has_agent_role(agent.key, ROLE_SWAP) @ VaultError::MissingRole,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// Vault token account the route spends from
+    #[account(
+        mut,
+        token::authority = vault,
+    )]
+    pub input_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Vault token account the route pays into
+    #[account(
+        mut,
+        token::authority = vault,
+        constraint = output_token_account.mint != input_token_account.mint @ VaultError::InvalidSwapRoute,
+    )]
+    pub output_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Only Jupiter is accepted as the swap program
+    #[account(address = JUPITER_PROGRAM_ID @ VaultError::InvalidSwapRoute)]
+    pub jupiter_program: UncheckedAccount<'info>,
+}
+
+/// Swap between two of the vault's token accounts in one instruction.
+///
+/// The vault signs a Jupiter CPI whose accounts are passed as remaining
+/// accounts, and the output lands back in a vault token account, so funds
+/// never sit in a session wallet. If the route fails the whole transaction
+/// reverts and nothing moves.
+///
+/// The route must name the vault as its authority and the two token
+/// accounts as its source and destination (see `jupiter_cpi::check_route`).
+/// It may spend at most `amount_in`, and must deliver at least the owner's
+/// swap floor for the pair on what it spent. `quoted_out` less the vault's
+/// max_slippage_bps only tightens that minimum. `amount_in` is capped at
+/// the input mint's max position percentage of the input balance.
+/// Only callable in Auto mode by an agent with a fresh heartbeat; `nonce`
+/// must equal the vault's agent_nonce.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, AgentSwap<'info>>,
+    amount_in: u64,
+    quoted_out: u64,
+    route_data: Vec<u8>,
+    nonce: u64,
+) -> Result<()> {
+    require!(amount_in > 0, VaultError::ZeroWithdraw);
+    require!(quoted_out > 0, VaultError::InvalidSwapQuote);
+
+    let vault = &mut ctx.accounts.vault;
+    require!(
+        vault.mode == AgentMode::Auto,
+        VaultError::NotAutoMode
+    );
+    vault.consume_agent_nonce(nonce)?;
+    vault.check_agent_heartbeat(Clock::get()?.unix_timestamp)?;
+
+    let input_mint = ctx.accounts.input_token_account.mint;
+    let output_mint = ctx.accounts.output_token_account.mint;
+    let min_rate = vault
+        .swap_floor(&input_mint, &output_mint)
+        .ok_or(VaultError::SwapFloorNotSet)?;
+
+    let max_slippage_bps = vault.risk_limits.max_slippage_bps as u128;
+    let quote_min_out = (quoted_out as u128 * (10_000 - max_slippage_bps) / 10_000) as u64;
+
+    let input_before = ctx.accounts.input_token_account.amount;
+    let output_before = ctx.accounts.output_token_account.amount;
+
+    // Enforce max position size on the input mint (per-mint cap if set)
+    let max_pct = vault.max_position_pct(&ctx.accounts.input_token_account.mint);
+    let max_position = (input_before as u128 * max_pct as u128 / 100) as u64;
+    require!(
+        amount_in <= max_position,
+        VaultError::ExceedsMaxPosition
+    );
+
+    jupiter_cpi::check_route(
+        ctx.remaining_accounts,
+        &vault.key(),
+        &ctx.accounts.input_token_account.key(),
+        &ctx.accounts.output_token_account.key(),
+    )?;
+
+    let owner = vault.owner;
+    let seeds: &[&[u8]] = &[b"vault", owner.as_ref(), &[vault.bump]];
+    jupiter_cpi::swap(
+        &ctx.accounts.jupiter_program.to_account_info(),
+        &vault.key(),
+        ctx.remaining_accounts,
+        route_data,
+        seeds,
+    )?;
+
+    ctx.accounts.input_token_account.reload()?;
+    ctx.accounts.output_token_account.reload()?;
+
+    let spent = input_before.saturating_sub(ctx.accounts.input_token_account.amount);
+    let received = ctx
+        .accounts
+        .output_token_account
+        .amount
+        .saturating_sub(output_before);
+    require!(spent <= amount_in, VaultError::SwapOverspent);
+
+    let floor_out = (spent as u128 * min_rate as u128 / SWAP_RATE_SCALE) as u64;
+    let min_out = floor_out.max(quote_min_out);
+    require!(received >= min_out, VaultError::SlippageExceeded);
+
+    let clock = Clock::get()?;
+    let vault = &mut ctx.accounts.vault;
+    vault.last_action_at = clock.unix_timestamp;
+
+    msg!(
+        "Agent swapped {} of {} for {} of {} (min out: {})",
+        spent,
+        input_mint,
+        received,
+        output_mint,
+        min_out
+    );
+
+    emit!(AgentSwapEvent {
+        vault: vault.key(),
+        input_mint,
+        output_mint,
+        amount_in: spent,
+        amount_out: received,
+        min_out,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
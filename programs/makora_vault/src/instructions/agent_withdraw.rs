@@ -1,10 +1,16 @@
 use anchor_lang::prelude::*;
-use crate::state::{Vault, AgentMode};
+use crate::state::{
+    Vault, AgentMode, GlobalConfig, withdrawal_fee, DEFAULT_SESSION_DURATION_SECS,
+    MIN_POSITION_BALANCE_FLOOR, ROLE_WITHDRAW,
+};
 use crate::errors::VaultError;
+use crate::events::{AgentWithdrawEvent, SOL_DECIMALS, SOL_MINT};
+use crate::oracle::min_reserve_lamports;
+use crate::utils::transfer_from_vault;
 
 #[derive(Accounts)]
 pub struct AgentWithdraw<'info> {
-    /// The agent authority that controls the vault in auto mode
+    /// A registered agent with the withdraw role
     #[account(mut)]
     pub agent: Signer<'info>,
 
@@ -14,23 +20,57 @@ pub struct AgentWithdraw<'info> {
         mut,
         seeds = [b"vault", vault.owner.as_ref()],
         bump = vault.bump,
-        constraint = vault.agent_authority == agent.key() @ VaultError::UnauthorizedAgent,
+        constraint = vault.agent_roles(agent.key).is_some() @ VaultError::UnauthorizedAgent,
+        constraint = vault.has_agent_role(agent.key, ROLE_WITHDRAW) @ VaultError::MissingRole,
     )]
     pub vault: Account<'info, Vault>,
 
+    /// Global config; its protocol fee is charged on the withdrawal.
+    /// Without it no fee is charged.
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Option<Account<'info, GlobalConfig>>,
+
+    /// Receives the protocol fee. Required when the config charges a fee.
+    /// CHECK: Validated against config.fee_collector in the handler.
+    #[account(mut)]
+    pub fee_collector: Option<UncheckedAccount<'info>>,
+
     /// The destination session wallet that receives the SOL
     /// CHECK: This is an ephemeral session wallet; no constraints needed.
     #[account(mut)]
     pub destination: UncheckedAccount<'info>,
 
+    /// Optional Pyth SOL/USD price update; converts min_reserve_usd to
+    /// lamports when the vault has one set
+    /// CHECK: Owner, discriminator and feed are checked in oracle.rs
+    pub price_update: Option<UncheckedAccount<'info>>,
+
     pub system_program: Program<'info, System>,
 }
 
 /// Agent withdraws SOL from the vault to a stealth session wallet.
-/// Only callable by the vault's agent_authority, and only when mode == Auto.
-pub fn handler(ctx: Context<AgentWithdraw>, amount: u64) -> Result<()> {
+/// Only callable by an agent with the withdraw role, and only when mode == Auto.
+/// `nonce` must equal the vault's agent_nonce, so a replayed withdraw fails.
+/// With a heartbeat timeout set, the agent must have sent a heartbeat
+/// within it. Rejected while the daily loss window has no headroom left.
+/// The opened session may be expired by anyone after `max_duration_secs`
+/// (0 = DEFAULT_SESSION_DURATION_SECS).
+pub fn handler(
+    ctx: Context<AgentWithdraw>,
+    amount: u64,
+    nonce: u64,
+    max_duration_secs: u32,
+) -> Result<()> {
     require!(amount > 0, VaultError::ZeroWithdraw);
 
+    // Protocol fee comes out of the withdrawn amount; the session
+    // wallet receives the remainder
+    let fee = withdrawal_fee(ctx.accounts.config.as_deref(), &ctx.accounts.fee_collector, amount)?;
+    let net_amount = amount - fee;
+
     let vault = &mut ctx.accounts.vault;
 
     // Only Auto mode allows agent operations
@@ -39,26 +79,47 @@ pub fn handler(ctx: Context<AgentWithdraw>, amount: u64) -> Result<()> {
         VaultError::NotAutoMode
     );
 
+    vault.consume_agent_nonce(nonce)?;
+
+    let clock = Clock::get()?;
+    vault.check_agent_heartbeat(clock.unix_timestamp)?;
+    if vault.agent_withdraw_cooldown {
+        vault.enforce_withdraw_cooldown(clock.unix_timestamp)?;
+    }
+
+    // Once the rolling window's realized losses reach max_daily_loss_pct,
+    // the agent cannot open new sessions until the window resets
+    require!(
+        vault.daily_loss_headroom(clock.unix_timestamp) > 0,
+        VaultError::DailyLossLimitReached
+    );
+
     let available = vault.current_balance();
+    require!(amount <= available, VaultError::InsufficientBalance);
 
     // Must respect min_sol_reserve
     let min_rent = Rent::get()?.minimum_balance(Vault::SIZE);
-    let min_reserve = vault.risk_limits.min_sol_reserve;
+    let min_reserve = min_reserve_lamports(
+        vault,
+        ctx.accounts.price_update.as_deref(),
+        clock.unix_timestamp,
+    )?;
     let total_min = min_rent
         .checked_add(min_reserve)
         .ok_or(VaultError::ArithmeticOverflow)?;
 
+    // The vault has the funds, but taking them would dip into the reserve
     let max_withdrawable = available.saturating_sub(total_min);
     require!(
         amount <= max_withdrawable,
-        VaultError::InsufficientBalance
+        VaultError::ReserveWouldBeBreached
     );
 
-    // Enforce max position size percentage
+    // Enforce max position size percentage (per-mint cap for SOL if set)
     let total_balance = vault.total_deposited.saturating_sub(vault.total_withdrawn);
     if total_balance > 0 {
         let max_position = total_balance
-            .checked_mul(vault.risk_limits.max_position_size_pct as u64)
+            .checked_mul(vault.max_position_pct(&SOL_MINT) as u64)
             .ok_or(VaultError::ArithmeticOverflow)?
             / 100;
         require!(
@@ -67,28 +128,73 @@ pub fn handler(ctx: Context<AgentWithdraw>, amount: u64) -> Result<()> {
         );
     }
 
+    // Enforce min position size percentage so the max cap can't be
+    // sidestepped by fragmenting into many tiny positions
+    if total_balance > MIN_POSITION_BALANCE_FLOOR {
+        let min_position = total_balance
+            .checked_mul(vault.risk_limits.min_position_size_pct as u64)
+            .ok_or(VaultError::ArithmeticOverflow)?
+            / 100;
+        require!(
+            amount >= min_position,
+            VaultError::BelowMinPosition
+        );
+    }
+
     // Transfer SOL from vault PDA to destination (session wallet)
     let vault_info = vault.to_account_info();
     let dest_info = ctx.accounts.destination.to_account_info();
 
-    **vault_info.try_borrow_mut_lamports()? -= amount;
-    **dest_info.try_borrow_mut_lamports()? += amount;
+    transfer_from_vault(&vault_info, &dest_info, net_amount)?;
+    if let Some(fee_collector) = &ctx.accounts.fee_collector {
+        transfer_from_vault(&vault_info, fee_collector, fee)?;
+    }
+
+    // The fee has left the vault for good; only the net amount is in session
+    vault.total_withdrawn = vault
+        .total_withdrawn
+        .checked_add(fee)
+        .ok_or(VaultError::ArithmeticOverflow)?;
 
     // Track SOL that's out in sessions
     vault.in_session_amount = vault
         .in_session_amount
-        .checked_add(amount)
+        .checked_add(net_amount)
         .ok_or(VaultError::ArithmeticOverflow)?;
 
-    let clock = Clock::get()?;
+    // Each withdraw opens its own session slot, pinned to the destination
+    let max_duration_secs = match max_duration_secs {
+        0 => DEFAULT_SESSION_DURATION_SECS,
+        d => d,
+    };
+    let session_id = vault.open_session(
+        net_amount,
+        ctx.accounts.destination.key(),
+        clock.unix_timestamp,
+        max_duration_secs,
+    )?;
+
     vault.last_action_at = clock.unix_timestamp;
 
     msg!(
-        "Agent withdrew {} lamports to session wallet {}. In-session: {}",
+        "Agent withdrew {} lamports (fee: {}) to session wallet {}. Session: {} In-session: {}",
         amount,
+        fee,
         ctx.accounts.destination.key(),
+        session_id,
         vault.in_session_amount
     );
 
+    emit!(AgentWithdrawEvent {
+        vault: vault.key(),
+        destination: ctx.accounts.destination.key(),
+        amount,
+        fee,
+        session_id,
+        mint: SOL_MINT,
+        decimals: SOL_DECIMALS,
+        timestamp: clock.unix_timestamp,
+    });
+
     Ok(())
 }
@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, CloseAccount, TokenAccount, TokenInterface};
+use crate::state::Vault;
+use crate::errors::VaultError;
+
+#[derive(Accounts)]
+pub struct CloseTokenAccount<'info> {
+    /// The vault owner; receives the reclaimed rent
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// The vault PDA that owns the token account
+    #[account(
+        seeds = [b"vault", owner.key().as_ref()],
+        bump = vault.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// Empty token account owned by the vault (e.g. an unwrapped wSOL ATA)
+    #[account(
+        mut,
+        constraint = token_account.owner == vault.key() @ VaultError::Unauthorized,
+        constraint = token_account.amount == 0 @ VaultError::TokenAccountNotEmpty,
+    )]
+    pub token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// SPL Token or Token-2022, matching the token account's owner
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Close a stranded, zero-balance token account owned by the vault
+/// and return its rent to the owner.
+pub fn handler(ctx: Context<CloseTokenAccount>) -> Result<()> {
+    let vault = &ctx.accounts.vault;
+    vault.check_cosigners(ctx.remaining_accounts)?;
+    let owner_key = ctx.accounts.owner.key();
+    let seeds: &[&[u8]] = &[b"vault", owner_key.as_ref(), &[vault.bump]];
+
+    let rent = ctx.accounts.token_account.to_account_info().lamports();
+
+    token_interface::close_account(CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        CloseAccount {
+            account: ctx.accounts.token_account.to_account_info(),
+            destination: ctx.accounts.owner.to_account_info(),
+            authority: vault.to_account_info(),
+        },
+        &[seeds],
+    ))?;
+
+    msg!(
+        "Closed vault token account {}, reclaimed {} lamports",
+        ctx.accounts.token_account.key(),
+        rent
+    );
+
+    Ok(())
+}
@@ -2,6 +2,8 @@ use anchor_lang::prelude::*;
 use anchor_lang::system_program;
 use crate::state::Vault;
 use crate::errors::VaultError;
+use crate::events::{DepositEvent, SOL_DECIMALS, SOL_MINT};
+use crate::strategy_cpi::{self, ACTION_KIND_DEPOSIT, STRATEGY_PROGRAM_ID};
 
 #[derive(Accounts)]
 pub struct Deposit<'info> {
@@ -22,11 +24,42 @@ pub struct Deposit<'info> {
 
     /// System program for the SOL transfer
     pub system_program: Program<'info, System>,
+
+    /// Optional: the owner's makora_strategy account. When passed with the
+    /// audit trail and strategy program, the deposit is logged via CPI.
+    /// CHECK: Must be the owner's strategy PDA; checked in the handler.
+    #[account(mut)]
+    pub strategy_account: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: The owner's audit trail PDA; validated by makora_strategy.
+    #[account(mut)]
+    pub audit_trail: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Must be the makora_strategy program.
+    #[account(address = STRATEGY_PROGRAM_ID @ VaultError::InvalidStrategyAccounts)]
+    pub strategy_program: Option<UncheckedAccount<'info>>,
 }
 
 pub fn handler(ctx: Context<Deposit>, amount: u64) -> Result<()> {
     // Validate amount
     require!(amount > 0, VaultError::ZeroDeposit);
+    require!(
+        amount >= ctx.accounts.vault.min_deposit,
+        VaultError::BelowMinimum
+    );
+
+    // Enforce the deposit cap (0 = unlimited)
+    let max_total_deposit = ctx.accounts.vault.max_total_deposit;
+    if max_total_deposit > 0 {
+        let new_total = ctx.accounts.vault
+            .total_deposited
+            .checked_add(amount)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+        require!(
+            new_total <= max_total_deposit,
+            VaultError::DepositCapExceeded
+        );
+    }
 
     // Transfer SOL from owner to vault PDA
     system_program::transfer(
@@ -56,5 +89,40 @@ pub fn handler(ctx: Context<Deposit>, amount: u64) -> Result<()> {
         vault.total_deposited
     );
 
+    // Record the deposit on the owner's strategy audit trail
+    match (
+        &ctx.accounts.strategy_account,
+        &ctx.accounts.audit_trail,
+        &ctx.accounts.strategy_program,
+    ) {
+        (Some(strategy_account), Some(audit_trail), Some(strategy_program)) => {
+            require!(
+                strategy_account.key() == strategy_cpi::strategy_address(&vault.owner),
+                VaultError::InvalidStrategyAccounts
+            );
+            strategy_cpi::log_action(
+                strategy_program,
+                &ctx.accounts.owner,
+                strategy_account,
+                audit_trail,
+                ACTION_KIND_DEPOSIT,
+                "makora_vault",
+                &format!("Deposit {} lamports", amount),
+            )?;
+        }
+        (None, None, None) => {}
+        _ => return err!(VaultError::InvalidStrategyAccounts),
+    }
+
+    emit!(DepositEvent {
+        vault: vault.key(),
+        owner: vault.owner,
+        amount,
+        mint: SOL_MINT,
+        decimals: SOL_DECIMALS,
+        total_deposited: vault.total_deposited,
+        timestamp: clock.unix_timestamp,
+    });
+
     Ok(())
 }
@@ -0,0 +1,91 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use crate::state::Vault;
+use crate::errors::VaultError;
+use crate::events::{DepositEvent, SOL_DECIMALS, SOL_MINT};
+
+#[derive(Accounts)]
+pub struct DepositMany<'info> {
+    /// Treasury wallet funding every deposit in the batch
+    #[account(mut)]
+    pub funder: Signer<'info>,
+
+    /// System program for the SOL transfers
+    pub system_program: Program<'info, System>,
+    // remaining_accounts: the vault PDAs to fund (writable), in the same
+    // order as `amounts`
+}
+
+/// Deposit `amounts[i]` from the funder into the i-th vault in
+/// `remaining_accounts`. Each vault's deposit cap and minimum still apply. Any
+/// failure aborts the whole batch.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, DepositMany<'info>>,
+    amounts: Vec<u64>,
+) -> Result<()> {
+    require!(
+        !amounts.is_empty() && amounts.len() == ctx.remaining_accounts.len(),
+        VaultError::AmountsLengthMismatch
+    );
+
+    let clock = Clock::get()?;
+
+    for (vault_info, &amount) in ctx.remaining_accounts.iter().zip(amounts.iter()) {
+        require!(amount > 0, VaultError::ZeroDeposit);
+
+        // Checks owner program and discriminator
+        let mut vault: Account<'info, Vault> = Account::try_from(vault_info)?;
+        let expected = Pubkey::create_program_address(
+            &[b"vault", vault.owner.as_ref(), &[vault.bump]],
+            ctx.program_id,
+        )
+        .map_err(|_| error!(VaultError::Unauthorized))?;
+        require_keys_eq!(vault_info.key(), expected, VaultError::Unauthorized);
+        require!(amount >= vault.min_deposit, VaultError::BelowMinimum);
+
+        let new_total = vault
+            .total_deposited
+            .checked_add(amount)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+        require!(
+            vault.max_total_deposit == 0 || new_total <= vault.max_total_deposit,
+            VaultError::DepositCapExceeded
+        );
+
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.funder.to_account_info(),
+                    to: vault_info.clone(),
+                },
+            ),
+            amount,
+        )?;
+
+        vault.total_deposited = new_total;
+        vault.last_action_at = clock.unix_timestamp;
+
+        // Remaining accounts are not written back automatically
+        vault.exit(ctx.program_id)?;
+
+        emit!(DepositEvent {
+            vault: vault_info.key(),
+            owner: vault.owner,
+            amount,
+            mint: SOL_MINT,
+            decimals: SOL_DECIMALS,
+            total_deposited: new_total,
+            timestamp: clock.unix_timestamp,
+        });
+    }
+
+    msg!(
+        "Batch deposit of {} lamports into {} vaults by {}",
+        amounts.iter().sum::<u64>(),
+        amounts.len(),
+        ctx.accounts.funder.key()
+    );
+
+    Ok(())
+}
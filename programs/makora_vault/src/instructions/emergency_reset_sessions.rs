@@ -0,0 +1,66 @@
+use anchor_lang::prelude::*;
+use crate::state::{Vault, SessionSlot, MAX_OPEN_SESSIONS};
+use crate::errors::VaultError;
+use crate::events::SessionsResetEvent;
+
+#[derive(Accounts)]
+pub struct EmergencyResetSessions<'info> {
+    /// The vault owner; only they can write off session funds
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", owner.key().as_ref()],
+        bump = vault.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: Account<'info, Vault>,
+}
+
+/// Clear all agent sessions and reconcile the vault's accounting with the
+/// lamports actually held by the PDA.
+///
+/// For when a session wallet lost its funds and `in_session_amount` would
+/// otherwise stay stuck. Any shortfall between the recorded balance and the
+/// PDA's spendable lamports is written off as withdrawn.
+pub fn handler(ctx: Context<EmergencyResetSessions>, confirm: bool) -> Result<()> {
+    require!(confirm, VaultError::ConfirmationRequired);
+
+    let vault = &mut ctx.accounts.vault;
+    vault.check_cosigners(ctx.remaining_accounts)?;
+    let cleared_in_session = vault.in_session_amount;
+
+    vault.in_session_amount = 0;
+    vault.sessions = [SessionSlot::default(); MAX_OPEN_SESSIONS];
+
+    // Spendable lamports actually in the PDA (excluding rent)
+    let min_rent = Rent::get()?.minimum_balance(Vault::SIZE);
+    let actual = vault.to_account_info().lamports().saturating_sub(min_rent);
+
+    let written_off = vault.current_balance().saturating_sub(actual);
+    vault.total_withdrawn = vault
+        .total_withdrawn
+        .checked_add(written_off)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+
+    let clock = Clock::get()?;
+    vault.last_action_at = clock.unix_timestamp;
+
+    msg!(
+        "WARNING: agent sessions reset. Cleared {} lamports in session, wrote off {}. Balance: {}",
+        cleared_in_session,
+        written_off,
+        vault.current_balance()
+    );
+
+    emit!(SessionsResetEvent {
+        vault: vault.key(),
+        owner: vault.owner,
+        cleared_in_session,
+        written_off,
+        current_balance: vault.current_balance(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+use crate::errors::VaultError;
+
+#[derive(Accounts)]
+pub struct EnsureVault<'info> {
+    /// The would-be vault owner
+    /// CHECK: Only used for PDA derivation.
+    pub owner: UncheckedAccount<'info>,
+
+    /// The vault PDA to check. May not exist yet.
+    /// CHECK: Only inspected for existing data; never deserialized.
+    #[account(
+        seeds = [b"vault", owner.key().as_ref()],
+        bump,
+    )]
+    pub vault: UncheckedAccount<'info>,
+}
+
+/// Fails with `AlreadyInitialized` if the owner's vault already exists.
+/// `initialize` uses Anchor's `init`, which rejects a second call with a
+/// generic "account already in use" before the handler runs; clients call
+/// this first (or in the same transaction) to get the typed error instead.
+pub fn handler(ctx: Context<EnsureVault>) -> Result<()> {
+    require!(
+        ctx.accounts.vault.data_is_empty(),
+        VaultError::AlreadyInitialized
+    );
+
+    msg!("No vault yet for {}", ctx.accounts.owner.key());
+
+    Ok(())
+}
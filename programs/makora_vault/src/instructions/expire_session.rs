@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+use crate::state::{Vault, SessionSlot};
+use crate::errors::VaultError;
+use crate::events::SessionExpiredEvent;
+
+#[derive(Accounts)]
+pub struct ExpireSession<'info> {
+    /// Anyone may expire a session once it is past its max duration
+    pub caller: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault.owner.as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, Vault>,
+}
+
+/// Stop tracking a session that never returned.
+///
+/// The session's SOL is at the session wallet, not the PDA, so nothing is
+/// transferred: the slot is freed and its amount leaves `in_session_amount`
+/// and is counted as withdrawn, like `emergency_reset_sessions` does for a
+/// shortfall. The session is kept in `expired_sessions`, so its wallet can
+/// still return the SOL through `agent_deposit` with the same session id.
+pub fn handler(ctx: Context<ExpireSession>, session_id: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    let vault = &mut ctx.accounts.vault;
+
+    let slot = vault.session_mut(session_id)?;
+    require!(slot.is_expired(clock.unix_timestamp), VaultError::SessionNotExpired);
+    let expired = *slot;
+    *slot = SessionSlot::default();
+    vault.record_expired_session(expired);
+
+    vault.in_session_amount = vault.in_session_amount.saturating_sub(expired.amount_out);
+    vault.total_withdrawn = vault
+        .total_withdrawn
+        .checked_add(expired.amount_out)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+    vault.last_action_at = clock.unix_timestamp;
+
+    msg!(
+        "Agent session {} expired. Wrote off {} lamports at {}. In-session: {}",
+        session_id,
+        expired.amount_out,
+        expired.wallet,
+        vault.in_session_amount
+    );
+
+    emit!(SessionExpiredEvent {
+        vault: vault.key(),
+        session_id,
+        wallet: expired.wallet,
+        written_off: expired.amount_out,
+        current_balance: vault.current_balance(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
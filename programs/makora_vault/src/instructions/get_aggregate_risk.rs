@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+use crate::state::Vault;
+use crate::errors::VaultError;
+
+#[derive(Accounts)]
+pub struct GetAggregateRisk<'info> {
+    /// Whoever requests the read; any signer (typically simulated)
+    pub caller: Signer<'info>,
+    // remaining_accounts: the vault PDAs to aggregate (read-only)
+}
+
+/// Combined figures across several vaults, returned via return data.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct AggregateRisk {
+    /// Number of vaults aggregated
+    pub vault_count: u8,
+    /// Sum of each vault's current_balance()
+    pub total_balance: u64,
+    /// Sum of SOL out in agent sessions
+    pub total_in_session: u64,
+    /// Strictest (lowest) max_slippage_bps across the vaults
+    pub min_max_slippage_bps: u16,
+}
+
+/// Aggregate risk figures over the vaults in `remaining_accounts`. Every
+/// account must be a distinct vault PDA.
+pub fn handler(ctx: Context<GetAggregateRisk>) -> Result<AggregateRisk> {
+    require!(
+        !ctx.remaining_accounts.is_empty() && ctx.remaining_accounts.len() <= u8::MAX as usize,
+        VaultError::InvalidVaultList
+    );
+
+    let mut seen: Vec<Pubkey> = Vec::with_capacity(ctx.remaining_accounts.len());
+    let mut total_balance: u64 = 0;
+    let mut total_in_session: u64 = 0;
+    let mut min_max_slippage_bps = u16::MAX;
+
+    for vault_info in ctx.remaining_accounts.iter() {
+        // Counting a vault twice would overstate the exposure
+        require!(!seen.contains(vault_info.key), VaultError::InvalidVaultList);
+        seen.push(vault_info.key());
+
+        // Read-only, so deserialize directly: owner program, then discriminator
+        require_keys_eq!(*vault_info.owner, *ctx.program_id, VaultError::Unauthorized);
+        let vault = Vault::try_deserialize(&mut &vault_info.try_borrow_data()?[..])?;
+        let expected = Pubkey::create_program_address(
+            &[b"vault", vault.owner.as_ref(), &[vault.bump]],
+            ctx.program_id,
+        )
+        .map_err(|_| error!(VaultError::Unauthorized))?;
+        require_keys_eq!(vault_info.key(), expected, VaultError::Unauthorized);
+
+        total_balance = total_balance.saturating_add(vault.current_balance());
+        total_in_session = total_in_session.saturating_add(vault.in_session_amount);
+        min_max_slippage_bps = min_max_slippage_bps.min(vault.risk_limits.max_slippage_bps);
+    }
+
+    Ok(AggregateRisk {
+        vault_count: seen.len() as u8,
+        total_balance,
+        total_in_session,
+        min_max_slippage_bps,
+    })
+}
@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+use crate::state::Vault;
+use crate::oracle::min_reserve_lamports;
+
+#[derive(Accounts)]
+pub struct GetVaultStatus<'info> {
+    /// The vault PDA to report on (read-only)
+    #[account(
+        seeds = [b"vault", vault.owner.as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// Optional Pyth SOL/USD price update, as passed to withdraw; converts
+    /// min_reserve_usd to lamports when the vault has one set
+    /// CHECK: Owner, discriminator and feed are checked in oracle.rs
+    pub price_update: Option<UncheckedAccount<'info>>,
+}
+
+/// Vault figures returned via return data, computed with the same
+/// logic the program uses when enforcing limits.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct VaultStatus {
+    /// Balance available for new operations (excludes in-session SOL)
+    pub current_balance: u64,
+    /// Largest withdrawal that still leaves rent + the reserve behind. The
+    /// reserve is resolved like a withdraw given the same `price_update`:
+    /// the USD reserve at the oracle price, else min_sol_reserve
+    pub max_withdrawable: u64,
+    /// SOL currently out in agent sessions
+    pub in_session_amount: u64,
+    /// Remaining loss allowed under max_daily_loss_pct after the realized
+    /// losses in the current rolling window
+    pub daily_loss_headroom: u64,
+    /// Seconds until the daily-loss window resets (0 = no window open)
+    pub daily_window_remaining_secs: i64,
+}
+
+pub fn handler(ctx: Context<GetVaultStatus>) -> Result<VaultStatus> {
+    let vault = &ctx.accounts.vault;
+    let now = Clock::get()?.unix_timestamp;
+
+    let current_balance = vault.current_balance();
+
+    let min_rent = Rent::get()?.minimum_balance(Vault::SIZE);
+    let price_update = ctx.accounts.price_update.as_ref().map(|p| p.as_ref());
+    let min_reserve = min_reserve_lamports(vault, price_update, now)?;
+    let total_min = min_rent.saturating_add(min_reserve);
+    let max_withdrawable = current_balance.saturating_sub(total_min);
+
+    Ok(VaultStatus {
+        current_balance,
+        max_withdrawable,
+        in_session_amount: vault.in_session_amount,
+        daily_loss_headroom: vault.daily_loss_headroom(now),
+        daily_window_remaining_secs: vault.daily_loss.remaining_secs(now),
+    })
+}
@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+use crate::state::Vault;
+use crate::errors::VaultError;
+
+#[derive(Accounts)]
+pub struct Heartbeat<'info> {
+    /// Any registered agent of the vault
+    pub agent: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault.owner.as_ref()],
+        bump = vault.bump,
+        constraint = vault.agent_roles(agent.key).is_some() @ VaultError::UnauthorizedAgent,
+    )]
+    pub vault: Account<'info, Vault>,
+}
+
+/// Record that the agent is alive. While the vault has a heartbeat
+/// timeout, agent withdraws and swaps require a heartbeat within it.
+pub fn handler(ctx: Context<Heartbeat>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    vault.last_heartbeat_at = Clock::get()?.unix_timestamp;
+
+    msg!("Agent {} heartbeat at {}", ctx.accounts.agent.key(), vault.last_heartbeat_at);
+
+    Ok(())
+}
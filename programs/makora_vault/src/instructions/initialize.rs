@@ -1,6 +1,7 @@
 use anchor_lang::prelude::*;
-use crate::state::{Vault, AgentMode, RiskLimits};
+use crate::state::{Vault, AgentMode, RiskLimits, SessionSlot, AgentRole, DailyLossWindow, MintRiskLimit, MAX_OPEN_SESSIONS, MAX_AGENTS, MAX_COSIGNERS, MAX_MINT_RISK_LIMITS, ROLE_ALL, VAULT_VERSION};
 use crate::errors::VaultError;
+use crate::events::InitializedEvent;
 
 #[derive(Accounts)]
 pub struct Initialize<'info> {
@@ -29,10 +30,12 @@ pub fn handler(
     agent_authority: Pubkey,
     mode: u8,
     max_position_size_pct: u8,
+    min_position_size_pct: u8,
     max_slippage_bps: u16,
     max_daily_loss_pct: u8,
     min_sol_reserve: u64,
     max_protocol_exposure_pct: u8,
+    max_total_deposit: u64,
 ) -> Result<()> {
     // Validate agent mode
     let agent_mode = AgentMode::from_u8(mode)?;
@@ -42,6 +45,10 @@ pub fn handler(
         max_position_size_pct <= 100,
         VaultError::InvalidRiskLimit
     );
+    require!(
+        min_position_size_pct <= max_position_size_pct,
+        VaultError::InvalidRiskLimit
+    );
     require!(
         max_slippage_bps <= 10_000, // max 100%
         VaultError::InvalidRiskLimit
@@ -65,6 +72,7 @@ pub fn handler(
     vault.mode = agent_mode;
     vault.risk_limits = RiskLimits {
         max_position_size_pct,
+        min_position_size_pct,
         max_slippage_bps,
         max_daily_loss_pct,
         min_sol_reserve,
@@ -74,7 +82,31 @@ pub fn handler(
     vault.last_action_at = clock.unix_timestamp;
     vault.bump = ctx.bumps.vault;
     vault.in_session_amount = 0;
-    vault._padding = [0u8; 24];
+    vault.session_id = 0;
+    vault.sessions = [SessionSlot::default(); MAX_OPEN_SESSIONS];
+    vault.total_realized_pnl = 0;
+    vault.max_total_deposit = max_total_deposit;
+    vault.agents = [AgentRole::default(); MAX_AGENTS];
+    vault.agents[0] = AgentRole {
+        authority: agent_authority,
+        roles: ROLE_ALL,
+    };
+    vault.pending_owner = Pubkey::default();
+    vault.agent_nonce = 0;
+    vault.withdraw_cooldown_secs = 0;
+    vault.last_withdraw_at = 0;
+    vault.agent_withdraw_cooldown = false;
+    vault.cosigners = [Pubkey::default(); MAX_COSIGNERS];
+    vault.cosigner_threshold = 0;
+    vault.daily_loss = DailyLossWindow::default();
+    vault.min_reserve_usd = 0;
+    vault.min_deposit = 0;
+    vault.min_withdraw = 0;
+    vault.mint_risk_limits = [MintRiskLimit::default(); MAX_MINT_RISK_LIMITS];
+    vault.heartbeat_timeout_secs = 0;
+    vault.last_heartbeat_at = 0;
+    vault.expired_sessions = [SessionSlot::default(); MAX_OPEN_SESSIONS];
+    vault.version = VAULT_VERSION;
 
     msg!(
         "Vault initialized for owner {} with mode {:?}",
@@ -82,5 +114,20 @@ pub fn handler(
         mode
     );
 
+    emit!(InitializedEvent {
+        owner: vault.owner,
+        vault: vault.key(),
+        agent_authority,
+        mode,
+        max_position_size_pct,
+        min_position_size_pct,
+        max_slippage_bps,
+        max_daily_loss_pct,
+        min_sol_reserve,
+        max_protocol_exposure_pct,
+        max_total_deposit,
+        timestamp: clock.unix_timestamp,
+    });
+
     Ok(())
 }
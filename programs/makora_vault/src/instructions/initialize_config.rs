@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+use crate::state::{GlobalConfig, MAX_FEE_BPS};
+use crate::errors::VaultError;
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    /// The governance authority creating the config (pays for account creation).
+    /// Must be the program's upgrade authority, so the config cannot be
+    /// claimed by whoever calls first.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// This program
+    #[account(
+        constraint = program.programdata_address()? == Some(program_data.key()) @ VaultError::Unauthorized,
+    )]
+    pub program: Program<'info, crate::program::MakoraVault>,
+
+    /// The program's data account, holding its upgrade authority
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(authority.key()) @ VaultError::Unauthorized,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    /// The global config PDA to initialize
+    /// Seeds: ["config"]
+    #[account(
+        init,
+        payer = authority,
+        space = GlobalConfig::SIZE,
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(
+    ctx: Context<InitializeConfig>,
+    fee_bps: u16,
+    fee_collector: Pubkey,
+) -> Result<()> {
+    require!(fee_bps <= MAX_FEE_BPS, VaultError::FeeTooHigh);
+
+    let config = &mut ctx.accounts.config;
+    config.authority = ctx.accounts.authority.key();
+    config.fee_bps = fee_bps;
+    config.fee_collector = fee_collector;
+    config.bump = ctx.bumps.config;
+
+    msg!(
+        "Global config initialized by {} with fee {} bps to {}",
+        config.authority,
+        fee_bps,
+        fee_collector
+    );
+
+    Ok(())
+}
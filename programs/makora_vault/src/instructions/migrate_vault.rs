@@ -0,0 +1,251 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_lang::Discriminator;
+
+use crate::errors::VaultError;
+use crate::state::{
+    Vault, AgentMode, RiskLimits, SessionSlot, AgentRole, DailyLossWindow, MintRiskLimit,
+    SwapFloor, MAX_OPEN_SESSIONS, MAX_AGENTS, MAX_COSIGNERS, MAX_MINT_RISK_LIMITS,
+    MAX_SWAP_FLOORS, ROLE_ALL, VAULT_VERSION,
+};
+
+/// Serialized length (with discriminator) of the original vault layout.
+/// Those accounts were allocated at least this large.
+pub const VAULT_V0_LEN: usize = 151;
+
+/// Risk limits as laid out before min_position_size_pct was added
+#[derive(AnchorSerialize, AnchorDeserialize)]
+struct RiskLimitsV0 {
+    max_position_size_pct: u8,
+    max_slippage_bps: u16,
+    max_daily_loss_pct: u8,
+    min_sol_reserve: u64,
+    max_protocol_exposure_pct: u8,
+}
+
+/// Vault fields in the original layout, after the discriminator
+#[derive(AnchorSerialize, AnchorDeserialize)]
+struct VaultV0 {
+    owner: Pubkey,
+    agent_authority: Pubkey,
+    total_deposited: u64,
+    total_withdrawn: u64,
+    mode: AgentMode,
+    risk_limits: RiskLimitsV0,
+    created_at: i64,
+    last_action_at: i64,
+    bump: u8,
+    in_session_amount: u64,
+    _padding: [u8; 24],
+}
+
+#[derive(Accounts)]
+pub struct MigrateVault<'info> {
+    /// The vault owner; pays the rent for the larger account
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: still in the original layout, so it cannot be loaded as a
+    /// Vault. The discriminator and stored owner are checked in the handler.
+    #[account(
+        mut,
+        seeds = [b"vault", owner.key().as_ref()],
+        bump,
+        owner = crate::ID @ VaultError::UnsupportedVaultLayout,
+    )]
+    pub vault: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Grow a vault created with the original layout to the current one.
+///
+/// The account is reallocated to Vault::SIZE with the owner covering the
+/// extra rent. Existing fields are carried over, min_position_size_pct is
+/// 0, agent_authority is registered with all roles as initialize does,
+/// and everything added since starts zeroed. SOL recorded as in session
+/// has no session slot to return through; the owner can reconcile it
+/// with emergency_reset_sessions.
+pub fn handler(ctx: Context<MigrateVault>) -> Result<()> {
+    let vault_info = ctx.accounts.vault.to_account_info();
+    require!(
+        vault_info.data_len() < Vault::SIZE,
+        VaultError::VaultAlreadyMigrated
+    );
+
+    let legacy = read_v0(&vault_info.try_borrow_data()?)?;
+    require_keys_eq!(
+        legacy.owner,
+        ctx.accounts.owner.key(),
+        VaultError::Unauthorized
+    );
+
+    let rent_due = Rent::get()?
+        .minimum_balance(Vault::SIZE)
+        .saturating_sub(vault_info.lamports());
+    if rent_due > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.owner.to_account_info(),
+                    to: vault_info.clone(),
+                },
+            ),
+            rent_due,
+        )?;
+    }
+    vault_info.realloc(Vault::SIZE, true)?;
+
+    let mut vault = upgrade_v0(legacy);
+    vault.bump = ctx.bumps.vault;
+    vault.last_action_at = Clock::get()?.unix_timestamp;
+
+    let mut data = vault_info.try_borrow_mut_data()?;
+    let mut dst: &mut [u8] = &mut data;
+    vault.try_serialize(&mut dst)?;
+
+    msg!(
+        "Vault {} migrated to layout v{} ({} bytes)",
+        vault_info.key(),
+        VAULT_VERSION,
+        Vault::SIZE
+    );
+    Ok(())
+}
+
+/// Parse an account in the original layout. Bytes past VAULT_V0_LEN
+/// were padding and are ignored.
+fn read_v0(data: &[u8]) -> Result<VaultV0> {
+    require!(
+        data.len() >= VAULT_V0_LEN && data[..8] == Vault::DISCRIMINATOR,
+        VaultError::UnsupportedVaultLayout
+    );
+    VaultV0::deserialize(&mut &data[8..])
+        .map_err(|_| error!(VaultError::UnsupportedVaultLayout))
+}
+
+fn upgrade_v0(legacy: VaultV0) -> Vault {
+    let mut agents = [AgentRole::default(); MAX_AGENTS];
+    agents[0] = AgentRole {
+        authority: legacy.agent_authority,
+        roles: ROLE_ALL,
+    };
+
+    Vault {
+        owner: legacy.owner,
+        agent_authority: legacy.agent_authority,
+        total_deposited: legacy.total_deposited,
+        total_withdrawn: legacy.total_withdrawn,
+        mode: legacy.mode,
+        risk_limits: RiskLimits {
+            max_position_size_pct: legacy.risk_limits.max_position_size_pct,
+            min_position_size_pct: 0,
+            max_slippage_bps: legacy.risk_limits.max_slippage_bps,
+            max_daily_loss_pct: legacy.risk_limits.max_daily_loss_pct,
+            min_sol_reserve: legacy.risk_limits.min_sol_reserve,
+            max_protocol_exposure_pct: legacy.risk_limits.max_protocol_exposure_pct,
+        },
+        created_at: legacy.created_at,
+        last_action_at: legacy.last_action_at,
+        bump: legacy.bump,
+        in_session_amount: legacy.in_session_amount,
+        session_id: 0,
+        sessions: [SessionSlot::default(); MAX_OPEN_SESSIONS],
+        total_realized_pnl: 0,
+        max_total_deposit: 0,
+        agents,
+        pending_owner: Pubkey::default(),
+        agent_nonce: 0,
+        withdraw_cooldown_secs: 0,
+        last_withdraw_at: 0,
+        agent_withdraw_cooldown: false,
+        cosigners: [Pubkey::default(); MAX_COSIGNERS],
+        cosigner_threshold: 0,
+        daily_loss: DailyLossWindow::default(),
+        min_reserve_usd: 0,
+        min_deposit: 0,
+        min_withdraw: 0,
+        mint_risk_limits: [MintRiskLimit::default(); MAX_MINT_RISK_LIMITS],
+        heartbeat_timeout_secs: 0,
+        last_heartbeat_at: 0,
+        swap_floors: [SwapFloor::default(); MAX_SWAP_FLOORS],
+        expired_sessions: [SessionSlot::default(); MAX_OPEN_SESSIONS],
+        version: VAULT_VERSION,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn legacy_account(len: usize) -> Vec<u8> {
+        let legacy = VaultV0 {
+            owner: Pubkey::new_unique(),
+            agent_authority: Pubkey::new_unique(),
+            total_deposited: 5_000,
+            total_withdrawn: 1_000,
+            mode: AgentMode::Auto,
+            risk_limits: RiskLimitsV0 {
+                max_position_size_pct: 50,
+                max_slippage_bps: 100,
+                max_daily_loss_pct: 10,
+                min_sol_reserve: 10_000_000,
+                max_protocol_exposure_pct: 40,
+            },
+            created_at: 1_700_000_000,
+            last_action_at: 1_700_000_100,
+            bump: 254,
+            in_session_amount: 300,
+            _padding: [0u8; 24],
+        };
+        let mut data = Vault::DISCRIMINATOR.to_vec();
+        legacy.serialize(&mut data).unwrap();
+        assert_eq!(data.len(), VAULT_V0_LEN);
+        data.resize(len, 0);
+        data
+    }
+
+    #[test]
+    fn upgrades_a_160_byte_vault() {
+        let data = legacy_account(160);
+        let legacy = read_v0(&data).unwrap();
+        let owner = legacy.owner;
+        let agent = legacy.agent_authority;
+
+        let mut migrated = vec![0u8; Vault::SIZE];
+        let mut dst: &mut [u8] = &mut migrated;
+        upgrade_v0(legacy).try_serialize(&mut dst).unwrap();
+        assert!(dst.is_empty());
+
+        let vault = Vault::try_deserialize(&mut &migrated[..]).unwrap();
+        assert_eq!(vault.owner, owner);
+        assert_eq!(vault.current_balance(), 5_000 - 1_000 - 300);
+        assert!(vault.mode == AgentMode::Auto);
+        assert_eq!(vault.risk_limits.max_position_size_pct, 50);
+        assert_eq!(vault.risk_limits.min_position_size_pct, 0);
+        assert_eq!(vault.risk_limits.max_slippage_bps, 100);
+        assert_eq!(vault.risk_limits.max_daily_loss_pct, 10);
+        assert_eq!(vault.risk_limits.min_sol_reserve, 10_000_000);
+        assert_eq!(vault.risk_limits.max_protocol_exposure_pct, 40);
+        assert_eq!(vault.created_at, 1_700_000_000);
+        assert_eq!(vault.bump, 254);
+        assert_eq!(vault.agents[0].authority, agent);
+        assert_eq!(vault.agents[0].roles, ROLE_ALL);
+        assert!(vault.agents[1].is_empty());
+        assert!(vault.sessions.iter().all(|s| !s.is_open()));
+        assert_eq!(vault.agent_nonce, 0);
+        assert_eq!(vault.cosigner_threshold, 0);
+        assert_eq!(vault.version, VAULT_VERSION);
+    }
+
+    #[test]
+    fn rejects_other_accounts() {
+        let mut data = legacy_account(160);
+        data[0] ^= 1;
+        assert!(read_v0(&data).is_err());
+
+        let data = legacy_account(VAULT_V0_LEN);
+        assert!(read_v0(&data[..VAULT_V0_LEN - 1]).is_err());
+    }
+}
@@ -1,13 +1,65 @@
 pub mod initialize;
 pub mod deposit;
 pub mod withdraw;
+pub mod withdraw_all;
 pub mod agent_withdraw;
 pub mod agent_deposit;
 pub mod set_mode;
+pub mod validate_swap_params;
+pub mod initialize_config;
+pub mod update_config;
+pub mod get_vault_status;
+pub mod close_token_account;
+pub mod emergency_reset_sessions;
+pub mod set_agent_roles;
+pub mod propose_owner;
+pub mod accept_owner;
+pub mod deposit_many;
+pub mod ensure_vault;
+pub mod set_withdraw_cooldown;
+pub mod withdraw_token;
+pub mod get_aggregate_risk;
+pub mod expire_session;
+pub mod set_cosigners;
+pub mod agent_swap;
+pub mod set_usd_reserve;
+pub mod set_min_amounts;
+pub mod set_mint_risk_limit;
+pub mod preview_withdraw;
+pub mod heartbeat;
+pub mod set_heartbeat_timeout;
+pub mod set_swap_floor;
+pub mod migrate_vault;
 
 pub use initialize::*;
 pub use deposit::*;
 pub use withdraw::*;
+pub use withdraw_all::*;
 pub use agent_withdraw::*;
 pub use agent_deposit::*;
 pub use set_mode::*;
+pub use validate_swap_params::*;
+pub use initialize_config::*;
+pub use update_config::*;
+pub use get_vault_status::*;
+pub use close_token_account::*;
+pub use emergency_reset_sessions::*;
+pub use set_agent_roles::*;
+pub use propose_owner::*;
+pub use accept_owner::*;
+pub use deposit_many::*;
+pub use ensure_vault::*;
+pub use set_withdraw_cooldown::*;
+pub use withdraw_token::*;
+pub use get_aggregate_risk::*;
+pub use expire_session::*;
+pub use set_cosigners::*;
+pub use agent_swap::*;
+pub use set_usd_reserve::*;
+pub use set_min_amounts::*;
+pub use set_mint_risk_limit::*;
+pub use preview_withdraw::*;
+pub use heartbeat::*;
+pub use set_heartbeat_timeout::*;
+pub use set_swap_floor::*;
+pub use migrate_vault::*;
@@ -0,0 +1,76 @@
+use anchor_lang::prelude::*;
+use crate::state::{Vault, GlobalConfig};
+use crate::errors::VaultError;
+use crate::instructions::withdraw::check_withdraw;
+use crate::utils::balance_after_debit;
+
+#[derive(Accounts)]
+pub struct PreviewWithdraw<'info> {
+    /// The vault owner the withdrawal would be made by
+    pub owner: Signer<'info>,
+
+    /// The vault PDA to preview the withdrawal from (read-only)
+    #[account(
+        seeds = [b"vault", owner.key().as_ref()],
+        bump = vault.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// Global config whose protocol fee the withdrawal would be charged
+    /// (none without it)
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Option<Account<'info, GlobalConfig>>,
+
+    /// The fee collector the withdrawal would pay.
+    /// CHECK: Validated against config.fee_collector in check_withdraw.
+    pub fee_collector: Option<UncheckedAccount<'info>>,
+
+    /// Optional Pyth SOL/USD price update, as for withdraw
+    /// CHECK: Owner, discriminator and feed are checked in oracle.rs
+    pub price_update: Option<UncheckedAccount<'info>>,
+}
+
+/// Outcome of a withdrawal, returned via return data.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct WithdrawPreview {
+    /// Lamports leaving the vault
+    pub amount: u64,
+    /// Protocol fee taken out of amount
+    pub fee: u64,
+    /// Lamports the owner would receive
+    pub net_amount: u64,
+    /// current_balance() after the withdrawal
+    pub remaining_balance: u64,
+}
+
+/// Preview an owner withdrawal of `amount` without moving lamports.
+/// Runs the same checks as withdraw, including the rent-exemption check on
+/// the transfer itself, so it fails with the same error the withdraw would. Co-signers go in remaining accounts, as for withdraw.
+pub fn handler(ctx: Context<PreviewWithdraw>, amount: u64) -> Result<WithdrawPreview> {
+    let vault = &ctx.accounts.vault;
+    let fee = check_withdraw(
+        vault,
+        ctx.accounts.config.as_deref(),
+        &ctx.accounts.fee_collector,
+        ctx.accounts.price_update.as_deref(),
+        ctx.remaining_accounts,
+        amount,
+        Clock::get()?.unix_timestamp,
+    )?;
+    balance_after_debit(
+        vault.to_account_info().lamports(),
+        amount,
+        Rent::get()?.minimum_balance(Vault::SIZE),
+    )?;
+
+    Ok(WithdrawPreview {
+        amount,
+        fee,
+        net_amount: amount.saturating_sub(fee),
+        remaining_balance: vault.current_balance().saturating_sub(amount),
+    })
+}
@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+use crate::state::Vault;
+use crate::errors::VaultError;
+
+#[derive(Accounts)]
+pub struct ProposeOwner<'info> {
+    /// Only the current owner can propose a transfer
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", owner.key().as_ref()],
+        bump = vault.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: Account<'info, Vault>,
+}
+
+/// First step of an ownership transfer: record `new_owner` as pending.
+/// Nothing moves until the new owner signs `accept_owner`.
+pub fn handler(ctx: Context<ProposeOwner>, new_owner: Pubkey) -> Result<()> {
+    require!(new_owner != ctx.accounts.owner.key(), VaultError::Unauthorized);
+
+    let vault = &mut ctx.accounts.vault;
+    vault.check_cosigners(ctx.remaining_accounts)?;
+    vault.pending_owner = new_owner;
+    vault.last_action_at = Clock::get()?.unix_timestamp;
+
+    msg!("Vault {} ownership proposed to {}", vault.key(), new_owner);
+
+    Ok(())
+}
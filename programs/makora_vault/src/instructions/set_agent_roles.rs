@@ -0,0 +1,55 @@
+use anchor_lang::prelude::*;
+use crate::state::{Vault, AgentRole, ROLE_ALL};
+use crate::errors::VaultError;
+
+#[derive(Accounts)]
+pub struct SetAgentRoles<'info> {
+    /// Only the vault owner can manage agents
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", owner.key().as_ref()],
+        bump = vault.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: Account<'info, Vault>,
+}
+
+/// Grant `roles` (ROLE_* bitmask) to `agent`, registering it if needed.
+/// `roles == 0` removes the agent.
+pub fn handler(ctx: Context<SetAgentRoles>, agent: Pubkey, roles: u8) -> Result<()> {
+    require!(roles & !ROLE_ALL == 0, VaultError::InvalidRoles);
+    require!(agent != Pubkey::default(), VaultError::InvalidRoles);
+
+    let vault = &mut ctx.accounts.vault;
+    vault.check_cosigners(ctx.remaining_accounts)?;
+
+    if let Some(entry) = vault
+        .agents
+        .iter_mut()
+        .find(|a| !a.is_empty() && a.authority == agent)
+    {
+        if roles == 0 {
+            *entry = AgentRole::default();
+        } else {
+            entry.roles = roles;
+        }
+    } else if roles != 0 {
+        let slot = vault
+            .agents
+            .iter_mut()
+            .find(|a| a.is_empty())
+            .ok_or(VaultError::TooManyAgents)?;
+        *slot = AgentRole {
+            authority: agent,
+            roles,
+        };
+    }
+
+    vault.last_action_at = Clock::get()?.unix_timestamp;
+
+    msg!("Agent {} roles set to {:#05b}", agent, roles);
+
+    Ok(())
+}
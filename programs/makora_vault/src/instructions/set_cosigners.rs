@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::VaultError;
+use crate::state::vault::{Vault, MAX_COSIGNERS};
+
+#[derive(Accounts)]
+pub struct SetCosigners<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", owner.key().as_ref()],
+        bump = vault.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub owner: Signer<'info>,
+}
+
+/// Replace the vault's co-signer set and threshold (threshold 0 turns
+/// multisig off). Once multisig is on, changing it needs the current
+/// threshold of co-signers too, passed as signer remaining accounts.
+pub fn handler(
+    ctx: Context<SetCosigners>,
+    cosigners: Vec<Pubkey>,
+    threshold: u8,
+) -> Result<()> {
+    require!(cosigners.len() <= MAX_COSIGNERS, VaultError::InvalidCosigners);
+    require!(
+        threshold as usize <= cosigners.len(),
+        VaultError::InvalidCosigners
+    );
+    for (i, c) in cosigners.iter().enumerate() {
+        require!(*c != Pubkey::default(), VaultError::InvalidCosigners);
+        require!(!cosigners[..i].contains(c), VaultError::InvalidCosigners);
+    }
+
+    let vault = &mut ctx.accounts.vault;
+    vault.check_cosigners(ctx.remaining_accounts)?;
+
+    vault.cosigners = [Pubkey::default(); MAX_COSIGNERS];
+    vault.cosigners[..cosigners.len()].copy_from_slice(&cosigners);
+    vault.cosigner_threshold = threshold;
+    vault.last_action_at = Clock::get()?.unix_timestamp;
+
+    msg!(
+        "Co-signers set: {} of {}",
+        threshold,
+        cosigners.len()
+    );
+    Ok(())
+}
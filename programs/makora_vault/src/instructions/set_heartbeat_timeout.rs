@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::VaultError;
+use crate::state::vault::Vault;
+
+/// Require an agent heartbeat within `timeout_secs` before the agent can
+/// move funds (0 disables the requirement).
+pub fn handler(ctx: Context<SetHeartbeatTimeout>, timeout_secs: u32) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    vault.check_cosigners(ctx.remaining_accounts)?;
+    vault.heartbeat_timeout_secs = timeout_secs;
+    vault.last_action_at = Clock::get()?.unix_timestamp;
+
+    msg!("Agent heartbeat timeout set to {}s", timeout_secs);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetHeartbeatTimeout<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", owner.key().as_ref()],
+        bump = vault.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub owner: Signer<'info>,
+}
@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::VaultError;
+use crate::state::vault::Vault;
+
+/// Set the smallest deposit and withdrawal the owner may make, in
+/// lamports (0 disables either). Agent session flows are not affected.
+pub fn handler(ctx: Context<SetMinAmounts>, min_deposit: u64, min_withdraw: u64) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    vault.check_cosigners(ctx.remaining_accounts)?;
+    vault.min_deposit = min_deposit;
+    vault.min_withdraw = min_withdraw;
+    vault.last_action_at = Clock::get()?.unix_timestamp;
+
+    msg!(
+        "Minimums set — deposit: {} lamports, withdraw: {} lamports",
+        min_deposit,
+        min_withdraw
+    );
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetMinAmounts<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", owner.key().as_ref()],
+        bump = vault.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub owner: Signer<'info>,
+}
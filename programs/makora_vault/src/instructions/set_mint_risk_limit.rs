@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+use crate::state::{Vault, MintRiskLimit};
+use crate::errors::VaultError;
+
+#[derive(Accounts)]
+pub struct SetMintRiskLimit<'info> {
+    /// Only the vault owner can change risk limits
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", owner.key().as_ref()],
+        bump = vault.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: Account<'info, Vault>,
+}
+
+/// Cap agent positions in `mint` at `max_position_size_pct` (1-100),
+/// overriding the global max position size for that mint.
+/// `max_position_size_pct == 0` removes the cap.
+pub fn handler(
+    ctx: Context<SetMintRiskLimit>,
+    mint: Pubkey,
+    max_position_size_pct: u8,
+) -> Result<()> {
+    require!(max_position_size_pct <= 100, VaultError::InvalidRiskLimit);
+    require!(mint != Pubkey::default(), VaultError::InvalidRiskLimit);
+
+    let vault = &mut ctx.accounts.vault;
+    vault.check_cosigners(ctx.remaining_accounts)?;
+
+    if let Some(entry) = vault
+        .mint_risk_limits
+        .iter_mut()
+        .find(|l| !l.is_empty() && l.mint == mint)
+    {
+        if max_position_size_pct == 0 {
+            *entry = MintRiskLimit::default();
+        } else {
+            entry.max_position_size_pct = max_position_size_pct;
+        }
+    } else if max_position_size_pct != 0 {
+        let slot = vault
+            .mint_risk_limits
+            .iter_mut()
+            .find(|l| l.is_empty())
+            .ok_or(VaultError::TooManyMintRiskLimits)?;
+        *slot = MintRiskLimit {
+            mint,
+            max_position_size_pct,
+        };
+    }
+
+    vault.last_action_at = Clock::get()?.unix_timestamp;
+
+    msg!(
+        "Max position size for mint {} set to {}%",
+        mint,
+        max_position_size_pct
+    );
+
+    Ok(())
+}
@@ -1,15 +1,34 @@
 use anchor_lang::prelude::*;
 
 use crate::errors::VaultError;
+use crate::events::VaultSnapshotEvent;
 use crate::state::vault::{AgentMode, Vault};
 
 /// Set the vault's agent operating mode.
 /// Only the vault owner can change modes.
 pub fn handler(ctx: Context<SetMode>, mode: u8) -> Result<()> {
     let vault = &mut ctx.accounts.vault;
+    vault.check_cosigners(ctx.remaining_accounts)?;
     let new_mode = AgentMode::from_u8(mode)?;
+    let old_mode = vault.mode;
     vault.mode = new_mode;
-    vault.last_action_at = Clock::get()?.unix_timestamp;
+    let now = Clock::get()?.unix_timestamp;
+    vault.last_action_at = now;
+
+    emit!(VaultSnapshotEvent {
+        vault: vault.key(),
+        owner: vault.owner,
+        old_mode: old_mode as u8,
+        new_mode: new_mode as u8,
+        risk_limits: vault.risk_limits,
+        total_deposited: vault.total_deposited,
+        total_withdrawn: vault.total_withdrawn,
+        current_balance: vault.current_balance(),
+        in_session_amount: vault.in_session_amount,
+        total_realized_pnl: vault.total_realized_pnl,
+        timestamp: now,
+    });
+
     Ok(())
 }
 
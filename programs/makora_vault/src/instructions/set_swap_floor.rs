@@ -0,0 +1,68 @@
+use anchor_lang::prelude::*;
+use crate::state::{Vault, SwapFloor};
+use crate::errors::VaultError;
+
+#[derive(Accounts)]
+pub struct SetSwapFloor<'info> {
+    /// Only the vault owner can set swap floors
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", owner.key().as_ref()],
+        bump = vault.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: Account<'info, Vault>,
+}
+
+/// Set the minimum price agent swaps from `input_mint` to `output_mint`
+/// must clear, in output base units per input base unit scaled by
+/// SWAP_RATE_SCALE. `min_rate == 0` removes the floor, which blocks agent
+/// swaps on that pair.
+pub fn handler(
+    ctx: Context<SetSwapFloor>,
+    input_mint: Pubkey,
+    output_mint: Pubkey,
+    min_rate: u64,
+) -> Result<()> {
+    require!(
+        input_mint != Pubkey::default() && input_mint != output_mint,
+        VaultError::InvalidSwapRoute
+    );
+
+    let vault = &mut ctx.accounts.vault;
+    vault.check_cosigners(ctx.remaining_accounts)?;
+
+    if let Some(entry) = vault.swap_floors.iter_mut().find(|f| {
+        !f.is_empty() && f.input_mint == input_mint && f.output_mint == output_mint
+    }) {
+        if min_rate == 0 {
+            *entry = SwapFloor::default();
+        } else {
+            entry.min_rate = min_rate;
+        }
+    } else if min_rate != 0 {
+        let slot = vault
+            .swap_floors
+            .iter_mut()
+            .find(|f| f.is_empty())
+            .ok_or(VaultError::TooManySwapFloors)?;
+        *slot = SwapFloor {
+            input_mint,
+            output_mint,
+            min_rate,
+        };
+    }
+
+    vault.last_action_at = Clock::get()?.unix_timestamp;
+
+    msg!(
+        "Swap floor for {} -> {} set to {}",
+        input_mint,
+        output_mint,
+        min_rate
+    );
+
+    Ok(())
+}
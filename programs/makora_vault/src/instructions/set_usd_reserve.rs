@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::VaultError;
+use crate::state::vault::Vault;
+
+/// Set the reserve in USD with 6 decimals (0 disables it). Withdrawals
+/// that pass a Pyth SOL/USD price update keep this much SOL's worth in
+/// the vault instead of `min_sol_reserve`.
+pub fn handler(ctx: Context<SetUsdReserve>, min_reserve_usd: u64) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    vault.check_cosigners(ctx.remaining_accounts)?;
+    vault.min_reserve_usd = min_reserve_usd;
+    vault.last_action_at = Clock::get()?.unix_timestamp;
+
+    msg!("USD reserve set to {} micro-USD", min_reserve_usd);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetUsdReserve<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", owner.key().as_ref()],
+        bump = vault.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub owner: Signer<'info>,
+}
@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::VaultError;
+use crate::state::vault::Vault;
+
+/// Set the minimum time between withdrawals (0 disables the cooldown).
+/// With `applies_to_agent`, agent withdraws share the same cooldown.
+pub fn handler(
+    ctx: Context<SetWithdrawCooldown>,
+    cooldown_secs: u32,
+    applies_to_agent: bool,
+) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    vault.check_cosigners(ctx.remaining_accounts)?;
+    vault.withdraw_cooldown_secs = cooldown_secs;
+    vault.agent_withdraw_cooldown = applies_to_agent;
+    vault.last_action_at = Clock::get()?.unix_timestamp;
+
+    msg!(
+        "Withdraw cooldown set to {}s (agent: {})",
+        cooldown_secs,
+        applies_to_agent
+    );
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetWithdrawCooldown<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", owner.key().as_ref()],
+        bump = vault.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub owner: Signer<'info>,
+}
@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+use crate::state::{GlobalConfig, MAX_FEE_BPS};
+use crate::errors::VaultError;
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    /// The governance authority
+    pub authority: Signer<'info>,
+
+    /// The global config PDA
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = authority @ VaultError::Unauthorized,
+    )]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+/// Update the protocol fee settings.
+/// Only the governance authority can change them.
+pub fn handler(
+    ctx: Context<UpdateConfig>,
+    fee_bps: u16,
+    fee_collector: Pubkey,
+) -> Result<()> {
+    require!(fee_bps <= MAX_FEE_BPS, VaultError::FeeTooHigh);
+
+    let config = &mut ctx.accounts.config;
+    config.fee_bps = fee_bps;
+    config.fee_collector = fee_collector;
+
+    msg!(
+        "Global config updated: fee {} bps to {}",
+        fee_bps,
+        fee_collector
+    );
+
+    Ok(())
+}
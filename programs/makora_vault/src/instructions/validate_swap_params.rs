@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+use crate::state::{Vault, ROLE_SWAP};
+use crate::errors::VaultError;
+
+#[derive(Accounts)]
+pub struct ValidateSwapParams<'info> {
+    /// An agent holding the swap role, about to execute the swap
+    pub agent: Signer<'info>,
+
+    /// The vault whose risk limits gate the swap.
+    /// Read-only: this instruction never moves funds.
+    #[account(
+        seeds = [b"vault", vault.owner.as_ref()],
+        bump = vault.bump,
+        constraint = vault.agent_roles(agent.key).is_some() @ VaultError::UnauthorizedAgent,
+        constraint = vault.has_agent_role(agent.key, ROLE_SWAP) @ VaultError::MissingRole,
+    )]
+    pub vault: Account<'info, Vault>,
+}
+
+/// Implied slippage of a quote against the expected output, in basis points.
+/// Rounds up so that any shortfall past the limit is never truncated away.
+/// A quote at or above the expected output has zero slippage.
+pub fn implied_slippage_bps(expected_out: u64, quoted_out: u64) -> Result<u64> {
+    require!(expected_out > 0, VaultError::InvalidSwapQuote);
+
+    let shortfall = expected_out.saturating_sub(quoted_out) as u128;
+    let expected = expected_out as u128;
+    let bps = shortfall
+        .checked_mul(10_000)
+        .ok_or(VaultError::ArithmeticOverflow)?
+        .checked_add(expected - 1)
+        .ok_or(VaultError::ArithmeticOverflow)?
+        / expected;
+
+    // shortfall <= expected, so bps <= 10_000
+    Ok(bps as u64)
+}
+
+/// Validate an intended swap against the vault's max_slippage_bps.
+/// Meant to be called by the agent in the same transaction as agent_withdraw
+/// so the slippage check is recorded on-chain alongside the fund movement.
+pub fn handler(ctx: Context<ValidateSwapParams>, expected_out: u64, quoted_out: u64) -> Result<()> {
+    let vault = &ctx.accounts.vault;
+
+    let slippage_bps = implied_slippage_bps(expected_out, quoted_out)?;
+    let max_slippage_bps = vault.risk_limits.max_slippage_bps as u64;
+
+    require!(
+        slippage_bps <= max_slippage_bps,
+        VaultError::SlippageExceeded
+    );
+
+    msg!(
+        "Swap params validated: expected {} quoted {} slippage {} bps (max {})",
+        expected_out,
+        quoted_out,
+        slippage_bps,
+        max_slippage_bps
+    );
+
+    Ok(())
+}
@@ -1,6 +1,9 @@
 use anchor_lang::prelude::*;
-use crate::state::Vault;
+use crate::state::{Vault, GlobalConfig, withdrawal_fee};
 use crate::errors::VaultError;
+use crate::events::{WithdrawEvent, SOL_DECIMALS, SOL_MINT};
+use crate::oracle::min_reserve_lamports;
+use crate::utils::transfer_from_vault;
 
 #[derive(Accounts)]
 pub struct Withdraw<'info> {
@@ -19,15 +22,50 @@ pub struct Withdraw<'info> {
     )]
     pub vault: Account<'info, Vault>,
 
+    /// Global config; its protocol fee is charged on the withdrawal.
+    /// Without it no fee is charged.
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Option<Account<'info, GlobalConfig>>,
+
+    /// Receives the protocol fee. Required when the config charges a fee.
+    /// CHECK: Validated against config.fee_collector in the handler.
+    #[account(mut)]
+    pub fee_collector: Option<UncheckedAccount<'info>>,
+
+    /// Optional Pyth SOL/USD price update; converts min_reserve_usd to
+    /// lamports when the vault has one set
+    /// CHECK: Owner, discriminator and feed are checked in oracle.rs
+    pub price_update: Option<UncheckedAccount<'info>>,
+
     /// System program (needed for lamport transfers via PDA)
     pub system_program: Program<'info, System>,
 }
 
-pub fn handler(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+/// Run every check an owner withdrawal of `amount` is subject to and
+/// return the protocol fee it would be charged. Shared with
+/// preview_withdraw so a preview fails exactly where the withdraw would.
+pub(crate) fn check_withdraw(
+    vault: &Vault,
+    config: Option<&GlobalConfig>,
+    fee_collector: &Option<UncheckedAccount>,
+    price_update: Option<&AccountInfo>,
+    remaining_accounts: &[AccountInfo],
+    amount: u64,
+    now: i64,
+) -> Result<u64> {
     // Validate amount
     require!(amount > 0, VaultError::ZeroWithdraw);
+    require!(amount >= vault.min_withdraw, VaultError::BelowMinimum);
+    vault.check_cosigners(remaining_accounts)?;
 
-    let vault = &mut ctx.accounts.vault;
+    // Protocol fee comes out of the withdrawn amount, so the reserve
+    // checks below still apply to the full amount leaving the vault
+    let fee = withdrawal_fee(config, fee_collector, amount)?;
+
+    vault.check_withdraw_cooldown(now)?;
 
     // Check that vault has sufficient balance
     let current_balance = vault.current_balance();
@@ -43,13 +81,14 @@ pub fn handler(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
         .ok_or(VaultError::ArithmeticOverflow)?;
 
     let min_rent = Rent::get()?.minimum_balance(Vault::SIZE);
-    let min_reserve = vault.risk_limits.min_sol_reserve;
+    let min_reserve = min_reserve_lamports(vault, price_update, now)?;
     let total_min = min_rent
         .checked_add(min_reserve)
         .ok_or(VaultError::ArithmeticOverflow)?;
 
     // Only enforce reserve if user is not withdrawing everything
-    // (full withdrawal = closing the vault effectively)
+    // (full withdrawal = closing the vault effectively). Rent exemption
+    // is still checked on every transfer out of the vault.
     if remaining_after > 0 {
         require!(
             remaining_after >= total_min,
@@ -57,14 +96,34 @@ pub fn handler(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
         );
     }
 
+    Ok(fee)
+}
+
+pub fn handler(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    let fee = check_withdraw(
+        &ctx.accounts.vault,
+        ctx.accounts.config.as_deref(),
+        &ctx.accounts.fee_collector,
+        ctx.accounts.price_update.as_deref(),
+        ctx.remaining_accounts,
+        amount,
+        clock.unix_timestamp,
+    )?;
+
+    let vault = &mut ctx.accounts.vault;
+    vault.enforce_withdraw_cooldown(clock.unix_timestamp)?;
+
     // Transfer SOL from vault PDA to owner
     // For PDA-owned lamports, we directly manipulate lamport balances
     // This is safe because the vault account is a PDA we control
     let vault_info = vault.to_account_info();
     let owner_info = ctx.accounts.owner.to_account_info();
 
-    **vault_info.try_borrow_mut_lamports()? -= amount;
-    **owner_info.try_borrow_mut_lamports()? += amount;
+    transfer_from_vault(&vault_info, &owner_info, amount - fee)?;
+    if let Some(fee_collector) = &ctx.accounts.fee_collector {
+        transfer_from_vault(&vault_info, fee_collector, fee)?;
+    }
 
     // Update vault state with checked arithmetic
     vault.total_withdrawn = vault
@@ -72,14 +131,25 @@ pub fn handler(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
         .checked_add(amount)
         .ok_or(VaultError::ArithmeticOverflow)?;
 
-    let clock = Clock::get()?;
     vault.last_action_at = clock.unix_timestamp;
 
     msg!(
-        "Withdrew {} lamports from vault. Total withdrawn: {}",
+        "Withdrew {} lamports from vault (fee: {}). Total withdrawn: {}",
         amount,
+        fee,
         vault.total_withdrawn
     );
 
+    emit!(WithdrawEvent {
+        vault: vault.key(),
+        owner: vault.owner,
+        amount,
+        fee,
+        mint: SOL_MINT,
+        decimals: SOL_DECIMALS,
+        total_withdrawn: vault.total_withdrawn,
+        timestamp: clock.unix_timestamp,
+    });
+
     Ok(())
 }
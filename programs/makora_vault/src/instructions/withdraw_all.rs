@@ -0,0 +1,85 @@
+use anchor_lang::prelude::*;
+use crate::state::{Vault, GlobalConfig, withdrawal_fee};
+use crate::errors::VaultError;
+use crate::events::{WithdrawEvent, SOL_DECIMALS, SOL_MINT};
+use crate::utils::transfer_from_vault;
+
+#[derive(Accounts)]
+pub struct WithdrawAll<'info> {
+    /// The vault owner draining the vault
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// The vault PDA to drain
+    #[account(
+        mut,
+        seeds = [b"vault", owner.key().as_ref()],
+        bump = vault.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// Global config; its protocol fee is charged on the withdrawal.
+    /// Without it no fee is charged.
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Option<Account<'info, GlobalConfig>>,
+
+    /// Receives the protocol fee. Required when the config charges a fee.
+    /// CHECK: Validated against config.fee_collector in the handler.
+    #[account(mut)]
+    pub fee_collector: Option<UncheckedAccount<'info>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Withdraw the vault's entire available balance to the owner, ignoring
+/// min_sol_reserve. SOL out in agent sessions is not touched. The
+/// withdraw cooldown still applies.
+pub fn handler(ctx: Context<WithdrawAll>) -> Result<()> {
+    let amount = ctx.accounts.vault.current_balance();
+    require!(amount > 0, VaultError::ZeroWithdraw);
+    ctx.accounts.vault.check_cosigners(ctx.remaining_accounts)?;
+
+    let fee = withdrawal_fee(ctx.accounts.config.as_deref(), &ctx.accounts.fee_collector, amount)?;
+
+    let vault = &mut ctx.accounts.vault;
+    let clock = Clock::get()?;
+    vault.enforce_withdraw_cooldown(clock.unix_timestamp)?;
+    let vault_info = vault.to_account_info();
+    let owner_info = ctx.accounts.owner.to_account_info();
+
+    transfer_from_vault(&vault_info, &owner_info, amount - fee)?;
+    if let Some(fee_collector) = &ctx.accounts.fee_collector {
+        transfer_from_vault(&vault_info, fee_collector, fee)?;
+    }
+
+    vault.total_withdrawn = vault
+        .total_withdrawn
+        .checked_add(amount)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+
+    vault.last_action_at = clock.unix_timestamp;
+
+    msg!(
+        "Withdrew all {} lamports from vault (fee: {}). Total withdrawn: {}",
+        amount,
+        fee,
+        vault.total_withdrawn
+    );
+
+    emit!(WithdrawEvent {
+        vault: vault.key(),
+        owner: vault.owner,
+        amount,
+        fee,
+        mint: SOL_MINT,
+        decimals: SOL_DECIMALS,
+        total_withdrawn: vault.total_withdrawn,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
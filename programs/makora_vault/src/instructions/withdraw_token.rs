@@ -0,0 +1,100 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+use crate::state::Vault;
+use crate::errors::VaultError;
+use crate::events::TokenWithdrawEvent;
+use crate::token_extensions::check_mint;
+
+#[derive(Accounts)]
+pub struct WithdrawToken<'info> {
+    /// The vault owner requesting the withdrawal
+    pub owner: Signer<'info>,
+
+    /// The vault PDA; authority over its token accounts
+    #[account(
+        seeds = [b"vault", owner.key().as_ref()],
+        bump = vault.bump,
+        has_one = owner @ VaultError::Unauthorized,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// Mint of the token being withdrawn (classic SPL or Token-2022)
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Token account owned by the vault
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = vault,
+        token::token_program = token_program,
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Receives the tokens
+    #[account(
+        mut,
+        token::mint = mint,
+        token::token_program = token_program,
+    )]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+
+    /// SPL Token or Token-2022, matching the mint's owner
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Withdraw SPL tokens held by the vault.
+///
+/// Always moves tokens with `transfer_checked` and the mint's decimals, so
+/// Token-2022 mints settle correctly. Mints with extensions the vault
+/// cannot handle are rejected; a transfer fee is reported on the event.
+pub fn handler(ctx: Context<WithdrawToken>, amount: u64) -> Result<()> {
+    require!(amount > 0, VaultError::ZeroWithdraw);
+    ctx.accounts.vault.check_cosigners(ctx.remaining_accounts)?;
+    require!(
+        amount <= ctx.accounts.vault_token_account.amount,
+        VaultError::InsufficientBalance
+    );
+
+    let mint = &ctx.accounts.mint;
+    let transfer_fee = check_mint(&mint.to_account_info(), amount)?;
+
+    let vault = &ctx.accounts.vault;
+    let owner_key = ctx.accounts.owner.key();
+    let seeds: &[&[u8]] = &[b"vault", owner_key.as_ref(), &[vault.bump]];
+
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.vault_token_account.to_account_info(),
+                mint: mint.to_account_info(),
+                to: ctx.accounts.destination.to_account_info(),
+                authority: vault.to_account_info(),
+            },
+            &[seeds],
+        ),
+        amount,
+        mint.decimals,
+    )?;
+
+    msg!(
+        "Withdrew {} of mint {} from vault (transfer fee: {})",
+        amount,
+        mint.key(),
+        transfer_fee
+    );
+
+    emit!(TokenWithdrawEvent {
+        vault: vault.key(),
+        owner: vault.owner,
+        amount,
+        transfer_fee,
+        mint: mint.key(),
+        decimals: mint.decimals,
+        token_program: ctx.accounts.token_program.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
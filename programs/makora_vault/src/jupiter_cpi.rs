@@ -0,0 +1,109 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_spl::token::spl_token::state::Account as SplTokenAccount;
+use anchor_lang::solana_program::program_pack::Pack;
+use crate::errors::VaultError;
+
+/// Jupiter aggregator v6 program id. agent_swap only routes through it.
+pub const JUPITER_PROGRAM_ID: Pubkey = pubkey!("JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4");
+
+/// Positions of the transfer authority and the source and destination
+/// token accounts in a route's accounts.
+pub const ROUTE_AUTHORITY_INDEX: usize = 0;
+pub const ROUTE_SOURCE_INDEX: usize = 1;
+pub const ROUTE_DESTINATION_INDEX: usize = 2;
+
+/// Check that a route only spends from `source` and pays into
+/// `destination` under the vault's authority.
+///
+/// The vault PDA signs the whole CPI, so the route must name it as its
+/// transfer authority, take `source` and `destination` as its user token
+/// accounts, and reference neither the vault nor any other token account
+/// the vault controls anywhere else.
+pub fn check_route(
+    route_accounts: &[AccountInfo],
+    vault: &Pubkey,
+    source: &Pubkey,
+    destination: &Pubkey,
+) -> Result<()> {
+    require!(
+        route_accounts.len() > ROUTE_DESTINATION_INDEX,
+        VaultError::InvalidSwapRoute
+    );
+    require_keys_eq!(
+        route_accounts[ROUTE_AUTHORITY_INDEX].key(),
+        *vault,
+        VaultError::InvalidSwapRoute
+    );
+    require_keys_eq!(
+        route_accounts[ROUTE_SOURCE_INDEX].key(),
+        *source,
+        VaultError::InvalidSwapRoute
+    );
+    require_keys_eq!(
+        route_accounts[ROUTE_DESTINATION_INDEX].key(),
+        *destination,
+        VaultError::InvalidSwapRoute
+    );
+
+    for account in &route_accounts[ROUTE_DESTINATION_INDEX + 1..] {
+        require!(
+            account.key != vault && !is_vault_token_account(account, vault),
+            VaultError::InvalidSwapRoute
+        );
+    }
+
+    Ok(())
+}
+
+/// True if `account` is a classic or Token-2022 token account whose
+/// authority is `vault`.
+pub(crate) fn is_vault_token_account(account: &AccountInfo, vault: &Pubkey) -> bool {
+    if *account.owner != anchor_spl::token::ID && *account.owner != anchor_spl::token_2022::ID {
+        return false;
+    }
+    let Ok(data) = account.try_borrow_data() else {
+        return false;
+    };
+    // Token-2022 accounts share the classic layout in their first bytes
+    data.len() >= SplTokenAccount::LEN
+        && SplTokenAccount::unpack_from_slice(&data[..SplTokenAccount::LEN])
+            .is_ok_and(|a| a.owner == *vault)
+}
+
+/// CPI into Jupiter with a client-built route.
+///
+/// `route_data` is the Jupiter instruction data and `route_accounts` its
+/// accounts in order, as returned by the Jupiter swap-instructions API.
+/// The vault PDA is the route's transfer authority, so its meta is marked
+/// signer and the call is signed with `vault_seeds`. Callers must run
+/// `check_route` on `route_accounts` first.
+pub fn swap<'info>(
+    jupiter_program: &AccountInfo<'info>,
+    vault: &Pubkey,
+    route_accounts: &[AccountInfo<'info>],
+    route_data: Vec<u8>,
+    vault_seeds: &[&[u8]],
+) -> Result<()> {
+    let accounts = route_accounts
+        .iter()
+        .map(|a| AccountMeta {
+            pubkey: a.key(),
+            is_signer: a.is_signer || a.key == vault,
+            is_writable: a.is_writable,
+        })
+        .collect();
+
+    let ix = Instruction {
+        program_id: JUPITER_PROGRAM_ID,
+        accounts,
+        data: route_data,
+    };
+
+    let mut infos = route_accounts.to_vec();
+    infos.push(jupiter_program.clone());
+    invoke_signed(&ix, &infos, &[vault_seeds])?;
+
+    Ok(())
+}
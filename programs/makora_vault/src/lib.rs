@@ -1,8 +1,14 @@
 use anchor_lang::prelude::*;
 
 pub mod errors;
+pub mod events;
 pub mod instructions;
+pub mod jupiter_cpi;
+pub mod oracle;
 pub mod state;
+pub mod strategy_cpi;
+pub mod token_extensions;
+pub mod utils;
 
 use instructions::*;
 
@@ -20,25 +26,38 @@ pub mod makora_vault {
         agent_authority: Pubkey,
         mode: u8,
         max_position_size_pct: u8,
+        min_position_size_pct: u8,
         max_slippage_bps: u16,
         max_daily_loss_pct: u8,
         min_sol_reserve: u64,
         max_protocol_exposure_pct: u8,
+        max_total_deposit: u64,
     ) -> Result<()> {
         instructions::initialize::handler(
             ctx,
             agent_authority,
             mode,
             max_position_size_pct,
+            min_position_size_pct,
             max_slippage_bps,
             max_daily_loss_pct,
             min_sol_reserve,
             max_protocol_exposure_pct,
+            max_total_deposit,
         )
     }
 
+    /// Check that no vault exists yet for `owner`.
+    /// Returns `AlreadyInitialized` instead of the generic error a second
+    /// `initialize` would hit.
+    pub fn ensure_vault(ctx: Context<EnsureVault>) -> Result<()> {
+        instructions::ensure_vault::handler(ctx)
+    }
+
     /// Deposit SOL into the vault.
-    /// Only the vault owner can deposit.
+    /// Only the vault owner can deposit, up to the vault's deposit cap.
+    /// If the owner's strategy account and audit trail are passed, the
+    /// deposit is also logged to the strategy audit trail.
     pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
         instructions::deposit::handler(ctx, amount)
     }
@@ -49,21 +68,251 @@ pub mod makora_vault {
         instructions::withdraw::handler(ctx, amount)
     }
 
+    /// Withdraw the vault's entire available balance to the owner,
+    /// ignoring the SOL reserve. Used to exit before closing the vault.
+    pub fn withdraw_all(ctx: Context<WithdrawAll>) -> Result<()> {
+        instructions::withdraw_all::handler(ctx)
+    }
+
     /// Agent withdraws SOL from the vault to a stealth session wallet.
-    /// Only callable by the vault's agent_authority when mode == Auto.
-    pub fn agent_withdraw(ctx: Context<AgentWithdraw>, amount: u64) -> Result<()> {
-        instructions::agent_withdraw::handler(ctx, amount)
+    /// Only callable by an agent with the withdraw role when mode == Auto.
+    /// Must carry the vault's current agent nonce. The session expires
+    /// after `max_duration_secs` (0 = 1 day).
+    pub fn agent_withdraw(
+        ctx: Context<AgentWithdraw>,
+        amount: u64,
+        nonce: u64,
+        max_duration_secs: u32,
+    ) -> Result<()> {
+        instructions::agent_withdraw::handler(ctx, amount, nonce, max_duration_secs)
     }
 
     /// Agent deposits SOL back into the vault from a session wallet.
-    /// Called during session sweep to return funds.
-    pub fn agent_deposit(ctx: Context<AgentDeposit>, amount: u64) -> Result<()> {
-        instructions::agent_deposit::handler(ctx, amount)
+    /// Called during session sweep to return funds against a specific
+    /// session, either partially or closing it out. Must carry the
+    /// vault's current agent nonce.
+    pub fn agent_deposit(
+        ctx: Context<AgentDeposit>,
+        amount: u64,
+        session_id: u64,
+        close_session: bool,
+        nonce: u64,
+    ) -> Result<()> {
+        instructions::agent_deposit::handler(ctx, amount, session_id, close_session, nonce)
+    }
+
+    /// Withdraw SPL tokens (classic or Token-2022) held by the vault.
+    /// Only the vault owner can withdraw. Mints with unsupported
+    /// Token-2022 extensions are rejected.
+    pub fn withdraw_token(ctx: Context<WithdrawToken>, amount: u64) -> Result<()> {
+        instructions::withdraw_token::handler(ctx, amount)
+    }
+
+    /// Set the minimum time between withdrawals (0 disables it), and
+    /// whether agent withdraws are subject to it. Owner only.
+    pub fn set_withdraw_cooldown(
+        ctx: Context<SetWithdrawCooldown>,
+        cooldown_secs: u32,
+        applies_to_agent: bool,
+    ) -> Result<()> {
+        instructions::set_withdraw_cooldown::handler(ctx, cooldown_secs, applies_to_agent)
+    }
+
+    /// Require an agent heartbeat within `timeout_secs` before agent
+    /// withdraws and swaps (0 disables it). Owner only.
+    pub fn set_heartbeat_timeout(
+        ctx: Context<SetHeartbeatTimeout>,
+        timeout_secs: u32,
+    ) -> Result<()> {
+        instructions::set_heartbeat_timeout::handler(ctx, timeout_secs)
+    }
+
+    /// Record that the agent is alive. Callable by any registered agent.
+    pub fn heartbeat(ctx: Context<Heartbeat>) -> Result<()> {
+        instructions::heartbeat::handler(ctx)
     }
 
     /// Set the vault's agent operating mode (Advisory or Auto).
-    /// Only the vault owner can change the mode.
+    /// Only the vault owner can change the mode. Emits a VaultSnapshotEvent
+    /// with the vault's risk limits and balances.
     pub fn set_mode(ctx: Context<SetMode>, mode: u8) -> Result<()> {
         instructions::set_mode::handler(ctx, mode)
     }
+
+    /// Validate a swap quote against the vault's max_slippage_bps.
+    /// Moves no funds; an agent with the swap role calls it alongside
+    /// agent_withdraw.
+    pub fn validate_swap_params(
+        ctx: Context<ValidateSwapParams>,
+        expected_out: u64,
+        quoted_out: u64,
+    ) -> Result<()> {
+        instructions::validate_swap_params::handler(ctx, expected_out, quoted_out)
+    }
+
+    /// Initialize the global config PDA holding the protocol fee.
+    /// Only the program's upgrade authority can call this; it becomes the
+    /// governance authority.
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        fee_bps: u16,
+        fee_collector: Pubkey,
+    ) -> Result<()> {
+        instructions::initialize_config::handler(ctx, fee_bps, fee_collector)
+    }
+
+    /// Update the protocol fee and its collector.
+    /// Only the governance authority can update the config.
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        fee_bps: u16,
+        fee_collector: Pubkey,
+    ) -> Result<()> {
+        instructions::update_config::handler(ctx, fee_bps, fee_collector)
+    }
+
+    /// Report current balance, withdrawable amount, in-session SOL and
+    /// daily-loss headroom via return data. Meant to be simulated; pass the
+    /// same price update as a withdraw to include a USD reserve.
+    pub fn get_vault_status(ctx: Context<GetVaultStatus>) -> Result<VaultStatus> {
+        instructions::get_vault_status::handler(ctx)
+    }
+
+    /// Report combined balance, in-session SOL and the strictest slippage
+    /// cap across several vaults (passed as remaining accounts) via return
+    /// data. Read-only.
+    pub fn get_aggregate_risk(ctx: Context<GetAggregateRisk>) -> Result<AggregateRisk> {
+        instructions::get_aggregate_risk::handler(ctx)
+    }
+
+    /// Report the fee, net lamports received and remaining balance of an
+    /// owner withdrawal of `amount` via return data, failing with the
+    /// same error withdraw would. Moves nothing; meant to be simulated.
+    pub fn preview_withdraw(
+        ctx: Context<PreviewWithdraw>,
+        amount: u64,
+    ) -> Result<WithdrawPreview> {
+        instructions::preview_withdraw::handler(ctx, amount)
+    }
+
+    /// Close an empty token account owned by the vault and return its rent
+    /// to the owner. Only the vault owner can call this.
+    pub fn close_token_account(ctx: Context<CloseTokenAccount>) -> Result<()> {
+        instructions::close_token_account::handler(ctx)
+    }
+
+    /// Emergency: clear all agent sessions and reconcile the balance with
+    /// the PDA's actual lamports. Owner only; requires confirm = true.
+    pub fn emergency_reset_sessions(
+        ctx: Context<EmergencyResetSessions>,
+        confirm: bool,
+    ) -> Result<()> {
+        instructions::emergency_reset_sessions::handler(ctx, confirm)
+    }
+
+    /// Stop tracking an agent session that outlived its max duration.
+    /// Permissionless; moves no funds. The session wallet can still return
+    /// the SOL through agent_deposit.
+    pub fn expire_session(ctx: Context<ExpireSession>, session_id: u64) -> Result<()> {
+        instructions::expire_session::handler(ctx, session_id)
+    }
+
+    /// Configure the M-of-N co-signer set gating owner withdrawals and
+    /// every other owner-only change to the vault. Co-signers sign as
+    /// remaining accounts; threshold 0 disables it.
+    pub fn set_cosigners(
+        ctx: Context<SetCosigners>,
+        cosigners: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        instructions::set_cosigners::handler(ctx, cosigners, threshold)
+    }
+
+    /// Atomically swap between two vault token accounts through Jupiter.
+    /// Route accounts are passed as remaining accounts and must spend from
+    /// and pay into the two vault accounts; the output must clear the
+    /// owner's swap floor and `quoted_out` less max_slippage_bps.
+    /// Requires the swap role, Auto mode.
+    pub fn agent_swap<'info>(
+        ctx: Context<'_, '_, '_, 'info, AgentSwap<'info>>,
+        amount_in: u64,
+        quoted_out: u64,
+        route_data: Vec<u8>,
+        nonce: u64,
+    ) -> Result<()> {
+        instructions::agent_swap::handler(ctx, amount_in, quoted_out, route_data, nonce)
+    }
+
+    /// Set the vault's reserve in USD (6 decimals, 0 = off). Withdrawals
+    /// that pass a Pyth SOL/USD price enforce it instead of min_sol_reserve.
+    pub fn set_usd_reserve(ctx: Context<SetUsdReserve>, min_reserve_usd: u64) -> Result<()> {
+        instructions::set_usd_reserve::handler(ctx, min_reserve_usd)
+    }
+
+    /// Set the smallest owner deposit and withdrawal in lamports (0 = no
+    /// minimum), to keep dust out of the vault's history. Owner only.
+    pub fn set_min_amounts(
+        ctx: Context<SetMinAmounts>,
+        min_deposit: u64,
+        min_withdraw: u64,
+    ) -> Result<()> {
+        instructions::set_min_amounts::handler(ctx, min_deposit, min_withdraw)
+    }
+
+    /// Cap agent positions in one mint as a percentage of the vault's
+    /// holding, overriding the global max position size for that mint
+    /// (0 removes the cap). Owner only.
+    pub fn set_mint_risk_limit(
+        ctx: Context<SetMintRiskLimit>,
+        mint: Pubkey,
+        max_position_size_pct: u8,
+    ) -> Result<()> {
+        instructions::set_mint_risk_limit::handler(ctx, mint, max_position_size_pct)
+    }
+
+    /// Set the minimum price agent swaps between two mints must clear
+    /// (0 removes it and blocks swaps on the pair). Owner only.
+    pub fn set_swap_floor(
+        ctx: Context<SetSwapFloor>,
+        input_mint: Pubkey,
+        output_mint: Pubkey,
+        min_rate: u64,
+    ) -> Result<()> {
+        instructions::set_swap_floor::handler(ctx, input_mint, output_mint, min_rate)
+    }
+
+    /// Register an agent authority or change its roles (0 removes it).
+    /// Only the vault owner can manage agents.
+    pub fn set_agent_roles(ctx: Context<SetAgentRoles>, agent: Pubkey, roles: u8) -> Result<()> {
+        instructions::set_agent_roles::handler(ctx, agent, roles)
+    }
+
+    /// Propose a new owner (default pubkey cancels a pending proposal).
+    /// Only the vault owner can propose.
+    pub fn propose_owner(ctx: Context<ProposeOwner>, new_owner: Pubkey) -> Result<()> {
+        instructions::propose_owner::handler(ctx, new_owner)
+    }
+
+    /// Accept a pending ownership transfer. Signed by the proposed owner.
+    /// Migrates the vault to the new owner's PDA and closes the old one.
+    /// Token accounts owned by the vault, with their token program, go in
+    /// remaining accounts and are re-assigned to the new PDA.
+    pub fn accept_owner<'info>(ctx: Context<'_, '_, '_, 'info, AcceptOwner<'info>>) -> Result<()> {
+        instructions::accept_owner::handler(ctx)
+    }
+
+    /// Fund several vaults from one signer in a single transaction.
+    /// Vaults are passed as remaining accounts, parallel to `amounts`.
+    pub fn deposit_many<'info>(
+        ctx: Context<'_, '_, 'info, 'info, DepositMany<'info>>,
+        amounts: Vec<u64>,
+    ) -> Result<()> {
+        instructions::deposit_many::handler(ctx, amounts)
+    }
+
+    /// Grow a vault created with the original account layout to the
+    /// current one (the owner pays the extra rent). Owner only.
+    pub fn migrate_vault(ctx: Context<MigrateVault>) -> Result<()> {
+        instructions::migrate_vault::handler(ctx)
+    }
 }
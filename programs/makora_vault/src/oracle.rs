@@ -0,0 +1,161 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+use crate::errors::VaultError;
+use crate::state::Vault;
+
+/// Pyth pull-oracle receiver program; owns PriceUpdateV2 accounts.
+pub const PYTH_RECEIVER_ID: Pubkey = pubkey!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+
+/// Pyth SOL/USD price feed id
+pub const SOL_USD_FEED_ID: [u8; 32] = [
+    0xef, 0x0d, 0x8b, 0x6f, 0xda, 0x2c, 0xeb, 0xa4, 0x1d, 0xa1, 0x5d, 0x40, 0x95, 0xd1, 0xda, 0x39,
+    0x2a, 0x0d, 0x2f, 0x8e, 0xd0, 0xc6, 0xc7, 0xbc, 0x0f, 0x4c, 0xfa, 0xc8, 0xc2, 0x80, 0xb5, 0x6d,
+];
+
+/// Oldest price update accepted for the reserve check
+pub const MAX_PRICE_AGE_SECS: i64 = 60;
+
+// Mirrors of the Pyth receiver's account types. The vault reads the
+// account by hand rather than linking the Pyth SDK.
+
+#[derive(AnchorDeserialize)]
+pub enum VerificationLevel {
+    Partial { num_signatures: u8 },
+    Full,
+}
+
+#[derive(AnchorDeserialize)]
+pub struct PriceFeedMessage {
+    pub feed_id: [u8; 32],
+    pub price: i64,
+    pub conf: u64,
+    pub exponent: i32,
+    pub publish_time: i64,
+    pub prev_publish_time: i64,
+    pub ema_price: i64,
+    pub ema_conf: u64,
+}
+
+#[derive(AnchorDeserialize)]
+pub struct PriceUpdateV2 {
+    pub write_authority: Pubkey,
+    pub verification_level: VerificationLevel,
+    pub price_message: PriceFeedMessage,
+    pub posted_slot: u64,
+}
+
+/// Read a fully verified SOL/USD price from a Pyth PriceUpdateV2 account.
+/// Returns (price, exponent); fails with StaleOracle if older than
+/// MAX_PRICE_AGE_SECS at `now`.
+pub fn read_sol_usd_price(price_update: &AccountInfo, now: i64) -> Result<(i64, i32)> {
+    require!(
+        *price_update.owner == PYTH_RECEIVER_ID,
+        VaultError::InvalidOracle
+    );
+
+    let data = price_update.try_borrow_data()?;
+    let discriminator = &hash(b"account:PriceUpdateV2").to_bytes()[..8];
+    require!(
+        data.len() > 8 && &data[..8] == discriminator,
+        VaultError::InvalidOracle
+    );
+    let update = PriceUpdateV2::deserialize(&mut &data[8..])
+        .map_err(|_| error!(VaultError::InvalidOracle))?;
+
+    let message = update.price_message;
+    require!(
+        matches!(update.verification_level, VerificationLevel::Full)
+            && message.feed_id == SOL_USD_FEED_ID
+            && message.price > 0,
+        VaultError::InvalidOracle
+    );
+    require!(
+        now.saturating_sub(message.publish_time) <= MAX_PRICE_AGE_SECS,
+        VaultError::StaleOracle
+    );
+
+    Ok((message.price, message.exponent))
+}
+
+/// Lamports worth `usd_micros` (USD with 6 decimals) at `price * 10^exponent`
+/// USD per SOL. Rounds up so the reserve is never understated.
+pub fn usd_to_lamports(usd_micros: u64, price: i64, exponent: i32) -> Result<u64> {
+    require!(price > 0, VaultError::InvalidOracle);
+    let scale = |e: u32| {
+        10u128
+            .checked_pow(e)
+            .ok_or(error!(VaultError::ArithmeticOverflow))
+    };
+
+    // lamports = usd_micros * 10^9 / 10^6 / (price * 10^exponent)
+    let mut numerator = (usd_micros as u128)
+        .checked_mul(1_000)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+    let mut denominator = price as u128;
+    if exponent < 0 {
+        numerator = numerator
+            .checked_mul(scale(exponent.unsigned_abs())?)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+    } else {
+        denominator = denominator
+            .checked_mul(scale(exponent as u32)?)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+    }
+
+    let lamports = numerator.div_ceil(denominator);
+    u64::try_from(lamports).map_err(|_| error!(VaultError::ArithmeticOverflow))
+}
+
+/// SOL reserve a withdrawal must leave behind, on top of rent.
+///
+/// With a USD reserve configured and a price update supplied, the USD
+/// amount is converted at the oracle price. Otherwise the vault falls back
+/// to the raw lamport `min_sol_reserve`.
+pub fn min_reserve_lamports(
+    vault: &Vault,
+    price_update: Option<&AccountInfo>,
+    now: i64,
+) -> Result<u64> {
+    match price_update {
+        Some(price_update) if vault.min_reserve_usd > 0 => {
+            let (price, exponent) = read_sol_usd_price(price_update, now)?;
+            usd_to_lamports(vault.min_reserve_usd, price, exponent)
+        }
+        _ => Ok(vault.risk_limits.min_sol_reserve),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_usd_at_negative_exponent() {
+        // $10 at $100.00000000 per SOL = 0.1 SOL
+        assert_eq!(usd_to_lamports(10_000_000, 10_000_000_000, -8).unwrap(), 100_000_000);
+    }
+
+    #[test]
+    fn converts_usd_at_positive_exponent() {
+        // $10 at 1 * 10^2 per SOL = 0.1 SOL
+        assert_eq!(usd_to_lamports(10_000_000, 1, 2).unwrap(), 100_000_000);
+    }
+
+    #[test]
+    fn rounds_reserve_up() {
+        // $1 at $3 per SOL = 0.333... SOL
+        assert_eq!(usd_to_lamports(1_000_000, 3, 0).unwrap(), 333_333_334);
+    }
+
+    #[test]
+    fn rejects_non_positive_price() {
+        let err = usd_to_lamports(1_000_000, 0, -8).unwrap_err();
+        assert_eq!(err, VaultError::InvalidOracle.into());
+    }
+
+    #[test]
+    fn rejects_overflowing_reserve() {
+        let err = usd_to_lamports(u64::MAX, 1, -8).unwrap_err();
+        assert_eq!(err, VaultError::ArithmeticOverflow.into());
+    }
+}
@@ -0,0 +1,79 @@
+use anchor_lang::prelude::*;
+use crate::errors::VaultError;
+
+/// Maximum protocol fee on withdrawals (100 bps = 1%)
+pub const MAX_FEE_BPS: u16 = 100;
+
+/// Global Config PDA.
+///
+/// Seeds: ["config"]
+/// One per program, created by the program's upgrade authority. Holds the
+/// protocol fee charged on withdrawals that pass it.
+///
+/// Size calculation:
+///   discriminator: 8
+///   authority: 32
+///   fee_bps: 2
+///   fee_collector: 32
+///   bump: 1
+///   TOTAL: 8 + 32 + 2 + 32 + 1 = 75
+#[account]
+pub struct GlobalConfig {
+    /// Governance authority allowed to update the fee settings
+    pub authority: Pubkey,
+
+    /// Protocol fee on withdrawals in basis points (0 = no fee)
+    pub fee_bps: u16,
+
+    /// Wallet that receives the protocol fee
+    pub fee_collector: Pubkey,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl GlobalConfig {
+    /// Account size for space allocation (includes discriminator)
+    pub const SIZE: usize = 8 + // discriminator
+        32 +  // authority
+        2 +   // fee_bps
+        32 +  // fee_collector
+        1;    // bump
+
+    /// Protocol fee owed on a withdrawal of `amount` lamports (rounded down).
+    pub fn fee_for(&self, amount: u64) -> Result<u64> {
+        let fee = (amount as u128)
+            .checked_mul(self.fee_bps as u128)
+            .ok_or(VaultError::ArithmeticOverflow)?
+            / 10_000;
+
+        // fee_bps <= 10_000, so fee <= amount
+        Ok(fee as u64)
+    }
+}
+
+/// Fee charged on a withdrawal of `amount` under `config` (0 without
+/// one). When a fee is due the collector must be present and match
+/// config.fee_collector.
+pub fn withdrawal_fee(
+    config: Option<&GlobalConfig>,
+    fee_collector: &Option<UncheckedAccount>,
+    amount: u64,
+) -> Result<u64> {
+    let Some(config) = config else {
+        return Ok(0);
+    };
+    let fee = config.fee_for(amount)?;
+    if fee > 0 {
+        let collector = fee_collector
+            .as_ref()
+            .ok_or(VaultError::InvalidFeeCollector)?;
+        require_keys_eq!(
+            collector.key(),
+            config.fee_collector,
+            VaultError::InvalidFeeCollector
+        );
+    }
+
+    Ok(fee)
+}
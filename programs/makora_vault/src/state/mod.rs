@@ -1,3 +1,5 @@
 pub mod vault;
+pub mod global_config;
 
 pub use vault::*;
+pub use global_config::*;
@@ -1,5 +1,44 @@
 use anchor_lang::prelude::*;
 
+/// Vault balance (lamports) above which min_position_size_pct is enforced.
+/// Below it, a percentage floor would block any meaningful position.
+pub const MIN_POSITION_BALANCE_FLOOR: u64 = 100_000_000; // 0.1 SOL
+
+/// Layout version written by initialize and migrate_vault
+pub const VAULT_VERSION: u8 = 1;
+
+/// Maximum number of agent sessions that can be open at once
+pub const MAX_OPEN_SESSIONS: usize = 4;
+
+/// Length of the rolling daily-loss window
+pub const DAILY_LOSS_WINDOW_SECS: i64 = 86_400;
+
+/// Session lifetime used when agent_withdraw passes max_duration_secs = 0
+pub const DEFAULT_SESSION_DURATION_SECS: u32 = 86_400; // 1 day
+
+/// Maximum number of agent authorities per vault
+pub const MAX_AGENTS: usize = 3;
+
+/// Maximum number of co-signers in a vault's multisig set
+pub const MAX_COSIGNERS: usize = 3;
+
+/// Maximum number of per-mint position caps per vault
+pub const MAX_MINT_RISK_LIMITS: usize = 4;
+
+/// Maximum number of owner-set swap price floors per vault
+pub const MAX_SWAP_FLOORS: usize = 4;
+
+/// Fixed-point scale of SwapFloor::min_rate (1e9 = one output base unit
+/// per input base unit)
+pub const SWAP_RATE_SCALE: u128 = 1_000_000_000;
+
+/// Agent role bits
+pub const ROLE_WITHDRAW: u8 = 1 << 0;
+pub const ROLE_DEPOSIT: u8 = 1 << 1;
+pub const ROLE_SET_PROTOCOL: u8 = 1 << 2;
+pub const ROLE_SWAP: u8 = 1 << 3;
+pub const ROLE_ALL: u8 = ROLE_WITHDRAW | ROLE_DEPOSIT | ROLE_SET_PROTOCOL | ROLE_SWAP;
+
 /// Agent operating mode
 /// 0 = Advisory (suggest only, user confirms)
 /// 1 = Auto (execute within risk limits)
@@ -33,6 +72,8 @@ impl AgentMode {
 pub struct RiskLimits {
     /// Maximum position size as percentage of portfolio (0-100)
     pub max_position_size_pct: u8,
+    /// Minimum position size as percentage of portfolio (0-100)
+    pub min_position_size_pct: u8,
     /// Maximum slippage in basis points (e.g., 100 = 1%)
     pub max_slippage_bps: u16,
     /// Maximum daily loss as percentage of portfolio (0-100)
@@ -43,6 +84,132 @@ pub struct RiskLimits {
     pub max_protocol_exposure_pct: u8,
 }
 
+/// An open agent session: SOL sent to a session wallet and not yet returned.
+/// A slot with session_id == 0 is free (ids start at 1).
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct SessionSlot {
+    /// Session id assigned by agent_withdraw
+    pub session_id: u64,
+    /// SOL still out in this session (lamports)
+    pub amount_out: u64,
+    /// Session wallet the SOL was sent to; only it may return funds
+    pub wallet: Pubkey,
+    /// Unix timestamp when the session was opened
+    pub opened_at: i64,
+    /// Seconds after opened_at at which anyone may expire the session
+    pub max_duration_secs: u32,
+}
+
+impl SessionSlot {
+    pub fn is_open(&self) -> bool {
+        self.session_id != 0
+    }
+
+    /// True once the session has outlived its max duration.
+    pub fn is_expired(&self, now: i64) -> bool {
+        now >= self.opened_at.saturating_add(self.max_duration_secs as i64)
+    }
+}
+
+/// Realized losses inside the current rolling 24h window.
+///
+/// The window opens at the first loss recorded after the previous one
+/// lapsed, and only resets once a full DAILY_LOSS_WINDOW_SECS has passed
+/// since daily_window_start, so trades cannot be timed around a calendar
+/// boundary to get a fresh budget.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct DailyLossWindow {
+    /// Unix timestamp the current window opened at (0 = never opened)
+    pub daily_window_start: i64,
+    /// Lamports lost since daily_window_start
+    pub accumulated_loss: u64,
+}
+
+impl DailyLossWindow {
+    /// True if no window was ever opened or a full window has passed
+    /// since daily_window_start.
+    pub fn is_lapsed(&self, now: i64) -> bool {
+        self.daily_window_start == 0
+            || now.saturating_sub(self.daily_window_start) >= DAILY_LOSS_WINDOW_SECS
+    }
+
+    /// Loss counted against the budget at `now`.
+    pub fn loss_at(&self, now: i64) -> u64 {
+        if self.is_lapsed(now) {
+            0
+        } else {
+            self.accumulated_loss
+        }
+    }
+
+    /// Seconds until the current window resets (0 when none is open).
+    pub fn remaining_secs(&self, now: i64) -> i64 {
+        if self.is_lapsed(now) {
+            0
+        } else {
+            self.daily_window_start + DAILY_LOSS_WINDOW_SECS - now
+        }
+    }
+
+    /// Add `loss` at `now`, first starting a new window if the current
+    /// one has lapsed.
+    pub fn record(&mut self, loss: u64, now: i64) {
+        if self.is_lapsed(now) {
+            self.daily_window_start = now;
+            self.accumulated_loss = 0;
+        }
+        self.accumulated_loss = self.accumulated_loss.saturating_add(loss);
+    }
+}
+
+/// An agent key and the operations it may perform (ROLE_* bitmask).
+/// A slot with the default pubkey is free.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct AgentRole {
+    pub authority: Pubkey,
+    pub roles: u8,
+}
+
+impl AgentRole {
+    pub fn is_empty(&self) -> bool {
+        self.authority == Pubkey::default()
+    }
+}
+
+/// Position cap for a single mint, overriding
+/// risk_limits.max_position_size_pct for agent actions on that mint.
+/// A slot with the default pubkey is free.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct MintRiskLimit {
+    pub mint: Pubkey,
+    /// Maximum position size as percentage of the vault's holding (1-100)
+    pub max_position_size_pct: u8,
+}
+
+impl MintRiskLimit {
+    pub fn is_empty(&self) -> bool {
+        self.mint == Pubkey::default()
+    }
+}
+
+/// Owner-set minimum price for agent swaps from input_mint to
+/// output_mint. agent_swap refuses pairs without one.
+/// A slot with the default input mint is free.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct SwapFloor {
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    /// Minimum output base units per input base unit, scaled by
+    /// SWAP_RATE_SCALE
+    pub min_rate: u64,
+}
+
+impl SwapFloor {
+    pub fn is_empty(&self) -> bool {
+        self.input_mint == Pubkey::default()
+    }
+}
+
 /// Vault PDA account.
 ///
 /// Seeds: ["vault", owner_pubkey]
@@ -55,14 +222,37 @@ pub struct RiskLimits {
 ///   total_deposited: 8
 ///   total_withdrawn: 8
 ///   mode: 1
-///   risk_limits: 1 + 2 + 1 + 8 + 1 = 13
+///   risk_limits: 1 + 1 + 2 + 1 + 8 + 1 = 14
 ///   created_at: 8
 ///   last_action_at: 8
 ///   bump: 1
 ///   in_session_amount: 8
-///   _padding: 24 (reserved for future fields)
-///   TOTAL: 8 + 32 + 32 + 8 + 8 + 1 + 13 + 8 + 8 + 1 + 8 + 24 = 151
-///   Round up to 160 for safety
+///   session_id: 8
+///   sessions: 4 * (8 + 8 + 32 + 8 + 4) = 240
+///   total_realized_pnl: 8
+///   max_total_deposit: 8
+///   agents: 3 * (32 + 1) = 99
+///   pending_owner: 32
+///   agent_nonce: 8
+///   withdraw_cooldown_secs: 4
+///   last_withdraw_at: 8
+///   agent_withdraw_cooldown: 1
+///   cosigners: 3 * 32 = 96
+///   cosigner_threshold: 1
+///   daily_loss: 8 + 8 = 16
+///   min_reserve_usd: 8
+///   min_deposit: 8
+///   min_withdraw: 8
+///   mint_risk_limits: 4 * (32 + 1) = 132
+///   heartbeat_timeout_secs: 4
+///   last_heartbeat_at: 8
+///   swap_floors: 4 * (32 + 32 + 8) = 288
+///   expired_sessions: 4 * 60 = 240
+///   version: 1
+///   TOTAL: 8 + 32 + 32 + 8 + 8 + 1 + 14 + 8 + 8 + 1 + 8 + 8 + 240 + 8 + 8 + 99 + 32 + 8 + 4 + 8 + 1 + 96 + 1 + 16 + 8 + 8 + 8 + 132 + 4 + 8 + 288 + 240 + 1 = 1354
+///
+/// Vaults created before the session ledger use the original layout and
+/// must go through migrate_vault before any other instruction can load them.
 #[account]
 pub struct Vault {
     /// The wallet owner who created this vault
@@ -95,26 +285,118 @@ pub struct Vault {
     /// SOL currently out in active stealth sessions (lamports)
     pub in_session_amount: u64,
 
-    /// Reserved space for future upgrades (avoid realloc)
-    pub _padding: [u8; 24],
+    /// Id of the most recently opened agent session (monotonically increasing)
+    pub session_id: u64,
+
+    /// Open agent sessions, one slot per session wallet
+    pub sessions: [SessionSlot; MAX_OPEN_SESSIONS],
+
+    /// Cumulative realized PnL across closed agent sessions (lamports)
+    pub total_realized_pnl: i64,
+
+    /// Cap on cumulative deposits, including booked session profit
+    /// (lamports, 0 = unlimited)
+    pub max_total_deposit: u64,
+
+    /// Agent authorities and their roles. agent_authority is registered
+    /// here with all roles at initialization.
+    pub agents: [AgentRole; MAX_AGENTS],
+
+    /// Owner proposed via propose_owner, awaiting accept_owner
+    /// (default pubkey = none)
+    pub pending_owner: Pubkey,
+
+    /// Nonce the next agent_withdraw/agent_deposit must carry.
+    /// Incremented on every successful agent call so a replayed
+    /// instruction is rejected.
+    pub agent_nonce: u64,
+
+    /// Minimum seconds between withdrawals (0 = no cooldown)
+    pub withdraw_cooldown_secs: u32,
+
+    /// Unix timestamp of the last withdrawal subject to the cooldown
+    pub last_withdraw_at: i64,
+
+    /// Whether agent withdraws are subject to the cooldown too
+    pub agent_withdraw_cooldown: bool,
+
+    /// Co-signers allowed to approve owner-only operations
+    /// (default pubkey = empty slot)
+    pub cosigners: [Pubkey; MAX_COSIGNERS],
+
+    /// Distinct co-signers required on owner-only operations (0 = off)
+    pub cosigner_threshold: u8,
+
+    /// Realized session losses in the current rolling 24h window
+    pub daily_loss: DailyLossWindow,
+
+    /// Reserve in USD with 6 decimals, used instead of min_sol_reserve
+    /// when a withdrawal supplies a SOL/USD price (0 = off)
+    pub min_reserve_usd: u64,
+
+    /// Smallest owner deposit accepted (lamports, 0 = no minimum)
+    pub min_deposit: u64,
+
+    /// Smallest owner withdrawal accepted (lamports, 0 = no minimum)
+    pub min_withdraw: u64,
+
+    /// Per-mint position caps; mints without an entry use
+    /// risk_limits.max_position_size_pct
+    pub mint_risk_limits: [MintRiskLimit; MAX_MINT_RISK_LIMITS],
+
+    /// Longest time since the last agent heartbeat after which the agent
+    /// may no longer move funds (seconds, 0 = no heartbeat required)
+    pub heartbeat_timeout_secs: u32,
+
+    /// Unix timestamp of the last agent heartbeat (0 = never)
+    pub last_heartbeat_at: i64,
+
+    /// Owner-set minimum prices for agent swaps, one per mint pair
+    pub swap_floors: [SwapFloor; MAX_SWAP_FLOORS],
+
+    /// Sessions removed by expire_session, with the SOL written off for
+    /// each, so agent_deposit can still credit what their wallets return
+    pub expired_sessions: [SessionSlot; MAX_OPEN_SESSIONS],
+
+    /// Layout version (VAULT_VERSION), bumped by future migrations
+    pub version: u8,
 }
 
 impl Vault {
     /// Account size for space allocation (includes discriminator)
-    /// in_session_amount uses 8 bytes from the former 32-byte _padding,
-    /// so total stays at 160 bytes.
     pub const SIZE: usize = 8 + // discriminator
         32 +  // owner
         32 +  // agent_authority
         8 +   // total_deposited
         8 +   // total_withdrawn
         1 +   // mode
-        13 +  // risk_limits (1+2+1+8+1)
+        14 +  // risk_limits (1+1+2+1+8+1)
         8 +   // created_at
         8 +   // last_action_at
         1 +   // bump
         8 +   // in_session_amount
-        24;   // _padding (was 32, now 24 after in_session_amount)
+        8 +   // session_id
+        60 * MAX_OPEN_SESSIONS + // sessions
+        8 +   // total_realized_pnl
+        8 +   // max_total_deposit
+        33 * MAX_AGENTS + // agents
+        32 +  // pending_owner
+        8 +   // agent_nonce
+        4 +   // withdraw_cooldown_secs
+        8 +   // last_withdraw_at
+        1 +   // agent_withdraw_cooldown
+        32 * MAX_COSIGNERS + // cosigners
+        1 +   // cosigner_threshold
+        16 +  // daily_loss
+        8 +   // min_reserve_usd
+        8 +   // min_deposit
+        8 +   // min_withdraw
+        33 * MAX_MINT_RISK_LIMITS + // mint_risk_limits
+        4 +   // heartbeat_timeout_secs
+        8 +   // last_heartbeat_at
+        72 * MAX_SWAP_FLOORS + // swap_floors
+        60 * MAX_OPEN_SESSIONS + // expired_sessions
+        1;    // version
 
     /// Current vault balance available for new operations.
     /// Excludes SOL currently out in stealth sessions.
@@ -123,4 +405,374 @@ impl Vault {
             .saturating_sub(self.total_withdrawn)
             .saturating_sub(self.in_session_amount)
     }
+
+    /// Loss still allowed under max_daily_loss_pct of the current balance,
+    /// after the realized losses in the rolling window at `now`.
+    pub fn daily_loss_headroom(&self, now: i64) -> u64 {
+        let budget = (self.current_balance() as u128)
+            .saturating_mul(self.risk_limits.max_daily_loss_pct as u128)
+            / 100;
+        budget.saturating_sub(self.daily_loss.loss_at(now) as u128) as u64
+    }
+
+    /// Book PnL realized by a closed session into the balance accounting:
+    /// profit that came back counts as deposited, a shortfall as withdrawn,
+    /// so current_balance() keeps matching the lamports the PDA holds. The
+    /// same shortfall is what the daily loss window records at `now`.
+    pub fn book_realized_pnl(&mut self, pnl: i64, now: i64) -> Result<()> {
+        if pnl >= 0 {
+            self.total_deposited = self
+                .total_deposited
+                .checked_add(pnl.unsigned_abs())
+                .ok_or(crate::errors::VaultError::ArithmeticOverflow)?;
+        } else {
+            self.total_withdrawn = self
+                .total_withdrawn
+                .checked_add(pnl.unsigned_abs())
+                .ok_or(crate::errors::VaultError::ArithmeticOverflow)?;
+            self.daily_loss.record(pnl.unsigned_abs(), now);
+        }
+        self.total_realized_pnl = self
+            .total_realized_pnl
+            .checked_add(pnl)
+            .ok_or(crate::errors::VaultError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Open a new session for `amount_out` lamports sent to `wallet`
+    /// in a free slot, expiring `max_duration_secs` after `now`.
+    /// Returns the new session id.
+    pub fn open_session(
+        &mut self,
+        amount_out: u64,
+        wallet: Pubkey,
+        now: i64,
+        max_duration_secs: u32,
+    ) -> Result<u64> {
+        let session_id = self
+            .session_id
+            .checked_add(1)
+            .ok_or(crate::errors::VaultError::ArithmeticOverflow)?;
+        let slot = self
+            .sessions
+            .iter_mut()
+            .find(|s| !s.is_open())
+            .ok_or(crate::errors::VaultError::TooManySessions)?;
+
+        *slot = SessionSlot {
+            session_id,
+            amount_out,
+            wallet,
+            opened_at: now,
+            max_duration_secs,
+        };
+        self.session_id = session_id;
+
+        Ok(session_id)
+    }
+
+    /// Roles granted to `agent`, or None if it is not a registered agent.
+    pub fn agent_roles(&self, agent: &Pubkey) -> Option<u8> {
+        self.agents
+            .iter()
+            .find(|a| !a.is_empty() && a.authority == *agent)
+            .map(|a| a.roles)
+    }
+
+    /// True if `agent` is registered and holds every bit in `role`.
+    pub fn has_agent_role(&self, agent: &Pubkey, role: u8) -> bool {
+        self.agent_roles(agent).is_some_and(|r| r & role == role)
+    }
+
+    /// Max position size percentage for agent actions on `mint`: its
+    /// per-mint cap if one is set, otherwise the global limit.
+    pub fn max_position_pct(&self, mint: &Pubkey) -> u8 {
+        self.mint_risk_limits
+            .iter()
+            .find(|l| !l.is_empty() && l.mint == *mint)
+            .map_or(self.risk_limits.max_position_size_pct, |l| {
+                l.max_position_size_pct
+            })
+    }
+
+    /// Owner-set floor rate for agent swaps from `input_mint` to
+    /// `output_mint`, or None if the pair has none.
+    pub fn swap_floor(&self, input_mint: &Pubkey, output_mint: &Pubkey) -> Option<u64> {
+        self.swap_floors
+            .iter()
+            .find(|f| {
+                !f.is_empty() && f.input_mint == *input_mint && f.output_mint == *output_mint
+            })
+            .map(|f| f.min_rate)
+    }
+
+    /// Check `nonce` against the expected agent nonce and advance it.
+    pub fn consume_agent_nonce(&mut self, nonce: u64) -> Result<()> {
+        require!(
+            nonce == self.agent_nonce,
+            crate::errors::VaultError::StaleNonce
+        );
+        self.agent_nonce = self
+            .agent_nonce
+            .checked_add(1)
+            .ok_or(crate::errors::VaultError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Reject agent fund movements when a heartbeat timeout is set and
+    /// the agent has not checked in within it.
+    pub fn check_agent_heartbeat(&self, now: i64) -> Result<()> {
+        if self.heartbeat_timeout_secs > 0 {
+            require!(
+                now.saturating_sub(self.last_heartbeat_at) <= self.heartbeat_timeout_secs as i64,
+                crate::errors::VaultError::AgentStale
+            );
+        }
+        Ok(())
+    }
+
+    /// Reject a withdrawal inside the cooldown window.
+    pub fn check_withdraw_cooldown(&self, now: i64) -> Result<()> {
+        if self.withdraw_cooldown_secs > 0 {
+            let ready_at = self
+                .last_withdraw_at
+                .checked_add(self.withdraw_cooldown_secs as i64)
+                .ok_or(crate::errors::VaultError::ArithmeticOverflow)?;
+            require!(now >= ready_at, crate::errors::VaultError::WithdrawCooldown);
+        }
+        Ok(())
+    }
+
+    /// Reject a withdrawal inside the cooldown window, otherwise start a
+    /// new window at `now`.
+    pub fn enforce_withdraw_cooldown(&mut self, now: i64) -> Result<()> {
+        self.check_withdraw_cooldown(now)?;
+        self.last_withdraw_at = now;
+        Ok(())
+    }
+
+    /// Require at least `cosigner_threshold` distinct configured co-signers
+    /// to have signed, counting signers among `accounts`. Every owner-only
+    /// instruction except deposits calls this, so the owner key alone can
+    /// neither move funds nor hand control to another key.
+    pub fn check_cosigners(&self, accounts: &[AccountInfo]) -> Result<()> {
+        if self.cosigner_threshold == 0 {
+            return Ok(());
+        }
+        let signed = self
+            .cosigners
+            .iter()
+            .filter(|c| **c != Pubkey::default())
+            .filter(|c| accounts.iter().any(|a| a.is_signer && a.key == *c))
+            .count();
+        require!(
+            signed >= self.cosigner_threshold as usize,
+            crate::errors::VaultError::InsufficientSigners
+        );
+        Ok(())
+    }
+
+    /// Find the open session slot with the given id.
+    pub fn session_mut(&mut self, session_id: u64) -> Result<&mut SessionSlot> {
+        self.sessions
+            .iter_mut()
+            .find(|s| s.is_open() && s.session_id == session_id)
+            .ok_or(error!(crate::errors::VaultError::UnknownSession))
+    }
+
+    /// Remember a session expire_session removed, overwriting the oldest
+    /// entry when all are taken.
+    pub fn record_expired_session(&mut self, expired: SessionSlot) {
+        if let Some(entry) = self.expired_sessions.iter_mut().min_by_key(|s| s.session_id) {
+            *entry = expired;
+        }
+    }
+
+    /// Credit `amount` returned by `wallet` for a session expire_session
+    /// removed. Its SOL was written off as withdrawn then, so it counts as
+    /// deposited now. Like an open session, a partial return may not exceed
+    /// what is still written off, and `close_session` drops the record.
+    /// Returns false if no expired session with that id is recorded.
+    pub fn credit_expired_session(
+        &mut self,
+        session_id: u64,
+        wallet: Pubkey,
+        amount: u64,
+        close_session: bool,
+    ) -> Result<bool> {
+        let Some(expired) = self
+            .expired_sessions
+            .iter_mut()
+            .find(|s| s.is_open() && s.session_id == session_id)
+        else {
+            return Ok(false);
+        };
+        require!(
+            expired.wallet == wallet,
+            crate::errors::VaultError::UnknownSource
+        );
+
+        if close_session {
+            *expired = SessionSlot::default();
+        } else {
+            expired.amount_out = expired
+                .amount_out
+                .checked_sub(amount)
+                .ok_or(crate::errors::VaultError::SessionAmountUnderflow)?;
+            if expired.amount_out == 0 {
+                *expired = SessionSlot::default();
+            }
+        }
+        self.total_deposited = self
+            .total_deposited
+            .checked_add(amount)
+            .ok_or(crate::errors::VaultError::ArithmeticOverflow)?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: i64 = 3_600;
+
+    #[test]
+    fn loss_late_in_window_still_counts() {
+        let mut window = DailyLossWindow::default();
+        window.record(100, 1_000);
+        window.record(50, 1_000 + 23 * HOUR);
+        assert_eq!(window.daily_window_start, 1_000);
+        assert_eq!(window.loss_at(1_000 + 23 * HOUR), 150);
+        assert_eq!(window.remaining_secs(1_000 + 23 * HOUR), HOUR);
+    }
+
+    #[test]
+    fn window_resets_after_a_full_day() {
+        let mut window = DailyLossWindow::default();
+        window.record(100, 1_000);
+        window.record(50, 1_000 + 23 * HOUR);
+        assert_eq!(window.loss_at(1_000 + 25 * HOUR), 0);
+        assert_eq!(window.remaining_secs(1_000 + 25 * HOUR), 0);
+
+        window.record(30, 1_000 + 25 * HOUR);
+        assert_eq!(window.daily_window_start, 1_000 + 25 * HOUR);
+        assert_eq!(window.loss_at(1_000 + 25 * HOUR), 30);
+    }
+
+    fn vault_with_balance(balance: u64) -> Vault {
+        Vault {
+            owner: Pubkey::default(),
+            agent_authority: Pubkey::default(),
+            total_deposited: balance,
+            total_withdrawn: 0,
+            mode: AgentMode::Auto,
+            risk_limits: RiskLimits::default(),
+            created_at: 0,
+            last_action_at: 0,
+            bump: 0,
+            in_session_amount: 0,
+            session_id: 0,
+            sessions: [SessionSlot::default(); MAX_OPEN_SESSIONS],
+            total_realized_pnl: 0,
+            max_total_deposit: 0,
+            agents: [AgentRole::default(); MAX_AGENTS],
+            pending_owner: Pubkey::default(),
+            agent_nonce: 0,
+            withdraw_cooldown_secs: 0,
+            last_withdraw_at: 0,
+            agent_withdraw_cooldown: false,
+            cosigners: [Pubkey::default(); MAX_COSIGNERS],
+            cosigner_threshold: 0,
+            daily_loss: DailyLossWindow::default(),
+            min_reserve_usd: 0,
+            min_deposit: 0,
+            min_withdraw: 0,
+            mint_risk_limits: [MintRiskLimit::default(); MAX_MINT_RISK_LIMITS],
+            heartbeat_timeout_secs: 0,
+            last_heartbeat_at: 0,
+            swap_floors: [SwapFloor::default(); MAX_SWAP_FLOORS],
+            expired_sessions: [SessionSlot::default(); MAX_OPEN_SESSIONS],
+            version: VAULT_VERSION,
+        }
+    }
+
+    #[test]
+    fn booked_pnl_moves_the_balance() {
+        let mut vault = vault_with_balance(1_000);
+        vault.book_realized_pnl(-100, 1_000).unwrap();
+        assert_eq!(vault.current_balance(), 900);
+        vault.book_realized_pnl(250, 1_000).unwrap();
+        assert_eq!(vault.current_balance(), 1_150);
+        assert_eq!(vault.total_realized_pnl, 150);
+    }
+
+    #[test]
+    fn booked_loss_feeds_the_daily_window() {
+        let mut vault = vault_with_balance(1_000);
+        vault.book_realized_pnl(-100, 1_000).unwrap();
+        vault.book_realized_pnl(40, 1_000 + HOUR).unwrap();
+        assert_eq!(vault.daily_loss.loss_at(1_000 + HOUR), 100);
+        assert_eq!(vault.total_withdrawn, vault.daily_loss.accumulated_loss);
+    }
+
+    #[test]
+    fn expired_session_returns_are_credited_back() {
+        let mut vault = vault_with_balance(1_000);
+        let wallet = Pubkey::new_unique();
+        let id = vault.open_session(400, wallet, 0, 60).unwrap();
+        vault.in_session_amount = 400;
+
+        // What expire_session books: the slot's SOL is written off
+        let expired = *vault.session_mut(id).unwrap();
+        vault.sessions = [SessionSlot::default(); MAX_OPEN_SESSIONS];
+        vault.in_session_amount = 0;
+        vault.total_withdrawn = 400;
+        vault.record_expired_session(expired);
+        assert_eq!(vault.current_balance(), 600);
+
+        let err = vault.credit_expired_session(id, Pubkey::new_unique(), 100, false).unwrap_err();
+        assert_eq!(err, crate::errors::VaultError::UnknownSource.into());
+        let err = vault.credit_expired_session(id, wallet, 500, false).unwrap_err();
+        assert_eq!(err, crate::errors::VaultError::SessionAmountUnderflow.into());
+
+        assert!(vault.credit_expired_session(id, wallet, 100, false).unwrap());
+        assert_eq!(vault.current_balance(), 700);
+        assert!(vault.credit_expired_session(id, wallet, 350, true).unwrap());
+        assert_eq!(vault.current_balance(), 1_050);
+        assert!(!vault.credit_expired_session(id, wallet, 1, true).unwrap());
+    }
+
+    #[test]
+    fn expired_sessions_overwrite_the_oldest() {
+        let mut vault = vault_with_balance(0);
+        let slot = |session_id| SessionSlot { session_id, amount_out: 1, ..SessionSlot::default() };
+        for id in 1..=MAX_OPEN_SESSIONS as u64 + 1 {
+            vault.record_expired_session(slot(id));
+        }
+        let mut ids: Vec<u64> = vault.expired_sessions.iter().map(|s| s.session_id).collect();
+        ids.sort();
+        assert_eq!(ids, (2..=MAX_OPEN_SESSIONS as u64 + 1).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn daily_loss_limit_holds_for_the_whole_window() {
+        let mut vault = vault_with_balance(1_100);
+        vault.risk_limits.max_daily_loss_pct = 10;
+        vault.book_realized_pnl(-100, 1_000).unwrap();
+        assert_eq!(vault.daily_loss_headroom(1_000), 0);
+
+        // A loss at hour 23 still counts against the limit
+        assert_eq!(vault.daily_loss_headroom(1_000 + 23 * HOUR), 0);
+        // At hour 25 the window has reset
+        assert_eq!(vault.daily_loss_headroom(1_000 + 25 * HOUR), 100);
+    }
+
+    #[test]
+    fn window_resets_exactly_at_boundary() {
+        let mut window = DailyLossWindow::default();
+        window.record(100, 1_000);
+        assert_eq!(window.loss_at(1_000 + DAILY_LOSS_WINDOW_SECS - 1), 100);
+        assert_eq!(window.loss_at(1_000 + DAILY_LOSS_WINDOW_SECS), 0);
+    }
 }
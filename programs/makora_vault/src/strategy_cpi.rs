@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke;
+
+/// makora_strategy program id.
+///
+/// makora_strategy depends on this crate (it reads `Vault`), so the vault
+/// cannot depend on the strategy crate's CPI module without a cycle. The
+/// `log_action` call is built by hand instead.
+pub const STRATEGY_PROGRAM_ID: Pubkey = pubkey!("EH5sixTHAoLsdFox1bR3YUqgwf5VuX2BdXFew5wTE6dj");
+
+/// makora_strategy ActionKind::Deposit
+pub const ACTION_KIND_DEPOSIT: u8 = 8;
+
+/// Strategy PDA for `owner`: seeds = ["strategy", owner]
+pub fn strategy_address(owner: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"strategy", owner.as_ref()], &STRATEGY_PROGRAM_ID).0
+}
+
+/// CPI into makora_strategy `log_action`, signed by the vault owner.
+/// No vault account is passed, so the entry's balance snapshot is 0.
+pub fn log_action<'info>(
+    strategy_program: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    strategy_account: &AccountInfo<'info>,
+    audit_trail: &AccountInfo<'info>,
+    action_kind: u8,
+    protocol: &str,
+    description: &str,
+) -> Result<()> {
+    let mut data = hash(b"global:log_action").to_bytes()[..8].to_vec();
+    (action_kind, protocol.to_string(), description.to_string(), true, true)
+        .serialize(&mut data)?;
+
+    // Account order follows makora_strategy::LogAction. The optional
+    // vault is omitted by passing the program id in its slot.
+    let ix = Instruction {
+        program_id: STRATEGY_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(authority.key(), true),
+            AccountMeta::new(strategy_account.key(), false),
+            AccountMeta::new(audit_trail.key(), false),
+            AccountMeta::new_readonly(authority.key(), false),
+            AccountMeta::new_readonly(STRATEGY_PROGRAM_ID, false),
+        ],
+        data,
+    };
+
+    invoke(
+        &ix,
+        &[
+            authority.clone(),
+            strategy_account.clone(),
+            audit_trail.clone(),
+            strategy_program.clone(),
+        ],
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{
+        transfer_fee::TransferFeeConfig, BaseStateWithExtensions, ExtensionType,
+        StateWithExtensions,
+    },
+    state::Mint,
+};
+use crate::errors::VaultError;
+
+/// Token-2022 mint extensions the vault cannot move safely.
+///
+/// Non-transferable tokens can never leave, transfer hooks need extra
+/// accounts the vault does not pass, a permanent delegate can move vault
+/// funds behind its back, and confidential balances are invisible to it.
+pub const UNSUPPORTED_EXTENSIONS: [ExtensionType; 4] = [
+    ExtensionType::NonTransferable,
+    ExtensionType::TransferHook,
+    ExtensionType::PermanentDelegate,
+    ExtensionType::ConfidentialTransferMint,
+];
+
+/// True if `mint` is owned by the Token-2022 program.
+pub fn is_token_2022(mint: &AccountInfo) -> bool {
+    *mint.owner == spl_token_2022::ID
+}
+
+/// Check that the vault can move `amount` of `mint`, and return the
+/// transfer fee the mint withholds from it (0 for classic SPL mints and
+/// Token-2022 mints without a fee).
+pub fn check_mint(mint: &AccountInfo, amount: u64) -> Result<u64> {
+    if !is_token_2022(mint) {
+        return Ok(0);
+    }
+
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<Mint>::unpack(&data)?;
+    let extensions = state.get_extension_types()?;
+    require!(
+        !extensions.iter().any(|e| UNSUPPORTED_EXTENSIONS.contains(e)),
+        VaultError::UnsupportedMintExtension
+    );
+
+    match state.get_extension::<TransferFeeConfig>() {
+        Ok(config) => Ok(config
+            .calculate_epoch_fee(Clock::get()?.epoch, amount)
+            .ok_or(VaultError::ArithmeticOverflow)?),
+        Err(_) => Ok(0),
+    }
+}
@@ -0,0 +1,111 @@
+use anchor_lang::prelude::*;
+use makora_escrow::transfer_lamports;
+use crate::errors::VaultError;
+use crate::state::Vault;
+
+/// Move lamports out of a program-owned account (e.g. the vault PDA).
+///
+/// Direct lamport manipulation is only valid when `from` is owned by this
+/// program. Both sides are checked so a malformed account returns
+/// `ArithmeticOverflow` instead of panicking.
+pub fn transfer_from_pda(from: &AccountInfo, to: &AccountInfo, amount: u64) -> Result<()> {
+    transfer_lamports(from, to, amount, VaultError::ArithmeticOverflow)
+}
+
+/// Move lamports out of the vault PDA, then check it is still rent-exempt.
+///
+/// Every debit of a live vault goes through here, so an accounting mistake
+/// upstream fails with `InsufficientReserve` instead of leaving the account
+/// below rent, including on full exits where the reserve check is skipped.
+pub fn transfer_from_vault(vault: &AccountInfo, to: &AccountInfo, amount: u64) -> Result<()> {
+    transfer_from_pda(vault, to, amount)?;
+    require_min_balance(vault, Rent::get()?.minimum_balance(Vault::SIZE))
+}
+
+/// Lamports left after debiting `amount` from `lamports`, failing exactly
+/// as `transfer_from_vault` would. Lets a preview run the same checks
+/// without moving anything.
+pub fn balance_after_debit(lamports: u64, amount: u64, min_balance: u64) -> Result<u64> {
+    let remaining = lamports
+        .checked_sub(amount)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+    require!(remaining >= min_balance, VaultError::InsufficientReserve);
+    Ok(remaining)
+}
+
+/// Fail with `InsufficientReserve` if `account` holds less than `min_balance`.
+pub fn require_min_balance(account: &AccountInfo, min_balance: u64) -> Result<()> {
+    require!(
+        account.lamports() >= min_balance,
+        VaultError::InsufficientReserve
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_accounts(from_lamports: u64, to_lamports: u64, f: impl FnOnce(&AccountInfo, &AccountInfo)) {
+        let (from_key, to_key, owner) = (Pubkey::new_unique(), Pubkey::new_unique(), crate::ID);
+        let (mut from_lamports, mut to_lamports) = (from_lamports, to_lamports);
+        let (mut from_data, mut to_data) = ([0u8; 0], [0u8; 0]);
+        let from = AccountInfo::new(&from_key, false, true, &mut from_lamports, &mut from_data, &owner, false, 0);
+        let to = AccountInfo::new(&to_key, false, true, &mut to_lamports, &mut to_data, &owner, false, 0);
+        f(&from, &to);
+    }
+
+    #[test]
+    fn moves_lamports() {
+        with_accounts(100, 5, |from, to| {
+            transfer_from_pda(from, to, 40).unwrap();
+            assert_eq!(from.lamports(), 60);
+            assert_eq!(to.lamports(), 45);
+        });
+    }
+
+    #[test]
+    fn rejects_source_underflow() {
+        with_accounts(10, 0, |from, to| {
+            let err = transfer_from_pda(from, to, 11).unwrap_err();
+            assert_eq!(err, VaultError::ArithmeticOverflow.into());
+            assert_eq!(from.lamports(), 10);
+            assert_eq!(to.lamports(), 0);
+        });
+    }
+
+    #[test]
+    fn rejects_destination_overflow() {
+        with_accounts(10, u64::MAX, |from, to| {
+            let err = transfer_from_pda(from, to, 1).unwrap_err();
+            assert_eq!(err, VaultError::ArithmeticOverflow.into());
+            assert_eq!(from.lamports(), 10);
+            assert_eq!(to.lamports(), u64::MAX);
+        });
+    }
+
+    #[test]
+    fn accepts_balance_at_minimum() {
+        with_accounts(100, 0, |from, _| {
+            require_min_balance(from, 100).unwrap();
+        });
+    }
+
+    #[test]
+    fn debit_preview_matches_transfer_checks() {
+        assert_eq!(balance_after_debit(100, 40, 60).unwrap(), 60);
+        let err = balance_after_debit(100, 41, 60).unwrap_err();
+        assert_eq!(err, VaultError::InsufficientReserve.into());
+        let err = balance_after_debit(10, 11, 0).unwrap_err();
+        assert_eq!(err, VaultError::ArithmeticOverflow.into());
+    }
+
+    #[test]
+    fn rejects_balance_below_minimum() {
+        with_accounts(100, 0, |from, to| {
+            transfer_from_pda(from, to, 1).unwrap();
+            let err = require_min_balance(from, 100).unwrap_err();
+            assert_eq!(err, VaultError::InsufficientReserve.into());
+        });
+    }
+}
@@ -0,0 +1,73 @@
+//! Test-only stand-in for the Jupiter aggregator.
+//!
+//! Deployed at Jupiter's program id through `[[test.genesis]]` so that
+//! makora_vault's `agent_swap` can be exercised on localnet. A "route"
+//! takes `in_amount` from the user and pays a fixed `out_amount` from a
+//! pool owned by the ["pool"] PDA.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+declare_id!("JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4");
+
+#[program]
+pub mod mock_jupiter {
+    use super::*;
+
+    /// Swap `in_amount` of the user's source token for `out_amount` of the
+    /// pool's. Fails, like a real route, if the pool cannot pay.
+    pub fn route(ctx: Context<Route>, in_amount: u64, out_amount: u64) -> Result<()> {
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.user_source.to_account_info(),
+                    to: ctx.accounts.pool_source.to_account_info(),
+                    authority: ctx.accounts.user_transfer_authority.to_account_info(),
+                },
+            ),
+            in_amount,
+        )?;
+
+        let bump = ctx.bumps.pool_authority;
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.pool_destination.to_account_info(),
+                    to: ctx.accounts.user_destination.to_account_info(),
+                    authority: ctx.accounts.pool_authority.to_account_info(),
+                },
+                &[&[b"pool", &[bump]]],
+            ),
+            out_amount,
+        )?;
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Route<'info> {
+    pub user_transfer_authority: Signer<'info>,
+
+    #[account(mut)]
+    pub user_source: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub user_destination: Account<'info, TokenAccount>,
+
+    /// Pool account receiving the input token
+    #[account(mut, token::authority = pool_authority)]
+    pub pool_source: Account<'info, TokenAccount>,
+
+    /// Pool account paying the output token
+    #[account(mut, token::authority = pool_authority)]
+    pub pool_destination: Account<'info, TokenAccount>,
+
+    /// CHECK: PDA that owns the pool token accounts
+    #[account(seeds = [b"pool"], bump)]
+    pub pool_authority: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
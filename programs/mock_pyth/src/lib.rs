@@ -0,0 +1,84 @@
+//! Test-only stand-in for the Pyth pull-oracle receiver.
+//!
+//! Deployed at the receiver's program id through `[[test.genesis]]` so that
+//! makora_vault's USD reserve can be exercised on localnet. Posts
+//! `PriceUpdateV2` accounts with the receiver's layout and any price,
+//! exponent and publish time the test asks for.
+
+use anchor_lang::prelude::*;
+
+declare_id!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+
+#[program]
+pub mod mock_pyth {
+    use super::*;
+
+    /// Write a fully verified price update into a new account.
+    pub fn post_price(
+        ctx: Context<PostPrice>,
+        feed_id: [u8; 32],
+        price: i64,
+        exponent: i32,
+        publish_time: i64,
+    ) -> Result<()> {
+        let update = &mut ctx.accounts.price_update;
+        update.write_authority = ctx.accounts.payer.key();
+        update.verification_level = VerificationLevel::Full;
+        update.price_message = PriceFeedMessage {
+            feed_id,
+            price,
+            conf: 0,
+            exponent,
+            publish_time,
+            prev_publish_time: publish_time,
+            ema_price: price,
+            ema_conf: 0,
+        };
+        update.posted_slot = Clock::get()?.slot;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct PostPrice<'info> {
+    #[account(init, payer = payer, space = 8 + PriceUpdateV2::SIZE)]
+    pub price_update: Account<'info, PriceUpdateV2>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub enum VerificationLevel {
+    Partial { num_signatures: u8 },
+    Full,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct PriceFeedMessage {
+    pub feed_id: [u8; 32],
+    pub price: i64,
+    pub conf: u64,
+    pub exponent: i32,
+    pub publish_time: i64,
+    pub prev_publish_time: i64,
+    pub ema_price: i64,
+    pub ema_conf: u64,
+}
+
+/// Same name and layout as the receiver's account, so the discriminator
+/// matches too.
+#[account]
+pub struct PriceUpdateV2 {
+    pub write_authority: Pubkey,
+    pub verification_level: VerificationLevel,
+    pub price_message: PriceFeedMessage,
+    pub posted_slot: u64,
+}
+
+impl PriceUpdateV2 {
+    /// Largest encoding (Partial verification level)
+    pub const SIZE: usize = 32 + 2 + (32 + 8 + 8 + 4 + 8 + 8 + 8 + 8) + 8;
+}
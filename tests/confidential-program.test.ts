@@ -0,0 +1,1615 @@
+import * as anchor from '@coral-xyz/anchor';
+import { Program } from '@coral-xyz/anchor';
+import { Keypair, LAMPORTS_PER_SOL, PublicKey, SystemProgram } from '@solana/web3.js';
+import { randomBytes } from 'crypto';
+import { expect } from 'chai';
+import type { MakoraConfidential } from '../target/types/makora_confidential';
+import type { MakoraVault } from '../target/types/makora_vault';
+
+describe('makora_confidential', () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.MakoraConfidential as Program<MakoraConfidential>;
+  const owner = provider.wallet as anchor.Wallet;
+  const mxeId = Array.from(randomBytes(32));
+  const usdcMint = new PublicKey('EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v');
+  const cluster = Keypair.generate();
+
+  let orderBookPda: PublicKey;
+
+  before(async () => {
+    [orderBookPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from('order_book'), owner.publicKey.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .initOrderBook(0, 0, new anchor.BN(0), owner.publicKey, cluster.publicKey) // default 32..512 byte orders, no fee
+      .accounts({
+        orderBook: orderBookPda,
+        authority: owner.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  });
+
+  function swapOrderPda(computationId: Buffer): PublicKey {
+    const [pda] = PublicKey.findProgramAddressSync(
+      [Buffer.from('swap_order'), owner.publicKey.toBuffer(), computationId],
+      program.programId
+    );
+    return pda;
+  }
+
+  function computationRecordPda(book: PublicKey, computationId: Buffer): PublicKey {
+    const [pda] = PublicKey.findProgramAddressSync(
+      [Buffer.from('computation'), book.toBuffer(), computationId],
+      program.programId
+    );
+    return pda;
+  }
+
+  function routeLabel(label: string): number[] {
+    const buf = Buffer.alloc(32);
+    buf.write(label);
+    return Array.from(buf);
+  }
+
+  async function submitOrder(
+    computationId: Buffer,
+    outputDestination: PublicKey = Keypair.generate().publicKey,
+    timeoutSecs = 0,
+    referenceQuote = 0,
+    schemaVersion = 1
+  ): Promise<string> {
+    return program.methods
+      .submitConfidentialSwap(
+        randomBytes(64),
+        Array.from(randomBytes(32)), // client x25519 pubkey
+        Array.from(randomBytes(12)), // nonce
+        Array.from(computationId),
+        mxeId,
+        outputDestination,
+        false,
+        timeoutSecs,
+        new anchor.BN(referenceQuote),
+        schemaVersion,
+      )
+      .accounts({
+        swapOrder: swapOrderPda(computationId),
+        orderBook: orderBookPda,
+        computationRecord: computationRecordPda(orderBookPda, computationId),
+        owner: owner.publicKey,
+        feeCollector: owner.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  }
+
+  describe('recent orders index', () => {
+    it('lists submitted orders from the order book', async () => {
+      const computationIds = [randomBytes(32), randomBytes(32), randomBytes(32)];
+      for (const id of computationIds) {
+        await submitOrder(id);
+      }
+
+      const book = await program.account.orderBook.fetch(orderBookPda);
+      expect(book.orderCount.toNumber()).to.equal(3);
+      expect(book.recentHead).to.equal(3);
+
+      computationIds.forEach((id, i) => {
+        const entry = book.recentOrders[i];
+        expect(Buffer.from(entry.computationId).equals(id)).to.be.true;
+        expect(entry.status).to.deep.equal({ pending: {} });
+      });
+    });
+  });
+
+  describe('order timestamps', () => {
+    it('stamps orders no earlier than the book was created', async () => {
+      const computationId = randomBytes(32);
+      await submitOrder(computationId);
+
+      const book = await program.account.orderBook.fetch(orderBookPda);
+      const order = await program.account.swapOrder.fetch(swapOrderPda(computationId));
+      expect(book.createdAt.toNumber()).to.be.greaterThan(0);
+      expect(order.submittedAt.toNumber()).to.be.at.least(book.createdAt.toNumber());
+    });
+  });
+
+  describe('failed computations', () => {
+
+    async function failOrder(computationId: Buffer, reason: number) {
+      await program.methods
+        .failConfidentialSwap(reason)
+        .accounts({
+          swapOrder: swapOrderPda(computationId),
+          orderBook: orderBookPda,
+          owner: owner.publicKey,
+          clusterAuthority: cluster.publicKey,
+        })
+        .signers([cluster])
+        .rpc();
+    }
+
+    it('marks a pending order failed with a reason code', async () => {
+      const computationId = randomBytes(32);
+      await submitOrder(computationId);
+      const before = await program.account.orderBook.fetch(orderBookPda);
+
+      await failOrder(computationId, 3);
+
+      const order = await program.account.swapOrder.fetch(swapOrderPda(computationId));
+      expect(order.status).to.deep.equal({ failed: {} });
+      expect(order.failureReason).to.equal(3);
+
+      const book = await program.account.orderBook.fetch(orderBookPda);
+      expect(book.failedCount.toNumber()).to.equal(before.failedCount.toNumber() + 1);
+    });
+
+    it('rejects failing an order that is no longer pending', async () => {
+      const computationId = randomBytes(32);
+      await submitOrder(computationId);
+      await failOrder(computationId, 1);
+
+      try {
+        await failOrder(computationId, 2);
+
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('OrderNotPending');
+      }
+    });
+
+    it('rejects a signer other than the book\'s cluster authority', async () => {
+      const computationId = randomBytes(32);
+      await submitOrder(computationId);
+      const impostor = Keypair.generate();
+
+      try {
+        await program.methods
+          .failConfidentialSwap(1)
+          .accounts({
+            swapOrder: swapOrderPda(computationId),
+            orderBook: orderBookPda,
+            owner: owner.publicKey,
+            clusterAuthority: impostor.publicKey,
+          })
+          .signers([impostor])
+          .rpc();
+
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('UnauthorizedCluster');
+      }
+
+      const order = await program.account.swapOrder.fetch(swapOrderPda(computationId));
+      expect(order.status).to.deep.equal({ pending: {} });
+    });
+
+    it('stores the cluster authority on the book', async () => {
+      const book = await program.account.orderBook.fetch(orderBookPda);
+      expect(book.clusterAuthority.toBase58()).to.equal(cluster.publicKey.toBase58());
+    });
+  });
+
+  describe('order size band', () => {
+    const bookOwner = Keypair.generate();
+    let bandBookPda: PublicKey;
+
+    before(async () => {
+      const sig = await provider.connection.requestAirdrop(bookOwner.publicKey, 1_000_000_000);
+      await provider.connection.confirmTransaction(sig);
+
+      [bandBookPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from('order_book'), bookOwner.publicKey.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .initOrderBook(64, 128, new anchor.BN(0), bookOwner.publicKey, cluster.publicKey)
+        .accounts({
+          orderBook: bandBookPda,
+          authority: bookOwner.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([bookOwner])
+        .rpc();
+    });
+
+    async function submitSized(len: number) {
+      const computationId = randomBytes(32);
+      const [swapOrder] = PublicKey.findProgramAddressSync(
+        [Buffer.from('swap_order'), bookOwner.publicKey.toBuffer(), computationId],
+        program.programId
+      );
+
+      await program.methods
+        .submitConfidentialSwap(
+          randomBytes(len),
+          Array.from(randomBytes(32)),
+          Array.from(randomBytes(12)),
+          Array.from(computationId),
+          mxeId,
+          Keypair.generate().publicKey,
+          false,
+          0,
+          new anchor.BN(0), // no reference quote
+          1, // schema version
+        )
+        .accounts({
+          swapOrder,
+          orderBook: bandBookPda,
+          computationRecord: computationRecordPda(bandBookPda, computationId),
+          owner: bookOwner.publicKey,
+          feeCollector: bookOwner.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([bookOwner])
+        .rpc();
+    }
+
+    it('stores the configured bounds', async () => {
+      const book = await program.account.orderBook.fetch(bandBookPda);
+      expect(book.minOrderLen).to.equal(64);
+      expect(book.maxOrderLen).to.equal(128);
+    });
+
+    it('accepts an order inside the band', async () => {
+      await submitSized(96);
+    });
+
+    it('rejects an order below the band', async () => {
+      try {
+        await submitSized(32);
+
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('OrderTooSmall');
+      }
+    });
+
+    it('rejects an order above the band', async () => {
+      try {
+        await submitSized(200);
+
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('OrderTooLarge');
+      }
+    });
+  });
+
+  describe('lifecycle events', () => {
+    const parser = new anchor.EventParser(program.programId, program.coder);
+
+    async function eventsOf(sig: string) {
+      const tx = await provider.connection.getTransaction(sig, {
+        commitment: 'confirmed',
+        maxSupportedTransactionVersion: 0,
+      });
+      return Array.from(parser.parseLogs(tx!.meta!.logMessages!));
+    }
+
+    it('emits OrderSubmitted on submit', async () => {
+      const computationId = randomBytes(32);
+      const sig = await submitOrder(computationId);
+      await provider.connection.confirmTransaction(sig, 'confirmed');
+
+      const event = (await eventsOf(sig)).find((e) => e.name === 'orderSubmitted');
+      expect(event).to.not.be.undefined;
+      expect(event!.data.order.toBase58()).to.equal(swapOrderPda(computationId).toBase58());
+      expect(Buffer.from(event!.data.computationIdPrefix).equals(computationId.subarray(0, 8))).to.be
+        .true;
+    });
+
+    it('emits OrderSettled once settlement completes', async () => {
+      const computationId = randomBytes(32);
+      const destination = Keypair.generate().publicKey;
+      await submitOrder(computationId, destination);
+
+      const accounts = {
+        swapOrder: swapOrderPda(computationId),
+        orderBook: orderBookPda,
+        owner: owner.publicKey,
+        outputDestination: destination,
+        clusterAuthority: cluster.publicKey,
+      };
+
+      await program.methods
+        .executeSwapCallback(
+          randomBytes(32),
+          Array.from(randomBytes(12)),
+          new anchor.BN(1_000),
+          new anchor.BN(0), // fully filled
+          mxeId,
+          routeLabel('jupiter'),
+          usdcMint,
+          6
+        )
+        .accounts(accounts)
+        .signers([cluster])
+        .rpc();
+
+      const sig = await program.methods
+        .completeSettlement()
+        .accounts(accounts)
+        .signers([cluster])
+        .rpc({ commitment: 'confirmed' });
+
+      const event = (await eventsOf(sig)).find((e) => e.name === 'orderSettled');
+      expect(event).to.not.be.undefined;
+      expect(event!.data.outputAmount.toNumber()).to.equal(1_000);
+    });
+  });
+
+  describe('callback timeout', () => {
+
+    async function postResult(computationId: Buffer, destination: PublicKey) {
+      await program.methods
+        .executeSwapCallback(
+          randomBytes(32),
+          Array.from(randomBytes(12)),
+          new anchor.BN(500),
+          new anchor.BN(0), // fully filled
+          mxeId,
+          routeLabel('jupiter'),
+          usdcMint,
+          6
+        )
+        .accounts({
+          swapOrder: swapOrderPda(computationId),
+          orderBook: orderBookPda,
+          owner: owner.publicKey,
+          outputDestination: destination,
+          clusterAuthority: cluster.publicKey,
+        })
+        .signers([cluster])
+        .rpc();
+    }
+
+    it('stores expires_at from the requested timeout', async () => {
+      const computationId = randomBytes(32);
+      await submitOrder(computationId, Keypair.generate().publicKey, 120);
+
+      const order = await program.account.swapOrder.fetch(swapOrderPda(computationId));
+      expect(order.expiresAt.toNumber()).to.equal(order.submittedAt.toNumber() + 120);
+    });
+
+    it('clamps an oversized timeout to the maximum', async () => {
+      const computationId = randomBytes(32);
+      await submitOrder(computationId, Keypair.generate().publicKey, 1_000_000);
+
+      const order = await program.account.swapOrder.fetch(swapOrderPda(computationId));
+      expect(order.expiresAt.toNumber()).to.equal(order.submittedAt.toNumber() + 3_600);
+    });
+
+    it('accepts a callback before the deadline', async () => {
+      const computationId = randomBytes(32);
+      const destination = Keypair.generate().publicKey;
+      await submitOrder(computationId, destination, 60);
+
+      await postResult(computationId, destination);
+
+      const order = await program.account.swapOrder.fetch(swapOrderPda(computationId));
+      expect(order.status).to.deep.equal({ settlementPending: {} });
+    });
+
+    it('rejects a callback after the deadline', async () => {
+      const computationId = randomBytes(32);
+      const destination = Keypair.generate().publicKey;
+      await submitOrder(computationId, destination, 1);
+      await new Promise((resolve) => setTimeout(resolve, 3_000));
+
+      try {
+        await postResult(computationId, destination);
+
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('OrderExpired');
+      }
+    });
+  });
+
+  describe('settlement route', () => {
+
+    async function postResult(computationId: Buffer, destination: PublicKey, route: number[]) {
+      await program.methods
+        .executeSwapCallback(
+          randomBytes(32),
+          Array.from(randomBytes(12)),
+          new anchor.BN(750),
+          new anchor.BN(0), // fully filled
+          mxeId,
+          route,
+          usdcMint,
+          6
+        )
+        .accounts({
+          swapOrder: swapOrderPda(computationId),
+          orderBook: orderBookPda,
+          owner: owner.publicKey,
+          outputDestination: destination,
+          clusterAuthority: cluster.publicKey,
+        })
+        .signers([cluster])
+        .rpc();
+    }
+
+    it('stores the route label with the output amount', async () => {
+      const computationId = randomBytes(32);
+      const destination = Keypair.generate().publicKey;
+      await submitOrder(computationId, destination);
+
+      await postResult(computationId, destination, routeLabel('orca-whirlpool'));
+
+      const order = await program.account.swapOrder.fetch(swapOrderPda(computationId));
+      const label = Buffer.from(order.routeLabel).toString().replace(/\0+$/, '');
+      expect(label).to.equal('orca-whirlpool');
+      expect(order.outputAmount.toNumber()).to.equal(750);
+    });
+
+    it('rejects an empty route label', async () => {
+      const computationId = randomBytes(32);
+      const destination = Keypair.generate().publicKey;
+      await submitOrder(computationId, destination);
+
+      try {
+        await postResult(computationId, destination, new Array(32).fill(0));
+
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('EmptyRoute');
+      }
+    });
+  });
+
+  describe('settlement token context', () => {
+    const solMint = new PublicKey('So11111111111111111111111111111111111111112');
+
+    async function postResult(
+      computationId: Buffer,
+      destination: PublicKey,
+      outputMint: PublicKey,
+      outputDecimals: number
+    ) {
+      await program.methods
+        .executeSwapCallback(
+          randomBytes(32),
+          Array.from(randomBytes(12)),
+          new anchor.BN(2_500_000),
+          new anchor.BN(0), // fully filled
+          mxeId,
+          routeLabel('jupiter'),
+          outputMint,
+          outputDecimals
+        )
+        .accounts({
+          swapOrder: swapOrderPda(computationId),
+          orderBook: orderBookPda,
+          owner: owner.publicKey,
+          outputDestination: destination,
+          clusterAuthority: cluster.publicKey,
+        })
+        .signers([cluster])
+        .rpc();
+    }
+
+    const cases: [string, PublicKey, number][] = [
+      ['USDC', usdcMint, 6],
+      ['SOL', solMint, 9],
+    ];
+
+    for (const [name, mint, decimals] of cases) {
+      it(`stores the output mint and decimals for ${name}`, async () => {
+        const computationId = randomBytes(32);
+        const destination = Keypair.generate().publicKey;
+        await submitOrder(computationId, destination);
+
+        let order = await program.account.swapOrder.fetch(swapOrderPda(computationId));
+        expect(order.outputMint.toBase58()).to.equal(PublicKey.default.toBase58());
+
+        await postResult(computationId, destination, mint, decimals);
+
+        order = await program.account.swapOrder.fetch(swapOrderPda(computationId));
+        expect(order.outputMint.toBase58()).to.equal(mint.toBase58());
+        expect(order.outputDecimals).to.equal(decimals);
+        expect(order.outputAmount.toNumber()).to.equal(2_500_000);
+      });
+    }
+  });
+
+  describe('vault settlement', () => {
+    const vaultProgram = anchor.workspace.MakoraVault as Program<MakoraVault>;
+    const vaultOwner = Keypair.generate();
+    const solMint = new PublicKey('So11111111111111111111111111111111111111112');
+    let vaultPda: PublicKey;
+
+    before(async () => {
+      for (const kp of [cluster, vaultOwner]) {
+        const sig = await provider.connection.requestAirdrop(kp.publicKey, 2 * LAMPORTS_PER_SOL);
+        await provider.connection.confirmTransaction(sig);
+      }
+
+      [vaultPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from('vault'), vaultOwner.publicKey.toBuffer()],
+        vaultProgram.programId
+      );
+
+      await vaultProgram.methods
+        .initialize(Keypair.generate().publicKey, 1, 50, 0, 100, 10, new anchor.BN(0), 40, new anchor.BN(0))
+        .accounts({
+          owner: vaultOwner.publicKey,
+          vault: vaultPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([vaultOwner])
+        .rpc();
+    });
+
+    async function settleIntoVault(computationId: Buffer, outputAmount: number, outputMint: PublicKey) {
+      await program.methods
+        .executeSwapCallback(
+          randomBytes(32),
+          Array.from(randomBytes(12)),
+          new anchor.BN(outputAmount),
+          new anchor.BN(0), // fully filled
+          mxeId,
+          routeLabel('jupiter'),
+          outputMint,
+          9
+        )
+        .accounts({
+          swapOrder: swapOrderPda(computationId),
+          orderBook: orderBookPda,
+          owner: owner.publicKey,
+          outputDestination: vaultPda,
+          clusterAuthority: cluster.publicKey,
+          destinationVault: vaultPda,
+          vaultProgram: vaultProgram.programId,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([cluster])
+        .rpc();
+    }
+
+    it('credits the output amount to the destination vault', async () => {
+      const computationId = randomBytes(32);
+      await submitOrder(computationId, vaultPda);
+
+      const depositedBefore = (await vaultProgram.account.vault.fetch(vaultPda)).totalDeposited.toNumber();
+      const balanceBefore = await provider.connection.getBalance(vaultPda);
+
+      await settleIntoVault(computationId, 300_000_000, solMint);
+
+      const vault = await vaultProgram.account.vault.fetch(vaultPda);
+      expect(vault.totalDeposited.toNumber() - depositedBefore).to.equal(300_000_000);
+      expect((await provider.connection.getBalance(vaultPda)) - balanceBefore).to.equal(300_000_000);
+
+      const order = await program.account.swapOrder.fetch(swapOrderPda(computationId));
+      expect(order.status).to.deep.equal({ settlementPending: {} });
+      expect(order.outputAmount.toNumber()).to.equal(300_000_000);
+    });
+
+    it('rejects vault settlement of a non-SOL output', async () => {
+      const computationId = randomBytes(32);
+      await submitOrder(computationId, vaultPda);
+
+      try {
+        await settleIntoVault(computationId, 2_500_000, usdcMint);
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('VaultSettlementNotSol');
+      }
+    });
+
+    it('rejects a vault that is not the order destination', async () => {
+      const computationId = randomBytes(32);
+      const destination = Keypair.generate().publicKey;
+      await submitOrder(computationId, destination);
+
+      try {
+        await program.methods
+          .executeSwapCallback(
+            randomBytes(32),
+            Array.from(randomBytes(12)),
+            new anchor.BN(100_000_000),
+            new anchor.BN(0), // fully filled
+            mxeId,
+            routeLabel('jupiter'),
+            solMint,
+            9
+          )
+          .accounts({
+            swapOrder: swapOrderPda(computationId),
+            orderBook: orderBookPda,
+            owner: owner.publicKey,
+            outputDestination: destination,
+            clusterAuthority: cluster.publicKey,
+            destinationVault: vaultPda,
+            vaultProgram: vaultProgram.programId,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([cluster])
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('OutputDestinationMismatch');
+      }
+    });
+  });
+
+  describe('partial fills', () => {
+
+    async function postFill(
+      computationId: Buffer,
+      destination: PublicKey,
+      outputAmount: number,
+      remainingAmount: number
+    ) {
+      await program.methods
+        .executeSwapCallback(
+          randomBytes(32),
+          Array.from(randomBytes(12)),
+          new anchor.BN(outputAmount),
+          new anchor.BN(remainingAmount),
+          mxeId,
+          routeLabel('jupiter'),
+          usdcMint,
+          6
+        )
+        .accounts({
+          swapOrder: swapOrderPda(computationId),
+          orderBook: orderBookPda,
+          owner: owner.publicKey,
+          outputDestination: destination,
+          clusterAuthority: cluster.publicKey,
+        })
+        .signers([cluster])
+        .rpc();
+    }
+
+    it('completes an order over two partial fills', async () => {
+      const computationId = randomBytes(32);
+      const destination = Keypair.generate().publicKey;
+      await submitOrder(computationId, destination);
+      const settledBefore = (await program.account.orderBook.fetch(orderBookPda)).settledCount.toNumber();
+
+      await postFill(computationId, destination, 600, 400);
+
+      let order = await program.account.swapOrder.fetch(swapOrderPda(computationId));
+      expect(order.status).to.deep.equal({ partiallyFilled: {} });
+      expect(order.filledAmount.toNumber()).to.equal(600);
+      expect(order.remainingAmount.toNumber()).to.equal(400);
+
+      await postFill(computationId, destination, 400, 0);
+
+      order = await program.account.swapOrder.fetch(swapOrderPda(computationId));
+      expect(order.status).to.deep.equal({ settlementPending: {} });
+      expect(order.filledAmount.toNumber()).to.equal(1_000);
+      expect(order.outputAmount.toNumber()).to.equal(1_000);
+
+      // Nothing counts as settled until the full fill is confirmed
+      let book = await program.account.orderBook.fetch(orderBookPda);
+      expect(book.settledCount.toNumber()).to.equal(settledBefore);
+
+      await program.methods
+        .completeSettlement()
+        .accounts({
+          swapOrder: swapOrderPda(computationId),
+          orderBook: orderBookPda,
+          owner: owner.publicKey,
+          outputDestination: destination,
+          clusterAuthority: cluster.publicKey,
+        })
+        .signers([cluster])
+        .rpc();
+
+      book = await program.account.orderBook.fetch(orderBookPda);
+      expect(book.settledCount.toNumber()).to.equal(settledBefore + 1);
+    });
+
+    it('rejects completing a partially filled order', async () => {
+      const computationId = randomBytes(32);
+      const destination = Keypair.generate().publicKey;
+      await submitOrder(computationId, destination);
+      await postFill(computationId, destination, 600, 400);
+
+      try {
+        await program.methods
+          .completeSettlement()
+          .accounts({
+            swapOrder: swapOrderPda(computationId),
+            orderBook: orderBookPda,
+            owner: owner.publicKey,
+            outputDestination: destination,
+            clusterAuthority: cluster.publicKey,
+          })
+          .signers([cluster])
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('InvalidStatusTransition');
+      }
+    });
+
+    it('rejects a fill that does not reduce the remaining amount', async () => {
+      const computationId = randomBytes(32);
+      const destination = Keypair.generate().publicKey;
+      await submitOrder(computationId, destination);
+      await postFill(computationId, destination, 600, 400);
+
+      try {
+        await postFill(computationId, destination, 100, 400);
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('InvalidFill');
+      }
+    });
+
+    it('expires a partially filled order past its deadline', async () => {
+      const computationId = randomBytes(32);
+      const destination = Keypair.generate().publicKey;
+      await submitOrder(computationId, destination, 2);
+      await postFill(computationId, destination, 600, 400);
+      const before = await program.account.orderBook.fetch(orderBookPda);
+
+      await new Promise((resolve) => setTimeout(resolve, 3_000));
+      await program.methods
+        .expireConfidentialSwap()
+        .accounts({
+          swapOrder: swapOrderPda(computationId),
+          orderBook: orderBookPda,
+          owner: owner.publicKey,
+        })
+        .rpc();
+
+      const order = await program.account.swapOrder.fetch(swapOrderPda(computationId));
+      expect(order.status).to.deep.equal({ expired: {} });
+      expect(order.filledAmount.toNumber()).to.equal(600);
+      const after = await program.account.orderBook.fetch(orderBookPda);
+      expect(after.pendingCount).to.equal(before.pendingCount - 1);
+    });
+  });
+
+  describe('duplicate computation ids', () => {
+    it('rejects resubmitting a computation id to the same book', async () => {
+      const computationId = randomBytes(32);
+      await submitOrder(computationId);
+      const countBefore = (await program.account.orderBook.fetch(orderBookPda)).orderCount.toNumber();
+
+      try {
+        await submitOrder(computationId);
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        // The owner's SwapOrder PDA for this id already exists, so the
+        // account init rejects it before the book-level check runs
+        expect(err.toString()).to.match(/already in use|DuplicateComputationId/);
+      }
+
+      const book = await program.account.orderBook.fetch(orderBookPda);
+      expect(book.orderCount.toNumber()).to.equal(countBefore);
+      const matches = book.recentOrders.filter((o: any) =>
+        Buffer.from(o.computationId).equals(computationId)
+      );
+      expect(matches).to.have.length(1);
+    });
+  });
+
+  describe('public swaps', () => {
+
+    async function submitPublicOrder(computationId: Buffer, destination: PublicKey) {
+      return program.methods
+        .submitPublicSwap(
+          Buffer.from(JSON.stringify({ inputMint: 'SOL', outputMint: 'USDC', amount: 1_000 })),
+          Array.from(computationId),
+          mxeId,
+          destination,
+          false,
+          0
+        )
+        .accounts({
+          swapOrder: swapOrderPda(computationId),
+          orderBook: orderBookPda,
+          computationRecord: computationRecordPda(orderBookPda, computationId),
+          owner: owner.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    }
+
+    it('records a public order with the same lifecycle', async () => {
+      const computationId = randomBytes(32);
+      const destination = Keypair.generate().publicKey;
+      await submitPublicOrder(computationId, destination);
+
+      const order = await program.account.swapOrder.fetch(swapOrderPda(computationId));
+      expect(order.isConfidential).to.be.false;
+      expect(order.status).to.deep.equal({ pending: {} });
+      expect(order.clientPubkey.every((b: number) => b === 0)).to.be.true;
+
+      const book = await program.account.orderBook.fetch(orderBookPda);
+      const entry = book.recentOrders.find((o: any) =>
+        Buffer.from(o.computationId).equals(computationId)
+      );
+      expect(entry).to.not.be.undefined;
+    });
+
+    it('settles a public order through execute_swap_callback', async () => {
+      const computationId = randomBytes(32);
+      const destination = Keypair.generate().publicKey;
+      await submitPublicOrder(computationId, destination);
+      const settledBefore = (await program.account.orderBook.fetch(orderBookPda)).settledCount.toNumber();
+
+      await program.methods
+        .executeSwapCallback(
+          Buffer.alloc(0),
+          Array.from(Buffer.alloc(12)),
+          new anchor.BN(1_000),
+          new anchor.BN(0), // fully filled
+          mxeId,
+          routeLabel('jupiter'),
+          usdcMint,
+          6
+        )
+        .accounts({
+          swapOrder: swapOrderPda(computationId),
+          orderBook: orderBookPda,
+          owner: owner.publicKey,
+          outputDestination: destination,
+          clusterAuthority: cluster.publicKey,
+        })
+        .signers([cluster])
+        .rpc();
+
+      await program.methods
+        .completeSettlement()
+        .accounts({
+          swapOrder: swapOrderPda(computationId),
+          orderBook: orderBookPda,
+          owner: owner.publicKey,
+          outputDestination: destination,
+          clusterAuthority: cluster.publicKey,
+        })
+        .signers([cluster])
+        .rpc();
+
+      const order = await program.account.swapOrder.fetch(swapOrderPda(computationId));
+      expect(order.status).to.deep.equal({ settled: {} });
+      expect(order.isConfidential).to.be.false;
+      expect(order.outputAmount.toNumber()).to.equal(1_000);
+
+      const book = await program.account.orderBook.fetch(orderBookPda);
+      expect(book.settledCount.toNumber()).to.equal(settledBefore + 1);
+    });
+
+    it('marks confidential orders as confidential', async () => {
+      const computationId = randomBytes(32);
+      await submitOrder(computationId);
+
+      const order = await program.account.swapOrder.fetch(swapOrderPda(computationId));
+      expect(order.isConfidential).to.be.true;
+    });
+
+    it('rejects an empty public order', async () => {
+      const computationId = randomBytes(32);
+      try {
+        await program.methods
+          .submitPublicSwap(
+            Buffer.alloc(0),
+            Array.from(computationId),
+            mxeId,
+            Keypair.generate().publicKey,
+            false,
+            0
+          )
+          .accounts({
+            swapOrder: swapOrderPda(computationId),
+            orderBook: orderBookPda,
+            computationRecord: computationRecordPda(orderBookPda, computationId),
+            owner: owner.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('OrderTooSmall');
+      }
+    });
+  });
+
+  describe('submit fee', () => {
+    const SUBMIT_FEE = 1_000_000; // above the rent floor of an empty account
+    const bookOwner = Keypair.generate();
+    const collector = Keypair.generate();
+    let feeBookPda: PublicKey;
+
+    before(async () => {
+      const sig = await provider.connection.requestAirdrop(bookOwner.publicKey, LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(sig);
+
+      [feeBookPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from('order_book'), bookOwner.publicKey.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .initOrderBook(0, 0, new anchor.BN(SUBMIT_FEE), collector.publicKey, cluster.publicKey)
+        .accounts({
+          orderBook: feeBookPda,
+          authority: bookOwner.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([bookOwner])
+        .rpc();
+    });
+
+    function feeOrderPda(computationId: Buffer): PublicKey {
+      const [pda] = PublicKey.findProgramAddressSync(
+        [Buffer.from('swap_order'), bookOwner.publicKey.toBuffer(), computationId],
+        program.programId
+      );
+      return pda;
+    }
+
+    async function submitWithFee(computationId: Buffer, feeCollector = collector.publicKey) {
+      await program.methods
+        .submitConfidentialSwap(
+          randomBytes(64),
+          Array.from(randomBytes(32)),
+          Array.from(randomBytes(12)),
+          Array.from(computationId),
+          mxeId,
+          Keypair.generate().publicKey,
+          false,
+          0,
+          new anchor.BN(0), // no reference quote
+          1, // schema version
+        )
+        .accounts({
+          swapOrder: feeOrderPda(computationId),
+          orderBook: feeBookPda,
+          computationRecord: computationRecordPda(feeBookPda, computationId),
+          owner: bookOwner.publicKey,
+          feeCollector,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([bookOwner])
+        .rpc();
+    }
+
+    it('stores the fee and collector', async () => {
+      const book = await program.account.orderBook.fetch(feeBookPda);
+      expect(book.submitFee.toNumber()).to.equal(SUBMIT_FEE);
+      expect(book.feeCollector.toBase58()).to.equal(collector.publicKey.toBase58());
+    });
+
+    it('pays the collector on every submission', async () => {
+      const before = await provider.connection.getBalance(collector.publicKey);
+
+      await submitWithFee(randomBytes(32));
+      expect(await provider.connection.getBalance(collector.publicKey)).to.equal(
+        before + SUBMIT_FEE
+      );
+
+      await submitWithFee(randomBytes(32));
+      expect(await provider.connection.getBalance(collector.publicKey)).to.equal(
+        before + 2 * SUBMIT_FEE
+      );
+    });
+
+    it('rejects a collector other than the configured one', async () => {
+      try {
+        await submitWithFee(randomBytes(32), Keypair.generate().publicKey);
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('FeePaymentFailed');
+      }
+    });
+
+    it('creates no order when the owner cannot pay the fee', async () => {
+      const broke = Keypair.generate();
+      const sig = await provider.connection.requestAirdrop(broke.publicKey, LAMPORTS_PER_SOL / 20);
+      await provider.connection.confirmTransaction(sig);
+
+      const [brokeBookPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from('order_book'), broke.publicKey.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .initOrderBook(0, 0, new anchor.BN(LAMPORTS_PER_SOL), collector.publicKey, cluster.publicKey)
+        .accounts({
+          orderBook: brokeBookPda,
+          authority: broke.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([broke])
+        .rpc();
+
+      const computationId = randomBytes(32);
+      const [swapOrder] = PublicKey.findProgramAddressSync(
+        [Buffer.from('swap_order'), broke.publicKey.toBuffer(), computationId],
+        program.programId
+      );
+
+      try {
+        await program.methods
+          .submitConfidentialSwap(
+            randomBytes(64),
+            Array.from(randomBytes(32)),
+            Array.from(randomBytes(12)),
+            Array.from(computationId),
+            mxeId,
+            Keypair.generate().publicKey,
+            false,
+            0,
+            new anchor.BN(0), // no reference quote
+            1, // schema version
+          )
+          .accounts({
+            swapOrder,
+            orderBook: brokeBookPda,
+            computationRecord: computationRecordPda(brokeBookPda, computationId),
+            owner: broke.publicKey,
+            feeCollector: collector.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([broke])
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('FeePaymentFailed');
+      }
+
+      expect(await provider.connection.getAccountInfo(swapOrder)).to.be.null;
+    });
+  });
+
+  describe('execution quality', () => {
+    const parser = new anchor.EventParser(program.programId, program.coder);
+
+    async function settleWithQuote(referenceQuote: number, outputAmount: number) {
+      const computationId = randomBytes(32);
+      const destination = Keypair.generate().publicKey;
+      await submitOrder(computationId, destination, 0, referenceQuote);
+
+      const accounts = {
+        swapOrder: swapOrderPda(computationId),
+        orderBook: orderBookPda,
+        owner: owner.publicKey,
+        outputDestination: destination,
+        clusterAuthority: cluster.publicKey,
+      };
+
+      await program.methods
+        .executeSwapCallback(
+          randomBytes(32),
+          Array.from(randomBytes(12)),
+          new anchor.BN(outputAmount),
+          new anchor.BN(0),
+          mxeId,
+          routeLabel('jupiter'),
+          usdcMint,
+          6
+        )
+        .accounts(accounts)
+        .signers([cluster])
+        .rpc();
+
+      const sig = await program.methods
+        .completeSettlement()
+        .accounts(accounts)
+        .signers([cluster])
+        .rpc({ commitment: 'confirmed' });
+
+      const tx = await provider.connection.getTransaction(sig, {
+        commitment: 'confirmed',
+        maxSupportedTransactionVersion: 0,
+      });
+      const event = Array.from(parser.parseLogs(tx!.meta!.logMessages!)).find(
+        (e) => e.name === 'orderSettled'
+      );
+      expect(event).to.not.be.undefined;
+      return { order: swapOrderPda(computationId), event: event!.data };
+    }
+
+    it('stores the reference quote on the order', async () => {
+      const { order } = await settleWithQuote(1_000_000, 1_000_000);
+      const swapOrder = await program.account.swapOrder.fetch(order);
+      expect(swapOrder.referenceQuote.toNumber()).to.equal(1_000_000);
+    });
+
+    it('reports a positive improvement when the fill beats the quote', async () => {
+      const { event } = await settleWithQuote(1_000_000, 1_012_500);
+      expect(event.referenceQuote.toNumber()).to.equal(1_000_000);
+      expect(event.improvementBps.toNumber()).to.equal(125);
+    });
+
+    it('reports a negative improvement when the fill misses the quote', async () => {
+      const { event } = await settleWithQuote(2_000_000, 1_990_000);
+      expect(event.improvementBps.toNumber()).to.equal(-50);
+    });
+
+    it('reports zero improvement without a reference quote', async () => {
+      const { event } = await settleWithQuote(0, 1_000_000);
+      expect(event.improvementBps.toNumber()).to.equal(0);
+    });
+  });
+
+  describe('schema versions', () => {
+    it('accepts the default schema version on a new book', async () => {
+      const book = await program.account.orderBook.fetch(orderBookPda);
+      expect(book.minSchemaVersion).to.equal(1);
+      expect(book.maxSchemaVersion).to.equal(1);
+
+      const computationId = randomBytes(32);
+      await submitOrder(computationId);
+      const order = await program.account.swapOrder.fetch(swapOrderPda(computationId));
+      expect(order.schemaVersion).to.equal(1);
+    });
+
+    it('rejects a version outside the accepted range', async () => {
+      try {
+        await submitOrder(randomBytes(32), undefined, 0, 0, 2);
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('UnsupportedSchemaVersion');
+      }
+    });
+
+    it('accepts old and new versions while a range is open', async () => {
+      await program.methods
+        .setSchemaVersions(1, 2)
+        .accounts({ orderBook: orderBookPda, authority: owner.publicKey })
+        .rpc();
+
+      for (const version of [1, 2]) {
+        const computationId = randomBytes(32);
+        await submitOrder(computationId, undefined, 0, 0, version);
+        const order = await program.account.swapOrder.fetch(swapOrderPda(computationId));
+        expect(order.schemaVersion).to.equal(version);
+      }
+    });
+
+    it('retires a version once the minimum moves past it', async () => {
+      await program.methods
+        .setSchemaVersions(2, 2)
+        .accounts({ orderBook: orderBookPda, authority: owner.publicKey })
+        .rpc();
+
+      try {
+        await submitOrder(randomBytes(32), undefined, 0, 0, 1);
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('UnsupportedSchemaVersion');
+      }
+
+      // Restore the default for anything that runs later
+      await program.methods
+        .setSchemaVersions(1, 1)
+        .accounts({ orderBook: orderBookPda, authority: owner.publicKey })
+        .rpc();
+    });
+
+    it('rejects an empty or inverted range', async () => {
+      for (const [min, max] of [[0, 1], [3, 2]]) {
+        try {
+          await program.methods
+            .setSchemaVersions(min, max)
+            .accounts({ orderBook: orderBookPda, authority: owner.publicKey })
+            .rpc();
+          expect.fail('Should have thrown an error');
+        } catch (err: any) {
+          expect(err.toString()).to.include('InvalidSchemaRange');
+        }
+      }
+    });
+  });
+
+  describe('pending order cap', () => {
+    const capped = [randomBytes(32), randomBytes(32)];
+
+    async function setMaxPending(maxPending: number) {
+      await program.methods
+        .setMaxPending(maxPending)
+        .accounts({ orderBook: orderBookPda, authority: owner.publicKey })
+        .rpc();
+    }
+
+    it('counts submitted orders as pending', async () => {
+      const before = await program.account.orderBook.fetch(orderBookPda);
+      await submitOrder(randomBytes(32));
+
+      const after = await program.account.orderBook.fetch(orderBookPda);
+      expect(after.pendingCount).to.equal(before.pendingCount + 1);
+      expect(after.maxPending).to.equal(0);
+    });
+
+    it('accepts orders up to the cap and rejects the next one', async () => {
+      const { pendingCount } = await program.account.orderBook.fetch(orderBookPda);
+      await setMaxPending(pendingCount + 2);
+
+      for (const id of capped) {
+        await submitOrder(id);
+      }
+
+      try {
+        await submitOrder(randomBytes(32));
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('TooManyPendingOrders');
+      }
+    });
+
+    it('frees a slot when an order fails', async () => {
+      await program.methods
+        .failConfidentialSwap(1)
+        .accounts({
+          swapOrder: swapOrderPda(capped[0]),
+          orderBook: orderBookPda,
+          owner: owner.publicKey,
+          clusterAuthority: cluster.publicKey,
+        })
+        .signers([cluster])
+        .rpc();
+
+      await submitOrder(randomBytes(32));
+      const book = await program.account.orderBook.fetch(orderBookPda);
+      expect(book.pendingCount).to.equal(book.maxPending);
+    });
+
+    it('frees a slot when a partially filled order fails', async () => {
+      await setMaxPending(0);
+      const computationId = randomBytes(32);
+      const destination = Keypair.generate().publicKey;
+      await submitOrder(computationId, destination);
+      await program.methods
+        .executeSwapCallback(
+          randomBytes(32),
+          Array.from(randomBytes(12)),
+          new anchor.BN(600),
+          new anchor.BN(400),
+          mxeId,
+          routeLabel('jupiter'),
+          usdcMint,
+          6
+        )
+        .accounts({
+          swapOrder: swapOrderPda(computationId),
+          orderBook: orderBookPda,
+          owner: owner.publicKey,
+          outputDestination: destination,
+          clusterAuthority: cluster.publicKey,
+        })
+        .signers([cluster])
+        .rpc();
+      const { pendingCount } = await program.account.orderBook.fetch(orderBookPda);
+      await setMaxPending(pendingCount);
+
+      await program.methods
+        .failConfidentialSwap(2)
+        .accounts({
+          swapOrder: swapOrderPda(computationId),
+          orderBook: orderBookPda,
+          owner: owner.publicKey,
+          clusterAuthority: cluster.publicKey,
+        })
+        .signers([cluster])
+        .rpc();
+
+      const order = await program.account.swapOrder.fetch(swapOrderPda(computationId));
+      expect(order.status).to.deep.equal({ failed: {} });
+      await submitOrder(randomBytes(32));
+    });
+
+    it('frees a slot when an order expires', async () => {
+      await setMaxPending(0);
+      const computationId = randomBytes(32);
+      await submitOrder(computationId, Keypair.generate().publicKey, 1);
+      const { pendingCount } = await program.account.orderBook.fetch(orderBookPda);
+      await setMaxPending(pendingCount);
+
+      try {
+        await program.methods
+          .expireConfidentialSwap()
+          .accounts({
+            swapOrder: swapOrderPda(computationId),
+            orderBook: orderBookPda,
+            owner: owner.publicKey,
+          })
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('OrderNotExpired');
+      }
+
+      await new Promise((resolve) => setTimeout(resolve, 3_000));
+      await program.methods
+        .expireConfidentialSwap()
+        .accounts({
+          swapOrder: swapOrderPda(computationId),
+          orderBook: orderBookPda,
+          owner: owner.publicKey,
+        })
+        .rpc();
+
+      const order = await program.account.swapOrder.fetch(swapOrderPda(computationId));
+      expect(order.status).to.deep.equal({ expired: {} });
+      await submitOrder(randomBytes(32));
+    });
+
+    it('rejects a cap above the limit', async () => {
+      try {
+        await setMaxPending(1_025);
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('InvalidMaxPending');
+      } finally {
+        // Lift the cap for anything that runs later
+        await setMaxPending(0);
+      }
+    });
+  });
+
+  describe('result nonce', () => {
+
+    function postResult(computationId: Buffer, destination: PublicKey, resultNonce: number[]) {
+      return program.methods
+        .executeSwapCallback(
+          randomBytes(32),
+          resultNonce,
+          new anchor.BN(1_000),
+          new anchor.BN(0), // fully filled
+          mxeId,
+          routeLabel('jupiter'),
+          usdcMint,
+          6
+        )
+        .accounts({
+          swapOrder: swapOrderPda(computationId),
+          orderBook: orderBookPda,
+          owner: owner.publicKey,
+          outputDestination: destination,
+          clusterAuthority: cluster.publicKey,
+        })
+        .signers([cluster])
+        .rpc();
+    }
+
+    it('rejects a result encrypted under the request nonce', async () => {
+      const computationId = randomBytes(32);
+      const destination = Keypair.generate().publicKey;
+      await submitOrder(computationId, destination);
+      const order = await program.account.swapOrder.fetch(swapOrderPda(computationId));
+
+      try {
+        await postResult(computationId, destination, order.nonce);
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('NonceReuse');
+      }
+
+      const after = await program.account.swapOrder.fetch(swapOrderPda(computationId));
+      expect(after.status).to.deep.equal({ pending: {} });
+    });
+
+    it('accepts a result with a distinct nonce', async () => {
+      const computationId = randomBytes(32);
+      const destination = Keypair.generate().publicKey;
+      await submitOrder(computationId, destination);
+      const order = await program.account.swapOrder.fetch(swapOrderPda(computationId));
+
+      // Differs from the request nonce in a single byte
+      const resultNonce = [...order.nonce];
+      resultNonce[11] ^= 1;
+      await postResult(computationId, destination, resultNonce);
+
+      const after = await program.account.swapOrder.fetch(swapOrderPda(computationId));
+      expect(after.status).to.deep.equal({ settlementPending: {} });
+    });
+  });
+
+  describe('expired order sweeper', () => {
+    const cranker = Keypair.generate();
+    const parser = new anchor.EventParser(program.programId, program.coder);
+    const BOUNTY = 5_000;
+
+    before(async () => {
+      // Keep the cranker rent-exempt so it can receive a small bounty
+      const sig = await provider.connection.requestAirdrop(cranker.publicKey, LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(sig);
+
+      await program.methods
+        .setSweepBounty(new anchor.BN(BOUNTY))
+        .accounts({ orderBook: orderBookPda, authority: owner.publicKey })
+        .rpc();
+      await program.methods
+        .fundSweepPool(new anchor.BN(1_000_000))
+        .accounts({
+          orderBook: orderBookPda,
+          funder: owner.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    });
+
+    async function submitExpiring(): Promise<Buffer> {
+      const computationId = randomBytes(32);
+      await submitOrder(computationId, Keypair.generate().publicKey, 1);
+      return computationId;
+    }
+
+    function sweep(computationId: Buffer) {
+      return program.methods
+        .sweepExpiredOrder()
+        .accounts({
+          swapOrder: swapOrderPda(computationId),
+          orderBook: orderBookPda,
+          owner: owner.publicKey,
+          cranker: cranker.publicKey,
+        })
+        .signers([cranker])
+        .rpc({ commitment: 'confirmed' });
+    }
+
+    const balance = (key: PublicKey) => provider.connection.getBalance(key, 'confirmed');
+
+    it('stores the bounty and funds the pool', async () => {
+      const book = await program.account.orderBook.fetch(orderBookPda);
+      expect(book.sweepBounty.toNumber()).to.equal(BOUNTY);
+      expect(book.sweepPool.toNumber()).to.equal(1_000_000);
+    });
+
+    it('rejects sweeping an order before its deadline', async () => {
+      const computationId = await submitExpiring();
+      try {
+        await sweep(computationId);
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('OrderNotExpired');
+      }
+    });
+
+    it('pays the cranker and refunds the rent to the owner', async () => {
+      const computationId = await submitExpiring();
+      await new Promise((resolve) => setTimeout(resolve, 3_000));
+
+      const orderPda = swapOrderPda(computationId);
+      const rent = await balance(orderPda);
+      const bookBefore = await program.account.orderBook.fetch(orderBookPda);
+      const ownerBefore = await balance(owner.publicKey);
+      const crankerBefore = await balance(cranker.publicKey);
+
+      const sig = await sweep(computationId);
+
+      // The owner also paid the transaction fee
+      const tx = await provider.connection.getTransaction(sig, {
+        commitment: 'confirmed',
+        maxSupportedTransactionVersion: 0,
+      });
+      const ownerAfter = await balance(owner.publicKey);
+      expect(ownerAfter - ownerBefore).to.equal(rent - tx!.meta!.fee);
+      expect((await balance(cranker.publicKey)) - crankerBefore).to.equal(BOUNTY);
+      expect(await provider.connection.getAccountInfo(orderPda)).to.be.null;
+
+      const book = await program.account.orderBook.fetch(orderBookPda);
+      expect(book.pendingCount).to.equal(bookBefore.pendingCount - 1);
+      expect(book.sweepPool.toNumber()).to.equal(bookBefore.sweepPool.toNumber() - BOUNTY);
+      const entry = book.recentOrders.find((o: any) =>
+        Buffer.from(o.computationId).equals(computationId)
+      );
+      expect(entry!.status).to.deep.equal({ expired: {} });
+
+      const event = Array.from(parser.parseLogs(tx!.meta!.logMessages!)).find(
+        (e) => e.name === 'orderSwept'
+      );
+      expect(event!.data.bounty.toNumber()).to.equal(BOUNTY);
+      expect(event!.data.rentRefunded.toNumber()).to.equal(rent);
+    });
+
+    it('keeps a swept order\'s computation id spent', async () => {
+      const computationId = await submitExpiring();
+      await new Promise((resolve) => setTimeout(resolve, 3_000));
+      await sweep(computationId);
+
+      // The order PDA is gone, but the per-id record still blocks reuse
+      try {
+        await submitOrder(computationId);
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('DuplicateComputationId');
+      }
+      expect(await provider.connection.getAccountInfo(swapOrderPda(computationId))).to.be.null;
+    });
+
+    it('closes an order that was already expired', async () => {
+      const computationId = await submitExpiring();
+      await new Promise((resolve) => setTimeout(resolve, 3_000));
+      await program.methods
+        .expireConfidentialSwap()
+        .accounts({
+          swapOrder: swapOrderPda(computationId),
+          orderBook: orderBookPda,
+          owner: owner.publicKey,
+        })
+        .rpc();
+      const before = await program.account.orderBook.fetch(orderBookPda);
+
+      await sweep(computationId);
+
+      const after = await program.account.orderBook.fetch(orderBookPda);
+      expect(after.pendingCount).to.equal(before.pendingCount);
+      expect(await provider.connection.getAccountInfo(swapOrderPda(computationId))).to.be.null;
+    });
+
+    it('sweeps a partially filled order past its deadline', async () => {
+        const computationId = randomBytes(32);
+      const destination = Keypair.generate().publicKey;
+      await submitOrder(computationId, destination, 2);
+      await program.methods
+        .executeSwapCallback(
+          randomBytes(32),
+          Array.from(randomBytes(12)),
+          new anchor.BN(600),
+          new anchor.BN(400),
+          mxeId,
+          routeLabel('jupiter'),
+          usdcMint,
+          6
+        )
+        .accounts({
+          swapOrder: swapOrderPda(computationId),
+          orderBook: orderBookPda,
+          owner: owner.publicKey,
+          outputDestination: destination,
+          clusterAuthority: cluster.publicKey,
+        })
+        .signers([cluster])
+        .rpc();
+      const before = await program.account.orderBook.fetch(orderBookPda);
+      const crankerBefore = await balance(cranker.publicKey);
+
+      await new Promise((resolve) => setTimeout(resolve, 3_000));
+      await sweep(computationId);
+
+      const after = await program.account.orderBook.fetch(orderBookPda);
+      expect(after.pendingCount).to.equal(before.pendingCount - 1);
+      expect((await balance(cranker.publicKey)) - crankerBefore).to.equal(BOUNTY);
+      expect(await provider.connection.getAccountInfo(swapOrderPda(computationId))).to.be.null;
+    });
+
+    it('rejects sweeping a failed order', async () => {
+        const computationId = await submitExpiring();
+      await program.methods
+        .failConfidentialSwap(1)
+        .accounts({
+          swapOrder: swapOrderPda(computationId),
+          orderBook: orderBookPda,
+          owner: owner.publicKey,
+          clusterAuthority: cluster.publicKey,
+        })
+        .signers([cluster])
+        .rpc();
+
+      try {
+        await sweep(computationId);
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('OrderNotPending');
+      }
+    });
+
+    it('rejects a bounty above the maximum', async () => {
+      try {
+        await program.methods
+          .setSweepBounty(new anchor.BN(100_001))
+          .accounts({ orderBook: orderBookPda, authority: owner.publicKey })
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('InvalidSweepBounty');
+      }
+    });
+  });
+});
@@ -1,8 +1,10 @@
 import * as anchor from '@coral-xyz/anchor';
-import { Program } from '@coral-xyz/anchor';
+import { Program, BN } from '@coral-xyz/anchor';
 import { Keypair, PublicKey, SystemProgram } from '@solana/web3.js';
 import { expect } from 'chai';
+import * as fs from 'fs';
 import type { MakoraStrategy } from '../target/types/makora_strategy';
+import type { MakoraVault } from '../target/types/makora_vault';
 
 describe('makora_strategy', () => {
   const provider = anchor.AnchorProvider.env();
@@ -29,6 +31,19 @@ describe('makora_strategy', () => {
     );
   });
 
+  // Mirrors the on-chain ActionKind discriminants
+  const ActionKind = {
+    Swap: 0,
+    Stake: 1,
+    Unstake: 2,
+    Rebalance: 3,
+    Lend: 4,
+    Borrow: 5,
+    Claim: 6,
+    Other: 7,
+    Deposit: 8,
+  };
+
   function padSymbol(symbol: string): number[] {
     const bytes = Buffer.alloc(8);
     bytes.write(symbol);
@@ -52,6 +67,7 @@ describe('makora_strategy', () => {
         5,  // max actions per cycle
         allocSymbols,
         Buffer.from(allocPcts),
+        [], // no allocation metadata
       )
       .accounts({
         owner: owner.publicKey,
@@ -92,6 +108,8 @@ describe('makora_strategy', () => {
         3,
         newAllocSymbols,
         Buffer.from(newAllocPcts),
+        null, // default slippage cap
+        [], // no allocation metadata
       )
       .accounts({
         authority: owner.publicKey,
@@ -127,6 +145,8 @@ describe('makora_strategy', () => {
         5,
         allocSymbols,
         Buffer.from(allocPcts),
+        null, // default slippage cap
+        [], // no allocation metadata
       )
       .accounts({
         authority: agentAuthority.publicKey,
@@ -143,7 +163,7 @@ describe('makora_strategy', () => {
   it('logs an action to the audit trail', async () => {
     await program.methods
       .logAction(
-        'stake',
+        ActionKind.Stake,
         'marinade',
         'Stake 5 SOL via Marinade for mSOL',
         true,
@@ -173,7 +193,7 @@ describe('makora_strategy', () => {
     for (let i = 0; i < 3; i++) {
       await program.methods
         .logAction(
-          'swap',
+          ActionKind.Swap,
           'jupiter',
           `Swap ${i + 1} SOL to USDC`,
           true,
@@ -198,52 +218,6 @@ describe('makora_strategy', () => {
     expect(lastEntry.success).to.be.false;
   });
 
-  it('updates permissions (owner only)', async () => {
-    const newAgent = Keypair.generate();
-
-    await program.methods
-      .updatePermissions(
-        newAgent.publicKey,
-        1, // auto mode
-      )
-      .accounts({
-        owner: owner.publicKey,
-        strategyAccount: strategyPda,
-      })
-      .rpc();
-
-    const strategy = await program.account.strategyAccount.fetch(strategyPda);
-    expect(strategy.agentAuthority.toBase58()).to.equal(newAgent.publicKey.toBase58());
-    expect(strategy.mode).to.deep.equal({ auto: {} });
-  });
-
-  it('rejects permissions update from non-owner', async () => {
-    const randomSigner = Keypair.generate();
-    const sig = await provider.connection.requestAirdrop(
-      randomSigner.publicKey,
-      1_000_000_000
-    );
-    await provider.connection.confirmTransaction(sig);
-
-    try {
-      await program.methods
-        .updatePermissions(
-          randomSigner.publicKey,
-          0,
-        )
-        .accounts({
-          owner: randomSigner.publicKey,
-          strategyAccount: strategyPda,
-        })
-        .signers([randomSigner])
-        .rpc();
-
-      expect.fail('Should have thrown an error');
-    } catch (err: any) {
-      expect(err.toString()).to.include('Error');
-    }
-  });
-
   it('rejects invalid strategy type', async () => {
     try {
       await program.methods
@@ -253,6 +227,8 @@ describe('makora_strategy', () => {
           5,
           [],
           Buffer.from([]),
+          null, // default slippage cap
+          [], // no allocation metadata
         )
         .accounts({
           authority: owner.publicKey,
@@ -281,6 +257,8 @@ describe('makora_strategy', () => {
           5,
           allocSymbols,
           Buffer.from(allocPcts),
+          null, // default slippage cap
+          [], // no allocation metadata
         )
         .accounts({
           authority: owner.publicKey,
@@ -293,4 +271,1408 @@ describe('makora_strategy', () => {
       expect(err.toString()).to.include('InvalidAllocationSum');
     }
   });
+
+  // update_strategy bumps the cycle, resetting the per-cycle action budget
+  async function startCycle(maxActionsPerCycle: number) {
+    await program.methods
+      .updateStrategy(
+        0,
+        40,
+        maxActionsPerCycle,
+        [],
+        Buffer.from([]),
+        null, // default slippage cap
+        [], // no allocation metadata
+      )
+      .accounts({
+        authority: owner.publicKey,
+        strategyAccount: strategyPda,
+      })
+      .rpc();
+  }
+
+  describe('symbol registry', () => {
+    let registryPda: PublicKey;
+
+    before(() => {
+      [registryPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from('symbol_registry')],
+        program.programId
+      );
+    });
+
+    it('registers a batch of symbols', async () => {
+      await program.methods
+        .initSymbolRegistry()
+        .accounts({
+          authority: owner.publicKey,
+          symbolRegistry: registryPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const entries = ['SOL', 'mSOL', 'USDC'].map((symbol) => ({
+        symbol: padSymbol(symbol),
+        mint: Keypair.generate().publicKey,
+      }));
+
+      await program.methods
+        .registerSymbolsBatch(entries)
+        .accounts({
+          authority: owner.publicKey,
+          symbolRegistry: registryPda,
+        })
+        .rpc();
+
+      const registry = await program.account.symbolRegistry.fetch(registryPda);
+      expect(registry.entries.length).to.equal(3);
+    });
+
+    it('rejects duplicates within a batch', async () => {
+      const mint = Keypair.generate().publicKey;
+
+      try {
+        await program.methods
+          .registerSymbolsBatch([
+            { symbol: padSymbol('JUP'), mint },
+            { symbol: padSymbol('JUP'), mint },
+          ])
+          .accounts({
+            authority: owner.publicKey,
+            symbolRegistry: registryPda,
+          })
+          .rpc();
+
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('DuplicateSymbol');
+      }
+    });
+
+    it('rejects symbols that are already registered', async () => {
+      try {
+        await program.methods
+          .registerSymbolsBatch([
+            { symbol: padSymbol('USDC'), mint: Keypair.generate().publicKey },
+          ])
+          .accounts({
+            authority: owner.publicKey,
+            symbolRegistry: registryPda,
+          })
+          .rpc();
+
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('DuplicateSymbol');
+      }
+    });
+
+    it('deregisters a symbol', async () => {
+      await program.methods
+        .deregisterSymbol(padSymbol('mSOL'))
+        .accounts({
+          authority: owner.publicKey,
+          symbolRegistry: registryPda,
+        })
+        .rpc();
+
+      const registry = await program.account.symbolRegistry.fetch(registryPda);
+      expect(registry.entries.length).to.equal(2);
+    });
+  });
+
+  describe('action cooldowns', () => {
+    before(async () => {
+      await startCycle(5);
+    });
+
+    async function logRebalance() {
+      await program.methods
+        .logAction(
+          ActionKind.Rebalance,
+          'jupiter',
+          'Rebalance to target allocation',
+          true,
+          true,
+        )
+        .accounts({
+          authority: owner.publicKey,
+          strategyAccount: strategyPda,
+          auditTrail: auditPda,
+          owner: owner.publicKey,
+        })
+        .rpc();
+    }
+
+    it('sets a cooldown for an action type', async () => {
+      await program.methods
+        .setActionCooldown(ActionKind.Rebalance, 3600)
+        .accounts({
+          owner: owner.publicKey,
+          strategyAccount: strategyPda,
+        })
+        .rpc();
+
+      const strategy = await program.account.strategyAccount.fetch(strategyPda);
+      const cooldown = strategy.actionCooldowns[0];
+      expect(Buffer.from(cooldown.actionType).toString().replace(/\0/g, '')).to.equal('rebalance');
+      expect(cooldown.cooldownSecs).to.equal(3600);
+    });
+
+    it('rejects a second executed action within the cooldown', async () => {
+      await logRebalance();
+
+      try {
+        await logRebalance();
+
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('ActionTypeOnCooldown');
+      }
+    });
+
+    it('allows the action again once the cooldown is cleared', async () => {
+      await program.methods
+        .setActionCooldown(ActionKind.Rebalance, 0)
+        .accounts({
+          owner: owner.publicKey,
+          strategyAccount: strategyPda,
+        })
+        .rpc();
+
+      await logRebalance();
+    });
+  });
+
+  describe('actions per cycle', () => {
+    async function logSwap(executed: boolean) {
+      await program.methods
+        .logAction(
+          ActionKind.Swap,
+          'jupiter',
+          'Swap SOL to USDC',
+          executed,
+          true,
+        )
+        .accounts({
+          authority: owner.publicKey,
+          strategyAccount: strategyPda,
+          auditTrail: auditPda,
+          owner: owner.publicKey,
+        })
+        .rpc();
+    }
+
+    before(async () => {
+      await startCycle(2);
+    });
+
+    it('does not count advisory logs toward the limit', async () => {
+      await logSwap(false);
+      await logSwap(false);
+      await logSwap(false);
+
+      const strategy = await program.account.strategyAccount.fetch(strategyPda);
+      expect(strategy.actionsThisCycle).to.equal(0);
+    });
+
+    it('rejects the N+1th executed action in a cycle', async () => {
+      await logSwap(true);
+      await logSwap(true);
+
+      try {
+        await logSwap(true);
+
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('MaxActionsExceeded');
+      }
+    });
+
+    it('resets the counter when a new cycle starts', async () => {
+      await startCycle(2);
+
+      const strategy = await program.account.strategyAccount.fetch(strategyPda);
+      expect(strategy.actionsThisCycle).to.equal(0);
+
+      await logSwap(true);
+    });
+  });
+
+  describe('action kinds', () => {
+    for (const [name, kind] of Object.entries(ActionKind)) {
+      it(`records ${name} as kind ${kind}`, async () => {
+        await program.methods
+          .logAction(
+            kind,
+            'jupiter',
+            `Advisory ${name}`,
+            false,
+            true,
+          )
+          .accounts({
+            authority: owner.publicKey,
+            strategyAccount: strategyPda,
+            auditTrail: auditPda,
+            owner: owner.publicKey,
+          })
+          .rpc();
+
+        const audit = await program.account.auditTrail.fetch(auditPda);
+        const latest = audit.entries[(audit.head - 1) % audit.entries.length];
+        expect(latest.actionKind).to.equal(kind);
+      });
+    }
+
+    it('rejects an unknown action kind', async () => {
+      try {
+        await program.methods
+          .logAction(
+            8,
+            'jupiter',
+            'Unknown action',
+            false,
+            true,
+          )
+          .accounts({
+            authority: owner.publicKey,
+            strategyAccount: strategyPda,
+            auditTrail: auditPda,
+            owner: owner.publicKey,
+          })
+          .rpc();
+
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('InvalidActionKind');
+      }
+    });
+  });
+
+  describe('vault balance snapshot', () => {
+    const vaultProgram = anchor.workspace.MakoraVault as Program<MakoraVault>;
+    const vaultOwner = Keypair.generate();
+    let ownerStrategyPda: PublicKey;
+    let ownerAuditPda: PublicKey;
+    let vaultPda: PublicKey;
+
+    before(async () => {
+      const sig = await provider.connection.requestAirdrop(vaultOwner.publicKey, 2_000_000_000);
+      await provider.connection.confirmTransaction(sig);
+
+      [ownerStrategyPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from('strategy'), vaultOwner.publicKey.toBuffer()],
+        program.programId
+      );
+      [ownerAuditPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from('audit'), vaultOwner.publicKey.toBuffer()],
+        program.programId
+      );
+      [vaultPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from('vault'), vaultOwner.publicKey.toBuffer()],
+        vaultProgram.programId
+      );
+
+      await vaultProgram.methods
+        .initialize(agentAuthority.publicKey, 0, 50, 0, 100, 10, new BN(0), 40, new BN(0))
+        .accounts({
+          owner: vaultOwner.publicKey,
+          vault: vaultPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([vaultOwner])
+        .rpc();
+
+      await vaultProgram.methods
+        .deposit(new BN(500_000_000))
+        .accounts({
+          owner: vaultOwner.publicKey,
+          vault: vaultPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([vaultOwner])
+        .rpc();
+
+      await program.methods
+        .initialize(agentAuthority.publicKey, 0, 0, 40, 5, [], Buffer.from([]), [])
+        .accounts({
+          owner: vaultOwner.publicKey,
+          strategyAccount: ownerStrategyPda,
+          auditTrail: ownerAuditPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([vaultOwner])
+        .rpc();
+    });
+
+    it('records the vault balance on the audit entry', async () => {
+      await program.methods
+        .logAction(
+          ActionKind.Swap,
+          'jupiter',
+          'Swap 0.1 SOL to USDC',
+          true,
+          true,
+        )
+        .accounts({
+          authority: vaultOwner.publicKey,
+          strategyAccount: ownerStrategyPda,
+          auditTrail: ownerAuditPda,
+          owner: vaultOwner.publicKey,
+          vault: vaultPda,
+        })
+        .signers([vaultOwner])
+        .rpc();
+
+      const vault = await vaultProgram.account.vault.fetch(vaultPda);
+      const currentBalance = vault.totalDeposited
+        .sub(vault.totalWithdrawn)
+        .sub(vault.inSessionAmount);
+
+      const audit = await program.account.auditTrail.fetch(ownerAuditPda);
+      expect(audit.entries[0].balanceAfter.toString()).to.equal(currentBalance.toString());
+    });
+
+    it("rejects a vault belonging to another owner", async () => {
+      try {
+        await program.methods
+          .logAction(
+            ActionKind.Swap,
+            'jupiter',
+            'Swap with foreign vault',
+            false,
+            true,
+          )
+          .accounts({
+            authority: owner.publicKey,
+            strategyAccount: strategyPda,
+            auditTrail: auditPda,
+            owner: owner.publicKey,
+            vault: vaultPda,
+          })
+          .rpc();
+
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('VaultOwnerMismatch');
+      }
+    });
+  });
+
+  describe('mode sync', () => {
+    const vaultProgram = anchor.workspace.MakoraVault as Program<MakoraVault>;
+    const modeOwner = Keypair.generate();
+    let modeStrategyPda: PublicKey;
+    let vaultPda: PublicKey;
+
+    before(async () => {
+      const sig = await provider.connection.requestAirdrop(modeOwner.publicKey, 1_000_000_000);
+      await provider.connection.confirmTransaction(sig);
+
+      [modeStrategyPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from('strategy'), modeOwner.publicKey.toBuffer()],
+        program.programId
+      );
+      const [modeAuditPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from('audit'), modeOwner.publicKey.toBuffer()],
+        program.programId
+      );
+      [vaultPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from('vault'), modeOwner.publicKey.toBuffer()],
+        vaultProgram.programId
+      );
+
+      // Vault in auto mode, strategy in advisory mode
+      await vaultProgram.methods
+        .initialize(agentAuthority.publicKey, 1, 50, 0, 100, 10, new BN(0), 40, new BN(0))
+        .accounts({
+          owner: modeOwner.publicKey,
+          vault: vaultPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([modeOwner])
+        .rpc();
+
+      await program.methods
+        .initialize(agentAuthority.publicKey, 0, 0, 40, 5, [], Buffer.from([]), [])
+        .accounts({
+          owner: modeOwner.publicKey,
+          strategyAccount: modeStrategyPda,
+          auditTrail: modeAuditPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([modeOwner])
+        .rpc();
+    });
+
+    it('copies the vault mode onto the strategy', async () => {
+      await program.methods
+        .syncMode()
+        .accounts({
+          authority: agentAuthority.publicKey,
+          strategyAccount: modeStrategyPda,
+          vault: vaultPda,
+        })
+        .signers([agentAuthority])
+        .rpc();
+
+      const strategy = await program.account.strategyAccount.fetch(modeStrategyPda);
+      const vault = await vaultProgram.account.vault.fetch(vaultPda);
+      expect(strategy.mode).to.deep.equal(vault.mode);
+      expect(strategy.mode).to.deep.equal({ auto: {} });
+    });
+
+    it('rejects a manual mode that disagrees with the vault', async () => {
+      try {
+        await program.methods
+          .updatePermissions(agentAuthority.publicKey, 0) // advisory
+          .accounts({
+            owner: modeOwner.publicKey,
+            strategyAccount: modeStrategyPda,
+            vault: vaultPda,
+          })
+          .signers([modeOwner])
+          .rpc();
+
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('ModeMismatch');
+      }
+    });
+
+    it('updates permissions when the mode matches the vault (owner only)', async () => {
+      const newAgent = Keypair.generate();
+
+      await program.methods
+        .updatePermissions(newAgent.publicKey, 1) // auto, as the vault
+        .accounts({
+          owner: modeOwner.publicKey,
+          strategyAccount: modeStrategyPda,
+          vault: vaultPda,
+        })
+        .signers([modeOwner])
+        .rpc();
+
+      const strategy = await program.account.strategyAccount.fetch(modeStrategyPda);
+      expect(strategy.agentAuthority.toBase58()).to.equal(newAgent.publicKey.toBase58());
+      expect(strategy.mode).to.deep.equal({ auto: {} });
+    });
+
+    it('rejects permissions update from non-owner', async () => {
+      const randomSigner = Keypair.generate();
+      const sig = await provider.connection.requestAirdrop(randomSigner.publicKey, 1_000_000_000);
+      await provider.connection.confirmTransaction(sig);
+
+      try {
+        await program.methods
+          .updatePermissions(randomSigner.publicKey, 1)
+          .accounts({
+            owner: randomSigner.publicKey,
+            strategyAccount: modeStrategyPda,
+            vault: vaultPda,
+          })
+          .signers([randomSigner])
+          .rpc();
+
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('UnauthorizedPermissionsUpdate');
+      }
+    });
+
+    it('rejects a vault that is not the owner\'s', async () => {
+      const otherOwner = Keypair.generate();
+      const sig = await provider.connection.requestAirdrop(otherOwner.publicKey, 1_000_000_000);
+      await provider.connection.confirmTransaction(sig);
+      const [otherVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from('vault'), otherOwner.publicKey.toBuffer()],
+        vaultProgram.programId
+      );
+      await vaultProgram.methods
+        .initialize(agentAuthority.publicKey, 1, 50, 0, 100, 10, new BN(0), 40, new BN(0))
+        .accounts({
+          owner: otherOwner.publicKey,
+          vault: otherVault,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([otherOwner])
+        .rpc();
+
+      try {
+        await program.methods
+          .updatePermissions(agentAuthority.publicKey, 1)
+          .accounts({
+            owner: modeOwner.publicKey,
+            strategyAccount: modeStrategyPda,
+            vault: otherVault,
+          })
+          .signers([modeOwner])
+          .rpc();
+
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('ConstraintSeeds');
+      }
+    });
+  });
+
+  describe('allocation history', () => {
+    let historyPda: PublicKey;
+
+    before(() => {
+      [historyPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from('allocation_history'), owner.publicKey.toBuffer()],
+        program.programId
+      );
+    });
+
+    async function setAllocation(symbols: string[], pcts: number[]) {
+      await program.methods
+        .updateStrategy(0, 40, 5, symbols.map(padSymbol), Buffer.from(pcts), null, [])
+        .accounts({
+          authority: owner.publicKey,
+          strategyAccount: strategyPda,
+        })
+        .rpc();
+    }
+
+    async function snapshot() {
+      await program.methods
+        .snapshotAllocation()
+        .accounts({
+          authority: owner.publicKey,
+          strategyAccount: strategyPda,
+          allocationHistory: historyPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    }
+
+    it('stores snapshots readable newest first', async () => {
+      const allocations: [string[], number[]][] = [
+        [['SOL'], [100]],
+        [['SOL', 'USDC'], [50, 50]],
+        [['SOL', 'mSOL', 'USDC'], [40, 30, 30]],
+      ];
+
+      for (const [symbols, pcts] of allocations) {
+        await setAllocation(symbols, pcts);
+        await snapshot();
+      }
+
+      const history = await program.account.allocationHistory.fetch(historyPda);
+      expect(history.owner.toBase58()).to.equal(owner.publicKey.toBase58());
+      expect(history.count).to.equal(3);
+
+      // Walk back from head, as off-chain readers do
+      const capacity = history.snapshots.length;
+      const newestFirst = [0, 1, 2].map(
+        (i) => history.snapshots[(history.head + capacity - 1 - i) % capacity]
+      );
+
+      newestFirst.forEach((snap: any, i: number) => {
+        const [symbols, pcts] = allocations[allocations.length - 1 - i];
+        expect(snap.allocationCount).to.equal(symbols.length);
+        expect(snap.allocation[0].targetPct).to.equal(pcts[0]);
+        expect(snap.timestamp.toNumber()).to.be.greaterThan(0);
+      });
+      expect(newestFirst[0].timestamp.toNumber()).to.be.at.least(newestFirst[2].timestamp.toNumber());
+    });
+  });
+
+  describe('slippage cap', () => {
+    async function setStrategy(strategyType: number, maxSlippageBps: number | null) {
+      await program.methods
+        .updateStrategy(strategyType, 40, 5, [], Buffer.from([]), maxSlippageBps, [])
+        .accounts({
+          authority: owner.publicKey,
+          strategyAccount: strategyPda,
+        })
+        .rpc();
+    }
+
+    async function validate(slippageBps: number) {
+      await program.methods
+        .validateActionSlippage(slippageBps)
+        .accounts({ strategyAccount: strategyPda })
+        .rpc();
+    }
+
+    const defaults: [string, number, number][] = [
+      ['yield', 0, 30],
+      ['trading', 1, 100],
+      ['rebalance', 2, 50],
+      ['liquidity', 3, 50],
+    ];
+
+    for (const [name, strategyType, expected] of defaults) {
+      it(`defaults to ${expected} bps for ${name} strategies`, async () => {
+        await setStrategy(strategyType, null);
+
+        const strategy = await program.account.strategyAccount.fetch(strategyPda);
+        expect(strategy.maxSlippageBps).to.equal(expected);
+      });
+    }
+
+    it('uses an explicit cap over the default', async () => {
+      await setStrategy(0, 250);
+
+      const strategy = await program.account.strategyAccount.fetch(strategyPda);
+      expect(strategy.maxSlippageBps).to.equal(250);
+    });
+
+    it('accepts slippage within the cap', async () => {
+      await setStrategy(0, null);
+      await validate(30);
+    });
+
+    it('rejects slippage above the cap', async () => {
+      await setStrategy(0, null);
+
+      try {
+        await validate(31);
+
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('SlippageExceeded');
+      }
+    });
+  });
+
+  describe('audit string encoding', () => {
+    async function logAdvisory(protocol: string, description: string) {
+      await program.methods
+        .logAction(ActionKind.Other, protocol, description, false, true)
+        .accounts({
+          authority: owner.publicKey,
+          strategyAccount: strategyPda,
+          auditTrail: auditPda,
+          owner: owner.publicKey,
+        })
+        .rpc();
+    }
+
+    it('rejects an emoji in the description', async () => {
+      try {
+        await logAdvisory('jupiter', 'Swap to SOL \u{1F680}');
+
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('NonAsciiString');
+      }
+    });
+
+    it('rejects a multibyte protocol name', async () => {
+      try {
+        await logAdvisory('jüpiter', 'Swap to SOL');
+
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('NonAsciiString');
+      }
+    });
+
+    it('stores ASCII strings unchanged', async () => {
+      await logAdvisory('marinade', 'Stake 1 SOL');
+
+      const audit = await program.account.auditTrail.fetch(auditPda);
+      const latest = audit.entries[(audit.head + audit.entries.length - 1) % audit.entries.length];
+      expect(Buffer.from(latest.protocol).toString().replace(/\0+$/, '')).to.equal('marinade');
+      expect(Buffer.from(latest.description).toString().replace(/\0+$/, '')).to.equal('Stake 1 SOL');
+    });
+  });
+
+  describe('adaptive confidence', () => {
+    async function setConfidenceStep(step: number) {
+      await program.methods
+        .setConfidenceStep(step)
+        .accounts({
+          owner: owner.publicKey,
+          strategyAccount: strategyPda,
+        })
+        .rpc();
+    }
+
+    async function logOutcomes(success: boolean, n: number) {
+      await startCycle(10);
+      for (let i = 0; i < n; i++) {
+        await program.methods
+          .logAction(ActionKind.Swap, 'jupiter', 'Swap SOL to USDC', true, success)
+          .accounts({
+            authority: owner.publicKey,
+            strategyAccount: strategyPda,
+            auditTrail: auditPda,
+            owner: owner.publicKey,
+          })
+          .rpc();
+      }
+    }
+
+    async function updateWithAudit(confidenceThreshold: number): Promise<number> {
+      await program.methods
+        .updateStrategy(0, confidenceThreshold, 10, [], Buffer.from([]), null, [])
+        .accounts({
+          authority: owner.publicKey,
+          strategyAccount: strategyPda,
+          auditTrail: auditPda,
+        })
+        .rpc();
+      return (await program.account.strategyAccount.fetch(strategyPda)).confidenceThreshold;
+    }
+
+    before(async () => {
+      await setConfidenceStep(10);
+    });
+
+    it('raises the threshold after a losing streak', async () => {
+      await logOutcomes(false, 3);
+      expect(await updateWithAudit(60)).to.equal(70);
+    });
+
+    it('caps the raised threshold at 100', async () => {
+      expect(await updateWithAudit(95)).to.equal(100);
+    });
+
+    it('lowers the threshold after a winning streak', async () => {
+      await logOutcomes(true, 3);
+      expect(await updateWithAudit(60)).to.equal(50);
+    });
+
+    it('leaves the threshold alone without the audit trail', async () => {
+      await startCycle(10);
+      const strategy = await program.account.strategyAccount.fetch(strategyPda);
+      expect(strategy.confidenceThreshold).to.equal(40);
+    });
+
+    it('leaves the threshold alone when the step is 0', async () => {
+      await setConfidenceStep(0);
+      await logOutcomes(false, 3);
+      expect(await updateWithAudit(60)).to.equal(60);
+    });
+
+    it('rejects a step over 100', async () => {
+      try {
+        await setConfidenceStep(101);
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('InvalidConfidenceStep');
+      }
+    });
+  });
+
+  describe('audit dump', () => {
+    it('exports the retained entries oldest first with decoded strings', async () => {
+      for (const description of ['Dump test first', 'Dump test second']) {
+        await program.methods
+          .logAction(ActionKind.Stake, 'marinade', description, false, true)
+          .accounts({
+            authority: owner.publicKey,
+            strategyAccount: strategyPda,
+            auditTrail: auditPda,
+            owner: owner.publicKey,
+          })
+          .rpc();
+      }
+
+      const dump = await program.methods
+        .dumpAudit()
+        .accounts({ auditTrail: auditPda })
+        .view();
+
+      const audit = await program.account.auditTrail.fetch(auditPda);
+      expect(dump.totalCount).to.equal(audit.count);
+      expect(dump.truncated).to.be.false;
+      expect(dump.entries).to.have.length(Math.min(audit.count, 8));
+
+      const indexes = dump.entries.map((e: any) => e.index);
+      expect(indexes).to.deep.equal([...indexes].sort((a: number, b: number) => a - b));
+
+      const [first, second] = dump.entries.slice(-2);
+      expect(first.description).to.equal('Dump test first');
+      expect(second.description).to.equal('Dump test second');
+      expect(second.actionKind).to.equal('stake');
+      expect(second.protocol).to.equal('marinade');
+      expect(second.executed).to.be.false;
+      expect(second.success).to.be.true;
+    });
+  });
+
+  describe('allocation summary', () => {
+    it('reports free slots and unassigned percentage', async () => {
+      const strategy = await program.account.strategyAccount.fetch(strategyPda);
+      const summary = await program.methods
+        .getAllocationSummary()
+        .accounts({ strategyAccount: strategyPda })
+        .view();
+
+      const assigned = strategy.targetAllocation
+        .slice(0, strategy.allocationCount)
+        .reduce((sum: number, a: any) => sum + a.targetPct, 0);
+      expect(summary.freeSlots).to.equal(5 - strategy.allocationCount);
+      expect(summary.remainingPct).to.equal(100 - assigned);
+    });
+  });
+
+  describe('allocation metadata', () => {
+    const metaOwner = Keypair.generate();
+    const solMint = new PublicKey('So11111111111111111111111111111111111111112');
+    const usdcMint = new PublicKey('EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v');
+    const bonkMint = new PublicKey('DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263');
+    let metaStrategyPda: PublicKey;
+    let metaPda: PublicKey;
+
+    before(async () => {
+      const sig = await provider.connection.requestAirdrop(metaOwner.publicKey, 1_000_000_000);
+      await provider.connection.confirmTransaction(sig);
+
+      [metaStrategyPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from('strategy'), metaOwner.publicKey.toBuffer()],
+        program.programId
+      );
+      const [metaAuditPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from('audit'), metaOwner.publicKey.toBuffer()],
+        program.programId
+      );
+      [metaPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from('allocation_meta'), metaOwner.publicKey.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .initialize(
+          agentAuthority.publicKey,
+          0,
+          0,
+          40,
+          5,
+          [padSymbol('SOL'), padSymbol('USDC')],
+          Buffer.from([70, 30]),
+          [
+            { mint: solMint, decimals: 9 },
+            { mint: usdcMint, decimals: 6 },
+          ]
+        )
+        .accounts({
+          owner: metaOwner.publicKey,
+          strategyAccount: metaStrategyPda,
+          auditTrail: metaAuditPda,
+          allocationMeta: metaPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([metaOwner])
+        .rpc();
+    });
+
+    it('stores the mint and decimals of each slot at initialize', async () => {
+      const meta = await program.account.allocationMeta.fetch(metaPda);
+      expect(meta.owner.toBase58()).to.equal(metaOwner.publicKey.toBase58());
+      expect(meta.entries[0].mint.toBase58()).to.equal(solMint.toBase58());
+      expect(meta.entries[0].decimals).to.equal(9);
+      expect(meta.entries[1].mint.toBase58()).to.equal(usdcMint.toBase58());
+      expect(meta.entries[1].decimals).to.equal(6);
+      expect(meta.entries[2].mint.toBase58()).to.equal(PublicKey.default.toBase58());
+    });
+
+    it('rewrites the metadata on update_strategy', async () => {
+      await program.methods
+        .updateStrategy(
+          0,
+          40,
+          5,
+          [padSymbol('USDC'), padSymbol('BONK'), padSymbol('SOL')],
+          Buffer.from([50, 20, 30]),
+          null,
+          [
+            { mint: usdcMint, decimals: 6 },
+            { mint: bonkMint, decimals: 5 },
+            { mint: solMint, decimals: 9 },
+          ]
+        )
+        .accounts({
+          authority: metaOwner.publicKey,
+          strategyAccount: metaStrategyPda,
+          allocationMeta: metaPda,
+        })
+        .signers([metaOwner])
+        .rpc();
+
+      const meta = await program.account.allocationMeta.fetch(metaPda);
+      expect(meta.entries.map((e: any) => e.decimals).slice(0, 3)).to.deep.equal([6, 5, 9]);
+      expect(meta.entries[1].mint.toBase58()).to.equal(bonkMint.toBase58());
+    });
+
+    it('rejects metadata that does not cover every slot', async () => {
+      try {
+        await program.methods
+          .updateStrategy(
+            0,
+            40,
+            5,
+            [padSymbol('USDC'), padSymbol('SOL')],
+            Buffer.from([50, 50]),
+            null,
+            [{ mint: usdcMint, decimals: 6 }]
+          )
+          .accounts({
+            authority: metaOwner.publicKey,
+            strategyAccount: metaStrategyPda,
+            allocationMeta: metaPda,
+          })
+          .signers([metaOwner])
+          .rpc();
+
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('InvalidAllocationMeta');
+      }
+    });
+  });
+
+  describe('allocation tolerance', () => {
+    const symbols = [padSymbol('SOL'), padSymbol('USDC'), padSymbol('mSOL')];
+
+    async function setTolerance(tolerance: number) {
+      await program.methods
+        .setAllocationTolerance(tolerance)
+        .accounts({
+          owner: owner.publicKey,
+          strategyAccount: strategyPda,
+        })
+        .rpc();
+    }
+
+    async function updateWithPcts(pcts: number[]) {
+      await program.methods
+        .updateStrategy(0, 40, 5, symbols, Buffer.from(pcts), null, [])
+        .accounts({
+          authority: owner.publicKey,
+          strategyAccount: strategyPda,
+        })
+        .rpc();
+    }
+
+    async function expectRejected(pcts: number[]) {
+      try {
+        await updateWithPcts(pcts);
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.toString()).to.include('InvalidAllocationSum');
+      }
+    }
+
+    it('requires exactly 100 by default', async () => {
+      const strategy = await program.account.strategyAccount.fetch(strategyPda);
+      expect(strategy.allocationTolerance).to.equal(0);
+
+      await updateWithPcts([34, 33, 33]);
+      await expectRejected([33, 33, 33]);
+      await expectRejected([34, 34, 33]);
+    });
+
+    it('accepts 99, 100 and 101 with a tolerance of 1', async () => {
+      await setTolerance(1);
+      for (const pcts of [[33, 33, 33], [34, 33, 33], [34, 34, 33]]) {
+        await updateWithPcts(pcts);
+        const strategy = await program.account.strategyAccount.fetch(strategyPda);
+        expect(strategy.targetAllocation.slice(0, 3).map((a: any) => a.targetPct)).to.deep.equal(pcts);
+      }
+      await expectRejected([33, 33, 32]);
+      await expectRejected([34, 34, 34]);
+    });
+
+    it('is owner-only and capped', async () => {
+      try {
+        await program.methods
+          .setAllocationTolerance(1)
+          .accounts({
+            owner: agentAuthority.publicKey,
+            strategyAccount: strategyPda,
+          })
+          .signers([agentAuthority])
+          .rpc();
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.toString()).to.include('UnauthorizedPermissionsUpdate');
+      }
+
+      try {
+        await setTolerance(6);
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.toString()).to.include('InvalidAllocationTolerance');
+      }
+
+      await setTolerance(0);
+    });
+  });
+
+  describe('performance score', () => {
+    async function logOutcomes(outcomes: boolean[]) {
+      await startCycle(10);
+      for (const success of outcomes) {
+        await program.methods
+          .logAction(ActionKind.Swap, 'jupiter', 'Swap SOL to USDC', true, success)
+          .accounts({
+            authority: owner.publicKey,
+            strategyAccount: strategyPda,
+            auditTrail: auditPda,
+            owner: owner.publicKey,
+          })
+          .rpc();
+      }
+    }
+
+    function computePerformance() {
+      return program.methods.computePerformance().accounts({
+        authority: owner.publicKey,
+        strategyAccount: strategyPda,
+        auditTrail: auditPda,
+      });
+    }
+
+    async function simulateReport() {
+      // The instruction writes the cached score, so it cannot be a view
+      const { raw } = await computePerformance().simulate();
+      const line = raw.find((l: string) => l.startsWith(`Program return: ${program.programId}`))!;
+      const data = Buffer.from(line.split(' ').pop()!, 'base64');
+      return program.coder.types.decode('PerformanceReport', data);
+    }
+
+    it('weights recent successes above older failures', async () => {
+      // Fill the whole trail: oldest four failed, newest four succeeded
+      await logOutcomes([false, false, false, false, true, true, true, true]);
+
+      const report = await simulateReport();
+      expect(report.executedActions).to.equal(8);
+      expect(report.successRatePct).to.equal(50);
+      expect(report.score).to.equal(72); // (8 + 7 + 6 + 5) / 36
+    });
+
+    it('weights recent failures above older successes', async () => {
+      await logOutcomes([true, true, true, true, false, false, false, false]);
+
+      const report = await simulateReport();
+      expect(report.successRatePct).to.equal(50);
+      expect(report.score).to.equal(27); // (4 + 3 + 2 + 1) / 36
+    });
+
+    it('caches the score on the strategy', async () => {
+      await computePerformance().rpc();
+
+      const strategy = await program.account.strategyAccount.fetch(strategyPda);
+      expect(strategy.lastPerformanceScore).to.equal(27);
+    });
+
+    it('rejects a signer that is neither owner nor agent', async () => {
+      const stranger = Keypair.generate();
+      try {
+        await program.methods
+          .computePerformance()
+          .accounts({
+            authority: stranger.publicKey,
+            strategyAccount: strategyPda,
+            auditTrail: auditPda,
+          })
+          .signers([stranger])
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('UnauthorizedStrategyUpdate');
+      }
+    });
+  });
+
+  describe('protocol allowlist', () => {
+    async function setAllowedProtocols(protocols: string[]) {
+      await program.methods
+        .setAllowedProtocols(protocols)
+        .accounts({
+          owner: owner.publicKey,
+          strategyAccount: strategyPda,
+        })
+        .rpc();
+    }
+
+    async function logExecuted(protocol: string, executed = true) {
+      await program.methods
+        .logAction(ActionKind.Swap, protocol, 'Swap SOL to USDC', executed, true)
+        .accounts({
+          authority: owner.publicKey,
+          strategyAccount: strategyPda,
+          auditTrail: auditPda,
+          owner: owner.publicKey,
+        })
+        .rpc();
+    }
+
+    before(async () => {
+      await startCycle(10);
+    });
+
+    it('allows every protocol with an empty list', async () => {
+      await logExecuted('kamino');
+    });
+
+    it('allows listed protocols', async () => {
+      await setAllowedProtocols(['jupiter', 'marinade']);
+      await logExecuted('jupiter');
+      await logExecuted('marinade');
+    });
+
+    it('rejects executed actions on unlisted protocols', async () => {
+      try {
+        await logExecuted('kamino');
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('ProtocolNotAllowed');
+      }
+    });
+
+    it('still logs proposals on unlisted protocols', async () => {
+      await logExecuted('kamino', false);
+    });
+
+    it('rejects more than 8 protocols', async () => {
+      try {
+        await setAllowedProtocols(Array.from({ length: 9 }, (_, i) => `protocol${i}`));
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('InvalidProtocolAllowlist');
+      }
+    });
+
+    it('rejects a list set by a non-owner', async () => {
+      const stranger = Keypair.generate();
+      try {
+        await program.methods
+          .setAllowedProtocols([])
+          .accounts({
+            owner: stranger.publicKey,
+            strategyAccount: strategyPda,
+          })
+          .signers([stranger])
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('UnauthorizedPermissionsUpdate');
+      }
+    });
+
+    it('allows every protocol again once cleared', async () => {
+      await setAllowedProtocols([]);
+      await logExecuted('kamino');
+    });
+  });
+
+  describe('batched action logging', () => {
+    const action = (actionKind: number, protocol: string, description: string, executed = true) => ({
+      actionKind,
+      protocol,
+      description,
+      executed,
+      success: true,
+    });
+
+    async function logBatch(actions: any[]) {
+      await program.methods
+        .logActionsBatch(actions)
+        .accounts({
+          authority: owner.publicKey,
+          strategyAccount: strategyPda,
+          auditTrail: auditPda,
+          owner: owner.publicKey,
+        })
+        .rpc();
+    }
+
+    async function counters() {
+      const strategy = await program.account.strategyAccount.fetch(strategyPda);
+      const audit = await program.account.auditTrail.fetch(auditPda);
+      return {
+        executed: strategy.totalActionsExecuted.toNumber(),
+        thisCycle: strategy.actionsThisCycle,
+        logged: audit.count,
+      };
+    }
+
+    async function expectRejected(actions: any[], error: string) {
+      const before = await counters();
+      try {
+        await logBatch(actions);
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include(error);
+      }
+      expect(await counters()).to.deep.equal(before);
+    }
+
+    before(async () => {
+      await startCycle(5);
+    });
+
+    it('logs five actions in one call', async () => {
+      const batch = [
+        action(ActionKind.Swap, 'jupiter', 'Swap SOL to USDC'),
+        action(ActionKind.Stake, 'marinade', 'Stake SOL'),
+        action(ActionKind.Lend, 'kamino', 'Lend USDC'),
+        action(ActionKind.Claim, 'marinade', 'Claim rewards'),
+        action(ActionKind.Rebalance, 'jupiter', 'Proposed rebalance', false),
+      ];
+      const before = await counters();
+
+      await logBatch(batch);
+
+      const after = await counters();
+      expect(after.logged - before.logged).to.equal(5);
+      // The proposal is logged but not counted as executed
+      expect(after.executed - before.executed).to.equal(4);
+      expect(after.thisCycle).to.equal(4);
+
+      const audit = await program.account.auditTrail.fetch(auditPda);
+      const logged = batch.map((_, i) => {
+        const index = (before.logged + i) % audit.entries.length;
+        const entry = audit.entries[index];
+        return [
+          entry.index,
+          entry.actionKind,
+          Buffer.from(entry.description).toString().replace(/\0/g, ''),
+          entry.executed,
+        ];
+      });
+      expect(logged).to.deep.equal(
+        batch.map((a, i) => [before.logged + i, a.actionKind, a.description, a.executed])
+      );
+    });
+
+    it('rejects a batch that would exceed the per-cycle cap', async () => {
+      // 4 of 5 executed actions are already used this cycle
+      await expectRejected(
+        [
+          action(ActionKind.Swap, 'jupiter', 'Swap SOL to USDC'),
+          action(ActionKind.Stake, 'marinade', 'Stake SOL'),
+        ],
+        'MaxActionsExceeded'
+      );
+    });
+
+    it('rejects the whole batch when one entry is too long', async () => {
+      await startCycle(5);
+      await expectRejected(
+        [
+          action(ActionKind.Swap, 'jupiter', 'Swap SOL to USDC'),
+          action(ActionKind.Other, 'jupiter', 'x'.repeat(65)),
+        ],
+        'DescriptionTooLong'
+      );
+    });
+
+    it('rejects empty and oversized batches', async () => {
+      await expectRejected([], 'ActionBatchTooLarge');
+      await expectRejected(
+        Array.from({ length: 9 }, () => action(ActionKind.Other, 'jupiter', 'Note', false)),
+        'ActionBatchTooLarge'
+      );
+    });
+
+    it('rejects a batch from an unauthorized signer', async () => {
+      const stranger = Keypair.generate();
+      try {
+        await program.methods
+          .logActionsBatch([action(ActionKind.Other, 'jupiter', 'Note', false)])
+          .accounts({
+            authority: stranger.publicKey,
+            strategyAccount: strategyPda,
+            auditTrail: auditPda,
+            owner: owner.publicKey,
+          })
+          .signers([stranger])
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('UnauthorizedLogAction');
+      }
+    });
+  });
+
+  describe('audit trail migration', () => {
+    // tests/fixtures/legacy-audit-trail.json is preloaded by Anchor.toml:
+    // a trail in the original layout with three entries logged under the
+    // free-form action types "Swap", "stake" and "yield-farm"
+    const legacyOwner = Keypair.fromSecretKey(
+      Uint8Array.from(JSON.parse(fs.readFileSync('tests/fixtures/legacy-audit-owner.json', 'utf8')))
+    );
+    let legacyAudit: PublicKey;
+
+    before(async () => {
+      [legacyAudit] = PublicKey.findProgramAddressSync(
+        [Buffer.from('audit'), legacyOwner.publicKey.toBuffer()],
+        program.programId
+      );
+      const sig = await provider.connection.requestAirdrop(legacyOwner.publicKey, 1_000_000_000);
+      await provider.connection.confirmTransaction(sig);
+    });
+
+    async function migrate(signer: Keypair = legacyOwner) {
+      await program.methods
+        .migrateAuditTrail()
+        .accounts({
+          owner: signer.publicKey,
+          auditTrail: legacyAudit,
+        })
+        .signers([signer])
+        .rpc();
+    }
+
+    it('cannot load a trail in the original layout', async () => {
+      try {
+        await program.methods.dumpAudit().accounts({ auditTrail: legacyAudit }).view();
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('AccountDidNotDeserialize');
+      }
+    });
+
+    it('rejects a migration signed by someone else', async () => {
+      const stranger = Keypair.generate();
+      try {
+        await migrate(stranger);
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('ConstraintSeeds');
+      }
+    });
+
+    it('rewrites the entries with typed action kinds', async () => {
+      await migrate();
+
+      const info = await provider.connection.getAccountInfo(legacyAudit);
+      expect(info!.data.length).to.equal(program.account.auditTrail.size);
+      const rent = await provider.connection.getMinimumBalanceForRentExemption(info!.data.length);
+      expect(info!.lamports).to.equal(rent);
+
+      const dump = await program.methods
+        .dumpAudit()
+        .accounts({ auditTrail: legacyAudit })
+        .view();
+      expect(dump.totalCount).to.equal(3);
+      expect(dump.entries.map((e: any) => e.actionKind)).to.deep.equal(['swap', 'stake', 'other']);
+      expect(dump.entries[0].protocol).to.equal('jupiter');
+      expect(dump.entries[0].description).to.equal('Swap 1 SOL to USDC');
+      expect(dump.entries[1].success).to.be.false;
+      expect(dump.entries[2].executed).to.be.false;
+      expect(dump.entries[0].balanceAfter.toNumber()).to.equal(0);
+
+      const audit = await program.account.auditTrail.fetch(legacyAudit);
+      expect(audit.version).to.equal(1);
+    });
+
+    it('refuses to migrate twice', async () => {
+      try {
+        await migrate();
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('AuditTrailAlreadyMigrated');
+      }
+    });
+  });
 });
@@ -0,0 +1,3308 @@
+import * as anchor from '@coral-xyz/anchor';
+import { Program, BN } from '@coral-xyz/anchor';
+import { Keypair, PublicKey, SystemProgram, Transaction, LAMPORTS_PER_SOL } from '@solana/web3.js';
+import { expect } from 'chai';
+import * as fs from 'fs';
+import {
+  ExtensionType,
+  TOKEN_2022_PROGRAM_ID,
+  TOKEN_PROGRAM_ID,
+  createAccount,
+  createInitializeMintInstruction,
+  createInitializeNonTransferableMintInstruction,
+  createMint,
+  getAccount,
+  getMintLen,
+  mintTo,
+} from '@solana/spl-token';
+import type { MakoraVault } from '../target/types/makora_vault';
+import type { MakoraStrategy } from '../target/types/makora_strategy';
+import type { MockJupiter } from '../target/types/mock_jupiter';
+import type { MockPyth } from '../target/types/mock_pyth';
+
+describe('makora_vault', () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.MakoraVault as Program<MakoraVault>;
+  const owner = provider.wallet as anchor.Wallet;
+  const agentAuthority = Keypair.generate();
+  const feeCollector = Keypair.generate();
+  const BPF_LOADER_UPGRADEABLE = new PublicKey('BPFLoaderUpgradeab1e11111111111111111111111');
+
+  let vaultPda: PublicKey;
+  let vaultBump: number;
+  let configPda: PublicKey;
+
+  before(async () => {
+    [vaultPda, vaultBump] = PublicKey.findProgramAddressSync(
+      [Buffer.from('vault'), owner.publicKey.toBuffer()],
+      program.programId
+    );
+
+    [configPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from('config')],
+      program.programId
+    );
+
+    const sig = await provider.connection.requestAirdrop(
+      agentAuthority.publicKey,
+      1_000_000_000
+    );
+    await provider.connection.confirmTransaction(sig);
+  });
+
+  it('initializes a vault', async () => {
+    let initEvent: any = null;
+    const listener = program.addEventListener('initializedEvent', (event) => {
+      initEvent = event;
+    });
+
+    await program.methods
+      .initialize(
+        agentAuthority.publicKey,
+        1,   // auto mode
+        50,  // max position size pct
+        5,   // min position size pct
+        100, // max slippage bps (1%)
+        10,  // max daily loss pct
+        new BN(10_000_000), // min SOL reserve
+        40,  // max protocol exposure pct
+        new BN(2 * LAMPORTS_PER_SOL), // max total deposit
+      )
+      .accounts({
+        owner: owner.publicKey,
+        vault: vaultPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const vault = await program.account.vault.fetch(vaultPda);
+    expect(vault.owner.toBase58()).to.equal(owner.publicKey.toBase58());
+    expect(vault.agentAuthority.toBase58()).to.equal(agentAuthority.publicKey.toBase58());
+    expect(vault.mode).to.deep.equal({ auto: {} });
+    expect(vault.riskLimits.minPositionSizePct).to.equal(5);
+    expect(vault.riskLimits.maxSlippageBps).to.equal(100);
+    expect(vault.maxTotalDeposit.toNumber()).to.equal(2 * LAMPORTS_PER_SOL);
+    expect(vault.bump).to.equal(vaultBump);
+
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+    await program.removeEventListener(listener);
+    expect(initEvent).to.not.be.null;
+    expect(initEvent.owner.toBase58()).to.equal(owner.publicKey.toBase58());
+    expect(initEvent.agentAuthority.toBase58()).to.equal(agentAuthority.publicKey.toBase58());
+    expect(initEvent.mode).to.equal(1);
+    expect(initEvent.maxPositionSizePct).to.equal(50);
+    expect(initEvent.minPositionSizePct).to.equal(5);
+    expect(initEvent.maxSlippageBps).to.equal(100);
+    expect(initEvent.maxDailyLossPct).to.equal(10);
+    expect(initEvent.minSolReserve.toNumber()).to.equal(10_000_000);
+    expect(initEvent.maxProtocolExposurePct).to.equal(40);
+    expect(initEvent.maxTotalDeposit.toNumber()).to.equal(2 * LAMPORTS_PER_SOL);
+  });
+
+  function initializeConfig(authority: Keypair) {
+    const [programData] = PublicKey.findProgramAddressSync(
+      [program.programId.toBuffer()],
+      BPF_LOADER_UPGRADEABLE
+    );
+    return program.methods
+      .initializeConfig(0, feeCollector.publicKey)
+      .accounts({
+        authority: authority.publicKey,
+        config: configPda,
+        program: program.programId,
+        programData,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+  }
+
+  it('only lets the upgrade authority create the global config', async () => {
+    try {
+      await initializeConfig(agentAuthority);
+      expect.fail('Should have thrown an error');
+    } catch (err: any) {
+      expect(err.toString()).to.include('Unauthorized');
+    }
+    expect(await provider.connection.getAccountInfo(configPda)).to.be.null;
+  });
+
+  it('initializes the global config', async () => {
+    // Every withdrawal passes the config, so it exists before any of them
+    await initializeConfig(owner.payer);
+
+    const config = await program.account.globalConfig.fetch(configPda);
+    expect(config.authority.toBase58()).to.equal(owner.publicKey.toBase58());
+    expect(config.feeBps).to.equal(0);
+    expect(config.feeCollector.toBase58()).to.equal(feeCollector.publicKey.toBase58());
+  });
+
+  async function fund(to: PublicKey, lamports: number) {
+    const tx = new Transaction().add(
+      SystemProgram.transfer({
+        fromPubkey: owner.publicKey,
+        toPubkey: to,
+        lamports,
+      })
+    );
+    await provider.sendAndConfirm(tx);
+  }
+
+  // Agent calls must carry the vault's current nonce
+  async function agentNonce(vault: PublicKey = vaultPda): Promise<BN> {
+    return (await program.account.vault.fetch(vault)).agentNonce;
+  }
+
+  async function agentWithdraw(
+    destination: PublicKey,
+    lamports: number,
+    maxDurationSecs = 0 // default session lifetime
+  ): Promise<number> {
+    await program.methods
+      .agentWithdraw(new BN(lamports), await agentNonce(), maxDurationSecs)
+      .accounts({
+        agent: agentAuthority.publicKey,
+        vault: vaultPda,
+        destination,
+        config: configPda,
+        feeCollector: null,
+        priceUpdate: null,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([agentAuthority])
+      .rpc();
+
+    // agent_withdraw opens a new session with the next id
+    const vault = await program.account.vault.fetch(vaultPda);
+    return vault.sessionId.toNumber();
+  }
+
+  async function agentDeposit(source: Keypair, lamports: number, sessionId: number, closeSession: boolean) {
+    await program.methods
+      .agentDeposit(new BN(lamports), new BN(sessionId), closeSession, await agentNonce())
+      .accounts({
+        agent: agentAuthority.publicKey,
+        vault: vaultPda,
+        source: source.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([agentAuthority, source])
+      .rpc();
+  }
+
+  it('reports AlreadyInitialized for an existing vault', async () => {
+    try {
+      await program.methods
+        .ensureVault()
+        .accounts({
+          owner: owner.publicKey,
+          vault: vaultPda,
+        })
+        .rpc();
+
+      expect.fail('Should have thrown an error');
+    } catch (err: any) {
+      expect(err.toString()).to.include('AlreadyInitialized');
+    }
+  });
+
+  it('passes ensure_vault for an owner without a vault', async () => {
+    const newcomer = Keypair.generate();
+    const [newcomerVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from('vault'), newcomer.publicKey.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .ensureVault()
+      .accounts({
+        owner: newcomer.publicKey,
+        vault: newcomerVault,
+      })
+      .rpc();
+  });
+
+  it('deposits SOL into the vault', async () => {
+    await program.methods
+      .deposit(new BN(LAMPORTS_PER_SOL))
+      .accounts({
+        owner: owner.publicKey,
+        vault: vaultPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const vault = await program.account.vault.fetch(vaultPda);
+    expect(vault.totalDeposited.toNumber()).to.equal(LAMPORTS_PER_SOL);
+  });
+
+  it('accepts a deposit within the deposit cap', async () => {
+    await program.methods
+      .deposit(new BN(LAMPORTS_PER_SOL / 2))
+      .accounts({
+        owner: owner.publicKey,
+        vault: vaultPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const vault = await program.account.vault.fetch(vaultPda);
+    expect(vault.totalDeposited.toNumber()).to.equal(1.5 * LAMPORTS_PER_SOL);
+  });
+
+  it('rejects a deposit past the deposit cap', async () => {
+    try {
+      await program.methods
+        .deposit(new BN(0.6 * LAMPORTS_PER_SOL))
+        .accounts({
+          owner: owner.publicKey,
+          vault: vaultPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      expect.fail('Should have thrown an error');
+    } catch (err: any) {
+      expect(err.toString()).to.include('DepositCapExceeded');
+    }
+  });
+
+  it('accepts a quote exactly at max slippage', async () => {
+    // 1_000_000 -> 990_000 is exactly 100 bps
+    await program.methods
+      .validateSwapParams(new BN(1_000_000), new BN(990_000))
+      .accounts({
+        agent: agentAuthority.publicKey,
+        vault: vaultPda,
+      })
+      .signers([agentAuthority])
+      .rpc();
+  });
+
+  it('accepts a quote above the expected output', async () => {
+    await program.methods
+      .validateSwapParams(new BN(1_000_000), new BN(1_000_500))
+      .accounts({
+        agent: agentAuthority.publicKey,
+        vault: vaultPda,
+      })
+      .signers([agentAuthority])
+      .rpc();
+  });
+
+  it('rejects a quote one lamport past max slippage', async () => {
+    try {
+      await program.methods
+        .validateSwapParams(new BN(1_000_000), new BN(989_999))
+        .accounts({
+          agent: agentAuthority.publicKey,
+          vault: vaultPda,
+        })
+        .signers([agentAuthority])
+        .rpc();
+
+      expect.fail('Should have thrown an error');
+    } catch (err: any) {
+      expect(err.toString()).to.include('SlippageExceeded');
+    }
+  });
+
+  it('rejects swap validation from a non-agent signer', async () => {
+    try {
+      await program.methods
+        .validateSwapParams(new BN(1_000_000), new BN(1_000_000))
+        .accounts({
+          agent: owner.publicKey,
+          vault: vaultPda,
+        })
+        .rpc();
+
+      expect.fail('Should have thrown an error');
+    } catch (err: any) {
+      expect(err.toString()).to.include('UnauthorizedAgent');
+    }
+  });
+
+  // The vault's recorded balance must match the lamports its PDA holds
+  async function expectBalanceMatchesLamports() {
+    const vault = await program.account.vault.fetch(vaultPda);
+    const lamports = await provider.connection.getBalance(vaultPda);
+    const minRent = await provider.connection.getMinimumBalanceForRentExemption(
+      program.account.vault.size
+    );
+    const currentBalance = vault.totalDeposited.sub(vault.totalWithdrawn).sub(vault.inSessionAmount);
+    expect(currentBalance.toNumber()).to.equal(lamports - minRent);
+  }
+
+  it('records realized PnL for a profitable session', async () => {
+    const sessionWallet = Keypair.generate();
+
+    const sessionId = await agentWithdraw(sessionWallet.publicKey, 100_000_000);
+    expect(sessionId).to.equal(1);
+
+    let vault = await program.account.vault.fetch(vaultPda);
+    expect(vault.sessions[0].sessionId.toNumber()).to.equal(1);
+    expect(vault.sessions[0].amountOut.toNumber()).to.equal(100_000_000);
+
+    // Simulate trading profit landing in the session wallet
+    await fund(sessionWallet.publicKey, 30_000_000);
+    await agentDeposit(sessionWallet, 120_000_000, sessionId, true);
+
+    vault = await program.account.vault.fetch(vaultPda);
+    expect(vault.sessions[0].sessionId.toNumber()).to.equal(0);
+    expect(vault.inSessionAmount.toNumber()).to.equal(0);
+    expect(vault.totalRealizedPnl.toNumber()).to.equal(20_000_000);
+    await expectBalanceMatchesLamports();
+  });
+
+  it('records realized PnL for a losing session', async () => {
+    const sessionWallet = Keypair.generate();
+
+    const sessionId = await agentWithdraw(sessionWallet.publicKey, 100_000_000);
+    expect(sessionId).to.equal(2);
+
+    await agentDeposit(sessionWallet, 90_000_000, sessionId, true);
+
+    const vault = await program.account.vault.fetch(vaultPda);
+    expect(vault.inSessionAmount.toNumber()).to.equal(0);
+    // +0.02 SOL from the first session, -0.01 SOL from this one
+    expect(vault.totalRealizedPnl.toNumber()).to.equal(10_000_000);
+    // The loss window records the same shortfall the balance was reduced by
+    expect(vault.dailyLoss.accumulatedLoss.toNumber()).to.equal(10_000_000);
+    await expectBalanceMatchesLamports();
+  });
+
+  it('tracks partial and full returns per session', async () => {
+    const walletA = Keypair.generate();
+    const walletB = Keypair.generate();
+
+    const sessionA = await agentWithdraw(walletA.publicKey, 100_000_000);
+    const sessionB = await agentWithdraw(walletB.publicKey, 80_000_000);
+
+    // Partial return from A leaves B untouched
+    await agentDeposit(walletA, 40_000_000, sessionA, false);
+
+    let vault = await program.account.vault.fetch(vaultPda);
+    const slotA = vault.sessions.find((s: any) => s.sessionId.toNumber() === sessionA);
+    const slotB = vault.sessions.find((s: any) => s.sessionId.toNumber() === sessionB);
+    expect(slotA.amountOut.toNumber()).to.equal(60_000_000);
+    expect(slotB.amountOut.toNumber()).to.equal(80_000_000);
+    expect(vault.inSessionAmount.toNumber()).to.equal(140_000_000);
+
+    // Full return of the rest of A closes it without PnL
+    await agentDeposit(walletA, 60_000_000, sessionA, false);
+    // B comes back flat and is closed explicitly
+    await agentDeposit(walletB, 80_000_000, sessionB, true);
+
+    vault = await program.account.vault.fetch(vaultPda);
+    expect(vault.sessions.every((s: any) => s.sessionId.toNumber() === 0)).to.be.true;
+    expect(vault.inSessionAmount.toNumber()).to.equal(0);
+    expect(vault.totalRealizedPnl.toNumber()).to.equal(10_000_000);
+  });
+
+  it('rejects a return against an unknown session', async () => {
+    const sessionWallet = Keypair.generate();
+    await fund(sessionWallet.publicKey, 10_000_000);
+
+    try {
+      await agentDeposit(sessionWallet, 1_000_000, 999, true);
+
+      expect.fail('Should have thrown an error');
+    } catch (err: any) {
+      expect(err.toString()).to.include('UnknownSession');
+    }
+  });
+
+  it('only accepts returns from the recorded session wallet', async () => {
+    const sessionWallet = Keypair.generate();
+    const stranger = Keypair.generate();
+    await fund(stranger.publicKey, 200_000_000);
+
+    const sessionId = await agentWithdraw(sessionWallet.publicKey, 100_000_000);
+
+    let vault = await program.account.vault.fetch(vaultPda);
+    const slot = vault.sessions.find((s: any) => s.sessionId.toNumber() === sessionId);
+    expect(slot.wallet.toBase58()).to.equal(sessionWallet.publicKey.toBase58());
+
+    try {
+      await agentDeposit(stranger, 100_000_000, sessionId, true);
+
+      expect.fail('Should have thrown an error');
+    } catch (err: any) {
+      expect(err.toString()).to.include('UnknownSource');
+    }
+
+    await agentDeposit(sessionWallet, 100_000_000, sessionId, true);
+
+    vault = await program.account.vault.fetch(vaultPda);
+    expect(vault.inSessionAmount.toNumber()).to.equal(0);
+  });
+
+  async function availableBalance(): Promise<number> {
+    const vault = await program.account.vault.fetch(vaultPda);
+    return vault.totalDeposited.sub(vault.totalWithdrawn).sub(vault.inSessionAmount).toNumber();
+  }
+
+  it('rejects an agent withdraw larger than the vault balance', async () => {
+    const available = await availableBalance();
+
+    try {
+      await agentWithdraw(Keypair.generate().publicKey, available + 1);
+
+      expect.fail('Should have thrown an error');
+    } catch (err: any) {
+      expect(err.toString()).to.include('InsufficientBalance');
+    }
+  });
+
+  it('rejects an agent withdraw that would dip into the reserve', async () => {
+    // Covered by the balance, but leaves less than rent + min_sol_reserve
+    const available = await availableBalance();
+
+    try {
+      await agentWithdraw(Keypair.generate().publicKey, available - 1_000);
+
+      expect.fail('Should have thrown an error');
+    } catch (err: any) {
+      expect(err.toString()).to.include('ReserveWouldBeBreached');
+    }
+  });
+
+  it('rejects an agent withdraw below the min position size', async () => {
+    try {
+      // ~0.7% of a ~1.5 SOL vault, under the 5% minimum
+      await agentWithdraw(Keypair.generate().publicKey, 10_000_000);
+
+      expect.fail('Should have thrown an error');
+    } catch (err: any) {
+      expect(err.toString()).to.include('BelowMinPosition');
+    }
+  });
+
+  it('advances the agent nonce on each agent call', async () => {
+    const sessionWallet = Keypair.generate();
+    const start = (await agentNonce()).toNumber();
+
+    const sessionId = await agentWithdraw(sessionWallet.publicKey, 100_000_000);
+    expect((await agentNonce()).toNumber()).to.equal(start + 1);
+
+    await agentDeposit(sessionWallet, 100_000_000, sessionId, true);
+    expect((await agentNonce()).toNumber()).to.equal(start + 2);
+  });
+
+  it('rejects an agent withdraw replayed with a stale nonce', async () => {
+    const sessionWallet = Keypair.generate();
+    const stale = await agentNonce();
+
+    const sessionId = await agentWithdraw(sessionWallet.publicKey, 100_000_000);
+
+    try {
+      await program.methods
+        .agentWithdraw(new BN(100_000_000), stale, 0)
+        .accounts({
+          agent: agentAuthority.publicKey,
+          vault: vaultPda,
+          destination: sessionWallet.publicKey,
+          config: configPda,
+          feeCollector: null,
+          priceUpdate: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([agentAuthority])
+        .rpc();
+
+      expect.fail('Should have thrown an error');
+    } catch (err: any) {
+      expect(err.toString()).to.include('StaleNonce');
+    }
+
+    // Only the original withdraw went through
+    const vault = await program.account.vault.fetch(vaultPda);
+    expect(vault.sessionId.toNumber()).to.equal(sessionId);
+    expect(vault.agentNonce.toNumber()).to.equal(stale.toNumber() + 1);
+
+    await agentDeposit(sessionWallet, 100_000_000, sessionId, true);
+  });
+
+  function setProtocolFee(feeBps: number) {
+    return program.methods
+      .updateConfig(feeBps, feeCollector.publicKey)
+      .accounts({
+        authority: owner.publicKey,
+        config: configPda,
+      })
+      .rpc();
+  }
+
+  describe('protocol fee', () => {
+    before(async () => {
+      // Keep the collector rent-exempt so it can receive small fee amounts
+      await fund(feeCollector.publicKey, 1_000_000);
+    });
+
+    after(async () => {
+      await setProtocolFee(0);
+    });
+
+    it('sets the protocol fee', async () => {
+      await setProtocolFee(50);
+
+      const config = await program.account.globalConfig.fetch(configPda);
+      expect(config.feeBps).to.equal(50);
+    });
+
+    it('requires the collector when a fee is due', async () => {
+      try {
+        await program.methods
+          .withdraw(new BN(100_000_000))
+          .accounts({
+            owner: owner.publicKey,
+            vault: vaultPda,
+            config: configPda,
+            feeCollector: null,
+            priceUpdate: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc();
+
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('InvalidFeeCollector');
+      }
+    });
+
+    it('rejects a fee above the cap', async () => {
+      try {
+        await program.methods
+          .updateConfig(101, feeCollector.publicKey)
+          .accounts({
+            authority: owner.publicKey,
+            config: configPda,
+          })
+          .rpc();
+
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('FeeTooHigh');
+      }
+    });
+
+    it('routes the fee to the collector on withdraw', async () => {
+      const collectorBefore = await provider.connection.getBalance(feeCollector.publicKey);
+      const vaultBefore = await provider.connection.getBalance(vaultPda);
+
+      await program.methods
+        .withdraw(new BN(100_000_000))
+        .accounts({
+          owner: owner.publicKey,
+          vault: vaultPda,
+          config: configPda,
+          feeCollector: feeCollector.publicKey,
+          priceUpdate: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      // 100_000_000 * 50 / 10_000
+      const collectorAfter = await provider.connection.getBalance(feeCollector.publicKey);
+      expect(collectorAfter - collectorBefore).to.equal(500_000);
+
+      const vaultAfter = await provider.connection.getBalance(vaultPda);
+      expect(vaultBefore - vaultAfter).to.equal(100_000_000);
+    });
+
+    it('routes the fee to the collector on agent withdraw', async () => {
+      const sessionWallet = Keypair.generate();
+      const collectorBefore = await provider.connection.getBalance(feeCollector.publicKey);
+
+      const sessionId = (await program.account.vault.fetch(vaultPda)).sessionId.toNumber() + 1;
+
+      await program.methods
+        .agentWithdraw(new BN(100_000_000), await agentNonce(), 0)
+        .accounts({
+          agent: agentAuthority.publicKey,
+          vault: vaultPda,
+          destination: sessionWallet.publicKey,
+          config: configPda,
+          feeCollector: feeCollector.publicKey,
+          priceUpdate: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([agentAuthority])
+        .rpc();
+
+      const collectorAfter = await provider.connection.getBalance(feeCollector.publicKey);
+      expect(collectorAfter - collectorBefore).to.equal(500_000);
+
+      const sessionBalance = await provider.connection.getBalance(sessionWallet.publicKey);
+      expect(sessionBalance).to.equal(99_500_000);
+
+      const vault = await program.account.vault.fetch(vaultPda);
+      expect(vault.inSessionAmount.toNumber()).to.equal(99_500_000);
+
+      await agentDeposit(sessionWallet, 99_500_000, sessionId, true);
+    });
+
+    it('rejects a fee collector that does not match the config', async () => {
+      try {
+        await program.methods
+          .withdraw(new BN(100_000_000))
+          .accounts({
+            owner: owner.publicKey,
+            vault: vaultPda,
+            config: configPda,
+            feeCollector: Keypair.generate().publicKey,
+            priceUpdate: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc();
+
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('InvalidFeeCollector');
+      }
+    });
+
+    it('charges no fee on a withdraw without the config', async () => {
+      const collectorBefore = await provider.connection.getBalance(feeCollector.publicKey);
+      const vaultBefore = await provider.connection.getBalance(vaultPda);
+
+      await program.methods
+        .withdraw(new BN(100_000_000))
+        .accounts({
+          owner: owner.publicKey,
+          vault: vaultPda,
+          config: null,
+          feeCollector: null,
+          priceUpdate: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const collectorAfter = await provider.connection.getBalance(feeCollector.publicKey);
+      expect(collectorAfter).to.equal(collectorBefore);
+
+      const vaultAfter = await provider.connection.getBalance(vaultPda);
+      expect(vaultBefore - vaultAfter).to.equal(100_000_000);
+    });
+  });
+
+  describe('vault status', () => {
+    it('returns balance figures via simulated return data', async () => {
+      const status = await program.methods
+        .getVaultStatus()
+        .accounts({ vault: vaultPda, priceUpdate: null })
+        .view();
+
+      const vault = await program.account.vault.fetch(vaultPda);
+      const currentBalance = vault.totalDeposited
+        .sub(vault.totalWithdrawn)
+        .sub(vault.inSessionAmount);
+      const minRent = await provider.connection.getMinimumBalanceForRentExemption(
+        program.account.vault.size
+      );
+      const reserve = new BN(minRent).add(vault.riskLimits.minSolReserve);
+      const expectedWithdrawable = BN.max(currentBalance.sub(reserve), new BN(0));
+
+      expect(status.currentBalance.toString()).to.equal(currentBalance.toString());
+      expect(status.maxWithdrawable.toString()).to.equal(expectedWithdrawable.toString());
+      expect(status.inSessionAmount.toString()).to.equal(vault.inSessionAmount.toString());
+      expect(status.dailyLossHeadroom.toString()).to.equal(
+        BN.max(
+          currentBalance
+            .muln(vault.riskLimits.maxDailyLossPct)
+            .divn(100)
+            .sub(vault.dailyLoss.accumulatedLoss),
+          new BN(0)
+        ).toString()
+      );
+    });
+
+    it('tracks realized session losses in a rolling 24h window', async () => {
+      const vault = await program.account.vault.fetch(vaultPda);
+      // Earlier sessions closed below principal, which opened the window
+      expect(vault.dailyLoss.accumulatedLoss.toNumber()).to.be.greaterThan(0);
+      expect(vault.dailyLoss.dailyWindowStart.toNumber()).to.be.greaterThan(0);
+
+      const status = await program.methods
+        .getVaultStatus()
+        .accounts({ vault: vaultPda, priceUpdate: null })
+        .view();
+      expect(status.dailyWindowRemainingSecs.toNumber()).to.be.greaterThan(0);
+      expect(status.dailyWindowRemainingSecs.toNumber()).to.be.at.most(86_400);
+    });
+  });
+
+  describe('close token account', () => {
+    let mint: PublicKey;
+
+    before(async () => {
+      mint = await createMint(provider.connection, owner.payer, owner.publicKey, null, 6);
+    });
+
+    async function vaultTokenAccount(): Promise<PublicKey> {
+      return createAccount(provider.connection, owner.payer, mint, vaultPda, Keypair.generate());
+    }
+
+    async function closeTokenAccount(tokenAccount: PublicKey) {
+      await program.methods
+        .closeTokenAccount()
+        .accounts({
+          owner: owner.publicKey,
+          vault: vaultPda,
+          tokenAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+    }
+
+    it('closes an empty vault-owned token account', async () => {
+      const tokenAccount = await vaultTokenAccount();
+
+      await closeTokenAccount(tokenAccount);
+
+      const info = await provider.connection.getAccountInfo(tokenAccount);
+      expect(info).to.be.null;
+    });
+
+    it('rejects closing a token account with a balance', async () => {
+      const tokenAccount = await vaultTokenAccount();
+      await mintTo(provider.connection, owner.payer, mint, tokenAccount, owner.publicKey, 1_000);
+
+      try {
+        await closeTokenAccount(tokenAccount);
+
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('TokenAccountNotEmpty');
+      }
+    });
+  });
+
+  describe('money-movement events', () => {
+    const SOL_MINT = 'So11111111111111111111111111111111111111112';
+
+    async function captureEvent(name: string, send: () => Promise<unknown>): Promise<any> {
+      let captured: any = null;
+      const listener = program.addEventListener(name as any, (event) => {
+        captured = event;
+      });
+      await send();
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+      await program.removeEventListener(listener);
+      return captured;
+    }
+
+    it('tags deposit events with the SOL mint and decimals', async () => {
+      const event = await captureEvent('depositEvent', () =>
+        program.methods
+          .deposit(new BN(LAMPORTS_PER_SOL / 10))
+          .accounts({
+            owner: owner.publicKey,
+            vault: vaultPda,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc()
+      );
+
+      expect(event).to.not.be.null;
+      expect(event.amount.toNumber()).to.equal(LAMPORTS_PER_SOL / 10);
+      expect(event.mint.toBase58()).to.equal(SOL_MINT);
+      expect(event.decimals).to.equal(9);
+    });
+
+    it('tags withdraw events with the SOL mint and decimals', async () => {
+      const event = await captureEvent('withdrawEvent', () =>
+        program.methods
+          .withdraw(new BN(LAMPORTS_PER_SOL / 20))
+          .accounts({
+            owner: owner.publicKey,
+            vault: vaultPda,
+            config: configPda,
+            feeCollector: null,
+            priceUpdate: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc()
+      );
+
+      expect(event).to.not.be.null;
+      expect(event.amount.toNumber()).to.equal(LAMPORTS_PER_SOL / 20);
+      expect(event.fee.toNumber()).to.equal(0);
+      expect(event.mint.toBase58()).to.equal(SOL_MINT);
+      expect(event.decimals).to.equal(9);
+    });
+  });
+
+  describe('withdraw all', () => {
+    it('drains the available balance to zero regardless of reserve', async () => {
+      const ownerBefore = await provider.connection.getBalance(owner.publicKey);
+      const before = await program.account.vault.fetch(vaultPda);
+      const available = before.totalDeposited
+        .sub(before.totalWithdrawn)
+        .sub(before.inSessionAmount);
+      expect(available.toNumber()).to.be.greaterThan(0);
+
+      await program.methods
+        .withdrawAll()
+        .accounts({
+          owner: owner.publicKey,
+          vault: vaultPda,
+          config: configPda,
+          feeCollector: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const after = await program.account.vault.fetch(vaultPda);
+      const remaining = after.totalDeposited
+        .sub(after.totalWithdrawn)
+        .sub(after.inSessionAmount);
+      expect(remaining.toNumber()).to.equal(0);
+      expect(after.totalWithdrawn.sub(before.totalWithdrawn).toString()).to.equal(available.toString());
+
+      // Owner receives the drained balance minus the transaction fee
+      const ownerAfter = await provider.connection.getBalance(owner.publicKey);
+      expect(ownerAfter - ownerBefore).to.be.greaterThan(available.toNumber() - 10_000);
+    });
+
+    it('rejects draining an already empty vault', async () => {
+      try {
+        await program.methods
+          .withdrawAll()
+          .accounts({
+            owner: owner.publicKey,
+            vault: vaultPda,
+            config: configPda,
+            feeCollector: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc();
+
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('ZeroWithdraw');
+      }
+    });
+  });
+
+  describe('emergency session reset', () => {
+    async function resetSessions(confirm: boolean) {
+      await program.methods
+        .emergencyResetSessions(confirm)
+        .accounts({
+          owner: owner.publicKey,
+          vault: vaultPda,
+        })
+        .rpc();
+    }
+
+    it('requires explicit confirmation', async () => {
+      try {
+        await resetSessions(false);
+
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('ConfirmationRequired');
+      }
+    });
+
+    it('clears stuck sessions and reconciles with PDA lamports', async () => {
+      await program.methods
+        .deposit(new BN(0.3 * LAMPORTS_PER_SOL))
+        .accounts({
+          owner: owner.publicKey,
+          vault: vaultPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      // Funds sent to this session wallet are never returned
+      const lostSession = Keypair.generate();
+      await agentWithdraw(lostSession.publicKey, 0.1 * LAMPORTS_PER_SOL);
+
+      const before = await program.account.vault.fetch(vaultPda);
+      expect(before.inSessionAmount.toNumber()).to.be.greaterThan(0);
+      const recorded = before.totalDeposited.sub(before.totalWithdrawn);
+
+      await resetSessions(true);
+
+      const after = await program.account.vault.fetch(vaultPda);
+      expect(after.inSessionAmount.toNumber()).to.equal(0);
+      after.sessions.forEach((slot: any) => expect(slot.sessionId.toNumber()).to.equal(0));
+
+      const lamports = await provider.connection.getBalance(vaultPda);
+      const minRent = await provider.connection.getMinimumBalanceForRentExemption(
+        program.account.vault.size
+      );
+      const actual = new BN(lamports - minRent);
+      const currentBalance = after.totalDeposited.sub(after.totalWithdrawn);
+      expect(currentBalance.toString()).to.equal(BN.min(recorded, actual).toString());
+    });
+  });
+
+  describe('agent roles', () => {
+    const ROLE_WITHDRAW = 1;
+    const ROLE_DEPOSIT = 2;
+    const ROLE_SWAP = 8;
+    const rebalancer = Keypair.generate();
+
+    async function setAgentRoles(agent: PublicKey, roles: number) {
+      await program.methods
+        .setAgentRoles(agent, roles)
+        .accounts({
+          owner: owner.publicKey,
+          vault: vaultPda,
+        })
+        .rpc();
+    }
+
+    async function withdrawAs(agent: Keypair, lamports: number) {
+      await program.methods
+        .agentWithdraw(new BN(lamports), await agentNonce(), 0)
+        .accounts({
+          agent: agent.publicKey,
+          vault: vaultPda,
+          destination: Keypair.generate().publicKey,
+          config: configPda,
+          feeCollector: null,
+          priceUpdate: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([agent])
+        .rpc();
+    }
+
+    before(async () => {
+      await fund(rebalancer.publicKey, 100_000_000);
+    });
+
+    it('registers the initial agent with all roles', async () => {
+      const vault = await program.account.vault.fetch(vaultPda);
+      expect(vault.agents[0].authority.toBase58()).to.equal(agentAuthority.publicKey.toBase58());
+      expect(vault.agents[0].roles).to.equal(0b1111);
+    });
+
+    it('rejects a withdraw from an agent without the withdraw role', async () => {
+      await setAgentRoles(rebalancer.publicKey, ROLE_DEPOSIT);
+
+      try {
+        await withdrawAs(rebalancer, 20_000_000);
+
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('MissingRole');
+      }
+    });
+
+    it('allows the withdraw once the role is granted', async () => {
+      await setAgentRoles(rebalancer.publicKey, ROLE_DEPOSIT | ROLE_WITHDRAW);
+
+      const before = await program.account.vault.fetch(vaultPda);
+      await withdrawAs(rebalancer, 20_000_000);
+
+      const after = await program.account.vault.fetch(vaultPda);
+      expect(after.inSessionAmount.sub(before.inSessionAmount).toNumber()).to.equal(20_000_000);
+    });
+
+    async function validateSwapAs(agent: Keypair) {
+      await program.methods
+        .validateSwapParams(new BN(1_000_000), new BN(1_000_000))
+        .accounts({
+          agent: agent.publicKey,
+          vault: vaultPda,
+        })
+        .signers([agent])
+        .rpc();
+    }
+
+    it('rejects swap validation from an agent without the swap role', async () => {
+      try {
+        await validateSwapAs(rebalancer);
+
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('MissingRole');
+      }
+    });
+
+    it('allows swap validation once the swap role is granted', async () => {
+      await setAgentRoles(rebalancer.publicKey, ROLE_SWAP);
+
+      await validateSwapAs(rebalancer);
+    });
+
+    it('rejects a removed agent', async () => {
+      await setAgentRoles(rebalancer.publicKey, 0);
+
+      try {
+        await withdrawAs(rebalancer, 20_000_000);
+
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('UnauthorizedAgent');
+      }
+    });
+  });
+
+  describe('ownership transfer', () => {
+    const oldOwner = Keypair.generate();
+    const newOwner = Keypair.generate();
+    let oldVault: PublicKey;
+    let newVault: PublicKey;
+
+    before(async () => {
+      for (const kp of [oldOwner, newOwner]) {
+        const sig = await provider.connection.requestAirdrop(kp.publicKey, 2 * LAMPORTS_PER_SOL);
+        await provider.connection.confirmTransaction(sig);
+      }
+
+      [oldVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from('vault'), oldOwner.publicKey.toBuffer()],
+        program.programId
+      );
+      [newVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from('vault'), newOwner.publicKey.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .initialize(agentAuthority.publicKey, 1, 50, 0, 100, 10, new BN(0), 40, new BN(0))
+        .accounts({
+          owner: oldOwner.publicKey,
+          vault: oldVault,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([oldOwner])
+        .rpc();
+
+      await program.methods
+        .deposit(new BN(LAMPORTS_PER_SOL / 2))
+        .accounts({
+          owner: oldOwner.publicKey,
+          vault: oldVault,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([oldOwner])
+        .rpc();
+    });
+
+    async function acceptOwner(signer: Keypair, tokenAccounts: PublicKey[] = []) {
+      const [targetVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from('vault'), signer.publicKey.toBuffer()],
+        program.programId
+      );
+      const remainingAccounts = tokenAccounts.length
+        ? [
+            ...tokenAccounts.map((pubkey) => ({ pubkey, isSigner: false, isWritable: true })),
+            { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+          ]
+        : [];
+
+      await program.methods
+        .acceptOwner()
+        .accounts({
+          newOwner: signer.publicKey,
+          vault: oldVault,
+          newVault: targetVault,
+          previousOwner: oldOwner.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(remainingAccounts)
+        .signers([signer])
+        .rpc();
+    }
+
+    it('rejects accepting without a proposal', async () => {
+      try {
+        await acceptOwner(newOwner);
+
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('NoPendingOwner');
+      }
+    });
+
+    it('records the proposed owner', async () => {
+      await program.methods
+        .proposeOwner(newOwner.publicKey)
+        .accounts({
+          owner: oldOwner.publicKey,
+          vault: oldVault,
+        })
+        .signers([oldOwner])
+        .rpc();
+
+      const vault = await program.account.vault.fetch(oldVault);
+      expect(vault.pendingOwner.toBase58()).to.equal(newOwner.publicKey.toBase58());
+    });
+
+    it('rejects acceptance by anyone but the proposed owner', async () => {
+      const stranger = Keypair.generate();
+      const sig = await provider.connection.requestAirdrop(stranger.publicKey, LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(sig);
+
+      try {
+        await acceptOwner(stranger);
+
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('NoPendingOwner');
+      }
+    });
+
+    it('migrates state and funds to the new owner\'s vault', async () => {
+      // A token account held by the old PDA moves with the vault
+      const mint = await createMint(provider.connection, owner.payer, owner.publicKey, null, 6);
+      const tokenAccount = await createAccount(
+        provider.connection, owner.payer, mint, oldVault, Keypair.generate()
+      );
+      await mintTo(provider.connection, owner.payer, mint, tokenAccount, owner.payer, 1_000);
+
+      const before = await program.account.vault.fetch(oldVault);
+      const oldLamports = await provider.connection.getBalance(oldVault);
+
+      await acceptOwner(newOwner, [tokenAccount]);
+
+      const moved = await getAccount(provider.connection, tokenAccount);
+      expect(moved.owner.toBase58()).to.equal(newVault.toBase58());
+      expect(Number(moved.amount)).to.equal(1_000);
+
+      const after = await program.account.vault.fetch(newVault);
+      expect(after.owner.toBase58()).to.equal(newOwner.publicKey.toBase58());
+      expect(after.pendingOwner.toBase58()).to.equal(PublicKey.default.toBase58());
+      expect(after.totalDeposited.toString()).to.equal(before.totalDeposited.toString());
+      expect(after.totalWithdrawn.toString()).to.equal(before.totalWithdrawn.toString());
+      expect(after.agentAuthority.toBase58()).to.equal(before.agentAuthority.toBase58());
+
+      // Funds above rent moved over; the new PDA's rent was paid by the new owner
+      const newLamports = await provider.connection.getBalance(newVault);
+      expect(newLamports).to.equal(oldLamports);
+
+      // The old PDA is closed
+      expect(await provider.connection.getAccountInfo(oldVault)).to.be.null;
+    });
+  });
+
+  describe('deposit audit logging', () => {
+    const strategyProgram = anchor.workspace.MakoraStrategy as Program<MakoraStrategy>;
+    const depositor = Keypair.generate();
+    let depositorVault: PublicKey;
+    let strategyPda: PublicKey;
+    let auditPda: PublicKey;
+
+    before(async () => {
+      const sig = await provider.connection.requestAirdrop(depositor.publicKey, 2 * LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(sig);
+
+      [depositorVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from('vault'), depositor.publicKey.toBuffer()],
+        program.programId
+      );
+      [strategyPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from('strategy'), depositor.publicKey.toBuffer()],
+        strategyProgram.programId
+      );
+      [auditPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from('audit'), depositor.publicKey.toBuffer()],
+        strategyProgram.programId
+      );
+
+      await program.methods
+        .initialize(agentAuthority.publicKey, 0, 50, 0, 100, 10, new BN(0), 40, new BN(0))
+        .accounts({
+          owner: depositor.publicKey,
+          vault: depositorVault,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([depositor])
+        .rpc();
+
+      await strategyProgram.methods
+        .initialize(agentAuthority.publicKey, 0, 0, 40, 5, [], Buffer.from([]))
+        .accounts({
+          owner: depositor.publicKey,
+          strategyAccount: strategyPda,
+          auditTrail: auditPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([depositor])
+        .rpc();
+    });
+
+    it('logs a deposit entry on the strategy audit trail', async () => {
+      await program.methods
+        .deposit(new BN(250_000_000))
+        .accounts({
+          owner: depositor.publicKey,
+          vault: depositorVault,
+          systemProgram: SystemProgram.programId,
+          strategyAccount: strategyPda,
+          auditTrail: auditPda,
+          strategyProgram: strategyProgram.programId,
+        })
+        .signers([depositor])
+        .rpc();
+
+      const vault = await program.account.vault.fetch(depositorVault);
+      expect(vault.totalDeposited.toNumber()).to.equal(250_000_000);
+
+      const audit = await strategyProgram.account.auditTrail.fetch(auditPda);
+      expect(audit.count).to.equal(1);
+      const entry = audit.entries[0];
+      expect(entry.actionKind).to.equal(8); // ActionKind::Deposit
+      expect(Buffer.from(entry.description).toString().replace(/\0+$/, '')).to.equal(
+        'Deposit 250000000 lamports'
+      );
+
+      // Deposits are not agent actions and leave the cycle budget alone
+      const strategy = await strategyProgram.account.strategyAccount.fetch(strategyPda);
+      expect(strategy.actionsThisCycle).to.equal(0);
+    });
+
+    it('deposits without logging when no strategy account is passed', async () => {
+      await program.methods
+        .deposit(new BN(100_000_000))
+        .accounts({
+          owner: depositor.publicKey,
+          vault: depositorVault,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([depositor])
+        .rpc();
+
+      const audit = await strategyProgram.account.auditTrail.fetch(auditPda);
+      expect(audit.count).to.equal(1);
+    });
+
+    it("rejects another owner's strategy account", async () => {
+      const [foreignStrategy] = PublicKey.findProgramAddressSync(
+        [Buffer.from('strategy'), owner.publicKey.toBuffer()],
+        strategyProgram.programId
+      );
+
+      try {
+        await program.methods
+          .deposit(new BN(100_000_000))
+          .accounts({
+            owner: depositor.publicKey,
+            vault: depositorVault,
+            systemProgram: SystemProgram.programId,
+            strategyAccount: foreignStrategy,
+            auditTrail: auditPda,
+            strategyProgram: strategyProgram.programId,
+          })
+          .signers([depositor])
+          .rpc();
+
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('InvalidStrategyAccounts');
+      }
+    });
+
+    it('rejects a partial set of strategy accounts', async () => {
+      try {
+        await program.methods
+          .deposit(new BN(100_000_000))
+          .accounts({
+            owner: depositor.publicKey,
+            vault: depositorVault,
+            systemProgram: SystemProgram.programId,
+            strategyAccount: strategyPda,
+          })
+          .signers([depositor])
+          .rpc();
+
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('InvalidStrategyAccounts');
+      }
+    });
+  });
+
+  describe('batched deposits', () => {
+    const vaultOwners = [Keypair.generate(), Keypair.generate(), Keypair.generate()];
+    const vaults: PublicKey[] = [];
+
+    before(async () => {
+      for (const kp of vaultOwners) {
+        const sig = await provider.connection.requestAirdrop(kp.publicKey, LAMPORTS_PER_SOL);
+        await provider.connection.confirmTransaction(sig);
+
+        const [pda] = PublicKey.findProgramAddressSync(
+          [Buffer.from('vault'), kp.publicKey.toBuffer()],
+          program.programId
+        );
+        vaults.push(pda);
+
+        await program.methods
+          .initialize(agentAuthority.publicKey, 1, 50, 0, 100, 10, new BN(0), 40, new BN(0))
+          .accounts({
+            owner: kp.publicKey,
+            vault: pda,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([kp])
+          .rpc();
+      }
+    });
+
+    const remaining = () =>
+      vaults.map((pubkey) => ({ pubkey, isWritable: true, isSigner: false }));
+
+    it('funds three vaults in one transaction', async () => {
+      const amounts = [100_000_000, 200_000_000, 300_000_000];
+      const before = await Promise.all(vaults.map((v) => provider.connection.getBalance(v)));
+
+      await program.methods
+        .depositMany(amounts.map((a) => new BN(a)))
+        .accounts({
+          funder: owner.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(remaining())
+        .rpc();
+
+      for (let i = 0; i < vaults.length; i++) {
+        const vault = await program.account.vault.fetch(vaults[i]);
+        expect(vault.totalDeposited.toNumber()).to.equal(amounts[i]);
+        const balance = await provider.connection.getBalance(vaults[i]);
+        expect(balance - before[i]).to.equal(amounts[i]);
+      }
+    });
+
+    it('rejects amounts that do not match the vault list', async () => {
+      try {
+        await program.methods
+          .depositMany([new BN(100_000_000), new BN(100_000_000)])
+          .accounts({
+            funder: owner.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .remainingAccounts(remaining())
+          .rpc();
+
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('AmountsLengthMismatch');
+      }
+
+      const vault = await program.account.vault.fetch(vaults[0]);
+      expect(vault.totalDeposited.toNumber()).to.equal(100_000_000);
+    });
+  });
+
+  describe('withdraw cooldown', () => {
+    const cooldownOwner = Keypair.generate();
+    const cooldownAgent = Keypair.generate();
+    let cooldownVault: PublicKey;
+
+    before(async () => {
+      const sig = await provider.connection.requestAirdrop(cooldownOwner.publicKey, 2 * LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(sig);
+
+      [cooldownVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from('vault'), cooldownOwner.publicKey.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .initialize(cooldownAgent.publicKey, 1, 50, 0, 100, 10, new BN(0), 40, new BN(0))
+        .accounts({
+          owner: cooldownOwner.publicKey,
+          vault: cooldownVault,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([cooldownOwner])
+        .rpc();
+
+      await program.methods
+        .deposit(new BN(LAMPORTS_PER_SOL))
+        .accounts({
+          owner: cooldownOwner.publicKey,
+          vault: cooldownVault,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([cooldownOwner])
+        .rpc();
+    });
+
+    async function setCooldown(secs: number, appliesToAgent: boolean) {
+      await program.methods
+        .setWithdrawCooldown(secs, appliesToAgent)
+        .accounts({
+          vault: cooldownVault,
+          owner: cooldownOwner.publicKey,
+        })
+        .signers([cooldownOwner])
+        .rpc();
+    }
+
+    async function ownerWithdraw() {
+      await program.methods
+        .withdraw(new BN(10_000_000))
+        .accounts({
+          owner: cooldownOwner.publicKey,
+          vault: cooldownVault,
+          config: configPda,
+          feeCollector: null,
+          priceUpdate: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([cooldownOwner])
+        .rpc();
+    }
+
+    async function agentWithdrawFromCooldownVault() {
+      await program.methods
+        .agentWithdraw(new BN(10_000_000), await agentNonce(cooldownVault), 0)
+        .accounts({
+          agent: cooldownAgent.publicKey,
+          vault: cooldownVault,
+          destination: Keypair.generate().publicKey,
+          config: configPda,
+          feeCollector: null,
+          priceUpdate: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([cooldownAgent])
+        .rpc();
+    }
+
+    it('rejects a second withdraw inside the cooldown', async () => {
+      await setCooldown(2, false);
+      await ownerWithdraw();
+
+      try {
+        await ownerWithdraw();
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('WithdrawCooldown');
+      }
+    });
+
+    it('allows a withdraw once the cooldown has elapsed', async () => {
+      await new Promise((resolve) => setTimeout(resolve, 3_000));
+      await ownerWithdraw();
+
+      const vault = await program.account.vault.fetch(cooldownVault);
+      expect(vault.lastWithdrawAt.toNumber()).to.be.greaterThan(0);
+    });
+
+    it('exempts agent withdraws unless flagged', async () => {
+      await agentWithdrawFromCooldownVault();
+
+      await setCooldown(60, true);
+      try {
+        await agentWithdrawFromCooldownVault();
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('WithdrawCooldown');
+      }
+    });
+
+    it('disables the cooldown when set to 0', async () => {
+      await setCooldown(0, false);
+      await ownerWithdraw();
+      await ownerWithdraw();
+    });
+  });
+
+  describe('rent exemption', () => {
+    const rentOwner = Keypair.generate();
+    let rentVault: PublicKey;
+
+    before(async () => {
+      const sig = await provider.connection.requestAirdrop(rentOwner.publicKey, LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(sig);
+
+      [rentVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from('vault'), rentOwner.publicKey.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .initialize(agentAuthority.publicKey, 1, 50, 0, 100, 10, new BN(0), 40, new BN(0))
+        .accounts({
+          owner: rentOwner.publicKey,
+          vault: rentVault,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([rentOwner])
+        .rpc();
+
+      await program.methods
+        .deposit(new BN(LAMPORTS_PER_SOL / 2))
+        .accounts({
+          owner: rentOwner.publicKey,
+          vault: rentVault,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([rentOwner])
+        .rpc();
+    });
+
+    async function withdrawFromRentVault(lamports: number) {
+      await program.methods
+        .withdraw(new BN(lamports))
+        .accounts({
+          owner: rentOwner.publicKey,
+          vault: rentVault,
+          config: configPda,
+          feeCollector: null,
+          priceUpdate: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([rentOwner])
+        .rpc();
+    }
+
+    it('rejects a near-full exit that would leave less than rent', async () => {
+      try {
+        await withdrawFromRentVault(LAMPORTS_PER_SOL / 2 - 1);
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('InsufficientReserve');
+      }
+    });
+
+    it('keeps the vault rent-exempt after a full exit', async () => {
+      await withdrawFromRentVault(LAMPORTS_PER_SOL / 2);
+
+      const info = await provider.connection.getAccountInfo(rentVault);
+      const minimum = await provider.connection.getMinimumBalanceForRentExemption(info!.data.length);
+      expect(info!.lamports).to.be.at.least(minimum);
+    });
+  });
+
+  describe('token withdrawals', () => {
+    async function vaultAndOwnerAccounts(mint: PublicKey, programId: PublicKey) {
+      const vaultAccount = await createAccount(
+        provider.connection, owner.payer, mint, vaultPda, Keypair.generate(), undefined, programId
+      );
+      const ownerAccount = await createAccount(
+        provider.connection, owner.payer, mint, owner.publicKey, Keypair.generate(), undefined, programId
+      );
+      await mintTo(provider.connection, owner.payer, mint, vaultAccount, owner.publicKey, 1_000_000, [], undefined, programId);
+      return { vaultAccount, ownerAccount };
+    }
+
+    async function withdrawToken(
+      mint: PublicKey,
+      vaultAccount: PublicKey,
+      destination: PublicKey,
+      programId: PublicKey,
+      amount: number
+    ) {
+      await program.methods
+        .withdrawToken(new BN(amount))
+        .accounts({
+          owner: owner.publicKey,
+          vault: vaultPda,
+          mint,
+          vaultTokenAccount: vaultAccount,
+          destination,
+          tokenProgram: programId,
+        })
+        .rpc();
+    }
+
+    for (const [name, programId] of [
+      ['classic SPL', TOKEN_PROGRAM_ID],
+      ['Token-2022', TOKEN_2022_PROGRAM_ID],
+    ] as [string, PublicKey][]) {
+      it(`withdraws a ${name} token`, async () => {
+        const mint = await createMint(
+          provider.connection, owner.payer, owner.publicKey, null, 6, Keypair.generate(), undefined, programId
+        );
+        const { vaultAccount, ownerAccount } = await vaultAndOwnerAccounts(mint, programId);
+
+        await withdrawToken(mint, vaultAccount, ownerAccount, programId, 400_000);
+
+        const vaultBalance = await getAccount(provider.connection, vaultAccount, undefined, programId);
+        const ownerBalance = await getAccount(provider.connection, ownerAccount, undefined, programId);
+        expect(Number(vaultBalance.amount)).to.equal(600_000);
+        expect(Number(ownerBalance.amount)).to.equal(400_000);
+      });
+    }
+
+    it('rejects a non-transferable Token-2022 mint', async () => {
+      const mintKp = Keypair.generate();
+      const space = getMintLen([ExtensionType.NonTransferable]);
+      const lamports = await provider.connection.getMinimumBalanceForRentExemption(space);
+      await provider.sendAndConfirm(
+        new Transaction().add(
+          SystemProgram.createAccount({
+            fromPubkey: owner.publicKey,
+            newAccountPubkey: mintKp.publicKey,
+            space,
+            lamports,
+            programId: TOKEN_2022_PROGRAM_ID,
+          }),
+          createInitializeNonTransferableMintInstruction(mintKp.publicKey, TOKEN_2022_PROGRAM_ID),
+          createInitializeMintInstruction(mintKp.publicKey, 6, owner.publicKey, null, TOKEN_2022_PROGRAM_ID)
+        ),
+        [mintKp]
+      );
+      const { vaultAccount, ownerAccount } = await vaultAndOwnerAccounts(mintKp.publicKey, TOKEN_2022_PROGRAM_ID);
+
+      try {
+        await withdrawToken(mintKp.publicKey, vaultAccount, ownerAccount, TOKEN_2022_PROGRAM_ID, 400_000);
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('UnsupportedMintExtension');
+      }
+    });
+  });
+
+  describe('aggregate risk', () => {
+    const riskOwners = [Keypair.generate(), Keypair.generate(), Keypair.generate()];
+    const slippages = [150, 40, 90];
+    const deposits = [100_000_000, 250_000_000, 50_000_000];
+    const riskVaults: PublicKey[] = [];
+
+    before(async () => {
+      for (let i = 0; i < riskOwners.length; i++) {
+        const kp = riskOwners[i];
+        const sig = await provider.connection.requestAirdrop(kp.publicKey, LAMPORTS_PER_SOL);
+        await provider.connection.confirmTransaction(sig);
+
+        const [pda] = PublicKey.findProgramAddressSync(
+          [Buffer.from('vault'), kp.publicKey.toBuffer()],
+          program.programId
+        );
+        riskVaults.push(pda);
+
+        await program.methods
+          .initialize(agentAuthority.publicKey, 1, 50, 0, slippages[i], 10, new BN(0), 40, new BN(0))
+          .accounts({
+            owner: kp.publicKey,
+            vault: pda,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([kp])
+          .rpc();
+      }
+
+      await program.methods
+        .depositMany(deposits.map((a) => new BN(a)))
+        .accounts({
+          funder: owner.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(
+          riskVaults.map((pubkey) => ({ pubkey, isWritable: true, isSigner: false }))
+        )
+        .rpc();
+    });
+
+    const readOnly = (keys: PublicKey[]) =>
+      keys.map((pubkey) => ({ pubkey, isWritable: false, isSigner: false }));
+
+    it('sums balances and takes the strictest slippage cap', async () => {
+      const risk = await program.methods
+        .getAggregateRisk()
+        .accounts({ caller: owner.publicKey })
+        .remainingAccounts(readOnly(riskVaults))
+        .view();
+
+      expect(risk.vaultCount).to.equal(3);
+      expect(risk.totalBalance.toNumber()).to.equal(deposits.reduce((a, b) => a + b, 0));
+      expect(risk.totalInSession.toNumber()).to.equal(0);
+      expect(risk.minMaxSlippageBps).to.equal(Math.min(...slippages));
+    });
+
+    it('rejects the same vault passed twice', async () => {
+      try {
+        await program.methods
+          .getAggregateRisk()
+          .accounts({ caller: owner.publicKey })
+          .remainingAccounts(readOnly([riskVaults[0], riskVaults[1], riskVaults[0]]))
+          .view();
+
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('InvalidVaultList');
+      }
+    });
+  });
+
+  describe('session expiry', () => {
+    const expiryOwner = Keypair.generate();
+    const expiryAgent = Keypair.generate();
+    let expiryVault: PublicKey;
+
+    before(async () => {
+      const sig = await provider.connection.requestAirdrop(expiryOwner.publicKey, 2 * LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(sig);
+
+      [expiryVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from('vault'), expiryOwner.publicKey.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .initialize(expiryAgent.publicKey, 1, 50, 0, 100, 10, new BN(0), 40, new BN(0))
+        .accounts({
+          owner: expiryOwner.publicKey,
+          vault: expiryVault,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([expiryOwner])
+        .rpc();
+
+      await program.methods
+        .deposit(new BN(LAMPORTS_PER_SOL))
+        .accounts({
+          owner: expiryOwner.publicKey,
+          vault: expiryVault,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([expiryOwner])
+        .rpc();
+    });
+
+    async function openSession(
+      maxDurationSecs: number,
+      destination: PublicKey = Keypair.generate().publicKey
+    ): Promise<number> {
+      await program.methods
+        .agentWithdraw(new BN(100_000_000), await agentNonce(expiryVault), maxDurationSecs)
+        .accounts({
+          agent: expiryAgent.publicKey,
+          vault: expiryVault,
+          destination,
+          config: configPda,
+          feeCollector: null,
+          priceUpdate: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([expiryAgent])
+        .rpc();
+      return (await program.account.vault.fetch(expiryVault)).sessionId.toNumber();
+    }
+
+    async function expireSession(sessionId: number) {
+      const caller = Keypair.generate();
+      await program.methods
+        .expireSession(new BN(sessionId))
+        .accounts({ caller: caller.publicKey, vault: expiryVault })
+        .signers([caller])
+        .rpc();
+    }
+
+    it('records when the session opened and its duration', async () => {
+      const sessionId = await openSession(0);
+
+      const vault = await program.account.vault.fetch(expiryVault);
+      const slot = vault.sessions.find((s: any) => s.sessionId.toNumber() === sessionId);
+      expect(slot.openedAt.toNumber()).to.be.greaterThan(0);
+      expect(slot.maxDurationSecs).to.equal(86_400);
+    });
+
+    it('rejects expiring a session before its duration', async () => {
+      const sessionId = await openSession(3_600);
+
+      try {
+        await expireSession(sessionId);
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('SessionNotExpired');
+      }
+    });
+
+    it('lets anyone expire a session after its duration', async () => {
+      const sessionId = await openSession(1);
+      const before = await program.account.vault.fetch(expiryVault);
+
+      await new Promise((resolve) => setTimeout(resolve, 2_000));
+      await expireSession(sessionId);
+
+      const after = await program.account.vault.fetch(expiryVault);
+      expect(after.sessions.some((s: any) => s.sessionId.toNumber() === sessionId)).to.be.false;
+      expect(before.inSessionAmount.toNumber() - after.inSessionAmount.toNumber()).to.equal(
+        100_000_000
+      );
+      // The SOL is at the session wallet, so it is written off rather than
+      // returned to the spendable balance
+      expect(after.totalWithdrawn.toNumber() - before.totalWithdrawn.toNumber()).to.equal(
+        100_000_000
+      );
+    });
+
+    it('credits SOL a session wallet returns after expiry', async () => {
+      const wallet = Keypair.generate();
+      const sessionId = await openSession(1, wallet.publicKey);
+      const before = await program.account.vault.fetch(expiryVault);
+
+      await new Promise((resolve) => setTimeout(resolve, 2_000));
+      await expireSession(sessionId);
+
+      const expired = await program.account.vault.fetch(expiryVault);
+      expect(
+        expired.expiredSessions.some((s: any) => s.sessionId.toNumber() === sessionId)
+      ).to.be.true;
+
+      await program.methods
+        .agentDeposit(new BN(100_000_000), new BN(sessionId), true, await agentNonce(expiryVault))
+        .accounts({
+          agent: expiryAgent.publicKey,
+          vault: expiryVault,
+          source: wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([expiryAgent, wallet])
+        .rpc();
+
+      const after = await program.account.vault.fetch(expiryVault);
+      const balance = (v: any) =>
+        v.totalDeposited.toNumber() - v.totalWithdrawn.toNumber() - v.inSessionAmount.toNumber();
+      // The written-off SOL is spendable again
+      expect(balance(after) - balance(expired)).to.equal(100_000_000);
+      expect(after.inSessionAmount.toNumber()).to.equal(
+        before.inSessionAmount.toNumber() - 100_000_000
+      );
+      expect(
+        after.expiredSessions.some((s: any) => s.sessionId.toNumber() === sessionId)
+      ).to.be.false;
+    });
+
+    it('rejects expiring an unknown session', async () => {
+      try {
+        await expireSession(9_999);
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('UnknownSession');
+      }
+    });
+  });
+
+  describe('daily loss limit', () => {
+    const lossOwner = Keypair.generate();
+    const lossAgent = Keypair.generate();
+    let lossVault: PublicKey;
+
+    before(async () => {
+      const sig = await provider.connection.requestAirdrop(lossOwner.publicKey, 2 * LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(sig);
+
+      [lossVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from('vault'), lossOwner.publicKey.toBuffer()],
+        program.programId
+      );
+
+      // 5% daily loss limit
+      await program.methods
+        .initialize(lossAgent.publicKey, 1, 50, 0, 100, 5, new BN(0), 40, new BN(0))
+        .accounts({
+          owner: lossOwner.publicKey,
+          vault: lossVault,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([lossOwner])
+        .rpc();
+
+      await program.methods
+        .deposit(new BN(LAMPORTS_PER_SOL))
+        .accounts({
+          owner: lossOwner.publicKey,
+          vault: lossVault,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([lossOwner])
+        .rpc();
+    });
+
+    async function withdrawTo(wallet: PublicKey) {
+      await program.methods
+        .agentWithdraw(new BN(200_000_000), await agentNonce(lossVault), 0)
+        .accounts({
+          agent: lossAgent.publicKey,
+          vault: lossVault,
+          destination: wallet,
+          config: configPda,
+          feeCollector: null,
+          priceUpdate: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([lossAgent])
+        .rpc();
+      return (await program.account.vault.fetch(lossVault)).sessionId.toNumber();
+    }
+
+    it('stops agent withdraws once the window\'s losses reach the limit', async () => {
+      const wallet = Keypair.generate();
+      const sessionId = await withdrawTo(wallet.publicKey);
+
+      // Only 0.1 of the 0.2 SOL comes back: a loss above 5% of the balance
+      await program.methods
+        .agentDeposit(new BN(100_000_000), new BN(sessionId), true, await agentNonce(lossVault))
+        .accounts({
+          agent: lossAgent.publicKey,
+          vault: lossVault,
+          source: wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([lossAgent, wallet])
+        .rpc();
+
+      const status = await program.methods
+        .getVaultStatus()
+        .accounts({ vault: lossVault, priceUpdate: null })
+        .view();
+      expect(status.dailyLossHeadroom.toNumber()).to.equal(0);
+
+      try {
+        await withdrawTo(Keypair.generate().publicKey);
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('DailyLossLimitReached');
+      }
+    });
+  });
+
+  describe('multisig', () => {
+    const msOwner = Keypair.generate();
+    const cosignerA = Keypair.generate();
+    const cosignerB = Keypair.generate();
+    const cosignerC = Keypair.generate();
+    let msVault: PublicKey;
+
+    before(async () => {
+      const sig = await provider.connection.requestAirdrop(msOwner.publicKey, 2 * LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(sig);
+
+      [msVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from('vault'), msOwner.publicKey.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .initialize(Keypair.generate().publicKey, 1, 50, 0, 100, 10, new BN(0), 40, new BN(0))
+        .accounts({
+          owner: msOwner.publicKey,
+          vault: msVault,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([msOwner])
+        .rpc();
+
+      await program.methods
+        .deposit(new BN(LAMPORTS_PER_SOL))
+        .accounts({
+          owner: msOwner.publicKey,
+          vault: msVault,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([msOwner])
+        .rpc();
+
+      await program.methods
+        .setCosigners([cosignerA.publicKey, cosignerB.publicKey, cosignerC.publicKey], 2)
+        .accounts({
+          vault: msVault,
+          owner: msOwner.publicKey,
+        })
+        .signers([msOwner])
+        .rpc();
+    });
+
+    async function withdrawWith(cosigners: Keypair[]) {
+      await program.methods
+        .withdraw(new BN(10_000_000))
+        .accounts({
+          owner: msOwner.publicKey,
+          vault: msVault,
+          config: configPda,
+          feeCollector: null,
+          priceUpdate: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(
+          cosigners.map((c) => ({ pubkey: c.publicKey, isWritable: false, isSigner: true }))
+        )
+        .signers([msOwner, ...cosigners])
+        .rpc();
+    }
+
+    it('stores the co-signer set and threshold', async () => {
+      const vault = await program.account.vault.fetch(msVault);
+      expect(vault.cosignerThreshold).to.equal(2);
+      expect(vault.cosigners[2].toBase58()).to.equal(cosignerC.publicKey.toBase58());
+    });
+
+    it('rejects a withdraw below the threshold', async () => {
+      try {
+        await withdrawWith([cosignerA]);
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.toString()).to.include('InsufficientSigners');
+      }
+    });
+
+    it('does not count an unknown signer toward the threshold', async () => {
+      try {
+        await withdrawWith([cosignerA, Keypair.generate()]);
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.toString()).to.include('InsufficientSigners');
+      }
+    });
+
+    it('allows a withdraw at the threshold', async () => {
+      const before = await program.account.vault.fetch(msVault);
+      await withdrawWith([cosignerA, cosignerC]);
+      const after = await program.account.vault.fetch(msVault);
+      expect(after.totalWithdrawn.sub(before.totalWithdrawn).toNumber()).to.equal(10_000_000);
+    });
+
+    it('requires co-signers to change the set', async () => {
+      try {
+        await program.methods
+          .setCosigners([], 0)
+          .accounts({
+            vault: msVault,
+            owner: msOwner.publicKey,
+          })
+          .signers([msOwner])
+          .rpc();
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.toString()).to.include('InsufficientSigners');
+      }
+    });
+
+    it('rejects a threshold larger than the set', async () => {
+      try {
+        await program.methods
+          .setCosigners([cosignerA.publicKey], 2)
+          .accounts({
+            vault: msVault,
+            owner: msOwner.publicKey,
+          })
+          .signers([msOwner])
+          .rpc();
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.toString()).to.include('InvalidCosigners');
+      }
+    });
+
+    function cosignerAccounts(cosigners: Keypair[]) {
+      return cosigners.map((c) => ({ pubkey: c.publicKey, isWritable: false, isSigner: true }));
+    }
+
+    it('rejects the owner alone granting a key of their own withdraw rights', async () => {
+      // Without the threshold, the owner could register their own agent,
+      // switch to Auto and drain the vault through agent_withdraw
+      const ownAgent = Keypair.generate();
+      try {
+        await program.methods
+          .setAgentRoles(ownAgent.publicKey, 1)
+          .accounts({ owner: msOwner.publicKey, vault: msVault })
+          .signers([msOwner])
+          .rpc();
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.toString()).to.include('InsufficientSigners');
+      }
+      const vault = await program.account.vault.fetch(msVault);
+      expect(
+        vault.agents.some((a: any) => a.authority.toBase58() === ownAgent.publicKey.toBase58())
+      ).to.equal(false);
+    });
+
+    it('requires co-signers for other owner-only changes', async () => {
+      const attempts = [
+        program.methods.setMode(0).accounts({ owner: msOwner.publicKey, vault: msVault }),
+        program.methods
+          .proposeOwner(Keypair.generate().publicKey)
+          .accounts({ owner: msOwner.publicKey, vault: msVault }),
+        program.methods
+          .emergencyResetSessions(true)
+          .accounts({ owner: msOwner.publicKey, vault: msVault }),
+        program.methods.setUsdReserve(new BN(0)).accounts({ owner: msOwner.publicKey, vault: msVault }),
+      ];
+      for (const attempt of attempts) {
+        try {
+          await attempt.signers([msOwner]).rpc();
+          expect.fail('Should have thrown');
+        } catch (err: any) {
+          expect(err.toString()).to.include('InsufficientSigners');
+        }
+      }
+    });
+
+    it('allows owner-only changes at the threshold', async () => {
+      const agent = Keypair.generate();
+      await program.methods
+        .setAgentRoles(agent.publicKey, 1)
+        .accounts({ owner: msOwner.publicKey, vault: msVault })
+        .remainingAccounts(cosignerAccounts([cosignerA, cosignerB]))
+        .signers([msOwner, cosignerA, cosignerB])
+        .rpc();
+
+      const vault = await program.account.vault.fetch(msVault);
+      expect(
+        vault.agents.some((a: any) => a.authority.toBase58() === agent.publicKey.toBase58())
+      ).to.equal(true);
+    });
+  });
+
+  describe('agent swap', () => {
+    const jupiter = anchor.workspace.MockJupiter as Program<MockJupiter>;
+    const swapOwner = Keypair.generate();
+    const swapAgent = Keypair.generate();
+    let swapVault: PublicKey;
+    let mintIn: PublicKey;
+    let mintOut: PublicKey;
+    let vaultIn: PublicKey;
+    let vaultOut: PublicKey;
+    let poolIn: PublicKey;
+    let poolOut: PublicKey;
+    let poolAuthority: PublicKey;
+
+    before(async () => {
+      const sig = await provider.connection.requestAirdrop(swapOwner.publicKey, 2 * LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(sig);
+
+      [swapVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from('vault'), swapOwner.publicKey.toBuffer()],
+        program.programId
+      );
+      [poolAuthority] = PublicKey.findProgramAddressSync([Buffer.from('pool')], jupiter.programId);
+
+      // Auto mode, 1% max slippage
+      await program.methods
+        .initialize(swapAgent.publicKey, 1, 50, 0, 100, 10, new BN(0), 40, new BN(0))
+        .accounts({
+          owner: swapOwner.publicKey,
+          vault: swapVault,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([swapOwner])
+        .rpc();
+
+      mintIn = await createMint(provider.connection, owner.payer, owner.publicKey, null, 6);
+      mintOut = await createMint(provider.connection, owner.payer, owner.publicKey, null, 6);
+      const account = (mint: PublicKey, authority: PublicKey) =>
+        createAccount(provider.connection, owner.payer, mint, authority, Keypair.generate());
+
+      vaultIn = await account(mintIn, swapVault);
+      vaultOut = await account(mintOut, swapVault);
+      poolIn = await account(mintIn, poolAuthority);
+      poolOut = await account(mintOut, poolAuthority);
+
+      await mintTo(provider.connection, owner.payer, mintIn, vaultIn, owner.publicKey, 1_000_000);
+      await mintTo(provider.connection, owner.payer, mintOut, poolOut, owner.publicKey, 1_000_000);
+
+      // The owner accepts at least 0.99 out per unit in
+      await program.methods
+        .setSwapFloor(mintIn, mintOut, new BN(990_000_000))
+        .accounts({ owner: swapOwner.publicKey, vault: swapVault })
+        .signers([swapOwner])
+        .rpc();
+    });
+
+    // Account order follows mock_jupiter::Route
+    function route(overrides: { [index: number]: PublicKey } = {}) {
+      return [swapVault, vaultIn, vaultOut, poolIn, poolOut, poolAuthority, TOKEN_PROGRAM_ID].map(
+        (pubkey, i) => ({
+          pubkey: overrides[i] ?? pubkey,
+          isWritable: i >= 1 && i <= 4,
+          isSigner: false,
+        })
+      );
+    }
+
+    async function balances(): Promise<[bigint, bigint]> {
+      return [
+        (await getAccount(provider.connection, vaultIn)).amount,
+        (await getAccount(provider.connection, vaultOut)).amount,
+      ];
+    }
+
+    async function agentSwap(
+      amountIn: number,
+      quotedOut: number,
+      routeIn: number,
+      routeOut: number,
+      jupiterProgram: PublicKey = jupiter.programId,
+      routeAccounts = route()
+    ) {
+      const routeData = jupiter.coder.instruction.encode('route', {
+        inAmount: new BN(routeIn),
+        outAmount: new BN(routeOut),
+      });
+
+      await program.methods
+        .agentSwap(new BN(amountIn), new BN(quotedOut), routeData, await agentNonce(swapVault))
+        .accounts({
+          agent: swapAgent.publicKey,
+          vault: swapVault,
+          inputTokenAccount: vaultIn,
+          outputTokenAccount: vaultOut,
+          jupiterProgram,
+        })
+        .remainingAccounts(routeAccounts)
+        .signers([swapAgent])
+        .rpc();
+    }
+
+    async function expectUnchanged(swap: Promise<void>, error: string) {
+      const before = await balances();
+      try {
+        await swap;
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.toString()).to.include(error);
+      }
+      expect(await balances()).to.deep.equal(before);
+    }
+
+    it('swaps between vault token accounts in one instruction', async () => {
+      const [inBefore, outBefore] = await balances();
+      const nonceBefore = await agentNonce(swapVault);
+
+      await agentSwap(100_000, 100_000, 100_000, 99_500);
+
+      const [inAfter, outAfter] = await balances();
+      expect(inBefore - inAfter).to.equal(100_000n);
+      expect(outAfter - outBefore).to.equal(99_500n);
+      expect((await agentNonce(swapVault)).toNumber()).to.equal(nonceBefore.toNumber() + 1);
+    });
+
+    it('rejects output below the max-slippage min-out', async () => {
+      // 1% of 100_000 leaves a 99_000 minimum
+      await expectUnchanged(agentSwap(100_000, 100_000, 100_000, 98_999), 'SlippageExceeded');
+    });
+
+    it('enforces the owner floor whatever the agent quotes', async () => {
+      // A 1-unit quote leaves the floor's 99_000 as the minimum
+      await expectUnchanged(agentSwap(100_000, 1, 100_000, 98_999), 'SlippageExceeded');
+    });
+
+    it('rejects a pair without a swap floor', async () => {
+      await program.methods
+        .setSwapFloor(mintIn, mintOut, new BN(0))
+        .accounts({ owner: swapOwner.publicKey, vault: swapVault })
+        .signers([swapOwner])
+        .rpc();
+      try {
+        await expectUnchanged(agentSwap(100_000, 100_000, 100_000, 100_000), 'SwapFloorNotSet');
+      } finally {
+        await program.methods
+          .setSwapFloor(mintIn, mintOut, new BN(990_000_000))
+          .accounts({ owner: swapOwner.publicKey, vault: swapVault })
+          .signers([swapOwner])
+          .rpc();
+      }
+    });
+
+    it('rejects a route that pays out to an account outside the vault', async () => {
+      const outside = await createAccount(
+        provider.connection,
+        owner.payer,
+        mintOut,
+        swapAgent.publicKey,
+        Keypair.generate()
+      );
+      await expectUnchanged(
+        agentSwap(100_000, 100_000, 100_000, 99_500, jupiter.programId, route({ 2: outside })),
+        'InvalidSwapRoute'
+      );
+      expect((await getAccount(provider.connection, outside)).amount).to.equal(0n);
+    });
+
+    it('rejects a route that touches another vault token account', async () => {
+      // A second vault-owned mintIn account posing as the pool's side
+      const otherVaultIn = await createAccount(
+        provider.connection,
+        owner.payer,
+        mintIn,
+        swapVault,
+        Keypair.generate()
+      );
+      await expectUnchanged(
+        agentSwap(100_000, 100_000, 100_000, 99_500, jupiter.programId, route({ 3: otherVaultIn })),
+        'InvalidSwapRoute'
+      );
+    });
+
+    it('rejects a route that spends more than amount_in', async () => {
+      await expectUnchanged(agentSwap(50_000, 100_000, 100_000, 100_000), 'SwapOverspent');
+    });
+
+    it('moves nothing when the route itself fails', async () => {
+      // The pool cannot pay this much, so the CPI fails
+      await expectUnchanged(agentSwap(100_000, 5_000_000, 100_000, 5_000_000), 'Error');
+    });
+
+    it('only routes through Jupiter', async () => {
+      await expectUnchanged(
+        agentSwap(100_000, 100_000, 100_000, 99_500, TOKEN_PROGRAM_ID),
+        'InvalidSwapRoute'
+      );
+    });
+  });
+
+  describe('usd reserve', () => {
+    const pyth = anchor.workspace.MockPyth as Program<MockPyth>;
+    const SOL_USD_FEED_ID = Array.from(
+      Buffer.from('ef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d', 'hex')
+    );
+    const reserveOwner = Keypair.generate();
+    const reserveAgent = Keypair.generate();
+    let reserveVault: PublicKey;
+
+    before(async () => {
+      const sig = await provider.connection.requestAirdrop(reserveOwner.publicKey, 2 * LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(sig);
+
+      [reserveVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from('vault'), reserveOwner.publicKey.toBuffer()],
+        program.programId
+      );
+
+      // No lamport reserve, so only the USD reserve can block a withdraw
+      await program.methods
+        .initialize(reserveAgent.publicKey, 1, 50, 0, 100, 10, new BN(0), 40, new BN(0))
+        .accounts({
+          owner: reserveOwner.publicKey,
+          vault: reserveVault,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([reserveOwner])
+        .rpc();
+
+      await program.methods
+        .deposit(new BN(LAMPORTS_PER_SOL))
+        .accounts({
+          owner: reserveOwner.publicKey,
+          vault: reserveVault,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([reserveOwner])
+        .rpc();
+
+      // $10 reserve
+      await program.methods
+        .setUsdReserve(new BN(10_000_000))
+        .accounts({
+          vault: reserveVault,
+          owner: reserveOwner.publicKey,
+        })
+        .signers([reserveOwner])
+        .rpc();
+    });
+
+    async function postPrice(
+      usdPerSol: number,
+      ageSecs = 0,
+      feedId: number[] = SOL_USD_FEED_ID
+    ): Promise<PublicKey> {
+      const now = await provider.connection.getBlockTime(await provider.connection.getSlot());
+      const priceUpdate = Keypair.generate();
+      await pyth.methods
+        .postPrice(feedId, new BN(usdPerSol).mul(new BN(100_000_000)), -8, new BN(now! - ageSecs))
+        .accounts({
+          priceUpdate: priceUpdate.publicKey,
+          payer: owner.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([priceUpdate])
+        .rpc();
+      return priceUpdate.publicKey;
+    }
+
+    async function ownerWithdraw(lamports: number, priceUpdate: PublicKey | null) {
+      await program.methods
+        .withdraw(new BN(lamports))
+        .accounts({
+          owner: reserveOwner.publicKey,
+          vault: reserveVault,
+          config: configPda,
+          feeCollector: null,
+          priceUpdate,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([reserveOwner])
+        .rpc();
+    }
+
+    it('stores the USD reserve', async () => {
+      const vault = await program.account.vault.fetch(reserveVault);
+      expect(vault.minReserveUsd.toNumber()).to.equal(10_000_000);
+    });
+
+    it('keeps $10 of SOL in the vault at the oracle price', async () => {
+      // At $100/SOL the reserve is 0.1 SOL, so leaving 0.05 SOL fails
+      const priceUpdate = await postPrice(100);
+      try {
+        await ownerWithdraw(0.95 * LAMPORTS_PER_SOL, priceUpdate);
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.toString()).to.include('InsufficientReserve');
+      }
+
+      // Leaving 0.5 SOL clears it
+      await ownerWithdraw(0.5 * LAMPORTS_PER_SOL, priceUpdate);
+    });
+
+    it('applies the USD reserve to agent withdraws', async () => {
+      // At $10/SOL the reserve is 1 SOL, more than the vault holds
+      const priceUpdate = await postPrice(10);
+      try {
+        await program.methods
+          .agentWithdraw(new BN(10_000_000), await agentNonce(reserveVault), 0)
+          .accounts({
+            agent: reserveAgent.publicKey,
+            vault: reserveVault,
+            destination: Keypair.generate().publicKey,
+            config: configPda,
+            feeCollector: null,
+            priceUpdate,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([reserveAgent])
+          .rpc();
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.toString()).to.include('ReserveWouldBeBreached');
+      }
+    });
+
+    it('rejects a stale price', async () => {
+      const priceUpdate = await postPrice(100, 120);
+      try {
+        await ownerWithdraw(10_000_000, priceUpdate);
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.toString()).to.include('StaleOracle');
+      }
+    });
+
+    it('rejects a price for another feed', async () => {
+      const priceUpdate = await postPrice(100, 0, Array(32).fill(1));
+      try {
+        await ownerWithdraw(10_000_000, priceUpdate);
+        expect.fail('Should have thrown');
+      } catch (err: any) {
+        expect(err.toString()).to.include('InvalidOracle');
+      }
+    });
+
+    it('reports max withdrawable net of the USD reserve', async () => {
+      const vault = await program.account.vault.fetch(reserveVault);
+      const balance = vault.totalDeposited.sub(vault.totalWithdrawn).sub(vault.inSessionAmount);
+      const minRent = await provider.connection.getMinimumBalanceForRentExemption(
+        program.account.vault.size
+      );
+      const status = (priceUpdate: PublicKey | null) =>
+        program.methods
+          .getVaultStatus()
+          .accounts({ vault: reserveVault, priceUpdate })
+          .view();
+
+      // At $100/SOL the $10 reserve is 0.1 SOL
+      const priced = await status(await postPrice(100));
+      expect(priced.maxWithdrawable.toString()).to.equal(
+        balance.subn(minRent).subn(0.1 * LAMPORTS_PER_SOL).toString()
+      );
+
+      // Without an oracle, like a withdraw, only the lamport reserve (0) applies
+      const unpriced = await status(null);
+      expect(unpriced.maxWithdrawable.toString()).to.equal(balance.subn(minRent).toString());
+    });
+
+    it('falls back to the lamport reserve without an oracle', async () => {
+      // min_sol_reserve is 0, so only rent has to stay behind
+      await ownerWithdraw(0.45 * LAMPORTS_PER_SOL, null);
+      const vault = await program.account.vault.fetch(reserveVault);
+      expect(vault.totalWithdrawn.toNumber()).to.equal(0.95 * LAMPORTS_PER_SOL);
+    });
+  });
+
+  describe('minimum amounts', () => {
+    const minOwner = Keypair.generate();
+    let minVault: PublicKey;
+
+    before(async () => {
+      const sig = await provider.connection.requestAirdrop(minOwner.publicKey, 2 * LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(sig);
+
+      [minVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from('vault'), minOwner.publicKey.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .initialize(Keypair.generate().publicKey, 1, 50, 0, 100, 10, new BN(0), 40, new BN(0))
+        .accounts({
+          owner: minOwner.publicKey,
+          vault: minVault,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([minOwner])
+        .rpc();
+
+      await program.methods
+        .setMinAmounts(new BN(100_000_000), new BN(50_000_000))
+        .accounts({
+          vault: minVault,
+          owner: minOwner.publicKey,
+        })
+        .signers([minOwner])
+        .rpc();
+    });
+
+    function deposit(lamports: number) {
+      return program.methods
+        .deposit(new BN(lamports))
+        .accounts({
+          owner: minOwner.publicKey,
+          vault: minVault,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([minOwner])
+        .rpc();
+    }
+
+    function withdraw(lamports: number) {
+      return program.methods
+        .withdraw(new BN(lamports))
+        .accounts({
+          owner: minOwner.publicKey,
+          vault: minVault,
+          config: configPda,
+          feeCollector: null,
+          priceUpdate: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([minOwner])
+        .rpc();
+    }
+
+    it('stores the minimums', async () => {
+      const vault = await program.account.vault.fetch(minVault);
+      expect(vault.minDeposit.toNumber()).to.equal(100_000_000);
+      expect(vault.minWithdraw.toNumber()).to.equal(50_000_000);
+    });
+
+    it('accepts a deposit at the minimum', async () => {
+      await deposit(100_000_000);
+      const vault = await program.account.vault.fetch(minVault);
+      expect(vault.totalDeposited.toNumber()).to.equal(100_000_000);
+    });
+
+    it('rejects a deposit below the minimum', async () => {
+      try {
+        await deposit(99_999_999);
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('BelowMinimum');
+      }
+    });
+
+    it('accepts a withdrawal at the minimum', async () => {
+      await withdraw(50_000_000);
+      const vault = await program.account.vault.fetch(minVault);
+      expect(vault.totalWithdrawn.toNumber()).to.equal(50_000_000);
+    });
+
+    it('rejects a withdrawal below the minimum', async () => {
+      try {
+        await withdraw(49_999_999);
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('BelowMinimum');
+      }
+    });
+
+    it('accepts dust once the minimums are cleared', async () => {
+      await program.methods
+        .setMinAmounts(new BN(0), new BN(0))
+        .accounts({
+          vault: minVault,
+          owner: minOwner.publicKey,
+        })
+        .signers([minOwner])
+        .rpc();
+
+      await deposit(1);
+      await withdraw(1);
+    });
+
+    it('rejects minimums set by a non-owner', async () => {
+      const stranger = Keypair.generate();
+      try {
+        await program.methods
+          .setMinAmounts(new BN(1), new BN(1))
+          .accounts({
+            vault: minVault,
+            owner: stranger.publicKey,
+          })
+          .signers([stranger])
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('Error');
+      }
+    });
+  });
+
+  describe('mode change snapshot', () => {
+    const snapOwner = Keypair.generate();
+    let snapVault: PublicKey;
+
+    before(async () => {
+      const sig = await provider.connection.requestAirdrop(snapOwner.publicKey, 2 * LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(sig);
+
+      [snapVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from('vault'), snapOwner.publicKey.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .initialize(Keypair.generate().publicKey, 0, 25, 2, 150, 8, new BN(10_000_000), 40, new BN(0))
+        .accounts({
+          owner: snapOwner.publicKey,
+          vault: snapVault,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([snapOwner])
+        .rpc();
+
+      await program.methods
+        .deposit(new BN(LAMPORTS_PER_SOL / 2))
+        .accounts({
+          owner: snapOwner.publicKey,
+          vault: snapVault,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([snapOwner])
+        .rpc();
+    });
+
+    async function setModeCapturing(mode: number): Promise<any> {
+      let captured: any = null;
+      const listener = program.addEventListener('vaultSnapshotEvent', (event) => {
+        captured = event;
+      });
+      await program.methods
+        .setMode(mode)
+        .accounts({
+          vault: snapVault,
+          owner: snapOwner.publicKey,
+        })
+        .signers([snapOwner])
+        .rpc();
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+      await program.removeEventListener(listener);
+      return captured;
+    }
+
+    it('emits the full vault state when switching to auto', async () => {
+      const event = await setModeCapturing(1);
+
+      expect(event).to.not.be.null;
+      expect(event.vault.toBase58()).to.equal(snapVault.toBase58());
+      expect(event.owner.toBase58()).to.equal(snapOwner.publicKey.toBase58());
+      expect(event.oldMode).to.equal(0);
+      expect(event.newMode).to.equal(1);
+      expect(event.riskLimits.maxPositionSizePct).to.equal(25);
+      expect(event.riskLimits.minPositionSizePct).to.equal(2);
+      expect(event.riskLimits.maxSlippageBps).to.equal(150);
+      expect(event.riskLimits.maxDailyLossPct).to.equal(8);
+      expect(event.riskLimits.minSolReserve.toNumber()).to.equal(10_000_000);
+      expect(event.riskLimits.maxProtocolExposurePct).to.equal(40);
+      expect(event.totalDeposited.toNumber()).to.equal(LAMPORTS_PER_SOL / 2);
+      expect(event.totalWithdrawn.toNumber()).to.equal(0);
+      expect(event.currentBalance.toNumber()).to.equal(LAMPORTS_PER_SOL / 2);
+      expect(event.inSessionAmount.toNumber()).to.equal(0);
+      expect(event.totalRealizedPnl.toNumber()).to.equal(0);
+    });
+
+    it('reports both modes when switching back to advisory', async () => {
+      const event = await setModeCapturing(0);
+
+      expect(event.oldMode).to.equal(1);
+      expect(event.newMode).to.equal(0);
+    });
+  });
+
+  describe('mint risk limits', () => {
+    const jupiter = anchor.workspace.MockJupiter as Program<MockJupiter>;
+    const capOwner = Keypair.generate();
+    const capAgent = Keypair.generate();
+    let capVault: PublicKey;
+    let mintA: PublicKey;
+    let mintB: PublicKey;
+    let vaultA: PublicKey;
+    let vaultB: PublicKey;
+    let poolA: PublicKey;
+    let poolB: PublicKey;
+    let poolAuthority: PublicKey;
+
+    before(async () => {
+      const sig = await provider.connection.requestAirdrop(capOwner.publicKey, 2 * LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(sig);
+
+      [capVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from('vault'), capOwner.publicKey.toBuffer()],
+        program.programId
+      );
+      [poolAuthority] = PublicKey.findProgramAddressSync([Buffer.from('pool')], jupiter.programId);
+
+      // Auto mode, 50% global max position, 1% max slippage
+      await program.methods
+        .initialize(capAgent.publicKey, 1, 50, 0, 100, 10, new BN(0), 40, new BN(0))
+        .accounts({
+          owner: capOwner.publicKey,
+          vault: capVault,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([capOwner])
+        .rpc();
+
+      mintA = await createMint(provider.connection, owner.payer, owner.publicKey, null, 6);
+      mintB = await createMint(provider.connection, owner.payer, owner.publicKey, null, 6);
+      const account = (mint: PublicKey, authority: PublicKey) =>
+        createAccount(provider.connection, owner.payer, mint, authority, Keypair.generate());
+
+      vaultA = await account(mintA, capVault);
+      vaultB = await account(mintB, capVault);
+      poolA = await account(mintA, poolAuthority);
+      poolB = await account(mintB, poolAuthority);
+
+      for (const [mint, to] of [[mintA, vaultA], [mintB, vaultB], [mintA, poolA], [mintB, poolB]]) {
+        await mintTo(provider.connection, owner.payer, mint, to, owner.publicKey, 1_000_000);
+      }
+
+      // 1:1 floors both ways
+      for (const [from, to] of [[mintA, mintB], [mintB, mintA]]) {
+        await program.methods
+          .setSwapFloor(from, to, new BN(1_000_000_000))
+          .accounts({ owner: capOwner.publicKey, vault: capVault })
+          .signers([capOwner])
+          .rpc();
+      }
+    });
+
+    function setMintRiskLimit(mint: PublicKey, pct: number, signer: Keypair = capOwner) {
+      return program.methods
+        .setMintRiskLimit(mint, pct)
+        .accounts({
+          vault: capVault,
+          owner: signer.publicKey,
+        })
+        .signers([signer])
+        .rpc();
+    }
+
+    // Swap `amountIn` out of the vault's `fromA ? A : B` account at a 1:1 quote
+    async function swap(fromA: boolean, amountIn: number) {
+      const [vaultIn, vaultOut, poolIn, poolOut] = fromA
+        ? [vaultA, vaultB, poolA, poolB]
+        : [vaultB, vaultA, poolB, poolA];
+      const routeData = jupiter.coder.instruction.encode('route', {
+        inAmount: new BN(amountIn),
+        outAmount: new BN(amountIn),
+      });
+
+      await program.methods
+        .agentSwap(new BN(amountIn), new BN(amountIn), routeData, await agentNonce(capVault))
+        .accounts({
+          agent: capAgent.publicKey,
+          vault: capVault,
+          inputTokenAccount: vaultIn,
+          outputTokenAccount: vaultOut,
+          jupiterProgram: jupiter.programId,
+        })
+        .remainingAccounts([
+          { pubkey: capVault, isWritable: false, isSigner: false },
+          { pubkey: vaultIn, isWritable: true, isSigner: false },
+          { pubkey: vaultOut, isWritable: true, isSigner: false },
+          { pubkey: poolIn, isWritable: true, isSigner: false },
+          { pubkey: poolOut, isWritable: true, isSigner: false },
+          { pubkey: poolAuthority, isWritable: false, isSigner: false },
+          { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+        ])
+        .signers([capAgent])
+        .rpc();
+    }
+
+    async function balance(account: PublicKey): Promise<bigint> {
+      return (await getAccount(provider.connection, account)).amount;
+    }
+
+    it('stores a cap per mint', async () => {
+      await setMintRiskLimit(mintA, 20);
+      await setMintRiskLimit(mintB, 80);
+
+      const vault = await program.account.vault.fetch(capVault);
+      const caps = vault.mintRiskLimits
+        .filter((l: any) => l.maxPositionSizePct > 0)
+        .map((l: any) => [l.mint.toBase58(), l.maxPositionSizePct]);
+      expect(caps).to.deep.equal([
+        [mintA.toBase58(), 20],
+        [mintB.toBase58(), 80],
+      ]);
+    });
+
+    it('rejects a swap above the input mint cap', async () => {
+      // 20% of 1_000_000 A
+      try {
+        await swap(true, 200_001);
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('ExceedsMaxPosition');
+      }
+      expect(await balance(vaultA)).to.equal(1_000_000n);
+
+      await swap(true, 200_000);
+      expect(await balance(vaultA)).to.equal(800_000n);
+    });
+
+    it('allows a swap above the global cap when the mint cap is higher', async () => {
+      // The vault holds 1_200_000 B: 80% is 960_000, the global 50% only 600_000
+      await swap(false, 700_000);
+      expect(await balance(vaultB)).to.equal(500_000n);
+    });
+
+    it('falls back to the global cap once a mint cap is cleared', async () => {
+      await setMintRiskLimit(mintA, 0);
+      const vault = await program.account.vault.fetch(capVault);
+      expect(
+        vault.mintRiskLimits.some((l: any) => l.mint.toBase58() === mintA.toBase58())
+      ).to.equal(false);
+
+      // The vault holds 1_500_000 A: 50% is 750_000
+      await swap(true, 300_000);
+      expect(await balance(vaultA)).to.equal(1_200_000n);
+    });
+
+    it('rejects a cap above 100%', async () => {
+      try {
+        await setMintRiskLimit(mintA, 101);
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('InvalidRiskLimit');
+      }
+    });
+
+    it('rejects caps set by a non-owner', async () => {
+      const stranger = Keypair.generate();
+      try {
+        await setMintRiskLimit(mintA, 10, stranger);
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('Error');
+      }
+    });
+  });
+
+  describe('withdraw preview', () => {
+    const previewOwner = Keypair.generate();
+    let previewVault: PublicKey;
+
+    before(async () => {
+      const sig = await provider.connection.requestAirdrop(previewOwner.publicKey, 2 * LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(sig);
+
+      [previewVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from('vault'), previewOwner.publicKey.toBuffer()],
+        program.programId
+      );
+      await setProtocolFee(50);
+
+      await program.methods
+        .initialize(Keypair.generate().publicKey, 0, 50, 0, 100, 10, new BN(10_000_000), 40, new BN(0))
+        .accounts({
+          owner: previewOwner.publicKey,
+          vault: previewVault,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([previewOwner])
+        .rpc();
+
+      await program.methods
+        .deposit(new BN(LAMPORTS_PER_SOL))
+        .accounts({
+          owner: previewOwner.publicKey,
+          vault: previewVault,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([previewOwner])
+        .rpc();
+    });
+
+    after(async () => {
+      await setProtocolFee(0);
+    });
+
+    function preview(lamports: number): Promise<any> {
+      return program.methods
+        .previewWithdraw(new BN(lamports))
+        .accounts({
+          owner: previewOwner.publicKey,
+          vault: previewVault,
+          config: configPda,
+          feeCollector: feeCollector.publicKey,
+          priceUpdate: null,
+        })
+        .signers([previewOwner])
+        .view();
+    }
+
+    function withdraw(lamports: number) {
+      return program.methods
+        .withdraw(new BN(lamports))
+        .accounts({
+          owner: previewOwner.publicKey,
+          vault: previewVault,
+          config: configPda,
+          feeCollector: feeCollector.publicKey,
+          priceUpdate: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([previewOwner])
+        .rpc();
+    }
+
+    async function currentBalance(): Promise<number> {
+      const vault = await program.account.vault.fetch(previewVault);
+      return vault.totalDeposited.sub(vault.totalWithdrawn).sub(vault.inSessionAmount).toNumber();
+    }
+
+    async function expectError(call: Promise<any>): Promise<string> {
+      try {
+        await call;
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        return err.toString();
+      }
+      return '';
+    }
+
+    it('moves no lamports', async () => {
+      const before = await provider.connection.getBalance(previewVault);
+      await preview(200_000_000);
+      expect(await provider.connection.getBalance(previewVault)).to.equal(before);
+    });
+
+    it('matches the outcome of the withdraw it previews', async () => {
+      const result = await preview(200_000_000);
+      expect(result.amount.toNumber()).to.equal(200_000_000);
+      expect(result.fee.toNumber()).to.be.greaterThan(0);
+      expect(result.netAmount.toNumber()).to.equal(200_000_000 - result.fee.toNumber());
+
+      const ownerBefore = await provider.connection.getBalance(previewOwner.publicKey);
+      const collectorBefore = await provider.connection.getBalance(feeCollector.publicKey);
+      await withdraw(200_000_000);
+
+      const ownerAfter = await provider.connection.getBalance(previewOwner.publicKey);
+      const collectorAfter = await provider.connection.getBalance(feeCollector.publicKey);
+      expect(ownerAfter - ownerBefore).to.equal(result.netAmount.toNumber());
+      expect(collectorAfter - collectorBefore).to.equal(result.fee.toNumber());
+      expect(await currentBalance()).to.equal(result.remainingBalance.toNumber());
+    });
+
+    it('previews a fee-free withdraw when no fee is set', async () => {
+      await setProtocolFee(0);
+      try {
+        const result = await preview(100_000_000);
+        expect(result.fee.toNumber()).to.equal(0);
+        expect(result.netAmount.toNumber()).to.equal(100_000_000);
+
+        await withdraw(100_000_000);
+        expect(await currentBalance()).to.equal(result.remainingBalance.toNumber());
+      } finally {
+        await setProtocolFee(50);
+      }
+    });
+
+    it('fails with the error the withdraw would produce', async () => {
+      const balance = await currentBalance();
+      const cases: [number, string][] = [
+        [0, 'ZeroWithdraw'],
+        [balance + 1, 'InsufficientBalance'],
+        // Leaves less than rent + min_sol_reserve behind
+        [balance - 1_000_000, 'InsufficientReserve'],
+      ];
+
+      for (const [lamports, error] of cases) {
+        expect(await expectError(preview(lamports))).to.include(error);
+        expect(await expectError(withdraw(lamports))).to.include(error);
+      }
+      expect(await currentBalance()).to.equal(balance);
+    });
+
+    it('rejects a fee collector that does not match the config', async () => {
+      const error = await expectError(
+        program.methods
+          .previewWithdraw(new BN(100_000_000))
+          .accounts({
+            owner: previewOwner.publicKey,
+            vault: previewVault,
+            config: configPda,
+            feeCollector: Keypair.generate().publicKey,
+            priceUpdate: null,
+          })
+          .signers([previewOwner])
+          .view()
+      );
+      expect(error).to.include('InvalidFeeCollector');
+    });
+  });
+
+  describe('agent heartbeat', () => {
+    const hbOwner = Keypair.generate();
+    const hbAgent = Keypair.generate();
+    let hbVault: PublicKey;
+
+    before(async () => {
+      const sig = await provider.connection.requestAirdrop(hbOwner.publicKey, 2 * LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(sig);
+
+      [hbVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from('vault'), hbOwner.publicKey.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .initialize(hbAgent.publicKey, 1, 50, 0, 100, 10, new BN(0), 40, new BN(0))
+        .accounts({
+          owner: hbOwner.publicKey,
+          vault: hbVault,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([hbOwner])
+        .rpc();
+
+      await program.methods
+        .deposit(new BN(LAMPORTS_PER_SOL))
+        .accounts({
+          owner: hbOwner.publicKey,
+          vault: hbVault,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([hbOwner])
+        .rpc();
+    });
+
+    async function setHeartbeatTimeout(secs: number) {
+      await program.methods
+        .setHeartbeatTimeout(secs)
+        .accounts({
+          vault: hbVault,
+          owner: hbOwner.publicKey,
+        })
+        .signers([hbOwner])
+        .rpc();
+    }
+
+    async function heartbeat(agent: Keypair = hbAgent) {
+      await program.methods
+        .heartbeat()
+        .accounts({
+          agent: agent.publicKey,
+          vault: hbVault,
+        })
+        .signers([agent])
+        .rpc();
+    }
+
+    async function agentWithdrawFromHbVault() {
+      await program.methods
+        .agentWithdraw(new BN(10_000_000), await agentNonce(hbVault), 0)
+        .accounts({
+          agent: hbAgent.publicKey,
+          vault: hbVault,
+          destination: Keypair.generate().publicKey,
+          config: configPda,
+          feeCollector: null,
+          priceUpdate: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([hbAgent])
+        .rpc();
+    }
+
+    it('does not require a heartbeat by default', async () => {
+      await agentWithdrawFromHbVault();
+    });
+
+    it('rejects an agent that never checked in', async () => {
+      await setHeartbeatTimeout(60);
+      try {
+        await agentWithdrawFromHbVault();
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('AgentStale');
+      }
+    });
+
+    it('allows withdraws after a fresh heartbeat', async () => {
+      await heartbeat();
+      const vault = await program.account.vault.fetch(hbVault);
+      expect(vault.lastHeartbeatAt.toNumber()).to.be.greaterThan(0);
+
+      await agentWithdrawFromHbVault();
+    });
+
+    it('rejects withdraws once the heartbeat goes stale', async () => {
+      await setHeartbeatTimeout(1);
+      await heartbeat();
+      await new Promise((resolve) => setTimeout(resolve, 3_000));
+
+      try {
+        await agentWithdrawFromHbVault();
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('AgentStale');
+      }
+    });
+
+    it('only accepts heartbeats from registered agents', async () => {
+      try {
+        await heartbeat(Keypair.generate());
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('UnauthorizedAgent');
+      }
+    });
+
+    it('rejects a timeout set by a non-owner', async () => {
+      const stranger = Keypair.generate();
+      try {
+        await program.methods
+          .setHeartbeatTimeout(0)
+          .accounts({
+            vault: hbVault,
+            owner: stranger.publicKey,
+          })
+          .signers([stranger])
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('Error');
+      }
+    });
+  });
+
+  describe('layout migration', () => {
+    // tests/fixtures/legacy-vault.json is preloaded by Anchor.toml: a
+    // 160-byte vault in the original layout, owned by this keypair
+    const legacyOwner = Keypair.fromSecretKey(
+      Uint8Array.from(JSON.parse(fs.readFileSync('tests/fixtures/legacy-vault-owner.json', 'utf8')))
+    );
+    let legacyVault: PublicKey;
+
+    before(async () => {
+      [legacyVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from('vault'), legacyOwner.publicKey.toBuffer()],
+        program.programId
+      );
+      const sig = await provider.connection.requestAirdrop(legacyOwner.publicKey, LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(sig);
+    });
+
+    async function migrate(signer: Keypair = legacyOwner) {
+      await program.methods
+        .migrateVault()
+        .accounts({
+          owner: signer.publicKey,
+          vault: legacyVault,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([signer])
+        .rpc();
+    }
+
+    it('cannot load a vault in the original layout', async () => {
+      const info = await provider.connection.getAccountInfo(legacyVault);
+      expect(info!.data.length).to.equal(160);
+
+      try {
+        await program.methods
+          .deposit(new BN(LAMPORTS_PER_SOL / 10))
+          .accounts({
+            owner: legacyOwner.publicKey,
+            vault: legacyVault,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([legacyOwner])
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('AccountDidNotDeserialize');
+      }
+    });
+
+    it('rejects a migration signed by someone else', async () => {
+      const stranger = Keypair.generate();
+      const sig = await provider.connection.requestAirdrop(stranger.publicKey, LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(sig);
+
+      try {
+        await migrate(stranger);
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('ConstraintSeeds');
+      }
+    });
+
+    it('grows the vault to the current layout', async () => {
+      await migrate();
+
+      const info = await provider.connection.getAccountInfo(legacyVault);
+      expect(info!.data.length).to.equal(program.account.vault.size);
+      const rent = await provider.connection.getMinimumBalanceForRentExemption(info!.data.length);
+      expect(info!.lamports).to.be.at.least(rent);
+
+      const vault = await program.account.vault.fetch(legacyVault);
+      expect(vault.owner.toBase58()).to.equal(legacyOwner.publicKey.toBase58());
+      expect(vault.version).to.equal(1);
+      expect(vault.riskLimits.maxPositionSizePct).to.equal(50);
+      expect(vault.riskLimits.minPositionSizePct).to.equal(0);
+      expect(vault.riskLimits.maxSlippageBps).to.equal(100);
+      expect(vault.riskLimits.maxDailyLossPct).to.equal(10);
+      expect(vault.riskLimits.minSolReserve.toNumber()).to.equal(10_000_000);
+      expect(vault.riskLimits.maxProtocolExposurePct).to.equal(40);
+      expect(vault.createdAt.toNumber()).to.equal(1_700_000_000);
+      expect(vault.agents[0].authority.toBase58()).to.equal(vault.agentAuthority.toBase58());
+      expect(vault.agents[0].roles).to.equal(0b1111);
+      expect(vault.sessionId.toNumber()).to.equal(0);
+      expect(vault.cosignerThreshold).to.equal(0);
+    });
+
+    it('loads normally once migrated', async () => {
+      await program.methods
+        .deposit(new BN(LAMPORTS_PER_SOL / 10))
+        .accounts({
+          owner: legacyOwner.publicKey,
+          vault: legacyVault,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([legacyOwner])
+        .rpc();
+
+      const vault = await program.account.vault.fetch(legacyVault);
+      expect(vault.totalDeposited.toNumber()).to.equal(LAMPORTS_PER_SOL / 10);
+    });
+
+    it('refuses to migrate twice', async () => {
+      try {
+        await migrate();
+        expect.fail('Should have thrown an error');
+      } catch (err: any) {
+        expect(err.toString()).to.include('VaultAlreadyMigrated');
+      }
+    });
+  });
+});